@@ -342,6 +342,7 @@ impl CameraController {
                 let scale = match camera.projection() {
                     Projection::Perspective(perspective) => 2.0 * perspective.fov.tan(),
                     Projection::Orthographic(orthographic) => 2.0 * orthographic.vertical_size,
+                    Projection::Frustum(frustum) => 2.0 * frustum.fov().tan(),
                 };
                 let side = camera
                     .side_vector()
@@ -365,7 +366,7 @@ impl CameraController {
         let camera = &mut graph[self.camera];
 
         match *camera.projection_mut() {
-            Projection::Perspective(_) => {
+            Projection::Perspective(_) | Projection::Frustum(_) => {
                 self.z_offset = (self.z_offset + delta).clamp(
                     -settings.camera.zoom_range.end,
                     -settings.camera.zoom_range.start,
@@ -454,7 +455,8 @@ impl CameraController {
         modifiers: KeyboardModifiers,
         graph: &mut Graph,
     ) {
-        let is_perspective = graph[self.camera].projection().is_perspective();
+        let projection = graph[self.camera].projection();
+        let is_perspective = projection.is_perspective() || projection.is_frustum();
 
         match button {
             MouseButton::Right
@@ -575,7 +577,7 @@ impl CameraController {
         camera.set_exposure(settings.camera.exposure);
 
         match camera.projection_value() {
-            Projection::Perspective(_) => {
+            Projection::Perspective(_) | Projection::Frustum(_) => {
                 let global_transform = camera.global_transform();
                 let look = global_transform.look();
                 let side = global_transform.side();
@@ -147,9 +147,7 @@ impl CameraPreviewControlPanel {
                         .first()
                         .send_sync(self.preview_frame, ImageMessage::Texture(rt.clone()));
                     camera.set_render_target(rt);
-                    camera
-                        .render_mask
-                        .set_value_and_mark_modified(BitMask(!GameScene::EDITOR_OBJECTS_MASK.0));
+                    camera.set_render_mask(BitMask(!GameScene::EDITOR_OBJECTS_MASK.0));
 
                     game_scene.preview_camera = node_handle;
 
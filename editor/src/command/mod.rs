@@ -101,6 +101,12 @@ pub trait CommandTrait: Debug + 'static {
     /// This object is leaving the command stack, so it will never
     /// be executed or reverted again.
     fn finalize(&mut self, _: &mut dyn CommandContext) {}
+    /// Returns approximate amount of memory (in bytes) occupied by the command. It is used by
+    /// [`CommandStack`] to enforce its memory budget (if any). Commands that hold large amounts of
+    /// heap-allocated data should override this method.
+    fn memory_usage(&self) -> usize {
+        std::mem::size_of_val(self)
+    }
 }
 
 /// An untyped command for the editor to execute or revert.
@@ -212,12 +218,22 @@ impl CommandTrait for CommandGroup {
             cmd.finalize(context);
         }
     }
+
+    fn memory_usage(&self) -> usize {
+        std::mem::size_of_val(self)
+            + self
+                .commands
+                .iter()
+                .map(|cmd| cmd.memory_usage())
+                .sum::<usize>()
+    }
 }
 
 pub struct CommandStack {
     pub commands: Vec<Command>,
     pub top: Option<usize>,
     max_capacity: usize,
+    memory_budget: Option<usize>,
     debug: bool,
 }
 
@@ -227,10 +243,44 @@ impl CommandStack {
             commands: Default::default(),
             top: None,
             max_capacity,
+            memory_budget: None,
             debug,
         }
     }
 
+    /// Sets approximate memory budget (in bytes) of the stack. When the total memory usage of the
+    /// commands (see [`CommandTrait::memory_usage`]) exceeds the budget, the oldest commands are
+    /// dropped. The most recent command is always kept, even if it alone exceeds the budget. The
+    /// max capacity of the stack is still respected.
+    pub fn with_memory_budget(mut self, memory_budget: usize) -> Self {
+        self.memory_budget = Some(memory_budget);
+        self
+    }
+
+    /// Returns approximate amount of memory (in bytes) occupied by the commands in the stack.
+    pub fn memory_usage(&self) -> usize {
+        self.commands.iter().map(|cmd| cmd.memory_usage()).sum()
+    }
+
+    fn enforce_memory_budget(&mut self, context: &mut dyn CommandContext) {
+        let Some(memory_budget) = self.memory_budget else {
+            return;
+        };
+
+        let mut memory_usage = self.memory_usage();
+        while memory_usage > memory_budget && self.commands.len() > 1 {
+            let mut dropped_command = self.commands.remove(0);
+            memory_usage -= dropped_command.memory_usage();
+            dropped_command.finalize(context);
+
+            // Keep top pointing at the same command.
+            self.top = match self.top {
+                None | Some(0) => None,
+                Some(top) => Some(top - 1),
+            };
+        }
+    }
+
     pub fn do_command(&mut self, mut command: Command, context: &mut dyn CommandContext) {
         if self.commands.is_empty() {
             self.top = Some(0);
@@ -280,6 +330,8 @@ impl CommandStack {
         command.execute(context);
 
         self.commands.push(command);
+
+        self.enforce_memory_budget(context);
     }
 
     pub fn top_command(&self) -> Option<&dyn CommandTrait> {
@@ -643,3 +695,49 @@ impl<F: EntityGetter> CommandTrait for RemoveCollectionItemCommand<F> {
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        command::{Command, CommandContext, CommandStack, CommandTrait},
+        fyrox::core::ComponentProvider,
+    };
+
+    #[derive(Debug, ComponentProvider)]
+    struct TestContext {}
+
+    impl CommandContext for TestContext {}
+
+    #[derive(Debug)]
+    struct HeavyCommand {
+        data: Vec<u8>,
+    }
+
+    impl CommandTrait for HeavyCommand {
+        fn name(&mut self, _: &dyn CommandContext) -> String {
+            "Heavy".to_string()
+        }
+
+        fn execute(&mut self, _: &mut dyn CommandContext) {}
+
+        fn revert(&mut self, _: &mut dyn CommandContext) {}
+
+        fn memory_usage(&self) -> usize {
+            self.data.len()
+        }
+    }
+
+    #[test]
+    fn test_command_stack_memory_budget() {
+        let mut stack = CommandStack::new(false, 2048).with_memory_budget(1000);
+        for _ in 0..10 {
+            stack.do_command(
+                Command::new(HeavyCommand { data: vec![0; 300] }),
+                &mut TestContext {},
+            );
+        }
+        assert_eq!(stack.commands.len(), 3);
+        assert_eq!(stack.top, Some(2));
+        assert!(stack.memory_usage() <= 1000);
+    }
+}
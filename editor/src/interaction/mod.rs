@@ -254,14 +254,15 @@ pub fn calculate_gizmo_distance_scaling(
     camera: Handle<Camera>,
     gizmo_origin: Handle<impl ObjectOrVariant<Node>>,
 ) -> Vector3<f32> {
+    let distance = || {
+        graph[gizmo_origin.to_base()]
+            .global_position()
+            .metric_distance(&graph[camera].global_position())
+    };
     let s = match graph[camera].projection() {
-        Projection::Perspective(proj) => {
-            distance_scale_factor(proj.fov)
-                * graph[gizmo_origin.to_base()]
-                    .global_position()
-                    .metric_distance(&graph[camera].global_position())
-        }
+        Projection::Perspective(proj) => distance_scale_factor(proj.fov) * distance(),
         Projection::Orthographic(ortho) => 0.4 * ortho.vertical_size,
+        Projection::Frustum(frustum) => distance_scale_factor(frustum.fov()) * distance(),
     };
 
     Vector3::new(s, s, s)
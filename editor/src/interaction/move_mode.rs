@@ -221,7 +221,7 @@ impl MoveContext {
             // In case of empty space, check intersection with oXZ plane (3D) or oXY (2D).
             let camera = &graph[game_scene.camera_controller.camera];
             let normal = match camera.projection() {
-                Projection::Perspective(_) => Vector3::new(0.0, 1.0, 0.0),
+                Projection::Perspective(_) | Projection::Frustum(_) => Vector3::new(0.0, 1.0, 0.0),
                 Projection::Orthographic(_) => Vector3::new(0.0, 0.0, 1.0),
             };
 
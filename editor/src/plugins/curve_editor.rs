@@ -23,45 +23,72 @@ use crate::{
     fyrox::{
         asset::Resource,
         core::{
-            futures::executor::block_on, math::curve::Curve, pool::Handle, type_traits::prelude::*,
+            algebra::Vector2,
+            color::Color,
+            futures::executor::block_on,
+            math::curve::{Curve, CurveKey, CurveKeyKind},
+            pool::Handle,
+            type_traits::prelude::*,
             visitor::prelude::*,
         },
         engine::Engine,
+        graph::SceneGraph,
         gui::{
             border::BorderBuilder,
+            brush::Brush,
             button::{ButtonBuilder, ButtonMessage},
+            check_box::{CheckBox, CheckBoxBuilder, CheckBoxMessage},
             curve::{CurveEditorBuilder, CurveEditorMessage},
+            dropdown_list::{DropdownList, DropdownListBuilder, DropdownListMessage},
             file_browser::FileSelectorMessage,
             grid::{Column, GridBuilder, Row},
             menu::{MenuBuilder, MenuItemBuilder, MenuItemContent, MenuItemMessage},
-            message::UiMessage,
+            message::{KeyCode, UiMessage},
             messagebox::{MessageBoxBuilder, MessageBoxResult},
+            numeric::{NumericUpDown, NumericUpDownBuilder, NumericUpDownMessage},
             stack_panel::StackPanelBuilder,
+            text::{Text, TextBuilder, TextMessage},
+            utils::{make_dropdown_list_option, make_simple_tooltip},
             widget::{WidgetBuilder, WidgetMessage},
             window::{WindowBuilder, WindowMessage, WindowTitle},
-            BuildContext, HorizontalAlignment, Orientation, Thickness, UserInterface,
+            BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+            VerticalAlignment,
+        },
+        resource::curve::{
+            curves_from_json, curves_to_json, is_json_path, CurveResource, CurveResourceState,
         },
-        resource::curve::{CurveResource, CurveResourceState},
     },
     menu::create_menu_item,
     plugin::EditorPlugin,
+    settings::windows::WindowGeometry,
     utils::create_file_selector,
     Editor, MessageBoxButtons, MessageBoxMessage,
 };
 use fyrox::asset::manager::ResourceManager;
-use fyrox::core::some_or_return;
+use fyrox::core::log::Log;
+use fyrox::core::{ok_or_return, some_or_return};
 use fyrox::gui::button::Button;
 use fyrox::gui::curve::CurveEditor;
-use fyrox::gui::file_browser::{FileSelector, FileSelectorMode, FileType};
+use fyrox::gui::file_browser::{
+    FileSelector, FileSelectorBuilder, FileSelectorMode, FileType, PathFilter,
+};
 use fyrox::gui::menu::MenuItem;
 use fyrox::gui::messagebox::MessageBox;
 use fyrox::gui::style::resource::StyleResourceExt;
 use fyrox::gui::style::Style;
 use fyrox::gui::window::{Window, WindowAlignment};
-use std::{fmt::Debug, path::PathBuf};
+use std::{
+    fmt::Debug,
+    path::{Path, PathBuf},
+};
 
 #[derive(Debug, ComponentProvider)]
-pub struct CurveEditorContext {}
+pub struct CurveEditorContext {
+    /// Channels of the edited curve.
+    channels: Vec<CurveResource>,
+    /// Index of the currently edited channel.
+    active_channel: usize,
+}
 
 impl CommandContext for CurveEditorContext {}
 
@@ -89,12 +116,87 @@ impl CommandTrait for ModifyCurveCommand {
     fn revert(&mut self, _: &mut dyn CommandContext) {
         self.swap();
     }
+
+    fn memory_usage(&self) -> usize {
+        std::mem::size_of::<Self>() + std::mem::size_of_val(self.curve.keys())
+    }
+}
+
+/// Adds a new channel to the edited curve and makes it active.
+#[derive(Debug)]
+struct AddChannelCommand {
+    curve_resource: CurveResource,
+    prev_active_channel: usize,
+}
+
+impl CommandTrait for AddChannelCommand {
+    fn name(&mut self, _: &dyn CommandContext) -> String {
+        "Add Channel".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut dyn CommandContext) {
+        let context = context.get_mut::<CurveEditorContext>();
+        context.channels.push(self.curve_resource.clone());
+        self.prev_active_channel =
+            std::mem::replace(&mut context.active_channel, context.channels.len() - 1);
+    }
+
+    fn revert(&mut self, context: &mut dyn CommandContext) {
+        let context = context.get_mut::<CurveEditorContext>();
+        context.channels.pop();
+        context.active_channel = self.prev_active_channel;
+    }
+}
+
+/// Removes a channel with the given index from the edited curve.
+#[derive(Debug)]
+struct RemoveChannelCommand {
+    index: usize,
+    curve_resource: Option<CurveResource>,
+    prev_active_channel: usize,
 }
 
+impl CommandTrait for RemoveChannelCommand {
+    fn name(&mut self, _: &dyn CommandContext) -> String {
+        "Remove Channel".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut dyn CommandContext) {
+        let context = context.get_mut::<CurveEditorContext>();
+        self.curve_resource = Some(context.channels.remove(self.index));
+        self.prev_active_channel = context.active_channel;
+        context.active_channel = context
+            .active_channel
+            .min(context.channels.len().saturating_sub(1));
+    }
+
+    fn revert(&mut self, context: &mut dyn CommandContext) {
+        let context = context.get_mut::<CurveEditorContext>();
+        if let Some(curve_resource) = self.curve_resource.take() {
+            context.channels.insert(self.index, curve_resource);
+        }
+        context.active_channel = self.prev_active_channel;
+    }
+}
+
+/// Max amount of memory (in bytes) that could be occupied by the command stack of the curve editor.
+/// Every command holds a full copy of the curve, so editing curves with large amounts of keys could
+/// consume lots of memory.
+const COMMAND_STACK_MEMORY_BUDGET: usize = 64 * 1024 * 1024;
+
+/// Default grid snapping steps along both axes.
+const DEFAULT_SNAP_STEP: Vector2<f32> = Vector2::new(0.1, 0.1);
+
+/// Amount of samples of a baked curve.
+const BAKE_SAMPLE_COUNT: usize = 256;
+
 struct FileMenu {
     new: Handle<MenuItem>,
     save: Handle<MenuItem>,
+    save_json: Handle<MenuItem>,
     load: Handle<MenuItem>,
+    import_csv: Handle<MenuItem>,
+    bake: Handle<MenuItem>,
 }
 
 struct EditMenu {
@@ -102,9 +204,184 @@ struct EditMenu {
     redo: Handle<MenuItem>,
 }
 
+struct ChannelMenu {
+    add: Handle<MenuItem>,
+    remove: Handle<MenuItem>,
+}
+
 struct Menu {
     file: FileMenu,
     edit: EditMenu,
+    channel: ChannelMenu,
+}
+
+/// Name of the visitor region that contains all the channels of a multi-channel curve file. The
+/// first channel is also stored in the `Curve` region, so the file could still be loaded as a
+/// plain curve resource.
+const CHANNELS_REGION: &str = "Channels";
+
+/// Returns a distinct color of a curve channel with the given index.
+fn channel_color(index: usize) -> Color {
+    Color::COLORS[3 + index % (Color::COLORS.len() - 3)]
+}
+
+/// Parses curve keys from a CSV text, where each line contains location and value of a key
+/// separated by a comma (`x,y`). Empty lines are ignored, malformed lines are reported to the log
+/// and skipped.
+fn parse_csv_keys(text: &str) -> Vec<CurveKey> {
+    let mut keys = Vec::new();
+    for (line_number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let mut columns = line.split(',').map(|column| column.trim().parse::<f32>());
+        match (columns.next(), columns.next(), columns.next()) {
+            (Some(Ok(location)), Some(Ok(value)), None) => {
+                keys.push(CurveKey::new(location, value, CurveKeyKind::Linear));
+            }
+            _ => Log::err(format!(
+                "Unable to import line {} ({line}) of a CSV curve, it must be in `x,y` format. \
+                The line is skipped.",
+                line_number + 1
+            )),
+        }
+    }
+    keys
+}
+
+/// Tangents presets, that could be applied to the selected keys.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum TangentPreset {
+    /// Zero slope on both sides of a key.
+    Flat,
+    /// Each tangent points toward the respective neighbor key.
+    Linear,
+    /// Both tangents have the slope of the line between the neighbor keys (Catmull-Rom).
+    Smooth,
+}
+
+fn slope(a: &CurveKey, b: &CurveKey) -> f32 {
+    let dx = b.location() - a.location();
+    if dx.abs() > f32::EPSILON {
+        (b.value - a.value) / dx
+    } else {
+        0.0
+    }
+}
+
+/// Converts the given slope (`dy/dx`) to a tangent of the cubic segment between the given keys.
+/// Cubic interpolation scales tangents by the value difference of the keys (see `cubicf`), so the
+/// same slope gives different tangents on different segments.
+fn slope_to_tangent(slope: f32, a: &CurveKey, b: &CurveKey) -> f32 {
+    let dy = (b.value - a.value).abs();
+    if dy > f32::EPSILON {
+        slope * (b.location() - a.location()) / dy
+    } else {
+        0.0
+    }
+}
+
+/// Makes the keys with the given ids cubic and sets their tangents using the given preset. The
+/// first and the last keys of the curve use the only neighbor they have. Returns `true` if any key
+/// was modified.
+fn apply_tangent_preset(curve: &mut Curve, keys: &[Uuid], preset: TangentPreset) -> bool {
+    let mut modified = false;
+    for i in 0..curve.keys.len() {
+        let key = &curve.keys[i];
+        if !keys.contains(&key.id) {
+            continue;
+        }
+
+        let prev = i.checked_sub(1).map(|prev| &curve.keys[prev]);
+        let next = curve.keys.get(i + 1);
+        let left = prev.map(|prev| slope(prev, key));
+        let right = next.map(|next| slope(key, next));
+        let (left_slope, right_slope) = match preset {
+            TangentPreset::Flat => (0.0, 0.0),
+            TangentPreset::Linear => (
+                left.or(right).unwrap_or_default(),
+                right.or(left).unwrap_or_default(),
+            ),
+            TangentPreset::Smooth => {
+                let slope = match (prev, next) {
+                    (Some(prev), Some(next)) => slope(prev, next),
+                    _ => left.or(right).unwrap_or_default(),
+                };
+                (slope, slope)
+            }
+        };
+        let left_tangent = prev.map(|prev| slope_to_tangent(left_slope, prev, key));
+        let right_tangent = next.map(|next| slope_to_tangent(right_slope, key, next));
+
+        let kind = CurveKeyKind::Cubic {
+            left_tangent: left_tangent.or(right_tangent).unwrap_or_default(),
+            right_tangent: right_tangent.or(left_tangent).unwrap_or_default(),
+        };
+        if curve.keys[i].kind != kind {
+            curve.keys[i].kind = kind;
+            modified = true;
+        }
+    }
+    modified
+}
+
+/// Loads all the curves from a curve file in JSON format.
+fn load_json_channels(path: &Path) -> Vec<Curve> {
+    match std::fs::read(path)
+        .map_err(|err| err.to_string())
+        .and_then(|bytes| curves_from_json(&bytes).map_err(|err| err.to_string()))
+    {
+        Ok(curves) => curves,
+        Err(err) => {
+            Log::err(format!(
+                "Unable to load a curve from {}. Reason: {err}",
+                path.display()
+            ));
+            Vec::new()
+        }
+    }
+}
+
+/// Loads all curves, except the first one, from a multi-channel curve file. The first curve is
+/// loaded as a curve resource.
+fn load_additional_channels(path: &Path) -> Vec<Curve> {
+    let Ok(mut visitor) = block_on(Visitor::load_from_file(path)) else {
+        return Vec::new();
+    };
+    let mut channels = Vec::<Curve>::new();
+    if channels.visit(CHANNELS_REGION, &mut visitor).is_err() {
+        return Vec::new();
+    }
+    channels.into_iter().skip(1).collect()
+}
+
+fn make_snap_step_field(
+    ctx: &mut BuildContext,
+    value: f32,
+    tooltip: &str,
+) -> Handle<NumericUpDown<f32>> {
+    NumericUpDownBuilder::<f32>::new(
+        WidgetBuilder::new()
+            .with_margin(Thickness::uniform(1.0))
+            .with_width(60.0)
+            .with_tooltip(make_simple_tooltip(ctx, tooltip)),
+    )
+    .with_value(value)
+    .with_min_value(0.0)
+    .build(ctx)
+}
+
+fn make_tool_button(ctx: &mut BuildContext, text: &str, tooltip: &str) -> Handle<Button> {
+    ButtonBuilder::new(
+        WidgetBuilder::new()
+            .with_margin(Thickness::uniform(1.0))
+            .with_width(60.0)
+            .with_tooltip(make_simple_tooltip(ctx, tooltip)),
+    )
+    .with_text(text)
+    .build(ctx)
 }
 
 pub struct CurveEditorWindow {
@@ -112,25 +389,65 @@ pub struct CurveEditorWindow {
     curve_editor: Handle<CurveEditor>,
     ok: Handle<Button>,
     cancel: Handle<Button>,
-    curve_resource: Option<CurveResource>,
+    zoom_to_fit: Handle<Button>,
+    flat_tangents: Handle<Button>,
+    linear_tangents: Handle<Button>,
+    smooth_tangents: Handle<Button>,
+    flip_horizontal: Handle<Button>,
+    flip_vertical: Handle<Button>,
+    reverse: Handle<Button>,
+    normalize: Handle<Button>,
+    cursor_value: Handle<Text>,
+    selected_keys: Vec<Uuid>,
+    snap_enabled: Handle<CheckBox>,
+    snap_x_step: Handle<NumericUpDown<f32>>,
+    snap_y_step: Handle<NumericUpDown<f32>>,
+    snap: (Vector2<f32>, bool),
+    curve_resources: Vec<CurveResource>,
+    active_channel: usize,
+    channel_selector: Handle<DropdownList>,
     command_stack: CommandStack,
     menu: Menu,
     load_file_selector: Handle<FileSelector>,
+    import_csv_file_selector: Handle<FileSelector>,
     save_file_selector: Handle<FileSelector>,
+    save_json_file_selector: Handle<FileSelector>,
     path: PathBuf,
     save_changes_message_box: Handle<MessageBox>,
     cancel_message_box: Handle<MessageBox>,
     modified: bool,
-    backup: Curve,
+    backup: Vec<(CurveResource, Curve)>,
+    geometry: Option<WindowGeometry>,
 }
 
 impl CurveEditorWindow {
-    pub fn new(ctx: &mut BuildContext) -> Self {
+    /// Creates new curve editor window. The window will have the given geometry, or the default
+    /// size and centered position if the geometry is not specified.
+    pub fn new(ctx: &mut BuildContext, geometry: Option<WindowGeometry>) -> Self {
         let file_type = FileType::new()
             .with_extension("crv")
             .with_description("Curve");
-        let load_file_selector =
-            create_file_selector(ctx, file_type.clone(), FileSelectorMode::Open);
+        let json_file_type = FileType::new()
+            .with_extension("json")
+            .with_description("Curve (JSON)");
+        let load_file_selector = FileSelectorBuilder::new(
+            WindowBuilder::new(WidgetBuilder::new().with_width(300.0).with_height(400.0))
+                .open(false),
+        )
+        .with_filter(
+            PathFilter::new()
+                .with_file_type(file_type.clone())
+                .with_file_type(json_file_type.clone()),
+        )
+        .with_mode(FileSelectorMode::Open)
+        .build(ctx);
+        let import_csv_file_selector = create_file_selector(
+            ctx,
+            FileType::new()
+                .with_extension("csv")
+                .with_description("Comma-Separated Values"),
+            FileSelectorMode::Open,
+        );
         let save_file_selector = create_file_selector(
             ctx,
             file_type,
@@ -138,6 +455,13 @@ impl CurveEditorWindow {
                 default_file_name: PathBuf::from("unnamed.crv"),
             },
         );
+        let save_json_file_selector = create_file_selector(
+            ctx,
+            json_file_type,
+            FileSelectorMode::Save {
+                default_file_name: PathBuf::from("unnamed.json"),
+            },
+        );
 
         let save_changes_message_box = MessageBoxBuilder::new(
             WindowBuilder::new(WidgetBuilder::new())
@@ -164,156 +488,379 @@ impl CurveEditorWindow {
         let cancel;
         let new;
         let save;
+        let save_json;
         let load;
+        let import_csv;
+        let bake;
         let undo;
         let redo;
-        let window = WindowBuilder::new(WidgetBuilder::new().with_width(400.0).with_height(300.0))
-            .open(false)
-            .with_content(
-                GridBuilder::new(
-                    WidgetBuilder::new()
-                        .with_child(
-                            MenuBuilder::new(WidgetBuilder::new())
-                                .with_items(vec![
-                                    MenuItemBuilder::new(WidgetBuilder::new())
-                                        .with_content(MenuItemContent::text("File"))
-                                        .with_items(vec![
-                                            {
-                                                new = MenuItemBuilder::new(WidgetBuilder::new())
-                                                    .with_content(
-                                                        MenuItemContent::text_with_shortcut(
-                                                            "New", "Ctrl+N",
-                                                        ),
-                                                    )
-                                                    .build(ctx);
-                                                new
-                                            },
-                                            {
-                                                load = MenuItemBuilder::new(WidgetBuilder::new())
-                                                    .with_content(
-                                                        MenuItemContent::text_with_shortcut(
-                                                            "Load", "Ctrl+L",
-                                                        ),
-                                                    )
-                                                    .build(ctx);
-                                                load
-                                            },
-                                            {
-                                                save = MenuItemBuilder::new(WidgetBuilder::new())
-                                                    .with_content(
-                                                        MenuItemContent::text_with_shortcut(
-                                                            "Save", "Ctrl+S",
-                                                        ),
-                                                    )
-                                                    .build(ctx);
-                                                save
-                                            },
-                                        ])
-                                        .build(ctx),
-                                    MenuItemBuilder::new(WidgetBuilder::new())
-                                        .with_content(MenuItemContent::text("Edit"))
-                                        .with_items(vec![
-                                            {
-                                                undo = MenuItemBuilder::new(WidgetBuilder::new())
-                                                    .with_content(
-                                                        MenuItemContent::text_with_shortcut(
-                                                            "Undo", "Ctrl+Z",
-                                                        ),
-                                                    )
-                                                    .build(ctx);
-                                                undo
-                                            },
-                                            {
-                                                redo = MenuItemBuilder::new(WidgetBuilder::new())
-                                                    .with_content(
-                                                        MenuItemContent::text_with_shortcut(
-                                                            "Redo", "Ctrl+Y",
-                                                        ),
-                                                    )
-                                                    .build(ctx);
-                                                redo
-                                            },
-                                        ])
-                                        .build(ctx),
-                                ])
+        let add_channel;
+        let remove_channel;
+        let channel_selector;
+        let zoom_to_fit;
+        let cursor_value;
+        let flat_tangents = make_tool_button(
+            ctx,
+            "Flat",
+            "Sets zero slope for the tangents of the selected keys.",
+        );
+        let linear_tangents = make_tool_button(
+            ctx,
+            "Linear",
+            "Points the tangents of the selected keys toward their neighbor keys.",
+        );
+        let smooth_tangents = make_tool_button(
+            ctx,
+            "Smooth",
+            "Sets the tangents of the selected keys parallel to the line between their neighbor \
+            keys.",
+        );
+        let flip_horizontal = make_tool_button(
+            ctx,
+            "Flip H",
+            "Mirrors the active curve horizontally around the middle of its location range.",
+        );
+        let flip_vertical = make_tool_button(
+            ctx,
+            "Flip V",
+            "Mirrors the active curve vertically around the middle of the range of its values.",
+        );
+        let reverse = make_tool_button(
+            ctx,
+            "Reverse",
+            "Reverses the active curve in time, so it plays backwards within the same location \
+            range.",
+        );
+        let normalize = make_tool_button(
+            ctx,
+            "Normalize",
+            "Remaps the values of the active curve so the lowest key is at 0 and the highest key \
+            is at 1.",
+        );
+        let snap_enabled = CheckBoxBuilder::new(
+            WidgetBuilder::new()
+                .with_margin(Thickness::left(10.0))
+                .with_tooltip(make_simple_tooltip(
+                    ctx,
+                    "Snaps moved and added keys to a grid with the given steps.",
+                )),
+        )
+        .checked(Some(false))
+        .with_content(
+            TextBuilder::new(WidgetBuilder::new())
+                .with_vertical_text_alignment(VerticalAlignment::Center)
+                .with_text("Snap")
+                .build(ctx),
+        )
+        .build(ctx);
+        let snap_x_step =
+            make_snap_step_field(ctx, DEFAULT_SNAP_STEP.x, "Snapping step along X axis.");
+        let snap_y_step =
+            make_snap_step_field(ctx, DEFAULT_SNAP_STEP.y, "Snapping step along Y axis.");
+        let toolbar = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .on_column(0)
+                .with_child(flat_tangents)
+                .with_child(linear_tangents)
+                .with_child(smooth_tangents)
+                .with_child(flip_horizontal)
+                .with_child(flip_vertical)
+                .with_child(reverse)
+                .with_child(normalize)
+                .with_child(snap_enabled)
+                .with_child(snap_x_step)
+                .with_child(snap_y_step),
+        )
+        .with_orientation(Orientation::Horizontal)
+        .build(ctx);
+
+        let size = geometry.map_or(Vector2::new(700.0, 400.0), |geometry| geometry.size);
+        let window =
+            WindowBuilder::new(WidgetBuilder::new().with_width(size.x).with_height(size.y))
+                .open(false)
+                .with_content(
+                    GridBuilder::new(
+                        WidgetBuilder::new()
+                            .with_child(
+                                MenuBuilder::new(WidgetBuilder::new())
+                                    .with_items(vec![
+                                        MenuItemBuilder::new(WidgetBuilder::new())
+                                            .with_content(MenuItemContent::text("File"))
+                                            .with_items(vec![
+                                                {
+                                                    new =
+                                                        MenuItemBuilder::new(WidgetBuilder::new())
+                                                            .with_content(
+                                                                MenuItemContent::text_with_shortcut(
+                                                                    "New", "Ctrl+N",
+                                                                ),
+                                                            )
+                                                            .build(ctx);
+                                                    new
+                                                },
+                                                {
+                                                    load =
+                                                        MenuItemBuilder::new(WidgetBuilder::new())
+                                                            .with_content(
+                                                                MenuItemContent::text_with_shortcut(
+                                                                    "Load", "Ctrl+L",
+                                                                ),
+                                                            )
+                                                            .build(ctx);
+                                                    load
+                                                },
+                                                {
+                                                    save =
+                                                        MenuItemBuilder::new(WidgetBuilder::new())
+                                                            .with_content(
+                                                                MenuItemContent::text_with_shortcut(
+                                                                    "Save", "Ctrl+S",
+                                                                ),
+                                                            )
+                                                            .build(ctx);
+                                                    save
+                                                },
+                                                {
+                                                    save_json =
+                                                        MenuItemBuilder::new(WidgetBuilder::new())
+                                                            .with_content(MenuItemContent::text(
+                                                                "Save As JSON",
+                                                            ))
+                                                            .build(ctx);
+                                                    save_json
+                                                },
+                                                {
+                                                    import_csv =
+                                                        MenuItemBuilder::new(WidgetBuilder::new())
+                                                            .with_content(MenuItemContent::text(
+                                                                "Import CSV",
+                                                            ))
+                                                            .build(ctx);
+                                                    import_csv
+                                                },
+                                                {
+                                                    bake =
+                                                        MenuItemBuilder::new(WidgetBuilder::new())
+                                                            .with_content(MenuItemContent::text(
+                                                                "Bake",
+                                                            ))
+                                                            .build(ctx);
+                                                    bake
+                                                },
+                                            ])
+                                            .build(ctx),
+                                        MenuItemBuilder::new(WidgetBuilder::new())
+                                            .with_content(MenuItemContent::text("Edit"))
+                                            .with_items(vec![
+                                                {
+                                                    undo =
+                                                        MenuItemBuilder::new(WidgetBuilder::new())
+                                                            .with_content(
+                                                                MenuItemContent::text_with_shortcut(
+                                                                    "Undo", "Ctrl+Z",
+                                                                ),
+                                                            )
+                                                            .build(ctx);
+                                                    undo
+                                                },
+                                                {
+                                                    redo =
+                                                        MenuItemBuilder::new(WidgetBuilder::new())
+                                                            .with_content(
+                                                                MenuItemContent::text_with_shortcut(
+                                                                    "Redo", "Ctrl+Y",
+                                                                ),
+                                                            )
+                                                            .build(ctx);
+                                                    redo
+                                                },
+                                            ])
+                                            .build(ctx),
+                                        MenuItemBuilder::new(WidgetBuilder::new())
+                                            .with_content(MenuItemContent::text("Channel"))
+                                            .with_items(vec![
+                                                {
+                                                    add_channel =
+                                                        MenuItemBuilder::new(WidgetBuilder::new())
+                                                            .with_content(MenuItemContent::text(
+                                                                "Add",
+                                                            ))
+                                                            .build(ctx);
+                                                    add_channel
+                                                },
+                                                {
+                                                    remove_channel =
+                                                        MenuItemBuilder::new(WidgetBuilder::new())
+                                                            .with_content(MenuItemContent::text(
+                                                                "Remove Active",
+                                                            ))
+                                                            .build(ctx);
+                                                    remove_channel
+                                                },
+                                            ])
+                                            .build(ctx),
+                                    ])
+                                    .build(ctx),
+                            )
+                            .with_child(toolbar)
+                            .with_child(
+                                BorderBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(2)
+                                        .on_column(0)
+                                        .with_background(ctx.style.property(Style::BRUSH_DARKEST))
+                                        .with_child({
+                                            curve_editor = CurveEditorBuilder::new(
+                                                WidgetBuilder::new().with_enabled(false),
+                                            )
+                                            .with_snap(DEFAULT_SNAP_STEP, false)
+                                            .build(ctx);
+                                            curve_editor
+                                        }),
+                                )
                                 .build(ctx),
-                        )
-                        .with_child(
-                            BorderBuilder::new(
-                                WidgetBuilder::new()
-                                    .on_row(1)
-                                    .on_column(0)
-                                    .with_background(ctx.style.property(Style::BRUSH_DARKEST))
-                                    .with_child({
-                                        curve_editor = CurveEditorBuilder::new(
-                                            WidgetBuilder::new().with_enabled(false),
-                                        )
-                                        .build(ctx);
-                                        curve_editor
-                                    }),
                             )
-                            .build(ctx),
-                        )
-                        .with_child(
-                            StackPanelBuilder::new(
-                                WidgetBuilder::new()
-                                    .on_row(2)
-                                    .on_column(0)
-                                    .with_horizontal_alignment(HorizontalAlignment::Right)
-                                    .with_child({
-                                        ok = ButtonBuilder::new(
+                            .with_child(
+                                StackPanelBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(3)
+                                        .on_column(0)
+                                        .with_horizontal_alignment(HorizontalAlignment::Left)
+                                        .with_child({
+                                            channel_selector = DropdownListBuilder::new(
                                             WidgetBuilder::new()
+                                                .with_width(150.0)
                                                 .with_margin(Thickness::uniform(1.0))
-                                                .with_width(100.0),
+                                                .with_tooltip(make_simple_tooltip(
+                                                    ctx,
+                                                    "Active curve channel. Only the active channel \
+                                                    could be edited, the rest of the channels are \
+                                                    shown in the background.",
+                                                )),
                                         )
-                                        .with_text("OK")
                                         .build(ctx);
-                                        ok
-                                    })
-                                    .with_child({
-                                        cancel = ButtonBuilder::new(
+                                            channel_selector
+                                        })
+                                        .with_child({
+                                            zoom_to_fit = ButtonBuilder::new(
                                             WidgetBuilder::new()
                                                 .with_margin(Thickness::uniform(1.0))
-                                                .with_width(100.0),
+                                                .with_width(100.0)
+                                                .with_tooltip(make_simple_tooltip(
+                                                    ctx,
+                                                    "Changes the view so all the keys are visible.",
+                                                )),
                                         )
-                                        .with_text("Cancel")
+                                        .with_text("Zoom To Fit")
                                         .build(ctx);
-                                        cancel
-                                    }),
+                                            zoom_to_fit
+                                        })
+                                        .with_child({
+                                            cursor_value = TextBuilder::new(
+                                                WidgetBuilder::new()
+                                                    .with_margin(Thickness::left(5.0)),
+                                            )
+                                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                                            .build(ctx);
+                                            cursor_value
+                                        }),
+                                )
+                                .with_orientation(Orientation::Horizontal)
+                                .build(ctx),
                             )
-                            .with_orientation(Orientation::Horizontal)
-                            .build(ctx),
-                        ),
+                            .with_child(
+                                StackPanelBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(3)
+                                        .on_column(0)
+                                        .with_horizontal_alignment(HorizontalAlignment::Right)
+                                        .with_child({
+                                            ok = ButtonBuilder::new(
+                                                WidgetBuilder::new()
+                                                    .with_margin(Thickness::uniform(1.0))
+                                                    .with_width(100.0),
+                                            )
+                                            .with_text("OK")
+                                            .build(ctx);
+                                            ok
+                                        })
+                                        .with_child({
+                                            cancel = ButtonBuilder::new(
+                                                WidgetBuilder::new()
+                                                    .with_margin(Thickness::uniform(1.0))
+                                                    .with_width(100.0),
+                                            )
+                                            .with_text("Cancel")
+                                            .build(ctx);
+                                            cancel
+                                        }),
+                                )
+                                .with_orientation(Orientation::Horizontal)
+                                .build(ctx),
+                            ),
+                    )
+                    .add_row(Row::strict(25.0))
+                    .add_row(Row::strict(25.0))
+                    .add_row(Row::stretch())
+                    .add_row(Row::strict(25.0))
+                    .add_column(Column::stretch())
+                    .build(ctx),
                 )
-                .add_row(Row::strict(25.0))
-                .add_row(Row::stretch())
-                .add_row(Row::strict(25.0))
-                .add_column(Column::stretch())
-                .build(ctx),
-            )
-            .with_remove_on_close(true)
-            .with_title(WindowTitle::text("Curve Editor"))
-            .with_tab_label("Curve")
-            .build(ctx);
+                .with_remove_on_close(true)
+                .with_title(WindowTitle::text("Curve Editor"))
+                .with_tab_label("Curve")
+                .build(ctx);
 
         Self {
             window,
             curve_editor,
             ok,
             cancel,
-            curve_resource: None,
-            command_stack: CommandStack::new(false, 2048),
+            zoom_to_fit,
+            flat_tangents,
+            linear_tangents,
+            smooth_tangents,
+            flip_horizontal,
+            flip_vertical,
+            reverse,
+            normalize,
+            cursor_value,
+            selected_keys: Default::default(),
+            snap_enabled,
+            snap_x_step,
+            snap_y_step,
+            snap: (DEFAULT_SNAP_STEP, false),
+            curve_resources: Default::default(),
+            active_channel: 0,
+            channel_selector,
+            command_stack: CommandStack::new(false, 2048)
+                .with_memory_budget(COMMAND_STACK_MEMORY_BUDGET),
             menu: Menu {
-                file: FileMenu { new, save, load },
+                file: FileMenu {
+                    new,
+                    save,
+                    save_json,
+                    load,
+                    import_csv,
+                    bake,
+                },
                 edit: EditMenu { undo, redo },
+                channel: ChannelMenu {
+                    add: add_channel,
+                    remove: remove_channel,
+                },
             },
             load_file_selector,
+            import_csv_file_selector,
             save_file_selector,
+            save_json_file_selector,
             path: Default::default(),
             save_changes_message_box,
             modified: false,
             backup: Default::default(),
             cancel_message_box,
+            geometry,
         }
     }
 
@@ -321,61 +868,287 @@ impl CurveEditorWindow {
         ui.send(self.cancel_message_box, WidgetMessage::Remove);
         ui.send(self.save_changes_message_box, WidgetMessage::Remove);
         ui.send(self.load_file_selector, WidgetMessage::Remove);
+        ui.send(self.import_csv_file_selector, WidgetMessage::Remove);
         ui.send(self.save_file_selector, WidgetMessage::Remove);
+        ui.send(self.save_json_file_selector, WidgetMessage::Remove);
         ui.send(self.window, WindowMessage::Close);
     }
 
+    /// Returns current position and size of the window.
+    pub fn geometry(&self, ui: &UserInterface) -> Option<WindowGeometry> {
+        ui.try_get_node(self.window.to_base())
+            .ok()
+            .map(|window| WindowGeometry {
+                position: window.actual_local_position(),
+                size: window.actual_local_size(),
+            })
+    }
+
     pub fn open(&self, ui: &UserInterface) {
         ui.send(
             self.window,
             WindowMessage::Open {
-                alignment: WindowAlignment::Center,
+                alignment: self.geometry.map_or(WindowAlignment::Center, |geometry| {
+                    WindowAlignment::Position(geometry.position)
+                }),
                 modal: true,
                 focus_content: true,
             },
         );
     }
 
+    fn active_curve_resource(&self) -> Option<&CurveResource> {
+        self.curve_resources.get(self.active_channel)
+    }
+
+    /// Shows the value of the active curve at the location under the cursor, so the interpolation
+    /// between the keys could be previewed without adding new keys.
+    fn sync_cursor_value(&self, screen_pos: Vector2<f32>, ui: &UserInterface) {
+        let curve_resource = some_or_return!(self.active_curve_resource());
+        let curve_editor = ok_or_return!(ui.try_get(self.curve_editor));
+        let location = curve_editor.screen_to_curve_space(screen_pos).x;
+        let value = curve_resource.data_ref().value_at(location);
+        ui.send(
+            self.cursor_value,
+            TextMessage::Text(format!("X: {location:.3} Value: {value:.3}")),
+        );
+    }
+
     fn sync_to_model(&mut self, ui: &UserInterface) {
-        if let Some(curve_resource) = self.curve_resource.as_ref() {
-            ui.send_sync(
-                self.curve_editor,
-                CurveEditorMessage::Sync(vec![curve_resource.data_ref().curve.clone()]),
-            );
+        if let Some(curve_resource) = self.active_curve_resource() {
+            let curve = curve_resource.data_ref().curve.clone();
+            let color_map = vec![(curve.id(), Brush::Solid(channel_color(self.active_channel)))];
+            ui.send_sync(self.curve_editor, CurveEditorMessage::Sync(vec![curve]));
+            ui.send_sync(self.curve_editor, CurveEditorMessage::Colorize(color_map));
         }
+
+        let background_curves = self
+            .curve_resources
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| *index != self.active_channel)
+            .map(|(_, curve_resource)| curve_resource.data_ref().curve.clone())
+            .collect::<Vec<_>>();
+        ui.send_sync(
+            self.curve_editor,
+            CurveEditorMessage::SyncBackground(background_curves),
+        );
+    }
+
+    fn sync_channel_selector(&self, ui: &mut UserInterface) {
+        let ctx = &mut ui.build_ctx();
+        let items = self
+            .curve_resources
+            .iter()
+            .enumerate()
+            .map(|(index, curve_resource)| {
+                let curve_resource = curve_resource.data_ref();
+                let name = if curve_resource.curve.name().is_empty() {
+                    format!("Channel {index}")
+                } else {
+                    curve_resource.curve.name().to_string()
+                };
+                make_dropdown_list_option(ctx, &name)
+            })
+            .collect::<Vec<_>>();
+        ui.send_sync(self.channel_selector, DropdownListMessage::Items(items));
+        ui.send_sync(
+            self.channel_selector,
+            DropdownListMessage::Selection(Some(self.active_channel)),
+        );
     }
 
     fn save(&self) {
-        if let Some(curve_resource) = self.curve_resource.as_ref() {
-            if let Some(state) = curve_resource.state().data() {
-                let mut visitor = Visitor::new();
-                state.curve.visit("Curve", &mut visitor).unwrap();
-                visitor.save_binary_to_file(&self.path).unwrap();
+        let channels = self
+            .curve_resources
+            .iter()
+            .filter_map(|curve_resource| {
+                curve_resource
+                    .state()
+                    .data()
+                    .map(|state| state.curve.clone())
+            })
+            .collect::<Vec<_>>();
+        if is_json_path(&self.path) {
+            let result = curves_to_json(&channels)
+                .map_err(|err| err.to_string())
+                .and_then(|text| std::fs::write(&self.path, text).map_err(|err| err.to_string()));
+            if let Err(err) = result {
+                Log::err(format!(
+                    "Unable to save the curve to {}. Reason: {err}",
+                    self.path.display()
+                ));
             }
+        } else if let Some(first) = channels.first() {
+            let mut visitor = Visitor::new();
+            // The first channel is also written separately, so the file could be loaded as a
+            // plain curve resource.
+            first.clone().visit("Curve", &mut visitor).unwrap();
+            channels
+                .clone()
+                .visit(CHANNELS_REGION, &mut visitor)
+                .unwrap();
+            visitor.save_binary_to_file(&self.path).unwrap();
         }
     }
 
-    fn set_curve(
+    /// Writes the curve, evaluated at evenly spaced points, next to the curve file. Each value is
+    /// written on a separate line.
+    fn bake(&self) {
+        let Some(curve_resource) = self.active_curve_resource() else {
+            return;
+        };
+
+        if self.path == PathBuf::default() {
+            Log::warn("Unable to bake the curve, because it is not saved. Save the curve first.");
+            return;
+        }
+
+        let values = curve_resource.data_ref().bake(BAKE_SAMPLE_COUNT);
+        let text = values
+            .iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let path = self.path.with_extension("baked.txt");
+        match std::fs::write(&path, text) {
+            Ok(()) => Log::info(format!(
+                "The curve was successfully baked to {}",
+                path.display()
+            )),
+            Err(err) => Log::err(format!(
+                "Unable to write baked curve to {}. Reason: {err}",
+                path.display()
+            )),
+        }
+    }
+
+    fn import_csv(&mut self, path: &Path, ui: &UserInterface) {
+        let Some(curve_resource) = self.active_curve_resource().cloned() else {
+            return;
+        };
+
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) => {
+                Log::err(format!(
+                    "Unable to import a curve from {}. Reason: {err}",
+                    path.display()
+                ));
+                return;
+            }
+        };
+
+        let mut curve = curve_resource.data_ref().curve.clone();
+        curve.keys = Curve::from(parse_csv_keys(&text)).keys;
+
+        self.do_command(Command::new(ModifyCurveCommand {
+            curve_resource,
+            curve,
+        }));
+
+        self.modified = true;
+
+        self.sync_to_model(ui);
+    }
+
+    fn sync_snap(&self, ui: &UserInterface) {
+        let (step, enabled) = self.snap;
+        ui.send(
+            self.curve_editor,
+            CurveEditorMessage::SetSnap {
+                x_step: step.x,
+                y_step: step.y,
+                enabled,
+            },
+        );
+    }
+
+    fn apply_tangent_preset(&mut self, preset: TangentPreset, ui: &UserInterface) {
+        let selected_keys = std::mem::take(&mut self.selected_keys);
+        self.modify_active_curve(ui, |curve| {
+            apply_tangent_preset(curve, &selected_keys, preset)
+        });
+        self.selected_keys = selected_keys;
+    }
+
+    /// Calls the given function with the command stack and the command context, that holds the
+    /// channels of the edited curve. Changes of the channels made by the commands are written back.
+    fn with_command_context<F>(&mut self, func: F)
+    where
+        F: FnOnce(&mut CommandStack, &mut CurveEditorContext),
+    {
+        let mut context = CurveEditorContext {
+            channels: std::mem::take(&mut self.curve_resources),
+            active_channel: self.active_channel,
+        };
+        func(&mut self.command_stack, &mut context);
+        self.curve_resources = context.channels;
+        self.active_channel = context.active_channel;
+    }
+
+    fn do_command(&mut self, command: Command) {
+        self.with_command_context(|command_stack, context| {
+            command_stack.do_command(command, context)
+        });
+    }
+
+    /// Modifies a copy of the active curve using the given function and commits the result using
+    /// [`ModifyCurveCommand`]. The function must return `true` if the curve was modified.
+    fn modify_active_curve<F>(&mut self, ui: &UserInterface, func: F)
+    where
+        F: FnOnce(&mut Curve) -> bool,
+    {
+        let Some(curve_resource) = self.active_curve_resource().cloned() else {
+            return;
+        };
+
+        let mut curve = curve_resource.data_ref().curve.clone();
+        if !func(&mut curve) {
+            return;
+        }
+
+        self.do_command(Command::new(ModifyCurveCommand {
+            curve_resource,
+            curve,
+        }));
+
+        self.modified = true;
+
+        self.sync_to_model(ui);
+    }
+
+    fn set_curves(
         &mut self,
         resource_manager: &ResourceManager,
-        curve: CurveResource,
-        ui: &UserInterface,
+        curves: Vec<CurveResource>,
+        ui: &mut UserInterface,
     ) {
-        self.backup = curve.data_ref().curve.clone();
-        self.curve_resource = Some(curve);
+        self.backup = curves
+            .iter()
+            .map(|curve| (curve.clone(), curve.data_ref().curve.clone()))
+            .collect();
+        self.curve_resources = curves;
+        self.active_channel = 0;
 
         ui.send(self.curve_editor, WidgetMessage::Enabled(true));
 
         self.sync_to_model(ui);
+        self.sync_channel_selector(ui);
         self.sync_title(resource_manager, ui);
 
+        ui.send(
+            self.curve_editor,
+            CurveEditorMessage::ZoomToFit { after_layout: true },
+        );
+
         self.modified = false;
 
-        self.command_stack.clear(&mut CurveEditorContext {});
+        self.with_command_context(|command_stack, context| command_stack.clear(context));
     }
 
     fn sync_title(&self, resource_manager: &ResourceManager, ui: &UserInterface) {
-        let title = if let Some(curve_resource) = self.curve_resource.as_ref() {
+        let title = if let Some(curve_resource) = self.curve_resources.first() {
             match resource_manager.resource_path(curve_resource.as_ref()) {
                 Some(path) => {
                     format!("Curve Editor - {}", path.display())
@@ -389,20 +1162,35 @@ impl CurveEditorWindow {
         ui.send(self.window, WindowMessage::Title(WindowTitle::text(title)));
     }
 
-    fn revert(&self) {
-        if let Some(curve_resource) = self.curve_resource.as_ref() {
-            curve_resource.data_ref().curve = self.backup.clone();
+    fn revert(&mut self) {
+        for (curve_resource, curve) in self.backup.iter() {
+            curve_resource.data_ref().curve = curve.clone();
         }
+        self.curve_resources = self
+            .backup
+            .iter()
+            .map(|(curve_resource, _)| curve_resource.clone())
+            .collect();
+        self.active_channel = 0;
     }
 
     fn open_save_file_dialog(&self, resource_manager: &ResourceManager, ui: &UserInterface) {
+        self.open_file_dialog(self.save_file_selector, resource_manager, ui);
+    }
+
+    fn open_file_dialog(
+        &self,
+        file_selector: Handle<FileSelector>,
+        resource_manager: &ResourceManager,
+        ui: &UserInterface,
+    ) {
         ui.send(
-            self.save_file_selector,
+            file_selector,
             FileSelectorMessage::Root(Some(resource_manager.registry_folder())),
         );
 
         ui.send(
-            self.save_file_selector,
+            file_selector,
             WindowMessage::Open {
                 alignment: WindowAlignment::Center,
                 modal: true,
@@ -411,12 +1199,150 @@ impl CurveEditorWindow {
         );
     }
 
+    fn on_menu_item_clicked(
+        &mut self,
+        item: Handle<UiNode>,
+        resource_manager: &ResourceManager,
+        ui: &mut UserInterface,
+    ) {
+        if item == self.menu.edit.undo {
+            self.with_command_context(|command_stack, context| command_stack.undo(context));
+
+            self.sync_to_model(ui);
+            self.sync_channel_selector(ui);
+        } else if item == self.menu.edit.redo {
+            self.with_command_context(|command_stack, context| command_stack.redo(context));
+
+            self.sync_to_model(ui);
+            self.sync_channel_selector(ui);
+        } else if item == self.menu.file.load {
+            ui.send(
+                self.load_file_selector,
+                FileSelectorMessage::Root(Some(resource_manager.registry_folder())),
+            );
+
+            ui.send(
+                self.load_file_selector,
+                WindowMessage::Open {
+                    alignment: WindowAlignment::Center,
+                    modal: true,
+                    focus_content: true,
+                },
+            );
+        } else if item == self.menu.file.new {
+            self.path = Default::default();
+
+            self.set_curves(
+                resource_manager,
+                vec![Resource::new_embedded(CurveResourceState::default())],
+                ui,
+            );
+        } else if item == self.menu.file.save {
+            if self.path == PathBuf::default() {
+                self.open_save_file_dialog(resource_manager, ui);
+            } else {
+                self.save();
+            }
+        } else if item == self.menu.file.save_json {
+            if !self.curve_resources.is_empty() {
+                self.open_file_dialog(self.save_json_file_selector, resource_manager, ui);
+            }
+        } else if item == self.menu.file.import_csv {
+            if !self.curve_resources.is_empty() {
+                ui.send(
+                    self.import_csv_file_selector,
+                    WindowMessage::Open {
+                        alignment: WindowAlignment::Center,
+                        modal: true,
+                        focus_content: true,
+                    },
+                );
+            }
+        } else if item == self.menu.file.bake {
+            self.bake();
+        } else if item == self.menu.channel.add {
+            if !self.curve_resources.is_empty() {
+                self.do_command(Command::new(AddChannelCommand {
+                    curve_resource: Resource::new_embedded(CurveResourceState::default()),
+                    prev_active_channel: self.active_channel,
+                }));
+                self.modified = true;
+
+                self.sync_to_model(ui);
+                self.sync_channel_selector(ui);
+            }
+        } else if item == self.menu.channel.remove {
+            // There must be at least one channel.
+            if self.curve_resources.len() > 1 {
+                self.do_command(Command::new(RemoveChannelCommand {
+                    index: self.active_channel,
+                    curve_resource: None,
+                    prev_active_channel: self.active_channel,
+                }));
+                self.modified = true;
+
+                self.sync_to_model(ui);
+                self.sync_channel_selector(ui);
+            }
+        }
+    }
+
+    /// Maps a hot key to the respective menu item. Hot keys are the same as the shortcuts shown
+    /// in the menu.
+    fn hot_key_menu_item(&self, key: KeyCode, ui: &UserInterface) -> Option<Handle<MenuItem>> {
+        if !ui.keyboard_modifiers().control {
+            return None;
+        }
+        match key {
+            KeyCode::KeyZ => Some(self.menu.edit.undo),
+            KeyCode::KeyY => Some(self.menu.edit.redo),
+            KeyCode::KeyS => Some(self.menu.file.save),
+            KeyCode::KeyN => Some(self.menu.file.new),
+            KeyCode::KeyL => Some(self.menu.file.load),
+            _ => None,
+        }
+    }
+
     pub fn handle_ui_message(mut self, message: &UiMessage, engine: &mut Engine) -> Option<Self> {
-        let ui = &engine.user_interfaces.first_mut();
+        let ui = engine.user_interfaces.first_mut();
 
         if let Some(ButtonMessage::Click) = message.data() {
-            if message.destination() == self.cancel {
-                if self.modified && self.curve_resource.is_some() {
+            if message.destination() == self.zoom_to_fit {
+                ui.send(
+                    self.curve_editor,
+                    CurveEditorMessage::ZoomToFit {
+                        after_layout: false,
+                    },
+                );
+            } else if message.destination() == self.flat_tangents {
+                self.apply_tangent_preset(TangentPreset::Flat, ui);
+            } else if message.destination() == self.linear_tangents {
+                self.apply_tangent_preset(TangentPreset::Linear, ui);
+            } else if message.destination() == self.smooth_tangents {
+                self.apply_tangent_preset(TangentPreset::Smooth, ui);
+            } else if message.destination() == self.flip_horizontal {
+                self.modify_active_curve(ui, |curve| {
+                    curve.flip_horizontal();
+                    curve.keys().len() > 1
+                });
+            } else if message.destination() == self.flip_vertical {
+                self.modify_active_curve(ui, |curve| {
+                    curve.flip_vertical();
+                    curve.keys().len() > 1
+                });
+            } else if message.destination() == self.reverse {
+                self.modify_active_curve(ui, |curve| {
+                    curve.reverse();
+                    curve.keys().len() > 1
+                });
+            } else if message.destination() == self.normalize {
+                self.modify_active_curve(ui, |curve| {
+                    let old_curve = curve.clone();
+                    curve.normalize_values();
+                    *curve != old_curve
+                });
+            } else if message.destination() == self.cancel {
+                if self.modified && !self.curve_resources.is_empty() {
                     ui.send(
                         self.cancel_message_box,
                         MessageBoxMessage::Open {
@@ -429,7 +1355,7 @@ impl CurveEditorWindow {
                     return None;
                 }
             } else if message.destination() == self.ok {
-                if self.modified && self.curve_resource.is_some() {
+                if self.modified && !self.curve_resources.is_empty() {
                     if self.path == PathBuf::default() {
                         ui.send(
                             self.save_changes_message_box,
@@ -449,66 +1375,89 @@ impl CurveEditorWindow {
                 }
             }
         } else if let Some(CurveEditorMessage::Sync(curve)) = message.data_from(self.curve_editor) {
-            if let Some(curve_resource) = self.curve_resource.as_ref() {
-                self.command_stack.do_command(
-                    Command::new(ModifyCurveCommand {
-                        curve_resource: curve_resource.clone(),
-                        curve: curve.first().cloned().unwrap(),
-                    }),
-                    &mut CurveEditorContext {},
-                );
+            if let Some(curve_resource) = self.active_curve_resource().cloned() {
+                self.do_command(Command::new(ModifyCurveCommand {
+                    curve_resource,
+                    curve: curve.first().cloned().unwrap(),
+                }));
 
                 self.modified = true;
             }
+        } else if let Some(CurveEditorMessage::SelectedKeys(keys)) =
+            message.data_from(self.curve_editor)
+        {
+            self.selected_keys.clone_from(keys);
+        } else if let Some(WidgetMessage::MouseMove { pos, .. }) =
+            message.data_from(self.curve_editor)
+        {
+            self.sync_cursor_value(*pos, ui);
+        } else if let Some(WidgetMessage::MouseLeave) = message.data_from(self.curve_editor) {
+            ui.send(self.cursor_value, TextMessage::Text(Default::default()));
+        } else if let Some(CheckBoxMessage::Check(Some(enabled))) =
+            message.data_from(self.snap_enabled)
+        {
+            self.snap.1 = *enabled;
+            self.sync_snap(ui);
+        } else if let Some(NumericUpDownMessage::<f32>::Value(step)) =
+            message.data_from(self.snap_x_step)
+        {
+            self.snap.0.x = *step;
+            self.sync_snap(ui);
+        } else if let Some(NumericUpDownMessage::<f32>::Value(step)) =
+            message.data_from(self.snap_y_step)
+        {
+            self.snap.0.y = *step;
+            self.sync_snap(ui);
         } else if let Some(MenuItemMessage::Click) = message.data() {
-            if message.destination() == self.menu.edit.undo {
-                self.command_stack.undo(&mut CurveEditorContext {});
-
-                self.sync_to_model(ui);
-            } else if message.destination() == self.menu.edit.redo {
-                self.command_stack.redo(&mut CurveEditorContext {});
-
-                self.sync_to_model(ui);
-            } else if message.destination() == self.menu.file.load {
-                ui.send(
-                    self.load_file_selector,
-                    FileSelectorMessage::Root(Some(engine.resource_manager.registry_folder())),
-                );
-
-                ui.send(
-                    self.load_file_selector,
-                    WindowMessage::Open {
-                        alignment: WindowAlignment::Center,
-                        modal: true,
-                        focus_content: true,
-                    },
-                );
-            } else if message.destination() == self.menu.file.new {
-                self.path = Default::default();
-
-                self.set_curve(
-                    &engine.resource_manager,
-                    Resource::new_embedded(CurveResourceState::default()),
-                    ui,
-                );
-            } else if message.destination() == self.menu.file.save {
-                if self.path == PathBuf::default() {
-                    self.open_save_file_dialog(&engine.resource_manager, ui);
-                } else {
-                    self.save();
+            self.on_menu_item_clicked(message.destination(), &engine.resource_manager, ui);
+        } else if let Some(WidgetMessage::KeyDown(key)) = message.data() {
+            // Text fields handle keyboard input themselves and mark the message as handled, so
+            // shortcuts will not fire while typing.
+            if !message.handled() && ui.has_descendant_or_equal(message.destination(), self.window)
+            {
+                if let Some(item) = self.hot_key_menu_item(*key, ui) {
+                    self.on_menu_item_clicked(item.to_base(), &engine.resource_manager, ui);
+                    message.set_handled(true);
                 }
             }
+        } else if let Some(DropdownListMessage::Selection(Some(index))) =
+            message.data_from(self.channel_selector)
+        {
+            if *index != self.active_channel && *index < self.curve_resources.len() {
+                self.active_channel = *index;
+                self.sync_to_model(ui);
+            }
         } else if let Some(FileSelectorMessage::Commit(path)) = message.data() {
-            if message.destination() == self.load_file_selector {
+            if message.destination() == self.load_file_selector && is_json_path(path) {
+                let curves = load_json_channels(path);
+                if !curves.is_empty() {
+                    self.path.clone_from(path);
+                    let curves = curves
+                        .into_iter()
+                        .map(|curve| Resource::new_embedded(CurveResourceState { curve }))
+                        .collect();
+                    self.set_curves(&engine.resource_manager, curves, ui);
+                }
+            } else if message.destination() == self.load_file_selector {
                 if let Ok(curve) =
                     block_on(engine.resource_manager.request::<CurveResourceState>(path))
                 {
                     self.path.clone_from(path);
-                    self.set_curve(&engine.resource_manager, curve, ui);
+                    let mut curves = vec![curve];
+                    curves.extend(
+                        load_additional_channels(path)
+                            .into_iter()
+                            .map(|curve| Resource::new_embedded(CurveResourceState { curve })),
+                    );
+                    self.set_curves(&engine.resource_manager, curves, ui);
                 }
-            } else if message.destination() == self.save_file_selector {
+            } else if message.destination() == self.save_file_selector
+                || message.destination() == self.save_json_file_selector
+            {
                 self.path.clone_from(path);
                 self.save();
+            } else if message.destination() == self.import_csv_file_selector {
+                self.import_csv(path, ui);
             }
         } else if let Some(MessageBoxMessage::Close(result)) =
             message.data_from(self.save_changes_message_box)
@@ -554,9 +1503,10 @@ impl CurveEditorPlugin {
     fn on_open_curve_editor_clicked(&mut self, editor: &mut Editor) {
         let ui = editor.engine.user_interfaces.first_mut();
         let ctx = &mut ui.build_ctx();
+        let geometry = editor.settings.windows.curve_editor_window;
         let curve_editor = self
             .curve_editor_window
-            .get_or_insert_with(|| CurveEditorWindow::new(ctx));
+            .get_or_insert_with(|| CurveEditorWindow::new(ctx, geometry));
         curve_editor.open(ui);
     }
 }
@@ -579,6 +1529,103 @@ impl EditorPlugin for CurveEditorPlugin {
             }
         }
         let curve_editor = some_or_return!(self.curve_editor_window.take());
+        let geometry = curve_editor.geometry(editor.engine.user_interfaces.first());
         self.curve_editor_window = curve_editor.handle_ui_message(message, &mut editor.engine);
+        if self.curve_editor_window.is_none() && geometry.is_some() {
+            // The window was closed, remember its geometry to restore it the next time.
+            editor.settings.windows.curve_editor_window = geometry;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        fyrox::core::math::curve::{Curve, CurveKey, CurveKeyKind},
+        plugins::curve_editor::{apply_tangent_preset, parse_csv_keys, TangentPreset},
+    };
+
+    #[test]
+    fn test_parse_csv_keys() {
+        let keys = parse_csv_keys("0.0,1.0\n\n 1.5 , 2.0 \nbroken\n2.0,3.0,4.0\n3.0,x\n4.0,-1.0");
+        let keys = keys
+            .iter()
+            .map(|key| (key.location(), key.value))
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec![(0.0, 1.0), (1.5, 2.0), (4.0, -1.0)]);
+    }
+
+    #[test]
+    fn test_apply_tangent_preset() {
+        let mut curve = Curve::from(vec![
+            CurveKey::new(0.0, 0.0, CurveKeyKind::Linear),
+            CurveKey::new(1.0, 2.0, CurveKeyKind::Linear),
+            CurveKey::new(3.0, 0.0, CurveKeyKind::Linear),
+        ]);
+        let ids = curve.keys().iter().map(|key| key.id).collect::<Vec<_>>();
+        let tangents = |curve: &Curve, index: usize| match curve.keys()[index].kind {
+            CurveKeyKind::Cubic {
+                left_tangent,
+                right_tangent,
+            } => (left_tangent, right_tangent),
+            _ => panic!("The key must be cubic!"),
+        };
+
+        // No selection is a no-op.
+        assert!(!apply_tangent_preset(&mut curve, &[], TangentPreset::Flat));
+        assert_eq!(curve.keys()[1].kind, CurveKeyKind::Linear);
+
+        assert!(apply_tangent_preset(
+            &mut curve,
+            &ids[1..2],
+            TangentPreset::Linear
+        ));
+        assert_eq!(tangents(&curve, 1), (1.0, -1.0));
+        assert_eq!(curve.keys()[0].kind, CurveKeyKind::Linear);
+
+        assert!(apply_tangent_preset(
+            &mut curve,
+            &ids,
+            TangentPreset::Smooth
+        ));
+        assert_eq!(tangents(&curve, 0), (1.0, 1.0));
+        assert_eq!(tangents(&curve, 1), (0.0, 0.0));
+        assert_eq!(tangents(&curve, 2), (-1.0, -1.0));
+
+        assert!(apply_tangent_preset(&mut curve, &ids, TangentPreset::Flat));
+        assert_eq!(tangents(&curve, 0), (0.0, 0.0));
+        // Applying the same preset again does not modify the curve.
+        assert!(!apply_tangent_preset(&mut curve, &ids, TangentPreset::Flat));
+    }
+
+    #[test]
+    fn test_linear_tangent_preset_gives_straight_lines() {
+        let cubic = CurveKeyKind::Cubic {
+            left_tangent: 3.0,
+            right_tangent: -2.0,
+        };
+        let mut curve = Curve::from(vec![
+            CurveKey::new(0.0, 0.0, cubic.clone()),
+            CurveKey::new(1.0, 2.0, cubic.clone()),
+            CurveKey::new(3.0, 0.5, cubic.clone()),
+            CurveKey::new(7.0, 0.5, cubic.clone()),
+            CurveKey::new(8.0, -4.0, cubic),
+        ]);
+        let ids = curve.keys().iter().map(|key| key.id).collect::<Vec<_>>();
+        assert!(apply_tangent_preset(
+            &mut curve,
+            &ids,
+            TangentPreset::Linear
+        ));
+
+        for pair in curve.keys().windows(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+            for i in 0..=10 {
+                let t = i as f32 / 10.0;
+                let location = a.location() + (b.location() - a.location()) * t;
+                let expected = a.value + (b.value - a.value) * t;
+                assert!((curve.value_at(location) - expected).abs() < 1.0e-4);
+            }
+        }
     }
 }
@@ -103,15 +103,15 @@ use crate::{
             self,
             base::{Base, LevelOfDetail, LodGroup, Property, PropertyValue, ScriptRecord},
             camera::{
-                Camera, ColorGradingLut, Exposure, OrthographicProjection, PerspectiveProjection,
-                Projection,
+                Camera, CameraClearColor, ColorGradingLut, Exposure, FrustumProjection,
+                OrthographicProjection, PerspectiveProjection, Projection, SizeMode,
             },
             collider::{
                 BallShape, BitMask, CapsuleShape, Collider, ColliderShape, ConeShape,
                 ConvexPolyhedronShape, CuboidShape, CylinderShape, GeometrySource,
                 HeightfieldShape, InteractionGroups, SegmentShape, TriangleShape, TrimeshShape,
             },
-            decal::Decal,
+            decal::{Decal, DecalColorSpace},
             dim2,
             graph::physics::CoefficientCombineRule,
             joint::*,
@@ -158,7 +158,7 @@ use crate::{
                 Tile, TileCollider, TileDefinitionHandle, TileMap,
             },
             transform::Transform,
-            EnvironmentLightingSource,
+            EnvironmentLightingSource, GBufferDebugTarget,
         },
     },
     message::MessageSender,
@@ -469,6 +469,7 @@ pub fn make_property_editors_container(
     container.register_inheritable_inspectable::<CuboidEmitter>();
     container.register_inheritable_inspectable::<PerspectiveProjection>();
     container.register_inheritable_inspectable::<OrthographicProjection>();
+    container.register_inheritable_inspectable::<FrustumProjection>();
     container.register_inheritable_inspectable::<Transform>();
     container.register_inheritable_inspectable::<CsmOptions>();
     container.register_inheritable_inspectable::<HdrSettings>();
@@ -496,13 +497,17 @@ pub fn make_property_editors_container(
     container.register_inheritable_enum::<PropertyValue, _>();
     container.register_inheritable_enum::<RigidBodyType, _>();
     container.register_inheritable_enum::<Exposure, _>();
+    container.register_inheritable_enum::<CameraClearColor, _>();
+    container.register_inheritable_enum::<SizeMode, _>();
     container.register_inheritable_enum::<FrustumSplitOptions, _>();
     container.register_inheritable_enum::<MaterialSearchOptions, _>();
     container.register_inheritable_enum::<DistanceModel, _>();
     container.register_inheritable_enum::<sound::Renderer, _>();
     container.register_inheritable_enum::<RenderPath, _>();
+    container.register_inheritable_enum::<DecalColorSpace, _>();
     container.register_inheritable_enum::<TexturePixelKind, _>();
     container.register_inheritable_enum::<EnvironmentLightingSource, _>();
+    container.register_inheritable_enum::<GBufferDebugTarget, _>();
     container.register_inheritable_enum::<CoordinateSystem, _>();
     container.register_inheritable_enum::<UpdateMode, _>();
     container.register_inheritable_enum::<LuminanceCalculationMethod, _>();
@@ -764,7 +764,7 @@ impl BrushMacro for AutoTileMacro {
         &mut self,
         _context: &BrushMacroInstance,
         _update: &mut MacroTilesUpdate,
-        _tile_map: &TileMapContext,
+        _tile_map: &mut TileMapContext,
     ) -> Option<Command> {
         None
     }
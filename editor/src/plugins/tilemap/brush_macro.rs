@@ -106,6 +106,13 @@ impl TileMapContext<'_> {
             .cast()
             .unwrap()
     }
+    /// The tile map that is being edited, for modifications such as adding
+    /// effects to change how the tile map renders.
+    pub fn tile_map_mut(&mut self) -> &mut TileMap {
+        self.engine.scenes[self.scene].graph[self.node]
+            .cast_mut()
+            .unwrap()
+    }
     /// The tile set resource from within the tile map, if the tile map has one.
     pub fn tile_set(&self) -> Option<&TileSetResource> {
         self.tile_map().tile_set()
@@ -402,8 +409,21 @@ pub trait BrushMacro: 'static + Send + Sync {
         &mut self,
         context: &BrushMacroInstance,
         update: &mut MacroTilesUpdate,
-        tile_map: &TileMapContext,
+        tile_map: &mut TileMapContext,
     ) -> Option<Command>;
+    /// This is called once per frame, allowing the macro to update any widgets that it owns
+    /// outside of the brush editor, such as a window that shows the progress of a long task.
+    fn update(&mut self, _ui: &mut UserInterface) {}
+    /// Handle any UI message, regardless of what the brush editor is displaying. This allows
+    /// the macro to respond to the widgets that it owns outside of the brush editor.
+    /// The `sender` may be used to submit commands in response to those widgets.
+    fn on_ui_message(
+        &mut self,
+        _message: &UiMessage,
+        _ui: &mut UserInterface,
+        _sender: &MessageSender,
+    ) {
+    }
 }
 
 /// List of [`BrushMacro`] implementations that the [`TileMapEditorPlugin`] keeps in order to allow
@@ -374,7 +374,7 @@ fn macro_amend_update(
 }
 
 fn macro_command_list(
-    tile_map: &TileMapContext,
+    tile_map: &mut TileMapContext,
     update: &mut TransTilesUpdate,
     macro_update: &mut MacroTilesUpdate,
     brush: &TileMapBrushResource,
@@ -626,14 +626,14 @@ impl InteractionMode for TileMapInteractionMode {
                     state.tile_set.as_ref().or(tile_map.tile_set()).cloned()
                 {
                     let update_source = &mut self.update_effect.safe_lock().update;
-                    let tile_map_context = TileMapContext {
+                    let mut tile_map_context = TileMapContext {
                         node: tile_map_handle,
                         scene: scene_handle,
                         engine,
                     };
                     let mut commands = if let Some(brush) = state.stamp.brush() {
                         macro_command_list(
-                            &tile_map_context,
+                            &mut tile_map_context,
                             update_source,
                             &mut self.macro_update,
                             brush,
@@ -654,7 +654,9 @@ impl InteractionMode for TileMapInteractionMode {
                         self.sender.do_command(
                             CommandGroup::from(commands).with_custom_name("Draw Tiles"),
                         );
-                    } else {
+                    } else if !command.tiles.is_empty() {
+                        // A macro may have taken every tile out of the update,
+                        // in which case there is nothing to put on the undo stack.
                         self.sender.do_command(command);
                     }
                     update_source.clear();
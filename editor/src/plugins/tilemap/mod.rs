@@ -772,6 +772,10 @@ impl EditorPlugin for TileMapEditorPlugin {
 
         let ui = editor.engine.user_interfaces.first_mut();
 
+        for brush_macro in self.brush_macro_list.lock().iter_mut() {
+            brush_macro.on_ui_message(message, ui, &editor.message_sender);
+        }
+
         if let Some(OpenTilePanelMessage { resource, center }) = message.data() {
             self.open_panel_for_tile_set(
                 resource.clone(),
@@ -794,6 +798,10 @@ impl EditorPlugin for TileMapEditorPlugin {
     fn on_update(&mut self, editor: &mut Editor, _loop_controller: ApplicationLoopController) {
         self.send_delayed_messages(editor.engine.user_interfaces.first_mut());
 
+        for brush_macro in self.brush_macro_list.lock().iter_mut() {
+            brush_macro.update(editor.engine.user_interfaces.first_mut());
+        }
+
         self.update_state();
 
         if self.state.check_dirty() {
@@ -18,15 +18,16 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use std::hash::Hash;
+use std::{borrow::Cow, hash::Hash};
 
-use super::*;
+use super::{commands::SetMapTilesCommand, *};
 use crate::command::{Command, CommandContext, CommandGroup, CommandTrait};
 use fyrox::gui::check_box::CheckBox;
 use fyrox::gui::numeric::NumericUpDown;
 use fyrox::gui::stack_panel::StackPanel;
 use fyrox::{
     asset::{untyped::UntypedResource, Resource, ResourceData},
+    autotile::WfcControlFlow,
     core::{swap_hash_map_entry, SafeLock},
     fxhash::FxHashMap,
     gui::{
@@ -34,22 +35,31 @@ use fyrox::{
         check_box::{CheckBoxBuilder, CheckBoxMessage},
         formatted_text::WrapMode,
         numeric::{NumericUpDownBuilder, NumericUpDownMessage},
+        progress_bar::{ProgressBar, ProgressBarBuilder, ProgressBarMessage},
         stack_panel::StackPanelBuilder,
+        text::{Text, TextMessage},
+        window::{Window, WindowAlignment, WindowBuilder, WindowMessage, WindowTitle},
     },
-    rand::thread_rng,
+    rand::{rngs::StdRng, thread_rng, Rng, SeedableRng},
     scene::tilemap::{
         brush::TileMapBrushResource,
         tileset::{
-            NamableValue, TileSetPropertyF32, TileSetPropertyId, TileSetPropertyNine,
-            TileSetPropertyType, TileSetPropertyValueElement,
+            NamableValue, TileSetPropertyF32, TileSetPropertyI32, TileSetPropertyId,
+            TileSetPropertyNine, TileSetPropertyType, TileSetPropertyValueElement,
         },
-        MacroTilesUpdate, TileMapDataResource, TileSetWfcConstraint, TileSetWfcPropagator,
-        TileTerrainId,
+        MacroTilesUpdate, TileColorEffect, TileMapDataResource, TilePreviewEffect,
+        TileSetWfcConstraint, TileSetWfcPropagator, TileTerrainId, TilesUpdate,
     },
 };
 
 const DEFAULT_MAX_ATTEMPTS: u32 = 300;
+const DEFAULT_BACKTRACK_DEPTH: u32 = 0;
 const DEFAULT_CONSTRAIN_EDGES: bool = true;
+const DEFAULT_CONSTRAIN_NEIGHBORS: bool = true;
+/// The opacity of the tiles that show the result of a preview.
+const PREVIEW_ALPHA: u8 = 160;
+/// The opacity of the colors of the entropy overlay.
+const ENTROPY_ALPHA: u8 = 128;
 
 const PATTERN_PROP_DESC: &str = concat!("Choose a nine-slice property from the tile set. ",
     "This property will provide the pattern that the autotiler uses to know whether two tiles match along each edge. ");
@@ -58,28 +68,122 @@ const FREQUENCY_PROP_DESC: &str = concat!("Choose a float property from the tile
     "This property will provide the frequency that the autotiler uses to know know often to choose a tile when there is more than one ",
     "tile with the same pattern.");
 
+const COST_PROP_DESC: &str = concat!("Choose a float property from the tile set. ",
+    "This property will provide the placement cost of each tile. Cheaper tiles are chosen preferentially, ",
+    "which produces more coherent results. Leave it empty to only use frequencies.");
+
+const COHESION_PROP_DESC: &str = concat!("Choose a float property from the tile set. ",
+    "This property will provide the cohesion of each tile. Tiles with higher cohesion are more likely to be chosen ",
+    "next to tiles of the same terrain, which forms clusters. Leave it empty to only use frequencies.");
+
+const TERRAIN_FREQUENCY_PROP_DESC: &str = concat!("Choose a float property from the tile set. ",
+    "The frequency of each terrain will be the average value of this property among the tiles of that terrain, ",
+    "and the terrain list below will be ignored. Empty cells are not generated in this mode. ",
+    "Leave it empty to enter the frequency of each terrain by hand.");
+
+const FORBIDDEN_ADJACENCY_DESC: &str = concat!(
+    "Choose two terrains that must never be placed next to each other, ",
+    "even when the patterns of their tiles match."
+);
+
+const TRANSFORM_PROP_DESC: &str = concat!("Choose an integer property from the tile set. ",
+    "Tiles with a non-zero value may also be placed rotated or reflected, using the transformed versions ",
+    "of the tile from the tile set's transform sets. Leave it empty to only use tiles as they are.");
+
 #[derive(Default)]
 pub struct WfcMacro {
     pattern_list: MacroPropertyField,
     frequency_list: MacroPropertyField,
+    cost_list: MacroPropertyField,
+    cohesion_list: MacroPropertyField,
+    transform_list: MacroPropertyField,
+    terrain_frequency_list: MacroPropertyField,
     edges_toggle: Handle<CheckBox>,
+    neighbors_toggle: Handle<CheckBox>,
+    preview_toggle: Handle<CheckBox>,
+    entropy_toggle: Handle<CheckBox>,
     attempts_field: Handle<NumericUpDown<u32>>,
+    backtrack_field: Handle<NumericUpDown<u32>>,
+    seed_toggle: Handle<CheckBox>,
+    seed_field: Handle<NumericUpDown<u64>>,
+    /// The most recent value of the seed field, remembered so that it may be used
+    /// when the seed is enabled.
+    seed_value: u64,
     terrain_list: Vec<TerrainWidgets>,
     value_field: MacroPropertyValueField,
     add_button: Handle<Button>,
     terrain_stack: Handle<StackPanel>,
+    /// The two terrains that will be forbidden from being adjacent when the forbid button is clicked.
+    adjacency_terrains: (TileTerrainId, TileTerrainId),
+    adjacency_first_field: MacroPropertyValueField,
+    adjacency_second_field: MacroPropertyValueField,
+    forbid_button: Handle<Button>,
+    adjacency_stack: Handle<StackPanel>,
+    adjacency_list: Vec<AdjacencyWidgets>,
+    /// The widgets for editing the terrain frequencies by hand, which are disabled
+    /// while the frequencies come from a tile set property.
+    manual_terrain_panel: Handle<StackPanel>,
     current_terrain: TileTerrainId,
+    /// A task that was just created and still needs a progress window.
+    new_task: Option<Arc<Mutex<WaveFunctionTaskCommandData>>>,
+    progress_window: Option<WfcProgressWindow>,
+    /// The effect that draws the result of a preview over the tile map.
+    preview_effect: Arc<Mutex<TilePreviewEffect>>,
+    /// A result that has been generated without modifying the tile map,
+    /// waiting for the user to accept it, run it again, or discard it.
+    preview: Option<WfcPreview>,
+    /// The effect that colors the cells of a failed wave by their number of remaining patterns.
+    entropy_effect: Arc<Mutex<TileColorEffect>>,
+    /// The task whose wave should be shown by the entropy overlay if it fails.
+    entropy_task: Option<Arc<Mutex<WaveFunctionTaskCommandData>>>,
+    /// The settings that were used to create the wave shown by the entropy overlay,
+    /// so that the overlay can be cleared when the settings change.
+    entropy_instance: Option<WfcInstance>,
 }
 
-#[derive(Debug, Clone, Visit, Reflect, TypeUuidProvider)]
+#[derive(Debug, Clone, PartialEq, Visit, Reflect, TypeUuidProvider)]
 #[type_uuid(id = "24f9947e-f58b-4623-ad14-cb21cd09297e")]
 pub(super) struct WfcInstance {
     frequency_property: Option<TileSetPropertyF32>,
+    #[visit(optional)]
+    cost_property: Option<TileSetPropertyF32>,
+    #[visit(optional)]
+    cohesion_property: Option<TileSetPropertyF32>,
+    #[visit(optional)]
+    transform_property: Option<TileSetPropertyI32>,
     pattern_property: Option<TileSetPropertyNine>,
+    /// The property that provides the frequency of each terrain. When it is set,
+    /// `terrain_freq` is ignored.
+    #[visit(optional)]
+    terrain_frequency_property: Option<TileSetPropertyF32>,
     #[reflect(hidden)]
     terrain_freq: FxHashMap<TileTerrainId, f32>,
+    /// Pairs of terrains that may never be placed in adjacent cells.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    forbidden_adjacencies: FxHashSet<TerrainPair>,
     max_attempts: u32,
+    #[visit(optional)]
+    backtrack_depth: u32,
+    /// The seed for the random number generator, or None to choose a new random layout
+    /// every time.
+    #[visit(optional)]
+    seed: Option<u64>,
+    /// Whether the empty cells around the wave should restrict the wave,
+    /// so that the result fits against the border of the tile map.
     constrain_edges: bool,
+    /// Whether the tiles that are already placed around the wave should restrict
+    /// the wave, so that the result blends into its surroundings.
+    #[visit(optional)]
+    constrain_neighbors: bool,
+    /// Whether the result should be shown over the tile map and wait for the user
+    /// to accept it, instead of immediately modifying the tile map.
+    #[visit(optional)]
+    preview: bool,
+    /// Whether the cells should be colored by their number of remaining patterns
+    /// when wave function collapse fails, to show where the contradictions happened.
+    #[visit(optional)]
+    show_entropy: bool,
     #[reflect(hidden)]
     cells: FxHashSet<TileDefinitionHandle>,
 }
@@ -88,15 +192,50 @@ impl Default for WfcInstance {
     fn default() -> Self {
         Self {
             frequency_property: None,
+            cost_property: None,
+            cohesion_property: None,
+            transform_property: None,
             pattern_property: None,
+            terrain_frequency_property: None,
             terrain_freq: FxHashMap::default(),
+            forbidden_adjacencies: FxHashSet::default(),
             max_attempts: DEFAULT_MAX_ATTEMPTS,
+            backtrack_depth: DEFAULT_BACKTRACK_DEPTH,
+            seed: None,
             constrain_edges: DEFAULT_CONSTRAIN_EDGES,
+            constrain_neighbors: DEFAULT_CONSTRAIN_NEIGHBORS,
+            preview: false,
+            show_entropy: false,
             cells: FxHashSet::default(),
         }
     }
 }
 
+/// A pair of terrains that may never be placed in adjacent cells. Since the order
+/// of the terrains does not matter, the smaller terrain is always first.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Visit)]
+struct TerrainPair {
+    first: TileTerrainId,
+    second: TileTerrainId,
+}
+
+impl TerrainPair {
+    fn new(a: TileTerrainId, b: TileTerrainId) -> Self {
+        Self {
+            first: a.min(b),
+            second: a.max(b),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct AdjacencyWidgets {
+    pair: TerrainPair,
+    colors: (Color, Color),
+    names: (String, String),
+    delete_button: Handle<Button>,
+}
+
 #[derive(Debug, Default, Clone)]
 struct TerrainWidgets {
     terrain: TileTerrainId,
@@ -228,7 +367,126 @@ fn make_terrain_list_element(
     (handle.to_base(), widgets)
 }
 
+fn terrain_color_and_name(
+    terrain: TileTerrainId,
+    layer: Option<&TileSetPropertyLayer>,
+) -> (Color, String) {
+    if let Some(layer) = layer {
+        let color = layer
+            .value_to_color(NamableValue::I8(terrain))
+            .unwrap_or(ELEMENT_MATCH_HIGHLIGHT_COLOR);
+        (color, layer.value_to_name(NamableValue::I8(terrain)))
+    } else {
+        (ELEMENT_MATCH_HIGHLIGHT_COLOR, "".into())
+    }
+}
+
+fn adjacency_list_needs_rebuild(
+    pairs: &[TerrainPair],
+    layer: Option<&TileSetPropertyLayer>,
+    list: &[AdjacencyWidgets],
+) -> bool {
+    let new_iter = pairs.iter().map(|&pair| {
+        let (first_color, first_name) = terrain_color_and_name(pair.first, layer);
+        let (second_color, second_name) = terrain_color_and_name(pair.second, layer);
+        (pair, (first_color, second_color), (first_name, second_name))
+    });
+    let old_iter = list.iter().map(|w| (w.pair, w.colors, w.names.clone()));
+    !new_iter.eq(old_iter)
+}
+
+fn make_adjacency_list(
+    pairs: &[TerrainPair],
+    layer: Option<&TileSetPropertyLayer>,
+    list: &mut Vec<AdjacencyWidgets>,
+    ctx: &mut BuildContext,
+) -> Vec<Handle<UiNode>> {
+    list.clear();
+    let mut result = Vec::default();
+    for &pair in pairs {
+        let (handle, widgets) = make_adjacency_list_element(pair, layer, ctx);
+        list.push(widgets);
+        result.push(handle);
+    }
+    result
+}
+
+fn make_adjacency_list_element(
+    pair: TerrainPair,
+    layer: Option<&TileSetPropertyLayer>,
+    ctx: &mut BuildContext,
+) -> (Handle<UiNode>, AdjacencyWidgets) {
+    let (first_color, first_name) = terrain_color_and_name(pair.first, layer);
+    let (second_color, second_name) = terrain_color_and_name(pair.second, layer);
+    let mut children = Vec::new();
+    for (column, terrain, color, name) in [
+        (0, pair.first, first_color, &first_name),
+        (2, pair.second, second_color, &second_name),
+    ] {
+        children.push(
+            BorderBuilder::new(
+                WidgetBuilder::new()
+                    .on_column(column)
+                    .with_background(Brush::Solid(color).into()),
+            )
+            .build(ctx)
+            .to_base(),
+        );
+        let text = if name.is_empty() {
+            terrain.to_string()
+        } else {
+            format!("{terrain}: {name}")
+        };
+        children.push(
+            TextBuilder::new(
+                WidgetBuilder::new()
+                    .on_column(column + 1)
+                    .with_margin(Thickness::left(5.0)),
+            )
+            .with_text(text)
+            .build(ctx)
+            .to_base(),
+        );
+    }
+    let delete_button = ButtonBuilder::new(
+        WidgetBuilder::new()
+            .on_column(4)
+            .with_margin(Thickness::uniform(2.0)),
+    )
+    .with_text("Delete")
+    .build(ctx);
+    let handle = GridBuilder::new(
+        WidgetBuilder::new()
+            .with_children(children)
+            .with_child(delete_button)
+            .with_margin(Thickness::uniform(2.0)),
+    )
+    .add_row(Row::auto())
+    .add_column(Column::strict(20.0))
+    .add_column(Column::stretch())
+    .add_column(Column::strict(20.0))
+    .add_column(Column::stretch())
+    .add_column(Column::strict(50.0))
+    .build(ctx);
+    let widgets = AdjacencyWidgets {
+        pair,
+        colors: (first_color, second_color),
+        names: (first_name, second_name),
+        delete_button,
+    };
+    (handle.to_base(), widgets)
+}
+
 impl WfcInstance {
+    fn sorted_forbidden_adjacencies(&self) -> Vec<TerrainPair> {
+        let mut result = self
+            .forbidden_adjacencies
+            .iter()
+            .copied()
+            .collect::<Vec<_>>();
+        result.sort();
+        result
+    }
     fn sorted_terrain_list(&self) -> Vec<(TileTerrainId, f32)> {
         let mut result = Vec::default();
         result.extend(self.terrain_freq.iter().map(|(&id, &f)| (id, f)));
@@ -287,6 +545,32 @@ impl BrushMacro for WfcMacro {
                     instance: context.settings().unwrap(),
                     data: uuid.map(TileSetPropertyF32),
                 });
+            } else if message.destination() == self.cost_list.handle() {
+                editor.message_sender.do_command(SetCostPropCommand {
+                    brush: context.brush.clone(),
+                    instance: context.settings().unwrap(),
+                    data: uuid.map(TileSetPropertyF32),
+                });
+            } else if message.destination() == self.cohesion_list.handle() {
+                editor.message_sender.do_command(SetCohesionPropCommand {
+                    brush: context.brush.clone(),
+                    instance: context.settings().unwrap(),
+                    data: uuid.map(TileSetPropertyF32),
+                });
+            } else if message.destination() == self.transform_list.handle() {
+                editor.message_sender.do_command(SetTransformPropCommand {
+                    brush: context.brush.clone(),
+                    instance: context.settings().unwrap(),
+                    data: uuid.map(TileSetPropertyI32),
+                });
+            } else if message.destination() == self.terrain_frequency_list.handle() {
+                editor
+                    .message_sender
+                    .do_command(SetTerrainFrequencyPropCommand {
+                        brush: context.brush.clone(),
+                        instance: context.settings().unwrap(),
+                        data: uuid.map(TileSetPropertyF32),
+                    });
             }
         } else if let Some(&CheckBoxMessage::Check(Some(checked))) = message.data() {
             if message.destination() == self.edges_toggle {
@@ -295,6 +579,45 @@ impl BrushMacro for WfcMacro {
                     instance: context.settings().unwrap(),
                     data: checked,
                 });
+            } else if message.destination() == self.neighbors_toggle {
+                editor
+                    .message_sender
+                    .do_command(SetConstrainNeighborsCommand {
+                        brush: context.brush.clone(),
+                        instance: context.settings().unwrap(),
+                        data: checked,
+                    });
+            } else if message.destination() == self.preview_toggle {
+                editor.message_sender.do_command(SetPreviewCommand {
+                    brush: context.brush.clone(),
+                    instance: context.settings().unwrap(),
+                    data: checked,
+                });
+            } else if message.destination() == self.entropy_toggle {
+                editor.message_sender.do_command(SetShowEntropyCommand {
+                    brush: context.brush.clone(),
+                    instance: context.settings().unwrap(),
+                    data: checked,
+                });
+            } else if message.destination() == self.seed_toggle {
+                editor.message_sender.do_command(SetSeedCommand {
+                    brush: context.brush.clone(),
+                    instance: context.settings().unwrap(),
+                    data: checked.then_some(self.seed_value),
+                });
+            }
+        } else if let Some(&NumericUpDownMessage::<u64>::Value(value)) = message.data() {
+            if message.destination() == self.seed_field {
+                self.seed_value = value;
+                let instance: Resource<WfcInstance> = context.settings().unwrap();
+                let seed = instance.data_ref().seed;
+                if seed.is_some() && seed != Some(value) {
+                    editor.message_sender.do_command(SetSeedCommand {
+                        brush: context.brush.clone(),
+                        instance,
+                        data: Some(value),
+                    });
+                }
             }
         } else if let Some(&NumericUpDownMessage::<u32>::Value(value)) = message.data() {
             if message.destination() == self.attempts_field {
@@ -303,6 +626,12 @@ impl BrushMacro for WfcMacro {
                     instance: context.settings().unwrap(),
                     data: value,
                 });
+            } else if message.destination() == self.backtrack_field {
+                editor.message_sender.do_command(SetBacktrackDepthCommand {
+                    brush: context.brush.clone(),
+                    instance: context.settings().unwrap(),
+                    data: value,
+                });
             }
         } else if let Some(ButtonMessage::Click) = message.data() {
             if message.destination() == self.add_button {
@@ -314,7 +643,29 @@ impl BrushMacro for WfcMacro {
                         terrain_id: self.current_terrain,
                         data: Some(1.0),
                     });
+            } else if message.destination() == self.forbid_button {
+                let (first, second) = self.adjacency_terrains;
+                editor
+                    .message_sender
+                    .do_command(SetForbiddenAdjacencyCommand {
+                        brush: context.brush.clone(),
+                        instance: context.settings().unwrap(),
+                        pair: TerrainPair::new(first, second),
+                        forbidden: true,
+                    });
             } else {
+                for w in self.adjacency_list.iter() {
+                    if message.destination() == w.delete_button {
+                        editor
+                            .message_sender
+                            .do_command(SetForbiddenAdjacencyCommand {
+                                brush: context.brush.clone(),
+                                instance: context.settings().unwrap(),
+                                pair: w.pair,
+                                forbidden: false,
+                            });
+                    }
+                }
                 for w in self.terrain_list.iter() {
                     if message.destination() == w.delete_button {
                         editor
@@ -333,6 +684,10 @@ impl BrushMacro for WfcMacro {
         {
             if message.destination() == self.value_field.handle() {
                 self.current_terrain = id;
+            } else if message.destination() == self.adjacency_first_field.handle() {
+                self.adjacency_terrains.0 = id;
+            } else if message.destination() == self.adjacency_second_field.handle() {
+                self.adjacency_terrains.1 = id;
             }
         } else if let Some(&NumericUpDownMessage::<f32>::Value(frequency)) = message.data() {
             for w in self.terrain_list.iter() {
@@ -351,6 +706,11 @@ impl BrushMacro for WfcMacro {
             let tile_set = tile_set.data_ref();
             self.pattern_list.on_ui_message(&tile_set, message, ui);
             self.frequency_list.on_ui_message(&tile_set, message, ui);
+            self.cost_list.on_ui_message(&tile_set, message, ui);
+            self.cohesion_list.on_ui_message(&tile_set, message, ui);
+            self.transform_list.on_ui_message(&tile_set, message, ui);
+            self.terrain_frequency_list
+                .on_ui_message(&tile_set, message, ui);
             let instance = context.settings::<WfcInstance>().unwrap();
             let instance = instance.data_ref();
             let pattern_id = instance
@@ -359,6 +719,10 @@ impl BrushMacro for WfcMacro {
                 .map(|p| p.property_uuid());
             let terrain_layer = pattern_id.and_then(|id| tile_set.find_property(*id));
             self.value_field.on_ui_message(terrain_layer, message, ui);
+            self.adjacency_first_field
+                .on_ui_message(terrain_layer, message, ui);
+            self.adjacency_second_field
+                .on_ui_message(terrain_layer, message, ui);
         }
     }
 
@@ -513,6 +877,19 @@ impl BrushMacro for WfcMacro {
             .frequency_property
             .as_ref()
             .map(|p| p.property_uuid());
+        let cost_id = instance.cost_property.as_ref().map(|p| p.property_uuid());
+        let cohesion_id = instance
+            .cohesion_property
+            .as_ref()
+            .map(|p| p.property_uuid());
+        let transform_id = instance
+            .transform_property
+            .as_ref()
+            .map(|p| p.property_uuid());
+        let terrain_frequency_id = instance
+            .terrain_frequency_property
+            .as_ref()
+            .map(|p| p.property_uuid());
         let tile_set = context.tile_set();
         let tile_set = tile_set.as_ref().map(|t| t.data_ref());
         let tile_set = tile_set.as_deref();
@@ -532,6 +909,38 @@ impl BrushMacro for WfcMacro {
             tile_set,
             ctx,
         );
+        self.cost_list = MacroPropertyField::new(
+            WidgetBuilder::new().with_margin(Thickness::uniform(5.0)),
+            "Cost Property".into(),
+            TileSetPropertyType::F32,
+            cost_id,
+            tile_set,
+            ctx,
+        );
+        self.cohesion_list = MacroPropertyField::new(
+            WidgetBuilder::new().with_margin(Thickness::uniform(5.0)),
+            "Cohesion Property".into(),
+            TileSetPropertyType::F32,
+            cohesion_id,
+            tile_set,
+            ctx,
+        );
+        self.transform_list = MacroPropertyField::new(
+            WidgetBuilder::new().with_margin(Thickness::uniform(5.0)),
+            "Transform Property".into(),
+            TileSetPropertyType::I32,
+            transform_id,
+            tile_set,
+            ctx,
+        );
+        self.terrain_frequency_list = MacroPropertyField::new(
+            WidgetBuilder::new().with_margin(Thickness::uniform(5.0)),
+            "Terrain Frequency Property".into(),
+            TileSetPropertyType::F32,
+            terrain_frequency_id,
+            tile_set,
+            ctx,
+        );
         let pattern_prop_help_text =
             TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(5.0)))
                 .with_wrap(WrapMode::Word)
@@ -542,11 +951,47 @@ impl BrushMacro for WfcMacro {
                 .with_wrap(WrapMode::Word)
                 .with_text(FREQUENCY_PROP_DESC)
                 .build(ctx);
+        let cost_prop_help_text =
+            TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(5.0)))
+                .with_wrap(WrapMode::Word)
+                .with_text(COST_PROP_DESC)
+                .build(ctx);
+        let cohesion_prop_help_text =
+            TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(5.0)))
+                .with_wrap(WrapMode::Word)
+                .with_text(COHESION_PROP_DESC)
+                .build(ctx);
+        let transform_prop_help_text =
+            TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(5.0)))
+                .with_wrap(WrapMode::Word)
+                .with_text(TRANSFORM_PROP_DESC)
+                .build(ctx);
+        let terrain_freq_prop_help_text =
+            TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(5.0)))
+                .with_wrap(WrapMode::Word)
+                .with_text(TERRAIN_FREQUENCY_PROP_DESC)
+                .build(ctx);
         let constrain_edges = instance.constrain_edges;
         let attempts = instance.max_attempts;
         self.attempts_field = NumericUpDownBuilder::new(WidgetBuilder::new().on_column(1))
             .with_value(attempts)
             .build(ctx);
+        self.backtrack_field = NumericUpDownBuilder::new(WidgetBuilder::new().on_column(1))
+            .with_value(instance.backtrack_depth)
+            .build(ctx);
+        if let Some(seed) = instance.seed {
+            self.seed_value = seed;
+        }
+        self.seed_toggle = CheckBoxBuilder::new(WidgetBuilder::new())
+            .checked(Some(instance.seed.is_some()))
+            .build(ctx);
+        self.seed_field = NumericUpDownBuilder::new(
+            WidgetBuilder::new()
+                .on_column(2)
+                .with_enabled(instance.seed.is_some()),
+        )
+        .with_value(self.seed_value)
+        .build(ctx);
         self.edges_toggle = CheckBoxBuilder::new(WidgetBuilder::new())
             .checked(Some(constrain_edges))
             .build(ctx);
@@ -563,6 +1008,60 @@ impl BrushMacro for WfcMacro {
         .add_column(Column::strict(20.0))
         .add_column(Column::stretch())
         .build(ctx);
+        self.neighbors_toggle = CheckBoxBuilder::new(WidgetBuilder::new())
+            .checked(Some(instance.constrain_neighbors))
+            .build(ctx);
+        let neighbors_field = GridBuilder::new(
+            WidgetBuilder::new()
+                .with_child(
+                    TextBuilder::new(WidgetBuilder::new().on_column(1))
+                        .with_text("Constrain Neighbors")
+                        .build(ctx),
+                )
+                .with_child(self.neighbors_toggle),
+        )
+        .add_row(Row::auto())
+        .add_column(Column::strict(20.0))
+        .add_column(Column::stretch())
+        .build(ctx);
+        self.preview_toggle = CheckBoxBuilder::new(WidgetBuilder::new())
+            .checked(Some(instance.preview))
+            .build(ctx);
+        let preview_field = GridBuilder::new(
+            WidgetBuilder::new()
+                .with_child(
+                    TextBuilder::new(WidgetBuilder::new().on_column(1))
+                        .with_text("Preview Before Applying")
+                        .build(ctx),
+                )
+                .with_child(self.preview_toggle),
+        )
+        .add_row(Row::auto())
+        .add_column(Column::strict(20.0))
+        .add_column(Column::stretch())
+        .build(ctx);
+        self.entropy_toggle = CheckBoxBuilder::new(WidgetBuilder::new())
+            .checked(Some(instance.show_entropy))
+            .build(ctx);
+        let entropy_field = GridBuilder::new(
+            WidgetBuilder::new()
+                .with_child(
+                    TextBuilder::new(WidgetBuilder::new().on_column(1))
+                        .with_text("Show Entropy On Failure")
+                        .build(ctx),
+                )
+                .with_child(self.entropy_toggle)
+                .with_tooltip(make_simple_tooltip(
+                    ctx,
+                    "When wave function collapse fails, color each cell by its number of \
+                    remaining tiles, from green for resolved cells to blue for unrestricted \
+                    cells. Contradictions are red.",
+                )),
+        )
+        .add_row(Row::auto())
+        .add_column(Column::strict(20.0))
+        .add_column(Column::stretch())
+        .build(ctx);
         let attempts_field = GridBuilder::new(
             WidgetBuilder::new()
                 .with_child(
@@ -576,6 +1075,34 @@ impl BrushMacro for WfcMacro {
         .add_column(Column::strict(150.0))
         .add_column(Column::stretch())
         .build(ctx);
+        let backtrack_field = GridBuilder::new(
+            WidgetBuilder::new()
+                .with_child(
+                    TextBuilder::new(WidgetBuilder::new())
+                        .with_text("Backtrack Depth")
+                        .build(ctx),
+                )
+                .with_child(self.backtrack_field),
+        )
+        .add_row(Row::auto())
+        .add_column(Column::strict(150.0))
+        .add_column(Column::stretch())
+        .build(ctx);
+        let seed_field = GridBuilder::new(
+            WidgetBuilder::new()
+                .with_child(self.seed_toggle)
+                .with_child(
+                    TextBuilder::new(WidgetBuilder::new().on_column(1))
+                        .with_text("Seed")
+                        .build(ctx),
+                )
+                .with_child(self.seed_field),
+        )
+        .add_row(Row::auto())
+        .add_column(Column::strict(20.0))
+        .add_column(Column::strict(130.0))
+        .add_column(Column::stretch())
+        .build(ctx);
         let terrain_layer =
             tile_set.and_then(|tile_set| pattern_id.and_then(|id| tile_set.find_property(*id)));
         self.value_field = MacroPropertyValueField::new(
@@ -609,57 +1136,185 @@ impl BrushMacro for WfcMacro {
                 ctx,
             )))
             .build(ctx);
-        let handle = StackPanelBuilder::new(
+        self.manual_terrain_panel = StackPanelBuilder::new(
             WidgetBuilder::new()
-                .with_margin(Thickness::uniform(5.0))
-                .with_child(pattern_prop_help_text)
-                .with_child(self.pattern_list.handle())
-                .with_child(freq_prop_help_text)
-                .with_child(self.frequency_list.handle())
-                .with_child(edges_field)
-                .with_child(attempts_field)
+                .with_enabled(instance.terrain_frequency_property.is_none())
                 .with_child(add_row_field)
                 .with_child(self.terrain_stack),
         )
-        .build(ctx)
-        .to_base();
-        Some(handle)
-    }
-
-    fn build_cell_editor(
-        &mut self,
-        _context: &BrushMacroCellContext,
-        _ctx: &mut BuildContext,
-    ) -> Option<Handle<UiNode>> {
-        None
-    }
-
-    fn sync_instance_editor(&mut self, context: &BrushMacroInstance, ui: &mut UserInterface) {
-        let Some(instance) = context.settings::<WfcInstance>() else {
-            return;
-        };
-        let instance = instance.data_ref();
-        let pattern_id = instance
-            .pattern_property
-            .as_ref()
+        .build(ctx);
+        let adjacency_help_text =
+            TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(5.0)))
+                .with_wrap(WrapMode::Word)
+                .with_text(FORBIDDEN_ADJACENCY_DESC)
+                .build(ctx);
+        self.adjacency_first_field = MacroPropertyValueField::new(
+            WidgetBuilder::new(),
+            "Terrain".into(),
+            TileSetPropertyValueElement::I8(self.adjacency_terrains.0),
+            terrain_layer,
+            ctx,
+        );
+        self.adjacency_second_field = MacroPropertyValueField::new(
+            WidgetBuilder::new(),
+            "Neighbor".into(),
+            TileSetPropertyValueElement::I8(self.adjacency_terrains.1),
+            terrain_layer,
+            ctx,
+        );
+        self.forbid_button = ButtonBuilder::new(
+            WidgetBuilder::new()
+                .on_column(1)
+                .with_margin(Thickness::uniform(1.0)),
+        )
+        .with_text("Forbid")
+        .build(ctx);
+        let forbid_row_field = GridBuilder::new(
+            WidgetBuilder::new()
+                .with_child(self.adjacency_second_field.handle())
+                .with_child(self.forbid_button),
+        )
+        .add_row(Row::auto())
+        .add_column(Column::stretch())
+        .add_column(Column::strict(50.0))
+        .build(ctx);
+        self.adjacency_stack =
+            StackPanelBuilder::new(WidgetBuilder::new().with_children(make_adjacency_list(
+                &instance.sorted_forbidden_adjacencies(),
+                terrain_layer,
+                &mut self.adjacency_list,
+                ctx,
+            )))
+            .build(ctx);
+        let handle = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .with_margin(Thickness::uniform(5.0))
+                .with_child(pattern_prop_help_text)
+                .with_child(self.pattern_list.handle())
+                .with_child(freq_prop_help_text)
+                .with_child(self.frequency_list.handle())
+                .with_child(cost_prop_help_text)
+                .with_child(self.cost_list.handle())
+                .with_child(cohesion_prop_help_text)
+                .with_child(self.cohesion_list.handle())
+                .with_child(transform_prop_help_text)
+                .with_child(self.transform_list.handle())
+                .with_child(edges_field)
+                .with_child(neighbors_field)
+                .with_child(preview_field)
+                .with_child(entropy_field)
+                .with_child(attempts_field)
+                .with_child(backtrack_field)
+                .with_child(seed_field)
+                .with_child(terrain_freq_prop_help_text)
+                .with_child(self.terrain_frequency_list.handle())
+                .with_child(self.manual_terrain_panel)
+                .with_child(adjacency_help_text)
+                .with_child(self.adjacency_first_field.handle())
+                .with_child(forbid_row_field)
+                .with_child(self.adjacency_stack),
+        )
+        .build(ctx)
+        .to_base();
+        Some(handle)
+    }
+
+    fn build_cell_editor(
+        &mut self,
+        _context: &BrushMacroCellContext,
+        _ctx: &mut BuildContext,
+    ) -> Option<Handle<UiNode>> {
+        None
+    }
+
+    fn sync_instance_editor(&mut self, context: &BrushMacroInstance, ui: &mut UserInterface) {
+        let Some(instance) = context.settings::<WfcInstance>() else {
+            return;
+        };
+        let instance = instance.data_ref();
+        let pattern_id = instance
+            .pattern_property
+            .as_ref()
             .map(|p| p.property_uuid());
         let frequency_id = instance
             .frequency_property
             .as_ref()
             .map(|p| p.property_uuid());
+        let cost_id = instance.cost_property.as_ref().map(|p| p.property_uuid());
+        let cohesion_id = instance
+            .cohesion_property
+            .as_ref()
+            .map(|p| p.property_uuid());
+        let transform_id = instance
+            .transform_property
+            .as_ref()
+            .map(|p| p.property_uuid());
+        let terrain_frequency_id = instance
+            .terrain_frequency_property
+            .as_ref()
+            .map(|p| p.property_uuid());
         let tile_set = context.tile_set();
         let tile_set = tile_set.as_ref().map(|t| t.data_ref());
         let tile_set = tile_set.as_deref();
         self.pattern_list.sync(pattern_id, tile_set, ui);
         self.frequency_list.sync(frequency_id, tile_set, ui);
+        self.cost_list.sync(cost_id, tile_set, ui);
+        self.cohesion_list.sync(cohesion_id, tile_set, ui);
+        self.transform_list.sync(transform_id, tile_set, ui);
+        self.terrain_frequency_list
+            .sync(terrain_frequency_id, tile_set, ui);
+        ui.send(
+            self.manual_terrain_panel,
+            WidgetMessage::Enabled(instance.terrain_frequency_property.is_none()),
+        );
         ui.send_sync(
             self.edges_toggle,
             CheckBoxMessage::Check(Some(instance.constrain_edges)),
         );
+        ui.send_sync(
+            self.neighbors_toggle,
+            CheckBoxMessage::Check(Some(instance.constrain_neighbors)),
+        );
+        ui.send_sync(
+            self.preview_toggle,
+            CheckBoxMessage::Check(Some(instance.preview)),
+        );
+        ui.send_sync(
+            self.entropy_toggle,
+            CheckBoxMessage::Check(Some(instance.show_entropy)),
+        );
+        // The overlay describes the settings as they were when the wave failed,
+        // so it is out of date as soon as they change.
+        if self
+            .entropy_instance
+            .as_ref()
+            .is_some_and(|i| i != instance.deref())
+        {
+            self.clear_entropy();
+        }
         ui.send_sync(
             self.attempts_field,
             NumericUpDownMessage::<u32>::Value(instance.max_attempts),
         );
+        ui.send_sync(
+            self.backtrack_field,
+            NumericUpDownMessage::<u32>::Value(instance.backtrack_depth),
+        );
+        if let Some(seed) = instance.seed {
+            self.seed_value = seed;
+        }
+        ui.send_sync(
+            self.seed_toggle,
+            CheckBoxMessage::Check(Some(instance.seed.is_some())),
+        );
+        ui.send_sync(
+            self.seed_field,
+            NumericUpDownMessage::<u64>::Value(self.seed_value),
+        );
+        ui.send(
+            self.seed_field,
+            WidgetMessage::Enabled(instance.seed.is_some()),
+        );
         let layer =
             tile_set.and_then(|tile_set| pattern_id.and_then(|id| tile_set.find_property(*id)));
         self.value_field.sync(
@@ -679,6 +1334,22 @@ impl BrushMacro for WfcMacro {
         } else {
             sync_terrain_list(&terrain_freq, &self.terrain_list, ui);
         }
+        self.adjacency_first_field.sync(
+            TileSetPropertyValueElement::I8(self.adjacency_terrains.0),
+            layer,
+            ui,
+        );
+        self.adjacency_second_field.sync(
+            TileSetPropertyValueElement::I8(self.adjacency_terrains.1),
+            layer,
+            ui,
+        );
+        let pairs = instance.sorted_forbidden_adjacencies();
+        if adjacency_list_needs_rebuild(&pairs, layer, &self.adjacency_list) {
+            let list =
+                make_adjacency_list(&pairs, layer, &mut self.adjacency_list, &mut ui.build_ctx());
+            ui.send(self.adjacency_stack, WidgetMessage::ReplaceChildren(list));
+        }
     }
 
     fn sync_cell_editors(&mut self, _context: &MacroMessageContext, _ui: &mut UserInterface) {}
@@ -703,22 +1374,51 @@ impl BrushMacro for WfcMacro {
         &mut self,
         context: &BrushMacroInstance,
         update: &mut MacroTilesUpdate,
-        tile_map: &TileMapContext,
+        tile_map: &mut TileMapContext,
     ) -> Option<Command> {
-        let tile_set = tile_map.tile_set()?;
-        if context.tile_set().as_deref() != Some(tile_set) {
+        let tile_set = tile_map.tile_set()?.clone();
+        if context.tile_set().as_deref() != Some(&tile_set) {
             return None;
         }
         let instance = context.settings::<WfcInstance>().unwrap();
         let instance = instance.data_ref();
         let pattern_property = instance.pattern_property?;
         let frequency_property = instance.frequency_property;
+        let cost_property = instance.cost_property;
+        let cohesion_property = instance.cohesion_property;
+        let transform_property = instance.transform_property;
+        // The property replaces the hand-made list instead of being merged with it,
+        // so that a terrain never gets its frequency from two places.
+        let terrain_freq =
+            if let Some(terrain_frequency_property) = instance.terrain_frequency_property {
+                match TileSetWfcConstraint::terrain_freq_from_property(
+                    &tile_set.data_ref(),
+                    pattern_property,
+                    terrain_frequency_property,
+                ) {
+                    Ok(terrain_freq) => Cow::Owned(terrain_freq),
+                    Err(e) => {
+                        Log::err(e.to_string());
+                        return None;
+                    }
+                }
+            } else {
+                Cow::Borrowed(&instance.terrain_freq)
+            };
         let mut constraint = TileSetWfcConstraint::default();
         if let Err(e) = constraint.fill_pattern_map(
             &tile_set.data_ref(),
             pattern_property,
             frequency_property,
-            &instance.terrain_freq,
+            cost_property,
+            cohesion_property,
+            transform_property,
+            &terrain_freq,
+            &instance
+                .forbidden_adjacencies
+                .iter()
+                .map(|pair| (pair.first, pair.second))
+                .collect(),
         ) {
             Log::err(e.to_string());
             return None;
@@ -740,26 +1440,474 @@ impl BrushMacro for WfcMacro {
         if propagator.is_empty() {
             return None;
         }
-        if instance.constrain_edges
-            && propagator
-                .constrain_edges(
-                    &tile_set.data_ref(),
-                    pattern_property,
-                    tile_map.tile_map(),
-                    update,
-                    constraint.deref(),
-                )
-                .is_err()
-        {
-            Log::err("WFC failed while constraining edges.");
-            return None;
+        if instance.constrain_edges || instance.constrain_neighbors {
+            // The edges are only found here. They are applied by the WFC task so that
+            // large edges do not block the editor and can be cancelled midway.
+            propagator.find_filtered_edge_restrictions(
+                &tile_set.data_ref(),
+                pattern_property,
+                tile_map.tile_map(),
+                update,
+                instance.constrain_neighbors,
+                instance.constrain_edges,
+            );
         }
-        Some(Command::new(WaveFunctionTaskCommand::new(
+        let command = WaveFunctionTaskCommand::new(
             tile_map.tile_map().tiles().cloned()?,
             instance.max_attempts,
+            instance.backtrack_depth,
+            instance.seed,
             constraint,
             propagator,
-        )))
+        );
+        self.new_task = Some(command.task_data.clone());
+        self.clear_entropy();
+        if instance.show_entropy {
+            let effect = self.entropy_effect.clone() as TileMapEffectRef;
+            let after_effects = &mut tile_map.tile_map_mut().after_effects;
+            if !after_effects.iter().any(|e| Arc::ptr_eq(e, &effect)) {
+                after_effects.push(effect);
+            }
+            self.entropy_task = Some(command.task_data.clone());
+            self.entropy_instance = Some(instance.deref().clone());
+        }
+        if instance.preview {
+            // Take the cells of the wave out of the update so that the tile map is left
+            // untouched until the user accepts the preview.
+            let task_data = command.task_data.safe_lock();
+            for p in task_data.initial_propagator.positions() {
+                _ = update.remove(p);
+            }
+            drop(task_data);
+            self.start_preview(command, tile_map);
+            return None;
+        }
+        Some(Command::new(command))
+    }
+
+    fn update(&mut self, ui: &mut UserInterface) {
+        if let Some(task_data) = self.new_task.take() {
+            if let Some(window) = self.progress_window.take() {
+                window.close(ui);
+            }
+            self.progress_window = Some(WfcProgressWindow::new(task_data, &mut ui.build_ctx()));
+        }
+        if let Some(window) = self.progress_window.take() {
+            self.progress_window = window.update(ui);
+        }
+        self.update_preview(ui);
+        self.update_entropy();
+    }
+
+    fn on_ui_message(
+        &mut self,
+        message: &UiMessage,
+        ui: &mut UserInterface,
+        sender: &MessageSender,
+    ) {
+        if let Some(window) = self.progress_window.as_ref() {
+            window.handle_ui_message(message);
+        }
+        let Some(ButtonMessage::Click) = message.data() else {
+            return;
+        };
+        let Some(preview) = self.preview.as_mut() else {
+            return;
+        };
+        let Some(window) = preview.window.as_ref() else {
+            return;
+        };
+        if message.destination() == window.accept {
+            let preview = self.preview.take().unwrap();
+            let (tile_map, tiles) = self.end_preview(preview, ui);
+            sender.do_command(SetMapTilesCommand { tile_map, tiles });
+        } else if message.destination() == window.reroll {
+            if let Some(window) = preview.window.take() {
+                window.close(ui);
+            }
+            self.preview_effect.safe_lock().active = false;
+            if self.entropy_instance.is_some() {
+                // Hide the overlay of the previous run and show the new one if it also fails.
+                self.entropy_effect.safe_lock().active = false;
+                self.entropy_task = Some(preview.task_data.clone());
+            }
+            preview.restart();
+            self.new_task = Some(preview.task_data.clone());
+        } else if message.destination() == window.cancel {
+            let preview = self.preview.take().unwrap();
+            _ = self.end_preview(preview, ui);
+        }
+    }
+}
+
+impl WfcMacro {
+    /// Hide the entropy overlay and stop waiting for a task to fail.
+    fn clear_entropy(&mut self) {
+        self.entropy_task = None;
+        self.entropy_instance = None;
+        let mut effect = self.entropy_effect.safe_lock();
+        effect.active = false;
+        effect.colors.clear();
+    }
+    /// Show the entropy overlay if the task that it is waiting for has failed.
+    fn update_entropy(&mut self) {
+        let Some(task_data) = self.entropy_task.as_ref() else {
+            return;
+        };
+        let task_data = task_data.safe_lock();
+        match task_data.state {
+            WfcTaskState::Running | WfcTaskState::Cancelled => return,
+            WfcTaskState::Finished if !task_data.solved => {
+                let propagator = &task_data.working_propagator;
+                let max_count = propagator.max_option_count();
+                let mut effect = self.entropy_effect.safe_lock();
+                effect.colors = propagator
+                    .option_counts()
+                    .map(|(&p, count)| (p, entropy_color(count, max_count)))
+                    .collect();
+                effect.active = true;
+            }
+            WfcTaskState::Finished | WfcTaskState::Aborted => (),
+        }
+        drop(task_data);
+        self.entropy_task = None;
+    }
+    /// Begin running the given task without modifying the tile map, so that
+    /// the result may be shown as a preview when the task finishes.
+    fn start_preview(&mut self, command: WaveFunctionTaskCommand, tile_map: &mut TileMapContext) {
+        if let Some(preview) = self.preview.take() {
+            preview.task_data.safe_lock().state = WfcTaskState::Aborted;
+            if let Some(window) = preview.window {
+                window.close(tile_map.engine.user_interfaces.first());
+            }
+        }
+        let effect = self.preview_effect.clone() as TileMapEffectRef;
+        let after_effects = &mut tile_map.tile_map_mut().after_effects;
+        if !after_effects.iter().any(|e| Arc::ptr_eq(e, &effect)) {
+            after_effects.push(effect);
+        }
+        let mut effect = self.preview_effect.safe_lock();
+        effect.active = false;
+        effect.alpha = PREVIEW_ALPHA;
+        effect.tiles.clear();
+        drop(effect);
+        let preview = WfcPreview {
+            tile_map: tile_map.node,
+            max_attempts: command.max_attempts,
+            backtrack_depth: command.backtrack_depth,
+            task_data: command.task_data,
+            window: None,
+        };
+        preview.spawn();
+        self.preview = Some(preview);
+    }
+    /// Show the result of the preview task once it is finished, or discard the preview
+    /// if the user aborted the task or if the task failed.
+    fn update_preview(&mut self, ui: &mut UserInterface) {
+        let Some(preview) = self.preview.as_mut() else {
+            return;
+        };
+        if preview.window.is_some() {
+            return;
+        }
+        let (state, solved) = {
+            let task_data = preview.task_data.safe_lock();
+            (task_data.state, task_data.solved)
+        };
+        match state {
+            WfcTaskState::Running | WfcTaskState::Cancelled => (),
+            WfcTaskState::Aborted => {
+                let preview = self.preview.take().unwrap();
+                _ = self.end_preview(preview, ui);
+            }
+            WfcTaskState::Finished if !solved => {
+                // The task has already logged the reason of the failure.
+                Log::err("WFC preview failed, there is no result to accept.");
+                let preview = self.preview.take().unwrap();
+                _ = self.end_preview(preview, ui);
+            }
+            WfcTaskState::Finished => {
+                let mut effect = self.preview_effect.safe_lock();
+                effect.tiles = preview.result();
+                effect.active = true;
+                preview.window = Some(WfcPreviewWindow::new(&mut ui.build_ctx()));
+            }
+        }
+    }
+    /// Close the preview and hide its tiles. The tile map handle and the tiles of the
+    /// preview are returned, in case the preview is to be applied to the tile map.
+    fn end_preview(
+        &mut self,
+        preview: WfcPreview,
+        ui: &UserInterface,
+    ) -> (Handle<Node>, TilesUpdate) {
+        if let Some(window) = preview.window {
+            window.close(ui);
+        }
+        let mut effect = self.preview_effect.safe_lock();
+        effect.active = false;
+        let mut tiles = TilesUpdate::default();
+        for (p, handle) in effect.tiles.drain() {
+            tiles.insert(p, Some(handle));
+        }
+        (preview.tile_map, tiles)
+    }
+}
+
+/// The color of a cell in the entropy overlay. Contradictions are red, and the other cells
+/// range from green for resolved cells to blue for cells where every pattern is still possible.
+fn entropy_color(count: usize, max_count: usize) -> Color {
+    let color = if count == 0 {
+        Color::RED
+    } else {
+        let t = (count - 1) as f32 / max_count.saturating_sub(1).max(1) as f32;
+        Color::GREEN.lerp(Color::BLUE, t.min(1.0))
+    };
+    color.with_new_alpha(ENTROPY_ALPHA)
+}
+
+/// A wave function collapse task that runs without modifying the tile map,
+/// so that its result can be previewed before it is applied.
+struct WfcPreview {
+    tile_map: Handle<Node>,
+    max_attempts: u32,
+    backtrack_depth: u32,
+    task_data: Arc<Mutex<WaveFunctionTaskCommandData>>,
+    /// The window that lets the user accept, run again, or discard the result.
+    /// This is None while the task is still running.
+    window: Option<WfcPreviewWindow>,
+}
+
+impl WfcPreview {
+    /// Start the task on a new thread.
+    fn spawn(&self) {
+        let task_data = self.task_data.clone();
+        let max_attempts = self.max_attempts;
+        let backtrack_depth = self.backtrack_depth;
+        match std::thread::Builder::new()
+            .name("Wave Function Collapse Preview".into())
+            .spawn(move || run_wfc(task_data, max_attempts, backtrack_depth, None))
+        {
+            Ok(_) => (),
+            Err(_) => {
+                Log::err("WFC thread failed to start.");
+            }
+        }
+    }
+    /// Run the task again from the beginning using the next state of its random number generator,
+    /// so that a different result is produced.
+    fn restart(&self) {
+        let mut task_data = self.task_data.safe_lock();
+        task_data.state = WfcTaskState::Running;
+        task_data.solved = false;
+        task_data.attempts = 0;
+        task_data.backtracks = 0;
+        let tile_seed = task_data.rng.gen();
+        task_data.tile_seed = tile_seed;
+        drop(task_data);
+        self.spawn();
+    }
+    /// The tiles chosen by the finished task.
+    fn result(&self) -> FxHashMap<Vector2<i32>, TileDefinitionHandle> {
+        let task_data = self.task_data.safe_lock();
+        let mut rng = StdRng::seed_from_u64(task_data.tile_seed);
+        let mut result = FxHashMap::default();
+        for (&p, pattern) in task_data.working_propagator.assigned_patterns() {
+            if let Some(&handle) = task_data.constraint.get_random(&mut rng, pattern) {
+                result.insert(p, handle);
+            }
+        }
+        result
+    }
+}
+
+/// A window that lets the user decide what to do with the result of a preview.
+struct WfcPreviewWindow {
+    window: Handle<Window>,
+    accept: Handle<Button>,
+    reroll: Handle<Button>,
+    cancel: Handle<Button>,
+}
+
+impl WfcPreviewWindow {
+    fn new(ctx: &mut BuildContext) -> Self {
+        let accept = make_preview_button(ctx, "Accept", 0);
+        let reroll = make_preview_button(ctx, "Reroll", 1);
+        let cancel = make_preview_button(ctx, "Cancel", 2);
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(300.0).with_height(60.0))
+            .with_title(WindowTitle::text("Wave Function Collapse Preview"))
+            .can_close(false)
+            .can_minimize(false)
+            .can_maximize(false)
+            .open(false)
+            .with_remove_on_close(true)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_margin(Thickness::uniform(2.0))
+                        .with_child(accept)
+                        .with_child(reroll)
+                        .with_child(cancel),
+                )
+                .add_row(Row::stretch())
+                .add_column(Column::stretch())
+                .add_column(Column::stretch())
+                .add_column(Column::stretch())
+                .build(ctx),
+            )
+            .build(ctx);
+        ctx.inner().send(
+            window,
+            WindowMessage::Open {
+                alignment: WindowAlignment::Center,
+                modal: false,
+                focus_content: false,
+            },
+        );
+        Self {
+            window,
+            accept,
+            reroll,
+            cancel,
+        }
+    }
+
+    fn close(self, ui: &UserInterface) {
+        ui.send(self.window, WindowMessage::Close);
+    }
+}
+
+fn make_preview_button(ctx: &mut BuildContext, text: &str, column: usize) -> Handle<Button> {
+    ButtonBuilder::new(
+        WidgetBuilder::new()
+            .on_column(column)
+            .with_height(25.0)
+            .with_margin(Thickness::uniform(2.0)),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+/// A window that shows the progress of a running wave function collapse task
+/// and allows the user to abort it.
+struct WfcProgressWindow {
+    window: Handle<Window>,
+    progress_bar: Handle<ProgressBar>,
+    text: Handle<Text>,
+    cancel: Handle<Button>,
+    task_data: Arc<Mutex<WaveFunctionTaskCommandData>>,
+}
+
+impl WfcProgressWindow {
+    fn new(task_data: Arc<Mutex<WaveFunctionTaskCommandData>>, ctx: &mut BuildContext) -> Self {
+        let progress_bar;
+        let text;
+        let cancel;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(300.0).with_height(100.0))
+            .with_title(WindowTitle::text("Wave Function Collapse"))
+            .can_close(false)
+            .can_minimize(false)
+            .can_maximize(false)
+            .open(false)
+            .with_remove_on_close(true)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_margin(Thickness::uniform(2.0))
+                        .with_child({
+                            progress_bar = ProgressBarBuilder::new(
+                                WidgetBuilder::new().on_row(0).with_height(25.0),
+                            )
+                            .build(ctx);
+                            progress_bar
+                        })
+                        .with_child({
+                            text = TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(0)
+                                    .with_horizontal_alignment(HorizontalAlignment::Center)
+                                    .with_vertical_alignment(VerticalAlignment::Center),
+                            )
+                            .build(ctx);
+                            text
+                        })
+                        .with_child({
+                            cancel = ButtonBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(2)
+                                    .with_width(100.0)
+                                    .with_height(25.0)
+                                    .with_horizontal_alignment(HorizontalAlignment::Right),
+                            )
+                            .with_text("Cancel")
+                            .build(ctx);
+                            cancel
+                        }),
+                )
+                .add_row(Row::auto())
+                .add_row(Row::stretch())
+                .add_row(Row::auto())
+                .add_column(Column::stretch())
+                .build(ctx),
+            )
+            .build(ctx);
+        ctx.inner().send(
+            window,
+            WindowMessage::Open {
+                alignment: WindowAlignment::Center,
+                modal: false,
+                focus_content: false,
+            },
+        );
+        Self {
+            window,
+            progress_bar,
+            text,
+            cancel,
+            task_data,
+        }
+    }
+
+    /// Show the current progress of the task, or close the window if the task
+    /// is no longer running.
+    fn update(self, ui: &UserInterface) -> Option<Self> {
+        let guard = self.task_data.safe_lock();
+        if guard.state != WfcTaskState::Running {
+            drop(guard);
+            self.close(ui);
+            return None;
+        }
+        let progress = guard.progress;
+        let attempt = guard.attempts + 1;
+        drop(guard);
+        let (text, fraction) = match progress {
+            WfcTaskProgress::Edges { remaining, total } => (
+                format!("Constraining edges: {} / {total}", total - remaining),
+                (total - remaining) as f32 / total.max(1) as f32,
+            ),
+            WfcTaskProgress::Cells { resolved, total } => (
+                format!("Attempt {attempt}: {resolved} / {total} cells"),
+                resolved as f32 / total.max(1) as f32,
+            ),
+        };
+        ui.send(self.progress_bar, ProgressBarMessage::Progress(fraction));
+        ui.send(self.text, TextMessage::Text(text));
+        Some(self)
+    }
+
+    fn handle_ui_message(&self, message: &UiMessage) {
+        if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.cancel {
+                let mut guard = self.task_data.safe_lock();
+                if guard.state == WfcTaskState::Running {
+                    guard.state = WfcTaskState::Aborted;
+                }
+            }
+        }
+    }
+
+    fn close(self, ui: &UserInterface) {
+        ui.send(self.window, WindowMessage::Close);
     }
 }
 
@@ -995,6 +2143,158 @@ impl CommandTrait for SetFrequencyPropCommand {
     }
 }
 
+#[derive(Debug)]
+struct SetCostPropCommand {
+    pub brush: TileMapBrushResource,
+    pub instance: Resource<WfcInstance>,
+    pub data: Option<TileSetPropertyF32>,
+}
+
+impl SetCostPropCommand {
+    fn swap(&mut self) {
+        let mut instance = self.instance.data_ref();
+        std::mem::swap(&mut instance.cost_property, &mut self.data);
+        self.brush.data_ref().change_flag.set();
+    }
+}
+
+impl CommandTrait for SetCostPropCommand {
+    fn name(&mut self, _context: &dyn CommandContext) -> String {
+        "Update Autotile Property".into()
+    }
+
+    fn execute(&mut self, _context: &mut dyn CommandContext) {
+        self.swap();
+    }
+
+    fn revert(&mut self, _context: &mut dyn CommandContext) {
+        self.swap();
+    }
+}
+
+#[derive(Debug)]
+struct SetCohesionPropCommand {
+    pub brush: TileMapBrushResource,
+    pub instance: Resource<WfcInstance>,
+    pub data: Option<TileSetPropertyF32>,
+}
+
+impl SetCohesionPropCommand {
+    fn swap(&mut self) {
+        let mut instance = self.instance.data_ref();
+        std::mem::swap(&mut instance.cohesion_property, &mut self.data);
+        self.brush.data_ref().change_flag.set();
+    }
+}
+
+impl CommandTrait for SetCohesionPropCommand {
+    fn name(&mut self, _context: &dyn CommandContext) -> String {
+        "Update Autotile Property".into()
+    }
+
+    fn execute(&mut self, _context: &mut dyn CommandContext) {
+        self.swap();
+    }
+
+    fn revert(&mut self, _context: &mut dyn CommandContext) {
+        self.swap();
+    }
+}
+
+#[derive(Debug)]
+struct SetTransformPropCommand {
+    pub brush: TileMapBrushResource,
+    pub instance: Resource<WfcInstance>,
+    pub data: Option<TileSetPropertyI32>,
+}
+
+impl SetTransformPropCommand {
+    fn swap(&mut self) {
+        let mut instance = self.instance.data_ref();
+        std::mem::swap(&mut instance.transform_property, &mut self.data);
+        self.brush.data_ref().change_flag.set();
+    }
+}
+
+impl CommandTrait for SetTransformPropCommand {
+    fn name(&mut self, _context: &dyn CommandContext) -> String {
+        "Update Autotile Property".into()
+    }
+
+    fn execute(&mut self, _context: &mut dyn CommandContext) {
+        self.swap();
+    }
+
+    fn revert(&mut self, _context: &mut dyn CommandContext) {
+        self.swap();
+    }
+}
+
+#[derive(Debug)]
+struct SetTerrainFrequencyPropCommand {
+    pub brush: TileMapBrushResource,
+    pub instance: Resource<WfcInstance>,
+    pub data: Option<TileSetPropertyF32>,
+}
+
+impl SetTerrainFrequencyPropCommand {
+    fn swap(&mut self) {
+        let mut instance = self.instance.data_ref();
+        std::mem::swap(&mut instance.terrain_frequency_property, &mut self.data);
+        self.brush.data_ref().change_flag.set();
+    }
+}
+
+impl CommandTrait for SetTerrainFrequencyPropCommand {
+    fn name(&mut self, _context: &dyn CommandContext) -> String {
+        "Update Autotile Property".into()
+    }
+
+    fn execute(&mut self, _context: &mut dyn CommandContext) {
+        self.swap();
+    }
+
+    fn revert(&mut self, _context: &mut dyn CommandContext) {
+        self.swap();
+    }
+}
+
+#[derive(Debug)]
+struct SetForbiddenAdjacencyCommand {
+    pub brush: TileMapBrushResource,
+    pub instance: Resource<WfcInstance>,
+    pub pair: TerrainPair,
+    pub forbidden: bool,
+}
+
+impl SetForbiddenAdjacencyCommand {
+    fn swap(&mut self) {
+        let mut instance = self.instance.data_ref();
+        let was_forbidden = instance.forbidden_adjacencies.contains(&self.pair);
+        if self.forbidden {
+            _ = instance.forbidden_adjacencies.insert(self.pair);
+        } else {
+            _ = instance.forbidden_adjacencies.remove(&self.pair);
+        }
+        self.forbidden = was_forbidden;
+        self.brush.data_ref().change_flag.set();
+    }
+}
+
+impl CommandTrait for SetForbiddenAdjacencyCommand {
+    fn name(&mut self, _context: &dyn CommandContext) -> String {
+        "Update Forbidden Adjacency".into()
+    }
+
+    fn execute(&mut self, _context: &mut dyn CommandContext) {
+        self.swap();
+    }
+
+    fn revert(&mut self, _context: &mut dyn CommandContext) {
+        self.swap();
+    }
+}
+
 #[derive(Debug)]
 struct SetTerrainFrequencyCommand {
     pub brush: TileMapBrushResource,
@@ -1054,6 +2354,93 @@ impl CommandTrait for SetConstrainEdgesCommand {
     }
 }
 
+#[derive(Debug)]
+struct SetConstrainNeighborsCommand {
+    pub brush: TileMapBrushResource,
+    pub instance: Resource<WfcInstance>,
+    pub data: bool,
+}
+
+impl SetConstrainNeighborsCommand {
+    fn swap(&mut self) {
+        let mut instance = self.instance.data_ref();
+        std::mem::swap(&mut instance.constrain_neighbors, &mut self.data);
+        self.brush.data_ref().change_flag.set();
+    }
+}
+
+impl CommandTrait for SetConstrainNeighborsCommand {
+    fn name(&mut self, _context: &dyn CommandContext) -> String {
+        "Update Constrain Neighbors".into()
+    }
+
+    fn execute(&mut self, _context: &mut dyn CommandContext) {
+        self.swap();
+    }
+
+    fn revert(&mut self, _context: &mut dyn CommandContext) {
+        self.swap();
+    }
+}
+
+#[derive(Debug)]
+struct SetPreviewCommand {
+    pub brush: TileMapBrushResource,
+    pub instance: Resource<WfcInstance>,
+    pub data: bool,
+}
+
+impl SetPreviewCommand {
+    fn swap(&mut self) {
+        let mut instance = self.instance.data_ref();
+        std::mem::swap(&mut instance.preview, &mut self.data);
+        self.brush.data_ref().change_flag.set();
+    }
+}
+
+impl CommandTrait for SetPreviewCommand {
+    fn name(&mut self, _context: &dyn CommandContext) -> String {
+        "Update Preview".into()
+    }
+
+    fn execute(&mut self, _context: &mut dyn CommandContext) {
+        self.swap();
+    }
+
+    fn revert(&mut self, _context: &mut dyn CommandContext) {
+        self.swap();
+    }
+}
+
+#[derive(Debug)]
+struct SetShowEntropyCommand {
+    pub brush: TileMapBrushResource,
+    pub instance: Resource<WfcInstance>,
+    pub data: bool,
+}
+
+impl SetShowEntropyCommand {
+    fn swap(&mut self) {
+        let mut instance = self.instance.data_ref();
+        std::mem::swap(&mut instance.show_entropy, &mut self.data);
+        self.brush.data_ref().change_flag.set();
+    }
+}
+
+impl CommandTrait for SetShowEntropyCommand {
+    fn name(&mut self, _context: &dyn CommandContext) -> String {
+        "Update Show Entropy".into()
+    }
+
+    fn execute(&mut self, _context: &mut dyn CommandContext) {
+        self.swap();
+    }
+
+    fn revert(&mut self, _context: &mut dyn CommandContext) {
+        self.swap();
+    }
+}
+
 #[derive(Debug)]
 struct SetMaxAttemptsCommand {
     pub brush: TileMapBrushResource,
@@ -1083,22 +2470,108 @@ impl CommandTrait for SetMaxAttemptsCommand {
     }
 }
 
+#[derive(Debug)]
+struct SetBacktrackDepthCommand {
+    pub brush: TileMapBrushResource,
+    pub instance: Resource<WfcInstance>,
+    pub data: u32,
+}
+
+impl SetBacktrackDepthCommand {
+    fn swap(&mut self) {
+        let mut instance = self.instance.data_ref();
+        std::mem::swap(&mut instance.backtrack_depth, &mut self.data);
+        self.brush.data_ref().change_flag.set();
+    }
+}
+
+impl CommandTrait for SetBacktrackDepthCommand {
+    fn name(&mut self, _context: &dyn CommandContext) -> String {
+        "Update Backtrack Depth".into()
+    }
+
+    fn execute(&mut self, _context: &mut dyn CommandContext) {
+        self.swap();
+    }
+
+    fn revert(&mut self, _context: &mut dyn CommandContext) {
+        self.swap();
+    }
+}
+
+#[derive(Debug)]
+struct SetSeedCommand {
+    pub brush: TileMapBrushResource,
+    pub instance: Resource<WfcInstance>,
+    pub data: Option<u64>,
+}
+
+impl SetSeedCommand {
+    fn swap(&mut self) {
+        let mut instance = self.instance.data_ref();
+        std::mem::swap(&mut instance.seed, &mut self.data);
+        self.brush.data_ref().change_flag.set();
+    }
+}
+
+impl CommandTrait for SetSeedCommand {
+    fn name(&mut self, _context: &dyn CommandContext) -> String {
+        "Update Seed".into()
+    }
+
+    fn execute(&mut self, _context: &mut dyn CommandContext) {
+        self.swap();
+    }
+
+    fn revert(&mut self, _context: &mut dyn CommandContext) {
+        self.swap();
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 enum WfcTaskState {
+    /// The task is running, or is about to start running.
     Running,
+    /// The command was reverted while the task was running. The task is paused
+    /// and will continue if the command is executed again.
     Cancelled,
+    /// The user aborted the task. The tiles are restored to what they were before
+    /// the task started and the task will never run again.
+    Aborted,
+    /// The task succeeded or ran out of attempts.
     Finished,
 }
 
+/// How far the task has progressed through its current stage.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum WfcTaskProgress {
+    /// The number of edge restrictions left to apply.
+    Edges { remaining: usize, total: usize },
+    /// The number of cells that have been given a pattern in the current attempt.
+    Cells { resolved: usize, total: usize },
+}
+
 struct WaveFunctionTaskCommand {
     tile_data_resource: TileMapDataResource,
     max_attempts: u32,
+    backtrack_depth: u32,
     task_data: Arc<Mutex<WaveFunctionTaskCommandData>>,
 }
 
 struct WaveFunctionTaskCommandData {
     state: WfcTaskState,
+    progress: WfcTaskProgress,
     attempts: u32,
+    backtracks: u32,
+    /// True if the task finished by finding a solution, rather than by running out of attempts.
+    solved: bool,
+    /// The tiles that were in the cells of the wave before the task changed them.
+    original_tiles: Option<Vec<(Vector2<i32>, Option<TileDefinitionHandle>)>>,
+    /// The random number generator for choosing patterns.
+    rng: StdRng,
+    /// The seed for choosing a tile for each pattern. Tiles are always chosen with the same
+    /// seed, so that executing the command again produces the same tiles.
+    tile_seed: u64,
     constraint: TileSetWfcConstraint,
     initial_propagator: TileSetWfcPropagator,
     working_propagator: TileSetWfcPropagator,
@@ -1108,12 +2581,25 @@ impl WaveFunctionTaskCommand {
     fn new(
         tile_data_resource: TileMapDataResource,
         max_attempts: u32,
+        backtrack_depth: u32,
+        seed: Option<u64>,
         constraint: TileSetWfcConstraint,
         propagator: TileSetWfcPropagator,
     ) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed.unwrap_or_else(|| thread_rng().gen()));
+        let tile_seed = rng.gen();
         let task_data = Arc::new(Mutex::new(WaveFunctionTaskCommandData {
             state: WfcTaskState::Running,
+            progress: WfcTaskProgress::Edges {
+                remaining: propagator.remaining_edge_restrictions(),
+                total: propagator.remaining_edge_restrictions(),
+            },
             attempts: 0,
+            backtracks: 0,
+            solved: false,
+            original_tiles: None,
+            rng,
+            tile_seed,
             constraint,
             initial_propagator: propagator,
             working_propagator: TileSetWfcPropagator::default(),
@@ -1121,6 +2607,7 @@ impl WaveFunctionTaskCommand {
         Self {
             tile_data_resource,
             max_attempts,
+            backtrack_depth,
             task_data,
         }
     }
@@ -1139,22 +2626,28 @@ impl CommandTrait for WaveFunctionTaskCommand {
 
     fn execute(&mut self, _context: &mut dyn CommandContext) {
         let mut data_guard = self.task_data.safe_lock();
-        if data_guard.state == WfcTaskState::Finished {
-            write_propagator_to_tile_data(
-                &data_guard.constraint,
-                &data_guard.working_propagator,
-                &self.tile_data_resource,
-            );
-            return;
+        match data_guard.state {
+            WfcTaskState::Finished => {
+                write_propagator_to_tile_data(
+                    &data_guard.constraint,
+                    &data_guard.working_propagator,
+                    data_guard.tile_seed,
+                    &self.tile_data_resource,
+                );
+                return;
+            }
+            WfcTaskState::Aborted => return,
+            WfcTaskState::Running | WfcTaskState::Cancelled => (),
         }
         data_guard.state = WfcTaskState::Running;
         drop(data_guard);
         let task_data = self.task_data.clone();
         let max_attempts = self.max_attempts;
+        let backtrack_depth = self.backtrack_depth;
         let resource = self.tile_data_resource.clone();
         match std::thread::Builder::new()
             .name("Wave Function Collapse".into())
-            .spawn(move || run_wfc(task_data, max_attempts, resource))
+            .spawn(move || run_wfc(task_data, max_attempts, backtrack_depth, Some(resource)))
         {
             Ok(_) => (),
             Err(_) => {
@@ -1165,7 +2658,7 @@ impl CommandTrait for WaveFunctionTaskCommand {
 
     fn revert(&mut self, _context: &mut dyn CommandContext) {
         let mut data_guard = self.task_data.safe_lock();
-        if data_guard.state != WfcTaskState::Finished {
+        if data_guard.state == WfcTaskState::Running {
             data_guard.state = WfcTaskState::Cancelled;
         }
     }
@@ -1174,43 +2667,155 @@ impl CommandTrait for WaveFunctionTaskCommand {
 fn write_propagator_to_tile_data(
     constraint: &TileSetWfcConstraint,
     propagator: &TileSetWfcPropagator,
+    tile_seed: u64,
     tile_data: &TileMapDataResource,
 ) {
     let mut tile_data = tile_data.data_ref();
-    propagator.apply_autotile_to_data(&mut thread_rng(), constraint, &mut tile_data);
+    let mut rng = StdRng::seed_from_u64(tile_seed);
+    propagator.apply_autotile_to_data(&mut rng, constraint, &mut tile_data);
 }
 
+impl WaveFunctionTaskCommandData {
+    /// Remember the tiles in the cells of the wave, if they have not been remembered already,
+    /// so that they can be restored if the task is aborted.
+    fn save_original_tiles(&mut self, data: &TileMapDataResource) {
+        if self.original_tiles.is_some() {
+            return;
+        }
+        let data = data.data_ref();
+        self.original_tiles = Some(
+            self.initial_propagator
+                .positions()
+                .map(|&p| (p, data.get(p)))
+                .collect(),
+        );
+    }
+    /// Put back the tiles that were saved by [`save_original_tiles`](Self::save_original_tiles).
+    fn restore_original_tiles(&mut self, data: &TileMapDataResource) {
+        let Some(original_tiles) = self.original_tiles.take() else {
+            return;
+        };
+        let mut data = data.data_ref();
+        for (p, handle) in original_tiles {
+            let _ = data.replace(p, handle);
+        }
+    }
+    /// Check whether the task should stop, and restore the tiles if the task was aborted.
+    /// True is returned if the task should stop.
+    fn should_stop(&mut self, data: Option<&TileMapDataResource>) -> bool {
+        match self.state {
+            WfcTaskState::Running => false,
+            WfcTaskState::Aborted => {
+                if let Some(data) = data {
+                    self.restore_original_tiles(data);
+                }
+                true
+            }
+            WfcTaskState::Cancelled | WfcTaskState::Finished => true,
+        }
+    }
+}
+
+/// The number of observations between each update of the task's progress,
+/// since counting the resolved cells requires iterating through the whole wave.
+const PROGRESS_INTERVAL: usize = 64;
+
+/// Run the wave function collapse task and write the result into the given tile data.
+/// If there is no tile data, the result is only kept in the working propagator of the task,
+/// which allows the result to be previewed without modifying the tile map.
 fn run_wfc(
     task_data: Arc<Mutex<WaveFunctionTaskCommandData>>,
     max_attempts: u32,
-    data: TileMapDataResource,
+    backtrack_depth: u32,
+    data: Option<TileMapDataResource>,
 ) {
-    let attempts = task_data.safe_lock().attempts;
-    let mut rng = thread_rng();
-    for i in attempts..max_attempts {
+    if let Some(data) = data.as_ref() {
+        task_data.safe_lock().save_original_tiles(data);
+    }
+    // Apply the edge restrictions one at a time, so that the task may be stopped midway.
+    // Edges are applied to the initial propagator, so the work is kept if the task is paused.
+    loop {
         let mut guard = task_data.safe_lock();
         let task_data = guard.deref_mut();
-        if task_data.state == WfcTaskState::Cancelled {
-            task_data.attempts = i;
+        if task_data.should_stop(data.as_ref()) {
             return;
         }
-        task_data
-            .working_propagator
-            .clone_from(&task_data.initial_propagator);
-        if let Ok(()) = task_data
-            .working_propagator
-            .observe_all(&mut rng, task_data.constraint.deref())
-        {
+        let result = task_data
+            .initial_propagator
+            .constrain_next_edge(task_data.constraint.deref());
+        if let WfcTaskProgress::Edges { remaining, .. } = &mut task_data.progress {
+            *remaining = task_data.initial_propagator.remaining_edge_restrictions();
+        }
+        match result {
+            Ok(WfcControlFlow::Continue) => (),
+            Ok(WfcControlFlow::Finish) => break,
+            Err(_) => {
+                Log::err("WFC failed while constraining edges.");
+                // Keep the contradiction, so it could be shown by the entropy overlay.
+                task_data
+                    .working_propagator
+                    .clone_from(&task_data.initial_propagator);
+                task_data.state = WfcTaskState::Finished;
+                return;
+            }
+        }
+    }
+    let attempts = task_data.safe_lock().attempts;
+    for i in attempts..max_attempts {
+        let mut guard = task_data.safe_lock();
+        let task = guard.deref_mut();
+        task.attempts = i;
+        task.working_propagator.clone_from(&task.initial_propagator);
+        task.progress = WfcTaskProgress::Cells {
+            resolved: task.working_propagator.assigned_patterns().count(),
+            total: task.working_propagator.positions().count(),
+        };
+        drop(guard);
+        let mut steps = 0;
+        let result = loop {
+            let mut guard = task_data.safe_lock();
+            let task = guard.deref_mut();
+            if task.should_stop(data.as_ref()) {
+                return;
+            }
+            let result = task.working_propagator.observe_one(
+                &mut task.rng,
+                task.constraint.deref(),
+                backtrack_depth,
+                &mut task.backtracks,
+            );
+            steps += 1;
+            if steps % PROGRESS_INTERVAL == 0 {
+                if let WfcTaskProgress::Cells { resolved, .. } = &mut task.progress {
+                    *resolved = task.working_propagator.assigned_patterns().count();
+                }
+            }
+            match result {
+                Ok(WfcControlFlow::Continue) => (),
+                Ok(WfcControlFlow::Finish) => break Ok(()),
+                Err(err) => break Err(err),
+            }
+        };
+        let mut guard = task_data.safe_lock();
+        let task = guard.deref_mut();
+        if let Some(data) = data.as_ref() {
             write_propagator_to_tile_data(
-                &task_data.constraint,
-                &task_data.working_propagator,
-                &data,
+                &task.constraint,
+                &task.working_propagator,
+                task.tile_seed,
+                data,
             );
-            task_data.state = WfcTaskState::Finished;
+        }
+        if result.is_ok() {
+            task.state = WfcTaskState::Finished;
+            task.solved = true;
             return;
         }
-        write_propagator_to_tile_data(&task_data.constraint, &task_data.working_propagator, &data);
     }
-    Log::err(format!("WFC failed after {max_attempts} attempts"));
-    task_data.safe_lock().state = WfcTaskState::Finished;
+    let mut guard = task_data.safe_lock();
+    let backtracks = guard.backtracks;
+    Log::err(format!(
+        "WFC failed after {max_attempts} attempts and {backtracks} backtracks"
+    ));
+    guard.state = WfcTaskState::Finished;
 }
@@ -645,7 +645,9 @@ impl SceneController for GameScene {
                     let camera = &graph[self.camera_controller.camera];
 
                     let normal = match camera.projection() {
-                        Projection::Perspective(_) => Vector3::new(0.0, 1.0, 0.0),
+                        Projection::Perspective(_) | Projection::Frustum(_) => {
+                            Vector3::new(0.0, 1.0, 0.0)
+                        }
                         Projection::Orthographic(_) => Vector3::new(0.0, 0.0, 1.0),
                     };
 
@@ -941,11 +943,14 @@ impl SceneController for GameScene {
         grid_material.set_property(
             "orientation",
             match projection {
-                Projection::Perspective(_) => 0i32,
+                Projection::Perspective(_) | Projection::Frustum(_) => 0i32,
                 Projection::Orthographic(_) => 1i32,
             },
         );
-        grid_material.set_property("isPerspective", projection.is_perspective());
+        grid_material.set_property(
+            "isPerspective",
+            projection.is_perspective() || projection.is_frustum(),
+        );
 
         let scale = if settings.move_mode_settings.grid_snapping {
             fn div_safe(a: f32, b: f32) -> f32 {
@@ -957,7 +962,7 @@ impl SceneController for GameScene {
             }
 
             match projection {
-                Projection::Perspective(_) => Vector2::new(
+                Projection::Perspective(_) | Projection::Frustum(_) => Vector2::new(
                     div_safe(1.0, settings.move_mode_settings.x_snap_step),
                     div_safe(1.0, settings.move_mode_settings.z_snap_step),
                 ),
@@ -972,7 +977,7 @@ impl SceneController for GameScene {
 
         grid_material.set_property("scale", scale);
 
-        let grid_offset = projection.z_far() - projection.z_near();
+        let grid_offset = projection.finite_z_far() - projection.z_near();
         scene.graph[self.grid]
             .local_transform_mut()
             .set_position(Vector3::new(0.0, 0.0, grid_offset));
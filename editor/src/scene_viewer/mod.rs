@@ -641,7 +641,7 @@ impl SceneViewer {
             .map(|game_scene| {
                 let scene = &engine.scenes[game_scene.scene];
                 match scene.graph[game_scene.camera_controller.camera].projection() {
-                    Projection::Perspective(_) => 0,
+                    Projection::Perspective(_) | Projection::Frustum(_) => 0,
                     Projection::Orthographic(_) => 1,
                 }
             });
@@ -849,7 +849,7 @@ impl SceneViewer {
                                         match graph[game_scene.camera_controller.camera]
                                             .projection()
                                         {
-                                            Projection::Perspective(_) => {
+                                            Projection::Perspective(_) | Projection::Frustum(_) => {
                                                 ui.send(
                                                     self.camera_projection,
                                                     DropdownListMessage::Selection(Some(1)),
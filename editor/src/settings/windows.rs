@@ -24,6 +24,13 @@ use crate::fyrox::{
 };
 use serde::{Deserialize, Serialize};
 
+/// Position and size of a window.
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone, Copy, Default, Reflect)]
+pub struct WindowGeometry {
+    pub position: Vector2<f32>,
+    pub size: Vector2<f32>,
+}
+
 #[derive(Deserialize, Serialize, PartialEq, Debug, Clone, Reflect)]
 pub struct WindowsSettings {
     #[serde(default)]
@@ -35,6 +42,10 @@ pub struct WindowsSettings {
     #[serde(default)]
     #[reflect(hidden)]
     pub layout: Option<DockingManagerLayoutDescriptor>,
+    /// Last geometry of the curve editor window. `None` means that the default geometry is used.
+    #[serde(default)]
+    #[reflect(hidden)]
+    pub curve_editor_window: Option<WindowGeometry>,
 }
 
 impl Default for WindowsSettings {
@@ -44,6 +55,7 @@ impl Default for WindowsSettings {
             window_size: Vector2::new(1024.0, 768.0),
             window_maximized: true,
             layout: None,
+            curve_editor_window: None,
         }
     }
 }
@@ -65,6 +65,7 @@ impl SceneLoader {
                 z_near: -0.1,
                 z_far: 16.0,
                 vertical_size: 2.0,
+                ..Default::default()
             }))
             .build(&mut scene.graph);
 
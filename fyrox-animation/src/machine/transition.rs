@@ -107,6 +107,7 @@ macro_rules! define_two_args_node {
                             max_value: None,
                             step: None,
                             precision: None,
+                            max_length: None,
                             doc: "",
                         };
 
@@ -125,7 +126,9 @@ macro_rules! define_two_args_node {
                             min_value: None,
                             max_value: None,
                             step: None,
-                            precision: None,doc: "",
+                            precision: None,
+                            max_length: None,
+                            doc: "",
                         };
 
                         FieldRef {
@@ -149,6 +152,7 @@ macro_rules! define_two_args_node {
                             max_value: None,
                             step: None,
                             precision: None,
+                            max_length: None,
                             doc: "",
                         };
 
@@ -167,7 +171,9 @@ macro_rules! define_two_args_node {
                             min_value: None,
                             max_value: None,
                             step: None,
-                            precision: None,doc: "",
+                            precision: None,
+                            max_length: None,
+                            doc: "",
                         };
 
                         FieldMut {
@@ -289,6 +295,7 @@ impl<T: EntityId> Reflect for NotNode<T> {
                 max_value: None,
                 step: None,
                 precision: None,
+                max_length: None,
                 doc: "",
             };
 
@@ -311,6 +318,7 @@ impl<T: EntityId> Reflect for NotNode<T> {
                 max_value: None,
                 step: None,
                 precision: None,
+                max_length: None,
                 doc: "",
             };
 
@@ -428,6 +428,16 @@ pub trait TilePattern {
     /// True if this pattern may be diagonal to the given other pattern when
     /// that other pattern is positioned at `offset` relative to this pattern.
     fn is_legal_diagonal(&self, offset: &Self::Diagonal, to: &Self) -> bool;
+    /// True if this pattern belongs to the same terrain as the given other pattern.
+    /// Wave function collapse uses this to count the matching neighbors of a cell
+    /// when choosing patterns with cohesion. By default, patterns only share
+    /// a terrain when they are equal.
+    fn is_same_terrain(&self, other: &Self) -> bool
+    where
+        Self: PartialEq,
+    {
+        self == other
+    }
 }
 
 /// `PatternBits` represents a tile's pattern as a 3x3 grid of `i8` values
@@ -549,6 +559,9 @@ impl TilePattern for PatternBits {
     fn is_legal_diagonal(&self, diagonal: &Vector2Diagonal, to: &Self) -> bool {
         self.0[diagonal.peering_bit()] == to.0[(-*diagonal).peering_bit()]
     }
+    fn is_same_terrain(&self, other: &Self) -> bool {
+        self.center() == other.center()
+    }
 }
 
 #[cfg(test)]
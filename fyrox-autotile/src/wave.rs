@@ -21,12 +21,14 @@
 //! Wave function collapse algorithm based upon [fast-wfc](https://github.com/math-fehr/fast-wfc).
 
 use std::{
+    collections::VecDeque,
     error::Error,
     fmt::Display,
     ops::{Deref, DerefMut},
 };
 
 use super::*;
+use fxhash::FxHashSet;
 
 type Entropy = f64;
 
@@ -113,6 +115,19 @@ pub trait WfcConstrain {
     /// True if the `from` pattern may be chosen when the `to` pattern has already been chosen
     /// for the cell at position `offset` relative to `from`.
     fn is_legal(&self, from: &Self::Pattern, offset: &Self::Offset, to: &Self::Pattern) -> bool;
+    /// How strongly the given pattern prefers to be placed beside matching neighbors.
+    /// When a pattern is chosen for a cell, its probability is multiplied by
+    /// `(1.0 + cohesion)` for each adjacent cell that has already been given a matching
+    /// pattern, as determined by [`is_matching_neighbor`](Self::is_matching_neighbor).
+    /// Values of 0.0 or less disable cohesion for the pattern, which is the default.
+    fn cohesion_of(&self, _pattern: &Self::Pattern) -> f32 {
+        0.0
+    }
+    /// True if `neighbor` counts as a matching neighbor of `pattern` for the purposes
+    /// of [`cohesion_of`](Self::cohesion_of). By default, only equal patterns match.
+    fn is_matching_neighbor(&self, pattern: &Self::Pattern, neighbor: &Self::Pattern) -> bool {
+        pattern == neighbor
+    }
 }
 
 /// An implementation for `WfcConstrain` that stores the information for each pattern
@@ -120,6 +135,9 @@ pub trait WfcConstrain {
 /// so that patterns may be translated into tiles after wave function collapse is complete.
 pub struct HashWfcConstraint<Pat, V> {
     pattern_map: FxHashMap<Pat, WfcPatternConstraint<V>>,
+    /// Pairs of patterns that may never be adjacent, even if the patterns match.
+    /// Each pair is stored in both directions.
+    forbidden_neighbors: FxHashMap<Pat, FxHashSet<Pat>>,
 }
 
 /// The data for a pattern, with probability of the pattern
@@ -127,6 +145,8 @@ pub struct HashWfcConstraint<Pat, V> {
 struct WfcPatternConstraint<V> {
     /// The probability of the pattern being chosen, between 0.0 and 1.0.
     probability: f32,
+    /// How strongly the pattern prefers to be beside matching neighbors.
+    cohesion: f32,
     /// The set of values that may be randomly chosen to represent the pattern
     /// when patterns are converted into tiles.
     value_set: ProbabilitySet<V>,
@@ -136,6 +156,7 @@ impl<V> Default for WfcPatternConstraint<V> {
     fn default() -> Self {
         Self {
             probability: 0.0,
+            cohesion: 0.0,
             value_set: ProbabilitySet::default(),
         }
     }
@@ -145,6 +166,7 @@ impl<Pat, V> Default for HashWfcConstraint<Pat, V> {
     fn default() -> Self {
         Self {
             pattern_map: FxHashMap::default(),
+            forbidden_neighbors: FxHashMap::default(),
         }
     }
 }
@@ -161,6 +183,27 @@ where
     /// to be reused with new pattern data.
     pub fn clear(&mut self) {
         self.pattern_map.clear();
+        self.forbidden_neighbors.clear();
+    }
+    /// Forbid the given patterns from being adjacent in any direction, regardless of whether
+    /// the patterns match. The propagator treats this as a hard rule, just like mismatched
+    /// patterns, so wave function collapse will backtrack or fail rather than break it.
+    pub fn forbid_neighbors(&mut self, a: Pat, b: Pat)
+    where
+        Pat: Clone,
+    {
+        self.forbidden_neighbors
+            .entry(a.clone())
+            .or_default()
+            .insert(b.clone());
+        self.forbidden_neighbors.entry(b).or_default().insert(a);
+    }
+    /// True if the given patterns have been forbidden from being adjacent
+    /// by [`forbid_neighbors`](Self::forbid_neighbors).
+    pub fn is_forbidden_neighbor(&self, a: &Pat, b: &Pat) -> bool {
+        self.forbidden_neighbors
+            .get(a)
+            .is_some_and(|set| set.contains(b))
     }
     /// Add a new value to the data with the given pattern and frequency.
     /// The frequency does not need to be between 0.0 and 1.0.
@@ -168,6 +211,17 @@ where
     /// frequencies will be automatically normalized into probabilities
     /// when [`finalize`](Self::finalize) is called.
     pub fn add(&mut self, pattern: Pat, frequency: f32, value: V)
+    where
+        Pat: Debug,
+        V: Debug,
+    {
+        self.add_with_cohesion(pattern, frequency, 0.0, value);
+    }
+    /// Add a new value to the data with the given pattern, frequency, and cohesion.
+    /// This is like [`add`](Self::add), but it also raises the cohesion of the pattern
+    /// to at least the given cohesion. Since many values may share a pattern, each pattern
+    /// uses the greatest cohesion of all its values. See [`WfcConstrain::cohesion_of`].
+    pub fn add_with_cohesion(&mut self, pattern: Pat, frequency: f32, cohesion: f32, value: V)
     where
         Pat: Debug,
         V: Debug,
@@ -175,11 +229,9 @@ where
         if frequency <= 0.0 {
             return;
         }
-        self.pattern_map
-            .entry(pattern)
-            .or_default()
-            .value_set
-            .add(frequency, value);
+        let data = self.pattern_map.entry(pattern).or_default();
+        data.value_set.add(frequency, value);
+        data.cohesion = data.cohesion.max(cohesion);
     }
     /// Calculate the probability of each pattern based on the frequencies
     /// of all values that were added with [`add`](Self::add).
@@ -262,7 +314,18 @@ where
     }
 
     fn is_legal(&self, from: &Self::Pattern, offset: &Self::Offset, to: &Self::Pattern) -> bool {
-        from.is_legal(offset, to)
+        from.is_legal(offset, to) && !self.is_forbidden_neighbor(from, to)
+    }
+
+    fn cohesion_of(&self, pattern: &Self::Pattern) -> f32 {
+        self.pattern_map
+            .get(pattern)
+            .map(|s| s.cohesion)
+            .unwrap_or_default()
+    }
+
+    fn is_matching_neighbor(&self, pattern: &Self::Pattern, neighbor: &Self::Pattern) -> bool {
+        pattern.is_same_terrain(neighbor)
     }
 }
 
@@ -439,6 +502,17 @@ pub struct WfcPropagator<Pos: WavePosition, Pat> {
     pending: Vec<(Pos, Pat)>,
     backtrack_cells: Vec<WaveCell<Pos::Counter, Pat>>,
     backtrack_map: FxHashMap<Pos, WaveCell<Pos::Counter, Pat>>,
+    checkpoints: VecDeque<WfcCheckpoint<Pos, Pat>>,
+    checkpoint_maps: Vec<FxHashMap<Pos, WaveCell<Pos::Counter, Pat>>>,
+}
+
+/// The changes of the wave since an observation, which allow the observation to be undone.
+/// Only the cells that were changed are saved, as they were before the first change.
+#[derive(Debug, Clone)]
+struct WfcCheckpoint<Pos: WavePosition, Pat> {
+    position: Pos,
+    pattern: Pat,
+    cells: FxHashMap<Pos, WaveCell<Pos::Counter, Pat>>,
 }
 
 impl<Pos: WavePosition + Debug, Pat: Clone + Hash + Eq + Debug> WfcPropagator<Pos, Pat> {
@@ -460,6 +534,26 @@ impl<Pos: WavePosition + Debug, Pat: Clone + Hash + Eq + Debug> WfcPropagator<Po
             .iter()
             .filter_map(|(p, c)| Some((p, c.single_pattern()?)))
     }
+    /// The number of patterns that are still possible for the cell at the given position,
+    /// or None if the wave has no cell at that position. A count of 1 means the cell
+    /// has been resolved, while a count of 0 means the cell is a contradiction.
+    pub fn option_count(&self, position: &Pos) -> Option<usize> {
+        self.wave
+            .get(position)
+            .map(|c| c.pattern_possibilities.len())
+    }
+    /// Iterator over every cell of the wave along with the number of patterns
+    /// that are still possible for that cell. See [`option_count`](Self::option_count).
+    pub fn option_counts(&self) -> impl Iterator<Item = (&Pos, usize)> {
+        self.wave
+            .iter()
+            .map(|(p, c)| (p, c.pattern_possibilities.len()))
+    }
+    /// The number of patterns that are possible for a cell before any restrictions are applied,
+    /// which is the largest value that [`option_count`](Self::option_count) could return.
+    pub fn max_option_count(&self) -> usize {
+        self.limits.max_cell.pattern_possibilities.len()
+    }
     /// Use the data in a [`WfcConstrain`] object to initialize the propagator
     /// for a new wave function collapse using the given constraint.
     /// After calling this method, the next step is to call [`add_cell`](Self::add_cell) for each
@@ -471,6 +565,7 @@ impl<Pos: WavePosition + Debug, Pat: Clone + Hash + Eq + Debug> WfcPropagator<Po
         self.limits.fill_from::<Pos, Con>(constraint);
         self.wave.clear();
         self.propagating.clear();
+        self.clear_checkpoints();
     }
     /// Create a new cell at the given position, assuming that wave function collapse has not yet begun
     /// and all the surrounding cells have no restrictions.
@@ -505,6 +600,94 @@ impl<Pos: WavePosition + Debug, Pat: Clone + Hash + Eq + Debug> WfcPropagator<Po
             self.backtrack_cells.push(backtrack_cell);
         }
     }
+    /// Save the cell at the given position in the most recent checkpoint, unless it is already
+    /// saved there. This must be called before the cell is changed.
+    fn save_checkpoint_cell(&mut self, position: &Pos) {
+        let Some(checkpoint) = self.checkpoints.back_mut() else {
+            return;
+        };
+        if let Entry::Vacant(entry) = checkpoint.cells.entry(position.clone()) {
+            let Some(cell) = self.wave.get(position) else {
+                return;
+            };
+            let mut backtrack_cell = self.backtrack_cells.pop().unwrap_or_default();
+            backtrack_cell.clone_from(cell);
+            entry.insert(backtrack_cell);
+        }
+    }
+    fn recycle_checkpoint(&mut self, mut checkpoint: WfcCheckpoint<Pos, Pat>) {
+        self.backtrack_cells
+            .extend(checkpoint.cells.drain().map(|(_, cell)| cell));
+        self.checkpoint_maps.push(checkpoint.cells);
+    }
+    /// Start recording the changes of the wave that are caused by observing the given pattern
+    /// at the given position, so that the observation can be undone by
+    /// [`undo_observation`](Self::undo_observation). This should be called right before the
+    /// observation. Only the `max_count` most recent checkpoints are kept, and no checkpoint
+    /// is created if `max_count` is 0.
+    pub fn push_checkpoint(&mut self, position: Pos, pattern: Pat, max_count: usize) {
+        if max_count == 0 {
+            return;
+        }
+        while self.checkpoints.len() >= max_count {
+            let Some(checkpoint) = self.checkpoints.pop_front() else {
+                break;
+            };
+            self.recycle_checkpoint(checkpoint);
+        }
+        let cells = self.checkpoint_maps.pop().unwrap_or_default();
+        self.checkpoints.push_back(WfcCheckpoint {
+            position,
+            pattern,
+            cells,
+        });
+    }
+    /// The number of observations that can currently be undone.
+    pub fn checkpoint_count(&self) -> usize {
+        self.checkpoints.len()
+    }
+    /// Forget all checkpoints, so that no observation can be undone.
+    pub fn clear_checkpoints(&mut self) {
+        while let Some(checkpoint) = self.checkpoints.pop_front() {
+            self.recycle_checkpoint(checkpoint);
+        }
+    }
+    /// Restore the wave to the state it had before the most recent observation that has
+    /// a checkpoint (see [`push_checkpoint`](Self::push_checkpoint)), and forbid the pattern that
+    /// was chosen by that observation, so that an alternative may be tried. If forbidding the
+    /// pattern leaves the cell with no possibilities, the observation before it is undone as well.
+    ///
+    /// `backtracks` is incremented for each undone observation. [`WfcFailure`] is returned if
+    /// there are no more checkpoints to restore.
+    pub fn undo_observation<Con>(
+        &mut self,
+        constraint: &Con,
+        backtracks: &mut u32,
+    ) -> Result<(), WfcFailure>
+    where
+        Con: WfcConstrain<Pattern = Pat, Offset = Pos::Offset>,
+    {
+        loop {
+            let Some(mut checkpoint) = self.checkpoints.pop_back() else {
+                return Err(WfcFailure);
+            };
+            *backtracks += 1;
+            for (pos, backtrack_cell) in checkpoint.cells.drain() {
+                if let Some(cell) = self.wave.get_mut(&pos) {
+                    cell.clone_from(&backtrack_cell);
+                }
+                self.backtrack_cells.push(backtrack_cell);
+            }
+            self.propagating.clear();
+            self.pending.clear();
+            // The changes caused by forbidding the pattern are recorded by the previous checkpoint.
+            let result = self.forbid_pattern(&checkpoint.position, &checkpoint.pattern, constraint);
+            self.checkpoint_maps.push(checkpoint.cells);
+            if result.is_ok() {
+                return Ok(());
+            }
+        }
+    }
     /// Randomly choose a low-entropy cell. This is used by [`observe_random_cell`](Self::observe_random_cell)
     /// to decide which cell will be observed.
     pub fn find_min_entropy<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<Pos>
@@ -529,6 +712,10 @@ impl<Pos: WavePosition + Debug, Pat: Clone + Hash + Eq + Debug> WfcPropagator<Po
     /// Choose a random pattern from among the potential patterns for the given cell based
     /// on probabilities given in `constraint`. This is called by
     /// [`observe_random_cell`](Self::observe_random_cell) to decide which pattern to assign.
+    ///
+    /// If any of the potential patterns has a positive [`WfcConstrain::cohesion_of`], then
+    /// the probabilities are weighted by the number of adjacent cells that have already been
+    /// given a matching pattern, so that matching patterns tend to form clusters.
     pub fn choose_random_pattern<R, Con>(
         &self,
         position: &Pos,
@@ -540,6 +727,13 @@ impl<Pos: WavePosition + Debug, Pat: Clone + Hash + Eq + Debug> WfcPropagator<Po
         Con: WfcConstrain<Pattern = Pat, Offset = Pos::Offset>,
     {
         let cell = self.wave.get(position)?;
+        if cell
+            .pattern_possibilities
+            .keys()
+            .any(|p| constraint.cohesion_of(p) > 0.0)
+        {
+            return self.choose_cohesive_pattern(position, cell, rng, constraint);
+        }
         let mut target = rng.gen_range(0.0..cell.sum);
         for pattern in cell.pattern_possibilities.keys() {
             let p = constraint.probability_of(pattern) as Entropy;
@@ -550,6 +744,46 @@ impl<Pos: WavePosition + Debug, Pat: Clone + Hash + Eq + Debug> WfcPropagator<Po
         }
         cell.pattern_possibilities.keys().next().cloned()
     }
+    fn choose_cohesive_pattern<R, Con>(
+        &self,
+        position: &Pos,
+        cell: &WaveCell<Pos::Counter, Pat>,
+        rng: &mut R,
+        constraint: &Con,
+    ) -> Option<Pat>
+    where
+        R: Rng + ?Sized,
+        Con: WfcConstrain<Pattern = Pat, Offset = Pos::Offset>,
+    {
+        let neighbors = Pos::all_offsets()
+            .filter_map(|offset| self.wave.get(&(position.clone() + offset)))
+            .filter_map(|c| c.single_pattern())
+            .collect::<Vec<_>>();
+        let weight = |pattern: &Pat| {
+            let p = constraint.probability_of(pattern) as Entropy;
+            let cohesion = constraint.cohesion_of(pattern) as Entropy;
+            if cohesion <= 0.0 {
+                return p;
+            }
+            let matches = neighbors
+                .iter()
+                .filter(|n| constraint.is_matching_neighbor(pattern, n))
+                .count();
+            p * (1.0 + cohesion).powi(matches as i32)
+        };
+        let sum: Entropy = cell.pattern_possibilities.keys().map(weight).sum();
+        if sum <= 0.0 {
+            return cell.pattern_possibilities.keys().next().cloned();
+        }
+        let mut target = rng.gen_range(0.0..sum);
+        for pattern in cell.pattern_possibilities.keys() {
+            target -= weight(pattern);
+            if target <= 0.0 {
+                return Some(pattern.clone());
+            }
+        }
+        cell.pattern_possibilities.keys().next().cloned()
+    }
     /// Constrain the cells around the given position based on the assumption that the given
     /// position has the given pattern. Calling this method twice on the same position may put
     /// the surrounding cells into an invalid state, even if both calls have the same pattern.
@@ -573,21 +807,22 @@ impl<Pos: WavePosition + Debug, Pat: Clone + Hash + Eq + Debug> WfcPropagator<Po
             let Some(other_cell) = self.wave.get_mut(&other_pos) else {
                 continue;
             };
-            other_cell
-                .pattern_possibilities
-                .retain(|other_pattern, _counter| {
-                    if !constraint.is_legal(pattern, &offset, other_pattern) {
-                        self.pending
-                            .push((other_pos.clone(), other_pattern.clone()));
-                        false
-                    } else {
-                        true
-                    }
-                });
-            if other_cell.pattern_possibilities.is_empty() {
+            // Illegal patterns are removed later by `restrict`, so that the consequences
+            // of the removal are propagated across the wave.
+            let mut any_legal = false;
+            for other_pattern in other_cell.pattern_possibilities.keys() {
+                if constraint.is_legal(pattern, &offset, other_pattern) {
+                    any_legal = true;
+                } else {
+                    self.pending
+                        .push((other_pos.clone(), other_pattern.clone()));
+                }
+            }
+            if !any_legal {
                 return Err(WfcFailure);
             }
         }
+        self.propagating.append(&mut self.pending);
         Ok(())
     }
     fn set_cell<Con>(
@@ -599,6 +834,7 @@ impl<Pos: WavePosition + Debug, Pat: Clone + Hash + Eq + Debug> WfcPropagator<Po
     where
         Con: WfcConstrain<Pattern = Pat, Offset = Pos::Offset>,
     {
+        self.save_checkpoint_cell(position);
         let cell = self.wave.get_mut(position).expect("Missing wave cell");
         let mut possibilities = std::mem::take(&mut cell.pattern_possibilities);
         let (pattern, count) = possibilities
@@ -661,12 +897,31 @@ impl<Pos: WavePosition + Debug, Pat: Clone + Hash + Eq + Debug> WfcPropagator<Po
         let pattern = self
             .choose_random_pattern(&position, rng, constraint)
             .unwrap();
+        self.observe_cell(&position, &pattern, constraint)?;
+        Ok(WfcControlFlow::Continue)
+    }
+    /// Assign the given pattern to the cell at the given position, as if it had been chosen by
+    /// [`observe_random_cell`](Self::observe_random_cell). Propagation should be finished before
+    /// this method is called, and it should be performed again afterward.
+    ///
+    /// If the pattern immediately leads to a contradiction in the surrounding cells, the pattern
+    /// is removed from the possibilities of the cell instead. [`WfcFailure`] is returned if that
+    /// leaves the cell with no remaining possibilities.
+    pub fn observe_cell<Con>(
+        &mut self,
+        position: &Pos,
+        pattern: &Pat,
+        constraint: &Con,
+    ) -> Result<(), WfcFailure>
+    where
+        Con: WfcConstrain<Pattern = Pat, Offset = Pos::Offset>,
+    {
         self.save_cell(position.clone());
         for offset in Pos::all_offsets() {
             let p = position.clone() + offset;
             self.save_cell(p);
         }
-        match self.set_cell(&position, &pattern, constraint) {
+        match self.set_cell(position, pattern, constraint) {
             Ok(()) => {
                 self.clear_backtrack();
                 self.propagating.append(&mut self.pending);
@@ -674,11 +929,26 @@ impl<Pos: WavePosition + Debug, Pat: Clone + Hash + Eq + Debug> WfcPropagator<Po
             Err(_) => {
                 self.backtrack();
                 self.pending.clear();
-                self.restrict(&position, &pattern, constraint)?;
-                self.propagating.append(&mut self.pending);
+                self.forbid_pattern(position, pattern, constraint)?;
             }
         }
-        Ok(WfcControlFlow::Continue)
+        Ok(())
+    }
+    /// Remove the given pattern from the possibilities of the cell at the given position,
+    /// and prepare to propagate the consequences of that restriction to the surrounding cells.
+    /// [`WfcFailure`] is returned if the cell is left with no remaining possibilities.
+    pub fn forbid_pattern<Con>(
+        &mut self,
+        position: &Pos,
+        pattern: &Pat,
+        constraint: &Con,
+    ) -> Result<(), WfcFailure>
+    where
+        Con: WfcConstrain<Pattern = Pat, Offset = Pos::Offset>,
+    {
+        self.restrict(position, pattern, constraint)?;
+        self.propagating.append(&mut self.pending);
+        Ok(())
     }
     /// Repeatedly call [`propagate`](Self::propagate) until it returns [`WfcControlFlow::Finish`],
     /// thus ensuring that all the cells are prepared for the next observation. This is called
@@ -720,6 +990,14 @@ impl<Pos: WavePosition + Debug, Pat: Clone + Hash + Eq + Debug> WfcPropagator<Po
     where
         Con: WfcConstrain<Pattern = Pat, Offset = Pos::Offset>,
     {
+        if !self
+            .wave
+            .get(position)
+            .is_some_and(|cell| cell.pattern_possibilities.contains_key(pattern))
+        {
+            return Ok(());
+        }
+        self.save_checkpoint_cell(position);
         let Some(cell) = self.wave.get_mut(position) else {
             return Ok(());
         };
@@ -758,26 +1036,25 @@ impl<Pos: WavePosition + Debug, Pat: Clone + Hash + Eq + Debug> WfcPropagator<Po
     {
         for offset in Pos::all_offsets() {
             let other_pos = position.clone() + offset.clone();
+            self.save_checkpoint_cell(&other_pos);
             let Some(other_cell) = self.wave.get_mut(&other_pos) else {
                 continue;
             };
-            other_cell
-                .pattern_possibilities
-                .retain(|other_pattern, counter| {
-                    if constraint.is_legal(pattern, &offset, other_pattern) {
-                        let c = counter.count_mut(&offset);
-                        *c -= 1;
-                        if *c == 0 {
-                            self.pending
-                                .push((other_pos.clone(), other_pattern.clone()));
-                        }
-                        *c > 0
-                    } else {
-                        true
+            // Patterns that lost their last support stay in the cell until `restrict` removes
+            // them, because `restrict` is what spreads the consequences further across the wave.
+            // Removing them here would stop the propagation at the adjacent cells.
+            for (other_pattern, counter) in other_cell.pattern_possibilities.iter_mut() {
+                if !constraint.is_legal(pattern, &offset, other_pattern) {
+                    continue;
+                }
+                let c = counter.count_mut(&offset);
+                if *c > 0 {
+                    *c -= 1;
+                    if *c == 0 {
+                        self.pending
+                            .push((other_pos.clone(), other_pattern.clone()));
                     }
-                });
-            if other_cell.pattern_possibilities.is_empty() {
-                return Err(WfcFailure);
+                }
             }
         }
         Ok(())
@@ -808,3 +1085,221 @@ impl<Pos: WavePosition + Debug, Pat: Clone + Hash + Eq + Debug> WfcPropagator<Po
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use PatternBits as Bits;
+
+    use super::*;
+
+    fn make_rng(seed: u64) -> rand::rngs::StdRng {
+        rand::rngs::StdRng::seed_from_u64(seed)
+    }
+
+    fn terrain(center: i8) -> Bits {
+        let mut bits = Bits::default();
+        bits.0[4] = center;
+        bits
+    }
+
+    /// Observe one of two adjacent cells, then count how often the other cell
+    /// would be given the same pattern out of 100 random choices.
+    fn count_matching_choices(constraint: &HashWfcConstraint<Bits, u32>) -> usize {
+        let mut propagator = WfcPropagator::<Vector2<i32>, Bits>::default();
+        propagator.fill_from(constraint);
+        propagator.add_cell(Vector2::new(0, 0));
+        propagator.add_cell(Vector2::new(1, 0));
+        let mut rng = make_rng(0);
+        propagator
+            .observe_random_cell(&mut rng, constraint)
+            .unwrap();
+        let (&observed, &pattern) = propagator.assigned_patterns().next().unwrap();
+        let other = Vector2::new(1 - observed.x, 0);
+        (0..100)
+            .filter(|_| {
+                propagator.choose_random_pattern(&other, &mut rng, constraint) == Some(pattern)
+            })
+            .count()
+    }
+
+    #[test]
+    fn forbidden_neighbors_are_never_adjacent() {
+        let mut constraint = HashWfcConstraint::<Bits, u32>::default();
+        constraint.add(terrain(1), 1.0, 1);
+        constraint.add(terrain(2), 1.0, 2);
+        constraint.forbid_neighbors(terrain(1), terrain(2));
+        constraint.finalize();
+        assert!(constraint.is_forbidden_neighbor(&terrain(2), &terrain(1)));
+        for seed in 0..20 {
+            let mut propagator = WfcPropagator::<Vector2<i32>, Bits>::default();
+            propagator.fill_from(&constraint);
+            for x in 0..4 {
+                propagator.add_cell(Vector2::new(x, 0));
+            }
+            propagator
+                .observe_all(&mut make_rng(seed), &constraint)
+                .unwrap();
+            let patterns = (0..4)
+                .map(|x| {
+                    propagator
+                        .assigned_patterns()
+                        .find(|(p, _)| p.x == x)
+                        .map(|(_, pat)| *pat)
+                        .unwrap()
+                })
+                .collect::<Vec<_>>();
+            // Terrains cannot mix, so the whole row must be one terrain.
+            assert!(patterns.iter().all(|p| *p == patterns[0]), "{patterns:?}");
+        }
+    }
+
+    #[test]
+    fn edge_restriction_propagates_across_wave() {
+        let mut constraint = HashWfcConstraint::<Bits, u32>::default();
+        constraint.add(terrain(1), 1.0, 1);
+        constraint.add(terrain(2), 1.0, 2);
+        constraint.forbid_neighbors(terrain(1), terrain(2));
+        constraint.finalize();
+        let mut propagator = WfcPropagator::<Vector2<i32>, Bits>::default();
+        propagator.fill_from(&constraint);
+        for x in 0..4 {
+            propagator.add_cell(Vector2::new(x, 0));
+        }
+        propagator
+            .restrict_edge(&Vector2::new(-1, 0), &terrain(1), &constraint)
+            .unwrap();
+        propagator.propagate_until_finished(&constraint).unwrap();
+        assert!(propagator.option_counts().all(|(_, count)| count == 1));
+        assert!(propagator
+            .assigned_patterns()
+            .all(|(_, pat)| *pat == terrain(1)));
+    }
+
+    #[test]
+    fn option_counts_shrink_after_observation() {
+        let mut constraint = HashWfcConstraint::<Bits, u32>::default();
+        constraint.add(terrain(1), 1.0, 1);
+        constraint.add(terrain(2), 1.0, 2);
+        constraint.finalize();
+        let mut propagator = WfcPropagator::<Vector2<i32>, Bits>::default();
+        propagator.fill_from(&constraint);
+        propagator.add_cell(Vector2::new(0, 0));
+        propagator.add_cell(Vector2::new(1, 0));
+        assert_eq!(propagator.max_option_count(), 2);
+        assert!(propagator.option_counts().all(|(_, count)| count == 2));
+        assert_eq!(propagator.option_count(&Vector2::new(5, 5)), None);
+        let mut rng = make_rng(0);
+        propagator
+            .observe_random_cell(&mut rng, &constraint)
+            .unwrap();
+        let (&observed, _) = propagator.assigned_patterns().next().unwrap();
+        assert_eq!(propagator.option_count(&observed), Some(1));
+    }
+
+    #[test]
+    fn cohesion_prefers_matching_neighbors() {
+        let mut constraint = HashWfcConstraint::<Bits, u32>::default();
+        constraint.add_with_cohesion(terrain(1), 1.0, 1000.0, 1);
+        constraint.add_with_cohesion(terrain(2), 1.0, 1000.0, 2);
+        constraint.finalize();
+        assert_eq!(constraint.cohesion_of(&terrain(1)), 1000.0);
+        let matching = count_matching_choices(&constraint);
+        assert!(matching >= 95, "{matching}");
+    }
+
+    #[test]
+    fn no_cohesion_ignores_neighbors() {
+        let mut constraint = HashWfcConstraint::<Bits, u32>::default();
+        constraint.add(terrain(1), 1.0, 1);
+        constraint.add(terrain(2), 1.0, 2);
+        constraint.finalize();
+        assert_eq!(constraint.cohesion_of(&terrain(1)), 0.0);
+        let matching = count_matching_choices(&constraint);
+        assert!((20..80).contains(&matching), "{matching}");
+    }
+
+    #[test]
+    fn backtracking_recovers_from_contradiction() {
+        // A square of four cells, where terrain 1 in the first cell forces its two neighbors
+        // into terrains that no pattern of the opposite cell can be next to at the same time.
+        // Terrain 4 is the only pattern of the first cell that leads to a solution, but that is
+        // only discovered by propagation after terrain 1 has already been observed.
+        let allowed = [
+            (1, 2),
+            (1, 5),
+            (4, 2),
+            (4, 3),
+            (4, 5),
+            (4, 6),
+            (7, 2),
+            (7, 6),
+            (8, 3),
+            (8, 5),
+        ];
+        let mut constraint = HashWfcConstraint::<Bits, u32>::default();
+        for a in 1..=8 {
+            constraint.add(terrain(a), 1.0, a as u32);
+            for b in a..=8 {
+                if !allowed.contains(&(a, b)) && !allowed.contains(&(b, a)) {
+                    constraint.forbid_neighbors(terrain(a), terrain(b));
+                }
+            }
+        }
+        constraint.finalize();
+        let cells = [
+            (Vector2::new(0, 0), [1, 4]),
+            (Vector2::new(1, 0), [2, 3]),
+            (Vector2::new(1, 1), [7, 8]),
+            (Vector2::new(0, 1), [5, 6]),
+        ];
+        let mut propagator = WfcPropagator::<Vector2<i32>, Bits>::default();
+        propagator.fill_from(&constraint);
+        for (position, _) in cells.iter() {
+            propagator.add_cell(*position);
+        }
+        for (position, terrains) in cells.iter() {
+            for t in (1..=8).filter(|t| !terrains.contains(t)) {
+                propagator
+                    .forbid_pattern(position, &terrain(t), &constraint)
+                    .unwrap();
+            }
+        }
+        propagator.propagate_until_finished(&constraint).unwrap();
+        assert!(propagator.option_counts().all(|(_, count)| count == 2));
+
+        let first = Vector2::new(0, 0);
+        propagator.push_checkpoint(first, terrain(1), 4);
+        propagator
+            .observe_cell(&first, &terrain(1), &constraint)
+            .unwrap();
+        assert!(propagator.propagate_until_finished(&constraint).is_err());
+
+        let mut backtracks = 0;
+        propagator
+            .undo_observation(&constraint, &mut backtracks)
+            .unwrap();
+        assert_eq!(backtracks, 1);
+        assert_eq!(propagator.checkpoint_count(), 0);
+        propagator.propagate_until_finished(&constraint).unwrap();
+        assert_eq!(propagator.option_count(&first), Some(1));
+        assert!(propagator
+            .option_counts()
+            .all(|(p, count)| p == &first || count == 2));
+
+        propagator
+            .observe_all(&mut make_rng(0), &constraint)
+            .unwrap();
+        let patterns = propagator
+            .assigned_patterns()
+            .map(|(p, pat)| (*p, *pat))
+            .collect::<FxHashMap<_, _>>();
+        assert_eq!(patterns.len(), 4);
+        assert_eq!(patterns[&first], terrain(4));
+        for (i, (position, _)) in cells.iter().enumerate() {
+            let next = cells[(i + 1) % cells.len()].0;
+            assert!(!constraint.is_forbidden_neighbor(&patterns[position], &patterns[&next]));
+        }
+    }
+}
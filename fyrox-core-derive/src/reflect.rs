@@ -112,6 +112,11 @@ fn quote_field_prop(
         Some(v) => quote! { Some(#v) },
     };
 
+    let max_length = match field.max_length {
+        None => quote! { None },
+        Some(v) => quote! { Some(#v) },
+    };
+
     let tag = field.tag.clone().unwrap_or_default();
 
     let read_only = field.read_only;
@@ -137,6 +142,7 @@ fn quote_field_prop(
                 max_value: #max_value,
                 step: #step,
                 precision: #precision,
+                max_length: #max_length,
             };
 
             #variant {
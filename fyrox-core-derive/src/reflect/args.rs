@@ -225,6 +225,12 @@ pub struct FieldArgs {
     /// Maximum amount of decimal places for a numeric property.
     #[darling(default)]
     pub precision: Option<usize>,
+
+    /// `#[reflect(max_length = "32")]`
+    ///
+    /// Maximum amount of characters of a string field. Works only for string fields!
+    #[darling(default)]
+    pub max_length: Option<usize>,
 }
 
 impl FieldArgs {
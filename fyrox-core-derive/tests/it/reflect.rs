@@ -355,6 +355,7 @@ fn default_prop_metadata() -> FieldMetadata<'static> {
         max_value: None,
         step: None,
         precision: None,
+        max_length: None,
         tag: "",
         doc: "",
     }
@@ -446,6 +447,7 @@ fn inspect_attributes() {
                 max_value: Some(1.1),
                 step: Some(0.1),
                 precision: Some(3),
+                max_length: None,
                 tag: "SomeTag",
                 doc: "",
             },
@@ -456,6 +458,31 @@ fn inspect_attributes() {
     data.fields_ref(&mut |fields_ref| assert_eq!(fields_ref[0..2], expected));
 }
 
+#[test]
+fn inspect_max_length() {
+    #[derive(Debug, Default, Clone, Reflect)]
+    pub struct Data {
+        #[reflect(max_length = 16)]
+        name: String,
+    }
+
+    let data = Data::default();
+
+    let name_metadata = FieldMetadata {
+        name: "name",
+        display_name: "Name",
+        max_length: Some(16),
+        ..default_prop_metadata()
+    };
+
+    let expected = vec![FieldRef {
+        metadata: &name_metadata,
+        value: &data.name,
+    }];
+
+    data.fields_ref(&mut |fields_ref| assert_eq!(fields_ref, expected));
+}
+
 #[test]
 fn inspect_struct() {
     #[derive(Debug, Default, Clone, Reflect)]
@@ -178,6 +178,7 @@ impl Reflect for DynTypeWrapper {
                 max_value: None,
                 step: None,
                 precision: None,
+                max_length: None,
                 doc: "",
             };
             FieldRef {
@@ -199,6 +200,7 @@ impl Reflect for DynTypeWrapper {
                 max_value: None,
                 step: None,
                 precision: None,
+                max_length: None,
                 doc: "",
             };
             FieldMut {
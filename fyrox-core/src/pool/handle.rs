@@ -94,6 +94,7 @@ static INDEX_METADATA: FieldMetadata = FieldMetadata {
     max_value: None,
     step: None,
     precision: None,
+    max_length: None,
     doc: "",
 };
 
@@ -107,6 +108,7 @@ static GENERATION_METADATA: FieldMetadata = FieldMetadata {
     max_value: None,
     step: None,
     precision: None,
+    max_length: None,
     doc: "",
 };
 
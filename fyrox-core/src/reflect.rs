@@ -130,7 +130,7 @@ pub struct FieldMetadata<'s> {
     /// A minimal value of the property. Works only with numeric properties!
     pub min_value: Option<f64>,
 
-    /// A minimal value of the property. Works only with numeric properties!
+    /// A maximal value of the property. Works only with numeric properties!
     pub max_value: Option<f64>,
 
     /// A minimal value of the property. Works only with numeric properties!
@@ -138,6 +138,9 @@ pub struct FieldMetadata<'s> {
 
     /// Maximum amount of decimal places for a numeric property.
     pub precision: Option<usize>,
+
+    /// Maximum amount of characters of a string property. Works only with string properties!
+    pub max_length: Option<usize>,
 }
 
 pub struct FieldRef<'a, 'b> {
@@ -285,6 +288,8 @@ where
 /// - `#[reflect(max_value = "1.0")]` - maximal value of the field. Works only for numeric fields!
 /// - `#[reflect(step = "0.1")]` - increment/decrement step of the field. Works only for numeric fields!
 /// - `#[reflect(precision = "3")]` - maximum amount of decimal places for a numeric property.
+/// - `#[reflect(max_length = "32")]` - maximum amount of characters of a string field. Works only
+/// for string fields!
 ///
 /// ### Clone
 ///
@@ -35,7 +35,9 @@ use fyrox_graphics::{
     geometry_buffer::GpuGeometryBuffer,
     gpu_program::GpuProgram,
     gpu_texture::image_2d_size_bytes,
-    gpu_texture::{CubeMapFace, GpuTextureKind, GpuTextureTrait, PixelElementKind},
+    gpu_texture::{
+        CubeMapFace, GpuTexture, GpuTextureKind, GpuTextureTrait, PixelElementKind, PixelKind,
+    },
     ColorMask, DrawParameters, ElementRange,
 };
 use glow::{HasContext, PixelPackData};
@@ -95,7 +97,49 @@ unsafe fn set_attachment(
     }
 }
 
+fn read_rect(server: &GlGraphicsServer, pixel_kind: PixelKind, rect: Rect<i32>) -> Vec<u8> {
+    let pixel_info = PixelDescriptor::from(pixel_kind);
+    let mut buffer = vec![0; image_2d_size_bytes(pixel_kind, rect.w() as usize, rect.h() as usize)];
+    unsafe {
+        server.gl.read_pixels(
+            rect.x(),
+            rect.y(),
+            rect.w(),
+            rect.h(),
+            pixel_info.format,
+            pixel_info.data_type,
+            PixelPackData::Slice(Some(buffer.as_mut_slice())),
+        );
+    }
+    buffer
+}
+
 impl GlFrameBuffer {
+    /// Binds the frame buffer for reading from the given target and returns the texture that is
+    /// attached to the target.
+    fn bind_read_target(
+        &self,
+        server: &GlGraphicsServer,
+        read_target: ReadTarget,
+    ) -> Option<&GpuTexture> {
+        server.set_framebuffer(FrameBufferBindingPoint::Read, self.id());
+
+        match read_target {
+            ReadTarget::Depth | ReadTarget::Stencil => {
+                Some(&self.depth_attachment.as_ref()?.texture)
+            }
+            ReadTarget::Color(index) => {
+                unsafe {
+                    server
+                        .gl
+                        .read_buffer(glow::COLOR_ATTACHMENT0 + index as u32);
+                }
+
+                Some(&self.color_attachments.get(index)?.texture)
+            }
+        }
+    }
+
     pub fn new(
         server: &GlGraphicsServer,
         depth_attachment: Option<Attachment>,
@@ -267,43 +311,36 @@ impl GpuFrameBufferTrait for GlFrameBuffer {
 
     fn read_pixels(&self, read_target: ReadTarget) -> Option<Vec<u8>> {
         let server = self.state.upgrade()?;
-
-        server.set_framebuffer(FrameBufferBindingPoint::Read, self.id());
-
-        let texture = match read_target {
-            ReadTarget::Depth | ReadTarget::Stencil => &self.depth_attachment.as_ref()?.texture,
-            ReadTarget::Color(index) => {
-                unsafe {
-                    server
-                        .gl
-                        .read_buffer(glow::COLOR_ATTACHMENT0 + index as u32);
-                }
-
-                &self.color_attachments.get(index)?.texture
-            }
-        };
-
+        let texture = self.bind_read_target(&server, read_target)?;
         if let GpuTextureKind::Rectangle { width, height } = texture.kind() {
-            let pixel_kind = texture.pixel_kind();
-            let pixel_info = PixelDescriptor::from(pixel_kind);
-            let mut buffer = vec![0; image_2d_size_bytes(pixel_kind, width, height)];
-            unsafe {
-                server.gl.read_pixels(
-                    0,
-                    0,
-                    width as i32,
-                    height as i32,
-                    pixel_info.format,
-                    pixel_info.data_type,
-                    PixelPackData::Slice(Some(buffer.as_mut_slice())),
-                );
-            }
-            Some(buffer)
+            Some(read_rect(
+                &server,
+                texture.pixel_kind(),
+                Rect::new(0, 0, width as i32, height as i32),
+            ))
         } else {
             None
         }
     }
 
+    fn read_pixels_in_rect(&self, read_target: ReadTarget, rect: Rect<i32>) -> Option<Vec<u8>> {
+        let server = self.state.upgrade()?;
+        let texture = self.bind_read_target(&server, read_target)?;
+        let GpuTextureKind::Rectangle { width, height } = texture.kind() else {
+            return None;
+        };
+        if rect.x() < 0
+            || rect.y() < 0
+            || rect.w() <= 0
+            || rect.h() <= 0
+            || rect.x() + rect.w() > width as i32
+            || rect.y() + rect.h() > height as i32
+        {
+            return None;
+        }
+        Some(read_rect(&server, texture.pixel_kind(), rect))
+    }
+
     fn clear(
         &self,
         viewport: Rect<i32>,
@@ -44,8 +44,8 @@ use fyrox_graphics::{
     sampler::{GpuSampler, GpuSamplerDescriptor},
     server::{GraphicsServer, ServerCapabilities, ServerMemoryUsage, SharedGraphicsServer},
     stats::PipelineStatistics,
-    BlendEquation, BlendFactor, BlendFunc, BlendMode, ColorMask, CompareFunc, CullFace,
-    DrawParameters, PolygonFace, PolygonFillMode, ScissorBox, StencilAction, StencilFunc,
+    BlendEquation, BlendFactor, BlendFunc, BlendMode, ClipDepthRange, ColorMask, CompareFunc,
+    CullFace, DrawParameters, PolygonFace, PolygonFillMode, ScissorBox, StencilAction, StencilFunc,
     StencilOp,
 };
 use glow::HasContext;
@@ -90,6 +90,15 @@ impl ToGlConstant for PolygonFillMode {
     }
 }
 
+impl ToGlConstant for ClipDepthRange {
+    fn into_gl(self) -> u32 {
+        match self {
+            Self::NegativeOneToOne => glow::NEGATIVE_ONE_TO_ONE,
+            Self::ZeroToOne => glow::ZERO_TO_ONE,
+        }
+    }
+}
+
 impl ToGlConstant for StencilAction {
     fn into_gl(self) -> u32 {
         match self {
@@ -232,6 +241,9 @@ pub(crate) struct InnerState {
     /// The fill mode for the back of polygons.
     /// Protects `glPolygonMode`.
     back_fill_mode: PolygonFillMode,
+    /// The depth range of the clip space.
+    /// Protects `glClipControl`.
+    clip_depth_range: ClipDepthRange,
 
     /// The identifier for the currently bound frame buffer for writing. Frame buffers in OpenGL are identified by numbers.
     /// If there is a currently bound frame buffer for writing, then this will be that frame buffer's number.
@@ -312,6 +324,7 @@ impl InnerState {
             scissor_test: false,
             front_fill_mode: PolygonFillMode::default(),
             back_fill_mode: PolygonFillMode::default(),
+            clip_depth_range: ClipDepthRange::default(),
             read_framebuffer: None,
             write_framebuffer: None,
             blend_func: Default::default(),
@@ -530,8 +543,13 @@ pub struct GlGraphicsServer {
     /// This is needed in order to convert a borrow of this server into an `Rc` reference of
     /// this server, for implementing [`GlGraphicsServer::weak`] and [`GraphicsServer::weak`].
     this: RefCell<Option<Weak<GlGraphicsServer>>>,
+    /// A pointer to `glClipControl` function, if it is supported by the driver. `glow` does not
+    /// provide this function, so it is loaded manually.
+    clip_control: Option<ClipControlFn>,
 }
 
+type ClipControlFn = unsafe extern "system" fn(origin: u32, depth: u32);
+
 #[derive(Copy, Clone)]
 struct TextureBinding {
     target: u32,
@@ -596,7 +614,7 @@ impl GlGraphicsServer {
         named_objects: bool,
     ) -> Result<(Window, SharedGraphicsServer), FrameworkError> {
         #[cfg(not(target_arch = "wasm32"))]
-        let (window, gl_context, gl_surface, mut context, gl_kind) = {
+        let (window, gl_context, gl_surface, mut context, gl_kind, clip_control) = {
             let mut template = ConfigTemplateBuilder::new()
                 .prefer_hardware_accelerated(Some(true))
                 .with_stencil_size(8)
@@ -668,14 +686,35 @@ impl GlGraphicsServer {
                     ));
                 }
 
+                let context = glow::Context::from_loader_function(|s| {
+                    gl_display.get_proc_address(&CString::new(s).unwrap())
+                });
+
+                // Clip control is a part of OpenGL since 4.5, older versions and OpenGL ES provide
+                // it via extensions.
+                let version = context.version();
+                let extensions = context.supported_extensions();
+                let clip_control = if (!version.is_embedded
+                    && (version.major, version.minor) >= (4, 5))
+                    || extensions.contains("GL_ARB_clip_control")
+                    || extensions.contains("GL_EXT_clip_control")
+                {
+                    ["glClipControl", "glClipControlEXT"]
+                        .into_iter()
+                        .map(|name| gl_display.get_proc_address(&CString::new(name).unwrap()))
+                        .find(|ptr| !ptr.is_null())
+                        .map(|ptr| std::mem::transmute::<_, ClipControlFn>(ptr))
+                } else {
+                    None
+                };
+
                 (
                     window,
                     gl_context,
                     gl_surface,
-                    glow::Context::from_loader_function(|s| {
-                        gl_display.get_proc_address(&CString::new(s).unwrap())
-                    }),
+                    context,
                     gl_kind,
+                    clip_control,
                 )
             }
         };
@@ -764,6 +803,10 @@ impl GlGraphicsServer {
             )
         };
 
+        // WebGL 2 does not support clip control.
+        #[cfg(target_arch = "wasm32")]
+        let clip_control = None;
+
         #[cfg(not(target_arch = "wasm32"))]
         gl_surface.resize(
             &gl_context,
@@ -867,6 +910,7 @@ impl GlGraphicsServer {
             )),
             memory_usage: Default::default(),
             this: Default::default(),
+            clip_control,
         };
 
         let shared = Rc::new(state);
@@ -1499,6 +1543,10 @@ impl GraphicsServer for GlGraphicsServer {
                     .get_parameter_i32(glow::UNIFORM_BUFFER_OFFSET_ALIGNMENT)
                     as usize,
                 max_lod_bias: gl.get_parameter_f32(glow::MAX_TEXTURE_LOD_BIAS),
+                max_anisotropy: gl
+                    .get_parameter_f32(glow::MAX_TEXTURE_MAX_ANISOTROPY)
+                    .max(1.0),
+                clip_control: self.clip_control.is_some(),
             }
         }
     }
@@ -1527,6 +1575,23 @@ impl GraphicsServer for GlGraphicsServer {
         }
     }
 
+    fn set_clip_depth_range(&self, clip_depth_range: ClipDepthRange) {
+        let Some(clip_control) = self.clip_control else {
+            return;
+        };
+
+        let mut state = self.state.borrow_mut();
+        if state.clip_depth_range != clip_depth_range {
+            state.clip_depth_range = clip_depth_range;
+
+            unsafe { clip_control(glow::LOWER_LEFT, clip_depth_range.into_gl()) }
+        }
+    }
+
+    fn clip_depth_range(&self) -> ClipDepthRange {
+        self.state.borrow().clip_depth_range
+    }
+
     fn memory_usage(&self) -> ServerMemoryUsage {
         self.memory_usage.borrow().clone()
     }
@@ -324,6 +324,14 @@ vec2 S_ComputeParallaxTextureCoordinates(in sampler2D heightTexture, vec3 eyeVec
     return prev * weight + currentTexCoords * (1.0 - weight);
 }
 
+// Calculates screen-space velocity of a fragment (in texture coordinates units) using its clip-space
+// positions in the current and the previous frames.
+vec2 S_ComputeVelocity(vec4 clipPosition, vec4 prevClipPosition) {
+    vec2 current = clipPosition.xy / clipPosition.w;
+    vec2 previous = prevClipPosition.xy / prevClipPosition.w;
+    return (current - previous) * 0.5;
+}
+
 vec4 S_LinearToSRGB(vec4 color) {
     vec3 a = 12.92 * color.rgb;
     vec3 b = 1.055 * pow(color.rgb, vec3(1.0 / 2.4)) - 0.055;
@@ -104,6 +104,12 @@ impl From<PixelKind> for PixelDescriptor {
                 glow::DEPTH24_STENCIL8,
                 None,
             ),
+            PixelKind::D32FS8 => (
+                glow::FLOAT_32_UNSIGNED_INT_24_8_REV,
+                glow::DEPTH_STENCIL,
+                glow::DEPTH32F_STENCIL8,
+                None,
+            ),
             PixelKind::RGBA8 => (glow::UNSIGNED_BYTE, glow::RGBA, glow::RGBA8, None),
             PixelKind::SRGBA8 => (glow::UNSIGNED_BYTE, glow::RGBA, glow::SRGB8_ALPHA8, None),
             PixelKind::RGB8 => (glow::UNSIGNED_BYTE, glow::RGB, glow::RGB8, None),
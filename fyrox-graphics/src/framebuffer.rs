@@ -283,6 +283,16 @@ pub trait GpuFrameBufferTrait: GpuFrameBufferAsAny {
     /// Reads texture pixels.
     fn read_pixels(&self, read_target: ReadTarget) -> Option<Vec<u8>>;
 
+    /// Reads texture pixels in the given rectangle. The origin of the rectangle is at the bottom
+    /// left corner of the texture. Returns [`None`] if the rectangle is empty or out of bounds of the
+    /// texture.
+    ///
+    /// # Performance
+    ///
+    /// Reading the pixels waits until the GPU finishes all the previous work with the frame buffer,
+    /// which stalls the pipeline. Read as few pixels as possible and do it sparingly.
+    fn read_pixels_in_rect(&self, read_target: ReadTarget, rect: Rect<i32>) -> Option<Vec<u8>>;
+
     /// Draws the specified geometry buffer using the given GPU program and a set of resources. This
     /// method the main method to draw anything.
     ///
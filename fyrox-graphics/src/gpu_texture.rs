@@ -72,6 +72,8 @@ pub enum PixelKind {
     D16,
     /// Integer 24-bit depth pixel + 8-bit stencil.
     D24S8,
+    /// Floating point 32-bit depth pixel + 8-bit stencil.
+    D32FS8,
     /// Red, Green, Blue, Alpha; all by 8-bit.
     RGBA8,
     /// Red, Green, Blue, Alpha in sRGB color space; all by 8-bit.
@@ -164,6 +166,7 @@ impl PixelKind {
             | Self::LA16
             | Self::D24S8
             | Self::D32F
+            | Self::D32FS8
             | Self::R32F
             | Self::R32UI
             | Self::RGB10A2 => Some(4),
@@ -210,6 +213,7 @@ impl PixelKind {
             | Self::R16
             | Self::D24S8
             | Self::D32F
+            | Self::D32FS8
             | Self::R32F
             | Self::R32UI
             | Self::RG8
@@ -239,6 +243,7 @@ impl PixelKind {
             | Self::RGBA16F
             | Self::RGB16F
             | Self::D32F
+            | Self::D32FS8
             | Self::R11G11B10F => PixelElementKind::Float,
             Self::D16
             | Self::D24S8
@@ -286,7 +291,7 @@ pub fn image_3d_size_bytes(
     match pixel_kind {
         PixelKind::RGBA32F => 16 * pixel_count,
         PixelKind::RGB32F => 12 * pixel_count,
-        PixelKind::RGBA16 | PixelKind::RGBA16F => 8 * pixel_count,
+        PixelKind::RGBA16 | PixelKind::RGBA16F | PixelKind::D32FS8 => 8 * pixel_count,
         PixelKind::RGB16 | PixelKind::RGB16F => 6 * pixel_count,
         PixelKind::RGBA8
         | PixelKind::SRGBA8
@@ -326,7 +331,7 @@ pub fn image_2d_size_bytes(pixel_kind: PixelKind, width: usize, height: usize) -
     match pixel_kind {
         PixelKind::RGBA32F => 16 * pixel_count,
         PixelKind::RGB32F => 12 * pixel_count,
-        PixelKind::RGBA16 | PixelKind::RGBA16F => 8 * pixel_count,
+        PixelKind::RGBA16 | PixelKind::RGBA16F | PixelKind::D32FS8 => 8 * pixel_count,
         PixelKind::RGB16 | PixelKind::RGB16F => 6 * pixel_count,
         PixelKind::RGBA8
         | PixelKind::SRGBA8
@@ -365,7 +370,7 @@ pub fn image_1d_size_bytes(pixel_kind: PixelKind, length: usize) -> usize {
     match pixel_kind {
         PixelKind::RGBA32F => 16 * length,
         PixelKind::RGB32F => 12 * length,
-        PixelKind::RGBA16 | PixelKind::RGBA16F => 8 * length,
+        PixelKind::RGBA16 | PixelKind::RGBA16F | PixelKind::D32FS8 => 8 * length,
         PixelKind::RGB16 | PixelKind::RGB16F => 6 * length,
         PixelKind::RGBA8
         | PixelKind::SRGBA8
@@ -29,7 +29,9 @@
 pub use fyrox_core as core;
 use std::fmt::Debug;
 
-use crate::core::{reflect::prelude::*, type_traits::prelude::*, visitor::prelude::*};
+use crate::core::{
+    algebra::Matrix4, reflect::prelude::*, type_traits::prelude::*, visitor::prelude::*,
+};
 use serde::{Deserialize, Serialize};
 use strum_macros::{AsRefStr, EnumString, VariantNames};
 
@@ -111,6 +113,35 @@ pub enum PolygonFillMode {
     Fill,
 }
 
+/// Depth range of the clip space, that is used to map clip-space depth to window-space depth.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub enum ClipDepthRange {
+    /// Clip-space depth is in `[-w; w]` range and it is mapped to window-space depth using
+    /// `(z / w + 1.0) / 2.0`. This is the default range of OpenGL.
+    #[default]
+    NegativeOneToOne,
+    /// Clip-space depth is in `[0; w]` range and it is mapped to window-space depth directly. This
+    /// range avoids precision loss of the addition above and makes reversed depth buffer useful.
+    ZeroToOne,
+}
+
+impl ClipDepthRange {
+    /// Modifies the given projection matrix (or any matrix that ends with one, such as
+    /// view-projection matrix), that was made for [`ClipDepthRange::NegativeOneToOne`] range, so
+    /// it produces the same window-space depth in this range.
+    pub fn adjust_projection_matrix(self, matrix: &Matrix4<f32>) -> Matrix4<f32> {
+        match self {
+            ClipDepthRange::NegativeOneToOne => *matrix,
+            ClipDepthRange::ZeroToOne => {
+                let mut adjusted = *matrix;
+                let z_row = (matrix.row(2) + matrix.row(3)).scale(0.5);
+                adjusted.set_row(2, &z_row);
+                adjusted
+            }
+        }
+    }
+}
+
 /// A function used to compare two values. Usually it is used for depth and stencil testing.
 #[derive(
     Copy,
@@ -159,6 +190,23 @@ pub enum CompareFunc {
     Always,
 }
 
+impl CompareFunc {
+    /// Returns the function with swapped order of operands, i.e. `Less` becomes `Greater` and so on.
+    /// Could be used to flip depth test when reversed depth buffer is used.
+    pub fn reversed(self) -> Self {
+        match self {
+            CompareFunc::Less => CompareFunc::Greater,
+            CompareFunc::LessOrEqual => CompareFunc::GreaterOrEqual,
+            CompareFunc::Greater => CompareFunc::Less,
+            CompareFunc::GreaterOrEqual => CompareFunc::LessOrEqual,
+            CompareFunc::Never
+            | CompareFunc::Equal
+            | CompareFunc::NotEqual
+            | CompareFunc::Always => self,
+        }
+    }
+}
+
 /// Defines how some color will be multiplied before being blended.
 /// The destination color is the color that is currently in the frame buffer,
 /// and the source color is the color that is produced by the fragment shader.
@@ -710,3 +758,36 @@ impl ElementKind {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Matrix4, Vector4},
+        ClipDepthRange,
+    };
+
+    #[test]
+    fn test_clip_depth_range_keeps_window_depth() {
+        // Reversed perspective projection, the near plane is mapped to 1.0 and the far plane to 0.0.
+        let mut projection = Matrix4::new_perspective(1.0, 1.0, 0.1, 100.0);
+        let z_row = -projection.row(2);
+        projection.set_row(2, &z_row);
+
+        let adjusted = ClipDepthRange::ZeroToOne.adjust_projection_matrix(&projection);
+        for distance in [0.1, 1.0, 10.0, 100.0] {
+            let point = Vector4::new(0.0, 0.0, -distance, 1.0);
+            let clip = projection * point;
+            let window_depth = (clip.z / clip.w + 1.0) / 2.0;
+            let adjusted_clip = adjusted * point;
+            let adjusted_window_depth = adjusted_clip.z / adjusted_clip.w;
+            assert!((window_depth - adjusted_window_depth).abs() < 1.0e-6);
+            assert_eq!(clip.xy(), adjusted_clip.xy());
+            assert_eq!(clip.w, adjusted_clip.w);
+        }
+
+        assert_eq!(
+            ClipDepthRange::NegativeOneToOne.adjust_projection_matrix(&projection),
+            projection
+        );
+    }
+}
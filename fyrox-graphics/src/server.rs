@@ -34,7 +34,7 @@ use crate::{
     read_buffer::GpuAsyncReadBuffer,
     sampler::{GpuSampler, GpuSamplerDescriptor},
     stats::PipelineStatistics,
-    PolygonFace, PolygonFillMode,
+    ClipDepthRange, PolygonFace, PolygonFillMode,
 };
 use fyrox_core::define_as_any_trait;
 use std::fmt::{Display, Formatter};
@@ -50,6 +50,12 @@ pub struct ServerCapabilities {
     /// The maximum, absolute value of the texture level-of-detail bias. The value must be at least
     /// 2.0.
     pub max_lod_bias: f32,
+    /// The maximum level of anisotropic filtering supported by the device. It is 1.0 if anisotropic
+    /// filtering is not supported.
+    pub max_anisotropy: f32,
+    /// Whether the depth range of the clip space could be changed using
+    /// [`GraphicsServer::set_clip_depth_range`] or not.
+    pub clip_control: bool,
 }
 
 /// Contains information about used memory per each category of GPU resource. This is not precise
@@ -216,6 +222,16 @@ pub trait GraphicsServer: GraphicsServerAsAny {
     /// See [`PolygonFace`] and [`PolygonFillMode`] docs for more info.
     fn set_polygon_fill_mode(&self, polygon_face: PolygonFace, polygon_fill_mode: PolygonFillMode);
 
+    /// Sets current depth range of the clip space. See [`ClipDepthRange`] docs for more info. Does
+    /// nothing if the graphics server does not support clip control (see
+    /// [`ServerCapabilities::clip_control`]).
+    fn set_clip_depth_range(&self, clip_depth_range: ClipDepthRange);
+
+    /// Returns current depth range of the clip space. Every geometry, that is rendered while the
+    /// range is [`ClipDepthRange::ZeroToOne`], must use projection matrices adjusted by
+    /// [`ClipDepthRange::adjust_projection_matrix`].
+    fn clip_depth_range(&self) -> ClipDepthRange;
+
     /// Generates mipmaps for the given texture. Graphics server implementation can pick any desired
     /// way of mipmaps generation, depending on the underlying GAPI capabilities.
     fn generate_mipmap(&self, texture: &GpuTexture);
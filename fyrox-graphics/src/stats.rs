@@ -96,6 +96,9 @@ pub struct RenderPassStatistics {
     pub draw_calls: usize,
     /// Amount of triangles per frame.
     pub triangles_rendered: usize,
+    /// Amount of draw calls that were made to render debug geometry (for example, decal
+    /// projection volumes). These draw calls are not included in `draw_calls`.
+    pub debug_draw_calls: usize,
 }
 
 impl Display for RenderPassStatistics {
@@ -103,8 +106,9 @@ impl Display for RenderPassStatistics {
         write!(
             f,
             "Draw Calls: {}\n\
-            Triangles Rendered: {}",
-            self.draw_calls, self.triangles_rendered
+            Triangles Rendered: {}\n\
+            Debug Draw Calls: {}",
+            self.draw_calls, self.triangles_rendered, self.debug_draw_calls
         )
     }
 }
@@ -113,6 +117,7 @@ impl std::ops::AddAssign for RenderPassStatistics {
     fn add_assign(&mut self, rhs: Self) {
         self.draw_calls += rhs.draw_calls;
         self.triangles_rendered += rhs.triangles_rendered;
+        self.debug_draw_calls += rhs.debug_draw_calls;
     }
 }
 
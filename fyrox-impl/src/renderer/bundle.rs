@@ -45,7 +45,7 @@ use crate::{
         gpu_texture::GpuTexture,
         server::GraphicsServer,
         uniform::{ByteStorage, StaticUniformBuffer, UniformBuffer},
-        ElementRange,
+        ColorMask, CompareFunc, ElementRange,
     },
     material::{self, shader::ShaderDefinition, Material, MaterialPropertyRef, MaterialResource},
     renderer::{
@@ -56,8 +56,9 @@ use crate::{
             uniform::{UniformBlockLocation, UniformMemoryAllocator},
             DynamicSurfaceCache, TimeToLive,
         },
+        gbuffer::GBuffer,
         observer::ObserverPosition,
-        RenderPassStatistics,
+        RenderPassStatistics, DEPTH_PRE_PASS_NAME,
     },
     resource::texture::TextureResource,
     scene::{
@@ -127,6 +128,21 @@ impl RenderContext<'_> {
     }
 }
 
+/// Defines how a render pass uses the depth buffer, that could be filled by a depth pre-pass
+/// (z-pre-pass).
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
+pub enum DepthPrePass {
+    /// There's no depth pre-pass, draw parameters of render passes are used as is.
+    #[default]
+    None,
+    /// The depth pre-pass itself. `DepthPrePass` render pass of a shader is used instead of the
+    /// requested one (if it exists), color writes are disabled, so only depth is written.
+    DepthOnly,
+    /// Rendering after the depth pre-pass. Depth test is set to [`CompareFunc::Equal`] and depth
+    /// writes are disabled, so every pixel is shaded only once.
+    Shading,
+}
+
 #[allow(missing_docs)] // TODO
 pub struct BundleRenderContext<'a> {
     pub texture_cache: &'a mut TextureCache,
@@ -143,6 +159,19 @@ pub struct BundleRenderContext<'a> {
     // TODO: Add depth pre-pass to remove Option here. Current architecture allows only forward
     // renderer to have access to depth buffer that is available from G-Buffer.
     pub scene_depth: Option<&'a GpuTexture>,
+    /// A G-Buffer, which custom attachments will be bound to the shader resources with matching
+    /// names. Must be `None` when rendering into the G-Buffer itself, shaders write custom
+    /// attachments directly there.
+    pub gbuffer: Option<&'a GBuffer>,
+    /// Whether the frame buffer uses reversed depth or not. If set, depth tests of the render
+    /// passes will be flipped (`Less` becomes `Greater` and so on).
+    pub reversed_z: bool,
+    /// View-projection matrix of the observer from the previous frame. It is used to calculate
+    /// screen-space velocity of pixels. If not set, the current view-projection matrix will be
+    /// used, which means zero velocity.
+    pub previous_view_projection_matrix: Option<Matrix4<f32>>,
+    /// Defines how the depth buffer is used, see [`DepthPrePass`] docs for more info.
+    pub depth_pre_pass: DepthPrePass,
     pub renderer_resources: &'a RendererResources,
 }
 
@@ -553,10 +582,19 @@ impl RenderDataBundle {
             return Ok(stats);
         };
 
-        let Some(render_pass) = shader_set
-            .render_passes
-            .get(render_context.render_pass_name)
-        else {
+        // The depth pre-pass uses a dedicated lightweight render pass if the shader has one,
+        // otherwise it falls back to the regular render pass with disabled color writes.
+        let depth_pre_pass = if render_context.depth_pre_pass == DepthPrePass::DepthOnly {
+            shader_set.render_passes.get(&*DEPTH_PRE_PASS_NAME)
+        } else {
+            None
+        };
+
+        let Some(render_pass) = depth_pre_pass.or_else(|| {
+            shader_set
+                .render_passes
+                .get(render_context.render_pass_name)
+        }) else {
             let shader_state = material.shader().state();
             if let Some(shader_data) = shader_state.data_ref() {
                 if !shader_data
@@ -585,6 +623,18 @@ impl RenderDataBundle {
         for resource_definition in shader.definition.resources.iter() {
             let name = resource_definition.name.as_str();
 
+            if let Some(texture) = render_context
+                .gbuffer
+                .and_then(|gbuffer| gbuffer.custom_attachment(name))
+            {
+                material_bindings.push(ResourceBinding::texture(
+                    texture,
+                    &render_context.renderer_resources.nearest_clamp_sampler,
+                    resource_definition.binding,
+                ));
+                continue;
+            }
+
             match name {
                 "fyrox_sceneDepth" => {
                     material_bindings.push(ResourceBinding::texture(
@@ -659,6 +709,32 @@ impl RenderDataBundle {
             }
         }
 
+        let overridden_draw_params =
+            if render_context.reversed_z || render_context.depth_pre_pass != DepthPrePass::None {
+                let mut draw_params = render_pass.draw_params.clone();
+                match render_context.depth_pre_pass {
+                    DepthPrePass::None => (),
+                    DepthPrePass::DepthOnly => {
+                        draw_params.color_write = ColorMask::all(false);
+                    }
+                    DepthPrePass::Shading => {
+                        if draw_params.depth_test.is_some() {
+                            draw_params.depth_test = Some(CompareFunc::Equal);
+                        }
+                        draw_params.depth_write = false;
+                    }
+                }
+                if render_context.reversed_z {
+                    draw_params.depth_test = draw_params.depth_test.map(CompareFunc::reversed);
+                }
+                Some(draw_params)
+            } else {
+                None
+            };
+        let draw_params = overridden_draw_params
+            .as_ref()
+            .unwrap_or(&render_pass.draw_params);
+
         for (instance, uniform_data) in self
             .instances
             .iter()
@@ -711,7 +787,7 @@ impl RenderDataBundle {
                 geometry,
                 render_context.viewport,
                 &render_pass.program,
-                &render_pass.draw_params,
+                draw_params,
                 &[
                     ResourceBindGroup {
                         bindings: &material_bindings,
@@ -1010,8 +1086,17 @@ impl RenderDataBundleStorage {
         self.bundles.sort_unstable_by_key(|b| b.sort_index);
     }
 
+    /// Returns view-projection matrix of the observer, that should be used to rasterize geometry
+    /// with the current clip-space depth range of the given graphics server.
+    fn raster_view_projection_matrix(&self, server: &dyn GraphicsServer) -> Matrix4<f32> {
+        server.clip_depth_range().adjust_projection_matrix(
+            &(self.observer_position.projection_matrix * self.observer_position.view_matrix),
+        )
+    }
+
     pub fn write_global_uniform_blocks(
         &self,
+        server: &dyn GraphicsServer,
         render_context: &mut BundleRenderContext,
     ) -> GlobalUniformData {
         let mut light_data = LightData::<{ ShaderDefinition::MAX_LIGHTS }>::default();
@@ -1071,8 +1156,7 @@ impl RenderDataBundleStorage {
             .view_matrix
             .try_inverse()
             .unwrap_or_default();
-        let view_projection =
-            self.observer_position.projection_matrix * self.observer_position.view_matrix;
+        let view_projection = self.raster_view_projection_matrix(server);
         let camera_up = inv_view.up();
         let camera_side = inv_view.side();
         let camera_uniforms = StaticUniformBuffer::<512>::new()
@@ -1082,7 +1166,12 @@ impl RenderDataBundleStorage {
             .with(&camera_side)
             .with(&self.observer_position.z_near)
             .with(&self.observer_position.z_far)
-            .with(&(self.observer_position.z_far - self.observer_position.z_near));
+            .with(&(self.observer_position.z_far - self.observer_position.z_near))
+            .with(
+                &render_context
+                    .previous_view_projection_matrix
+                    .unwrap_or(view_projection),
+            );
         let camera_block = render_context
             .uniform_memory_allocator
             .allocate(camera_uniforms);
@@ -1113,10 +1202,9 @@ impl RenderDataBundleStorage {
         BundleFilter: FnMut(&RenderDataBundle) -> bool,
         InstanceFilter: FnMut(&SurfaceInstanceData) -> bool,
     {
-        let global_uniforms = self.write_global_uniform_blocks(&mut render_context);
+        let global_uniforms = self.write_global_uniform_blocks(server, &mut render_context);
 
-        let view_projection =
-            self.observer_position.projection_matrix * self.observer_position.view_matrix;
+        let view_projection = self.raster_view_projection_matrix(server);
         let mut bundle_uniform_data_set = Vec::with_capacity(self.bundles.len());
         for bundle in self.bundles.iter() {
             if !bundle_filter(bundle) {
@@ -1257,8 +1345,23 @@ impl RenderDataBundleStorageTrait for RenderDataBundleStorage {
 #[cfg(test)]
 mod test {
     use crate::renderer::bundle::{RenderContext, RenderDataBundleStorage};
-    use crate::renderer::observer::ObserverPosition;
-    use fyrox_core::algebra::{Matrix4, Vector3};
+    use crate::renderer::observer::{Observer, ObserverPosition};
+    use crate::scene::{
+        base::BaseBuilder,
+        camera::{Camera, CameraBuilder},
+        collider::BitMask,
+        graph::Graph,
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            MeshBuilder,
+        },
+        transform::TransformBuilder,
+    };
+    use fyrox_core::{
+        algebra::{Matrix4, Vector2, Vector3},
+        pool::Handle,
+        sstorage::ImmutableString,
+    };
 
     //noinspection ALL
     #[test]
@@ -1305,4 +1408,47 @@ mod test {
             center - 3000
         );
     }
+
+    #[test]
+    fn test_camera_render_mask() {
+        let mut graph = Graph::new();
+        let mesh = MeshBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 0.0, 5.0))
+                    .build(),
+            ),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_embedded(
+            SurfaceData::make_cube(Matrix4::identity()),
+        ))
+        .build()])
+        .build(&mut graph);
+        graph[mesh]
+            .render_mask
+            .set_value_and_mark_modified(BitMask(0b01));
+        let first = CameraBuilder::new(BaseBuilder::new())
+            .with_render_mask(BitMask(0b01))
+            .build(&mut graph);
+        let second = CameraBuilder::new(BaseBuilder::new())
+            .with_render_mask(BitMask(0b10))
+            .build(&mut graph);
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0, Default::default());
+
+        let collect = |camera: Handle<Camera>| {
+            let observer = Observer::from_camera(&graph[camera], Vector2::new(100.0, 100.0));
+            RenderDataBundleStorage::from_graph(
+                &graph,
+                observer.render_mask,
+                0.0,
+                &observer.position,
+                ImmutableString::new("GBuffer"),
+                Default::default(),
+                &mut Default::default(),
+            )
+        };
+
+        assert_eq!(collect(first).bundles.len(), 1);
+        assert!(collect(second).bundles.is_empty());
+    }
 }
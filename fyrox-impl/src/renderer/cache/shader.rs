@@ -29,7 +29,7 @@ use crate::{
         sampler::GpuSampler,
         server::GraphicsServer,
         uniform::StaticUniformBuffer,
-        DrawParameters, ElementRange,
+        CompareFunc, DrawParameters, ElementRange,
     },
     material::{
         shader::{Shader, ShaderResource},
@@ -255,6 +255,25 @@ impl RenderPassContainer {
         })
     }
 
+    /// Returns draw parameters of the given render pass with the flipped depth test if the depth
+    /// buffer is reversed, [`None`] - otherwise. The result is meant to be used as
+    /// `override_params` of [`Self::run_pass`] for passes that test against the depth of a scene.
+    pub fn depth_draw_params(
+        &self,
+        render_pass_name: &ImmutableString,
+        reversed_z: bool,
+    ) -> Result<Option<DrawParameters>, FrameworkError> {
+        if !reversed_z {
+            return Ok(None);
+        }
+
+        let draw_params = &self.get(render_pass_name)?.draw_params;
+        Ok(Some(DrawParameters {
+            depth_test: draw_params.depth_test.map(CompareFunc::reversed),
+            ..draw_params.clone()
+        }))
+    }
+
     pub fn run_pass<const N: usize>(
         &self,
         instance_count: usize,
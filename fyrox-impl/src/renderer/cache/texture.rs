@@ -46,11 +46,15 @@ pub struct TextureRenderData {
     pub gpu_sampler: GpuSampler,
     modifications_counter: u64,
     sampler_modifications_counter: u64,
+    /// Anisotropy limit that was used to create the sampler.
+    max_anisotropy: Option<f32>,
 }
 
 #[derive(Default)]
 pub struct TextureCache {
     cache: TemporaryCache<TextureRenderData>,
+    /// Upper limit of anisotropic filtering level of the samplers. See [`TextureCache::set_max_anisotropy`].
+    max_anisotropy: Option<f32>,
 }
 
 fn convert_texture_kind(v: TextureKind) -> GpuTextureKind {
@@ -90,6 +94,7 @@ pub fn convert_pixel_kind(texture_kind: TexturePixelKind) -> PixelKind {
         TexturePixelKind::RGB16 => PixelKind::RGB16,
         TexturePixelKind::RGBA16 => PixelKind::RGBA16,
         TexturePixelKind::RGB16F => PixelKind::RGB16F,
+        TexturePixelKind::RGBA16F => PixelKind::RGBA16F,
         TexturePixelKind::DXT1RGB => PixelKind::DXT1RGB,
         TexturePixelKind::DXT1RGBA => PixelKind::DXT1RGBA,
         TexturePixelKind::DXT3RGBA => PixelKind::DXT3RGBA,
@@ -140,6 +145,7 @@ pub fn convert_wrap_mode(v: TextureWrapMode) -> WrapMode {
 fn create_sampler(
     server: &dyn GraphicsServer,
     texture: &Texture,
+    max_anisotropy: Option<f32>,
 ) -> Result<GpuSampler, FrameworkError> {
     server.create_sampler(GpuSamplerDescriptor {
         mag_filter: convert_magnification_filter(texture.magnification_filter()),
@@ -147,7 +153,9 @@ fn create_sampler(
         s_wrap_mode: convert_wrap_mode(texture.s_wrap_mode()),
         t_wrap_mode: convert_wrap_mode(texture.t_wrap_mode()),
         r_wrap_mode: convert_wrap_mode(texture.r_wrap_mode()),
-        anisotropy: texture.anisotropy_level(),
+        anisotropy: max_anisotropy.map_or(texture.anisotropy_level(), |max_anisotropy| {
+            texture.anisotropy_level().min(max_anisotropy)
+        }),
         min_lod: texture.min_lod(),
         max_lod: texture.max_lod(),
         lod_bias: texture.lod_bias(),
@@ -159,6 +167,7 @@ fn create_gpu_texture(
     resource_manager: &ResourceManager,
     uuid: &Uuid,
     texture: &Texture,
+    max_anisotropy: Option<f32>,
 ) -> Result<TextureRenderData, FrameworkError> {
     let path = resource_manager
         .try_get_state(Duration::from_millis(1))
@@ -180,9 +189,10 @@ fn create_gpu_texture(
 
     Ok(TextureRenderData {
         gpu_texture,
-        gpu_sampler: create_sampler(server, texture)?,
+        gpu_sampler: create_sampler(server, texture, max_anisotropy)?,
         modifications_counter: texture.modifications_count(),
         sampler_modifications_counter: texture.sampler_modifications_count(),
+        max_anisotropy,
     })
 }
 
@@ -196,12 +206,13 @@ impl TextureCache {
         texture: &TextureResource,
     ) -> Result<(), FrameworkError> {
         let uuid = texture.resource_uuid();
+        let max_anisotropy = self.max_anisotropy;
         let texture = texture.state();
         if let Some(texture) = texture.data_ref() {
             self.cache.get_entry_mut_or_insert_with(
                 &texture.cache_index,
                 Default::default(),
-                || create_gpu_texture(server, resource_manager, &uuid, texture),
+                || create_gpu_texture(server, resource_manager, &uuid, texture, max_anisotropy),
             )?;
             Ok(())
         } else {
@@ -218,12 +229,13 @@ impl TextureCache {
         texture_resource: &TextureResource,
     ) -> Option<&TextureRenderData> {
         let uuid = texture_resource.resource_uuid();
+        let max_anisotropy = self.max_anisotropy;
         let texture_data_guard = texture_resource.state();
         if let Some(texture) = texture_data_guard.data_ref() {
             match self.cache.get_mut_or_insert_with(
                 &texture.cache_index,
                 Default::default(),
-                || create_gpu_texture(server, resource_manager, &uuid, texture),
+                || create_gpu_texture(server, resource_manager, &uuid, texture, max_anisotropy),
             ) {
                 Ok(entry) => {
                     // Check if some value has changed in resource.
@@ -247,8 +259,12 @@ impl TextureCache {
                     }
 
                     if entry.sampler_modifications_counter != texture.sampler_modifications_count()
+                        || entry.max_anisotropy != max_anisotropy
                     {
-                        entry.gpu_sampler = create_sampler(server, texture).unwrap();
+                        entry.gpu_sampler =
+                            create_sampler(server, texture, max_anisotropy).unwrap();
+                        entry.sampler_modifications_counter = texture.sampler_modifications_count();
+                        entry.max_anisotropy = max_anisotropy;
                     }
 
                     return Some(entry);
@@ -270,6 +286,18 @@ impl TextureCache {
         self.cache.update(dt)
     }
 
+    /// Sets the upper limit of anisotropic filtering level of the samplers of the textures. The
+    /// samplers use the lowest of the limit and the anisotropy level of their textures. `None`
+    /// means no limit. Existing samplers are re-created on next use.
+    pub fn set_max_anisotropy(&mut self, max_anisotropy: Option<f32>) {
+        self.max_anisotropy = max_anisotropy;
+    }
+
+    /// Returns the upper limit of anisotropic filtering level of the samplers of the textures.
+    pub fn max_anisotropy(&self) -> Option<f32> {
+        self.max_anisotropy
+    }
+
     pub fn clear(&mut self) {
         self.cache.clear();
     }
@@ -300,9 +328,10 @@ impl TextureCache {
             self.cache.spawn(
                 TextureRenderData {
                     gpu_texture,
-                    gpu_sampler: create_sampler(server, &data)?,
+                    gpu_sampler: create_sampler(server, &data, self.max_anisotropy)?,
                     modifications_counter: data.modifications_count(),
                     sampler_modifications_counter: data.sampler_modifications_count(),
+                    max_anisotropy: self.max_anisotropy,
                 },
                 index,
                 TimeToLive::default(),
@@ -82,6 +82,26 @@ pub fn draw_rect(rect: &Rect<f32>, lines: &mut Vec<Line>, color: Color) {
     }
 }
 
+fn fill_buffers(lines: &[Line], vertices: &mut Vec<Vertex>, line_indices: &mut Vec<[u32; 2]>) {
+    vertices.clear();
+    line_indices.clear();
+
+    let mut i = 0;
+    for line in lines.iter() {
+        let color = line.color.into();
+        vertices.push(Vertex {
+            position: line.begin,
+            color,
+        });
+        vertices.push(Vertex {
+            position: line.end,
+            color,
+        });
+        line_indices.push([i, i + 1]);
+        i += 2;
+    }
+}
+
 impl DebugRenderer {
     pub(crate) fn new(server: &dyn GraphicsServer) -> Result<Self, FrameworkError> {
         let desc = GpuGeometryBufferDescriptor {
@@ -119,27 +139,52 @@ impl DebugRenderer {
 
     /// Uploads the new set of lines to GPU.
     pub fn set_lines(&mut self, lines: &[Line]) {
-        self.vertices.clear();
-        self.line_indices.clear();
-
-        let mut i = 0;
-        for line in lines.iter() {
-            let color = line.color.into();
-            self.vertices.push(Vertex {
-                position: line.begin,
-                color,
-            });
-            self.vertices.push(Vertex {
-                position: line.end,
-                color,
-            });
-            self.line_indices.push([i, i + 1]);
-            i += 2;
-        }
+        fill_buffers(lines, &mut self.vertices, &mut self.line_indices);
+        self.upload();
+    }
+
+    fn upload(&mut self) {
         self.geometry.set_buffer_data_of_type(0, &self.vertices);
         self.geometry.set_lines(&self.line_indices);
     }
 
+    /// Renders the given lines once. The lines set by [`Self::set_lines`] are kept and will be
+    /// rendered as usual next time.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn render_lines(
+        &mut self,
+        lines: &[Line],
+        server: &dyn GraphicsServer,
+        uniform_buffer_cache: &mut UniformBufferCache,
+        viewport: Rect<i32>,
+        framebuffer: &GpuFrameBuffer,
+        view_projection: Matrix4<f32>,
+        reversed_z: bool,
+        renderer_resources: &RendererResources,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let mut vertices = Vec::new();
+        let mut line_indices = Vec::new();
+        fill_buffers(lines, &mut vertices, &mut line_indices);
+        self.geometry.set_buffer_data_of_type(0, &vertices);
+        self.geometry.set_lines(&line_indices);
+
+        let statistics = self.render(
+            server,
+            uniform_buffer_cache,
+            viewport,
+            framebuffer,
+            view_projection,
+            reversed_z,
+            renderer_resources,
+        );
+
+        // Bring back the persistent lines.
+        self.upload();
+
+        statistics
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn render(
         &mut self,
         server: &dyn GraphicsServer,
@@ -147,25 +192,35 @@ impl DebugRenderer {
         viewport: Rect<i32>,
         framebuffer: &GpuFrameBuffer,
         view_projection: Matrix4<f32>,
+        reversed_z: bool,
         renderer_resources: &RendererResources,
     ) -> Result<RenderPassStatistics, FrameworkError> {
         let _debug_scope = server.begin_scope("DebugRendering");
 
         let mut statistics = RenderPassStatistics::default();
 
+        let view_projection = server
+            .clip_depth_range()
+            .adjust_projection_matrix(&view_projection);
         let properties = PropertyGroup::from([property("worldViewProjection", &view_projection)]);
         let material = RenderMaterial::from([binding("properties", &properties)]);
 
+        let pass_name = ImmutableString::new("Primary");
+        let draw_params = renderer_resources
+            .shaders
+            .debug
+            .depth_draw_params(&pass_name, reversed_z)?;
+
         statistics += renderer_resources.shaders.debug.run_pass(
             1,
-            &ImmutableString::new("Primary"),
+            &pass_name,
             framebuffer,
             &self.geometry,
             viewport,
             &material,
             uniform_buffer_cache,
             Default::default(),
-            None,
+            draw_params.as_ref(),
         )?;
 
         Ok(statistics)
@@ -0,0 +1,125 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Depth of field blurs the parts of the final image that are out of focus of a camera. See
+//! [`DepthOfField`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector2},
+        math::Rect,
+        sstorage::ImmutableString,
+    },
+    graphics::{error::FrameworkError, framebuffer::GpuFrameBuffer, gpu_texture::GpuTexture},
+    renderer::{
+        cache::{
+            shader::{binding, property, PropertyGroup, RenderMaterial},
+            uniform::UniformBufferCache,
+        },
+        make_viewport_matrix,
+        resources::RendererResources,
+        RenderPassStatistics,
+    },
+    scene::camera::DepthOfField,
+};
+use fyrox_graphics::server::GraphicsServer;
+
+pub(crate) struct DepthOfFieldArgs<'a> {
+    pub server: &'a dyn GraphicsServer,
+    pub viewport: Rect<i32>,
+    pub frame_texture: &'a GpuTexture,
+    pub depth_texture: &'a GpuTexture,
+    pub frame_buffer: &'a GpuFrameBuffer,
+    pub depth_of_field: &'a DepthOfField,
+    pub projection_matrix: Matrix4<f32>,
+    pub z_far: f32,
+    /// Depth value of the pixels, where nothing was rendered.
+    pub sky_depth: f32,
+    pub uniform_buffer_cache: &'a mut UniformBufferCache,
+    pub renderer_resources: &'a RendererResources,
+}
+
+#[derive(Default)]
+pub struct DepthOfFieldRenderer {}
+
+impl DepthOfFieldRenderer {
+    pub(crate) fn render(
+        &self,
+        args: DepthOfFieldArgs,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let DepthOfFieldArgs {
+            server,
+            viewport,
+            frame_texture,
+            depth_texture,
+            frame_buffer,
+            depth_of_field,
+            projection_matrix,
+            z_far,
+            sky_depth,
+            uniform_buffer_cache,
+            renderer_resources,
+        } = args;
+
+        let _debug_scope = server.begin_scope("DepthOfField");
+
+        let mut statistics = RenderPassStatistics::default();
+
+        let frame_matrix = make_viewport_matrix(viewport);
+
+        let inv_projection = projection_matrix.try_inverse().unwrap_or_default();
+        let inv_screen_size = Vector2::new(1.0 / viewport.w() as f32, 1.0 / viewport.h() as f32);
+        let properties = PropertyGroup::from([
+            property("worldViewProjection", &frame_matrix),
+            property("inverseProjectionMatrix", &inv_projection),
+            property("inverseScreenSize", &inv_screen_size),
+            property("focusDistance", &depth_of_field.focus_distance),
+            property("focalRange", &depth_of_field.focal_range),
+            property("maxBlur", &depth_of_field.max_blur),
+            property("zFar", &z_far),
+            property("skyDepth", &sky_depth),
+        ]);
+        let material = RenderMaterial::from([
+            binding(
+                "screenTexture",
+                (frame_texture, &renderer_resources.linear_clamp_sampler),
+            ),
+            binding(
+                "depthTexture",
+                (depth_texture, &renderer_resources.nearest_clamp_sampler),
+            ),
+            binding("properties", &properties),
+        ]);
+
+        statistics += renderer_resources.shaders.depth_of_field.run_pass(
+            1,
+            &ImmutableString::new("Primary"),
+            frame_buffer,
+            &renderer_resources.quad,
+            viewport,
+            &material,
+            uniform_buffer_cache,
+            Default::default(),
+            None,
+        )?;
+
+        Ok(statistics)
+    }
+}
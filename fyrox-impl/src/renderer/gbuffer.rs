@@ -25,43 +25,86 @@
 //! RT2: RGBA16F - Ambient light + emission (both in xyz)
 //! RT3: RGBA8 - Metallic (x) + Roughness (y) + Ambient Occlusion (z)
 //! RT4: R8UI - Decal mask (x)
+//! RT5..: Custom attachments (see [`CustomAttachmentDesc`])
 //!
 //! Every alpha channel is used for layer blending for terrains. This is inefficient, but for
 //! now I don't know better solution.
 
 use crate::{
-    core::{algebra::Vector2, color::Color, math::Rect, sstorage::ImmutableString},
+    core::{
+        algebra::{Matrix4, Vector2, Vector3, Vector4},
+        color::Color,
+        math::Rect,
+        pool::Handle,
+        sstorage::ImmutableString,
+    },
     graphics::{
         error::FrameworkError,
-        framebuffer::{Attachment, GpuFrameBuffer},
+        framebuffer::{Attachment, DrawCallStatistics, GpuFrameBuffer, ReadTarget},
         gpu_texture::{GpuTexture, PixelKind},
         server::GraphicsServer,
+        BlendFactor, BlendFunc, BlendParameters,
     },
     renderer::{
-        bundle::{BundleRenderContext, RenderDataBundleStorage, SurfaceInstanceData},
+        bundle::{BundleRenderContext, DepthPrePass, RenderDataBundleStorage, SurfaceInstanceData},
         cache::{
             shader::{binding, property, PropertyGroup, RenderMaterial, ShaderCache},
             uniform::{UniformBufferCache, UniformMemoryAllocator},
         },
-        debug_renderer::DebugRenderer,
-        observer::Observer,
+        make_viewport_matrix,
+        observer::{Observer, ObserverPosition},
         occlusion::OcclusionTester,
         resources::RendererResources,
-        GeometryCache, QualitySettings, RenderPassStatistics, TextureCache,
+        GeometryCache, OcclusionStatistics, QualitySettings, RenderPassStatistics, TextureCache,
     },
-    scene::{decal::Decal, graph::Graph, mesh::RenderPath},
+    scene::{decal::Decal, graph::Graph, mesh::RenderPath, node::Node, GBufferDebugTarget},
 };
 use fxhash::FxHashSet;
 use fyrox_resource::manager::ResourceManager;
 
+/// Index of the first custom attachment in the list of color attachments of the G-Buffer.
+pub const FIRST_CUSTOM_ATTACHMENT_INDEX: usize = 6;
+
+/// A description of a custom G-Buffer attachment. Custom attachments are appended right after the
+/// standard ones, so the first custom attachment is written by a shader to `layout(location = 6)`,
+/// the second one to `layout(location = 7)` and so on.
+///
+/// Keep in mind that graphics hardware has a limit of simultaneously bound color attachments
+/// (multiple render targets, MRT). The minimal guaranteed limit is 8 color attachments, which
+/// leaves only 2 slots for custom attachments, G-Buffer creation will fail if the limit is
+/// exceeded. Every custom attachment also increases the memory bandwidth of the G-Buffer pass, so
+/// use as few and as narrow attachments as possible.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CustomAttachmentDesc {
+    /// Pixel format of the attachment.
+    pub pixel_kind: PixelKind,
+    /// Unique name of the attachment. The attachment can be fetched from the G-Buffer by this name
+    /// and it will be bound to every shader resource with the same name in the passes that are
+    /// executed after the G-Buffer pass.
+    pub name: ImmutableString,
+}
+
 pub struct GBuffer {
     framebuffer: GpuFrameBuffer,
     decal_framebuffer: GpuFrameBuffer,
+    custom_attachments: Vec<CustomAttachmentDesc>,
+    /// A frame buffer with a copy of the depth buffer produced by z-pre-pass. It is created on
+    /// demand, when z-pre-pass is enabled.
+    pre_pass_depth_framebuffer: Option<GpuFrameBuffer>,
     pub width: i32,
     pub height: i32,
 
     render_pass_name: ImmutableString,
     occlusion_tester: OcclusionTester,
+    /// Position of the last observer that used occlusion culling. It defines the cell of the
+    /// visibility grid that contains the latest occlusion verdicts.
+    occlusion_observer_position: Option<Vector3<f32>>,
+    /// See [`QualitySettings::occlusion_culling_delay`].
+    occlusion_culling_delay: u32,
+    occlusion_statistics: OcclusionStatistics,
+    /// Projection matrix of the observer that filled the G-Buffer last time. It is used to
+    /// linearize the depth, see [`GBuffer::read_depth_pixel`].
+    depth_projection_matrix: Option<Matrix4<f32>>,
 }
 
 pub(crate) struct GBufferRenderContext<'a, 'b> {
@@ -76,9 +119,96 @@ pub(crate) struct GBufferRenderContext<'a, 'b> {
     pub graph: &'b Graph,
     pub uniform_buffer_cache: &'a mut UniformBufferCache,
     pub uniform_memory_allocator: &'a mut UniformMemoryAllocator,
-    #[allow(dead_code)]
-    pub screen_space_debug_renderer: &'a mut DebugRenderer,
     pub resource_manager: &'a ResourceManager,
+    /// View-projection matrix of the observer from the previous frame. It is used to calculate
+    /// screen-space velocity (motion vectors) of pixels.
+    pub previous_view_projection_matrix: Matrix4<f32>,
+    /// Resolved ambient lighting color of the observer. It is passed to the shaders of the G-Buffer
+    /// pass as `fyrox_lightData.ambientLightColor`.
+    pub ambient_light: Color,
+}
+
+/// Converts the depth buffer value at the given point in normalized device coordinates to the
+/// distance from the observer along its view direction.
+fn linearize_depth(projection_matrix: &Matrix4<f32>, ndc: Vector2<f32>, depth: f32) -> Option<f32> {
+    let view_position =
+        projection_matrix.try_inverse()? * Vector4::new(ndc.x, ndc.y, 2.0 * depth - 1.0, 1.0);
+    let linear_depth = -view_position.z / view_position.w;
+    linear_depth.is_finite().then_some(linear_depth)
+}
+
+/// Creates the main G-Buffer frame buffer and the frame buffer for decals, which shares diffuse
+/// and normal textures with the main one.
+fn create_framebuffers(
+    server: &dyn GraphicsServer,
+    width: usize,
+    height: usize,
+    custom_attachments: &[CustomAttachmentDesc],
+    depth_stencil_pixel_kind: PixelKind,
+) -> Result<(GpuFrameBuffer, GpuFrameBuffer), FrameworkError> {
+    let diffuse_texture =
+        server.create_2d_render_target("GBufferDiffuseTexture", PixelKind::RGBA8, width, height)?;
+    let normal_texture = server.create_2d_render_target(
+        "GBufferNormalTexture",
+        PixelKind::RGB10A2,
+        width,
+        height,
+    )?;
+    let mut color_attachments = vec![
+        Attachment::color(diffuse_texture.clone()),
+        Attachment::color(normal_texture.clone()),
+        Attachment::color(server.create_2d_render_target(
+            "GBufferAmbientTexture",
+            PixelKind::RGB10A2,
+            width,
+            height,
+        )?),
+        Attachment::color(server.create_2d_render_target(
+            "GBufferMaterialTexture",
+            PixelKind::RGBA8,
+            width,
+            height,
+        )?),
+        Attachment::color(server.create_2d_render_target(
+            "GBufferDecalMaskTexture",
+            PixelKind::R8UI,
+            width,
+            height,
+        )?),
+        Attachment::color(server.create_2d_render_target(
+            "GBufferVelocityTexture",
+            PixelKind::RG16F,
+            width,
+            height,
+        )?),
+    ];
+    for desc in custom_attachments {
+        color_attachments.push(Attachment::color(server.create_2d_render_target(
+            &desc.name,
+            desc.pixel_kind,
+            width,
+            height,
+        )?));
+    }
+    let framebuffer = server.create_frame_buffer(
+        Some(Attachment::depth_stencil(server.create_2d_render_target(
+            "GBufferDepthStencilTexture",
+            depth_stencil_pixel_kind,
+            width,
+            height,
+        )?)),
+        color_attachments,
+    )?;
+
+    let decal_framebuffer = server.create_frame_buffer(
+        None,
+        vec![
+            Attachment::color(diffuse_texture),
+            Attachment::color(normal_texture),
+        ],
+    )?;
+
+    Ok((framebuffer, decal_framebuffer))
 }
 
 impl GBuffer {
@@ -86,56 +216,16 @@ impl GBuffer {
         server: &dyn GraphicsServer,
         width: usize,
         height: usize,
+        custom_attachments: &[CustomAttachmentDesc],
+        occlusion_grid_cell_size: f32,
+        depth_stencil_pixel_kind: PixelKind,
     ) -> Result<Self, FrameworkError> {
-        let diffuse_texture = server.create_2d_render_target(
-            "GBufferDiffuseTexture",
-            PixelKind::RGBA8,
-            width,
-            height,
-        )?;
-        let normal_texture = server.create_2d_render_target(
-            "GBufferNormalTexture",
-            PixelKind::RGB10A2,
+        let (framebuffer, decal_framebuffer) = create_framebuffers(
+            server,
             width,
             height,
-        )?;
-        let framebuffer = server.create_frame_buffer(
-            Some(Attachment::depth_stencil(server.create_2d_render_target(
-                "GBufferDepthStencilTexture",
-                PixelKind::D24S8,
-                width,
-                height,
-            )?)),
-            vec![
-                Attachment::color(diffuse_texture.clone()),
-                Attachment::color(normal_texture.clone()),
-                Attachment::color(server.create_2d_render_target(
-                    "GBufferAmbientTexture",
-                    PixelKind::RGB10A2,
-                    width,
-                    height,
-                )?),
-                Attachment::color(server.create_2d_render_target(
-                    "GBufferMaterialTexture",
-                    PixelKind::RGBA8,
-                    width,
-                    height,
-                )?),
-                Attachment::color(server.create_2d_render_target(
-                    "GBufferDecalMaskTexture",
-                    PixelKind::R8UI,
-                    width,
-                    height,
-                )?),
-            ],
-        )?;
-
-        let decal_framebuffer = server.create_frame_buffer(
-            None,
-            vec![
-                Attachment::color(diffuse_texture),
-                Attachment::color(normal_texture),
-            ],
+            custom_attachments,
+            depth_stencil_pixel_kind,
         )?;
 
         Ok(Self {
@@ -143,11 +233,55 @@ impl GBuffer {
             width: width as i32,
             height: height as i32,
             decal_framebuffer,
+            custom_attachments: custom_attachments.to_vec(),
+            pre_pass_depth_framebuffer: None,
             render_pass_name: ImmutableString::new("GBuffer"),
-            occlusion_tester: OcclusionTester::new(server, width, height, 16)?,
+            occlusion_tester: OcclusionTester::new(
+                server,
+                width,
+                height,
+                16,
+                occlusion_grid_cell_size,
+                depth_stencil_pixel_kind,
+            )?,
+            occlusion_observer_position: None,
+            occlusion_culling_delay: 1,
+            occlusion_statistics: Default::default(),
+            depth_projection_matrix: None,
         })
     }
 
+    /// Changes the size of the G-Buffer. Only the render targets (including the custom ones) are
+    /// re-created, the rest of the G-Buffer state is preserved. Does nothing if the size is the
+    /// same.
+    pub fn set_size(
+        &mut self,
+        server: &dyn GraphicsServer,
+        width: usize,
+        height: usize,
+    ) -> Result<(), FrameworkError> {
+        if self.width == width as i32 && self.height == height as i32 {
+            return Ok(());
+        }
+
+        let (framebuffer, decal_framebuffer) = create_framebuffers(
+            server,
+            width,
+            height,
+            &self.custom_attachments,
+            self.depth().pixel_kind(),
+        )?;
+        self.occlusion_tester.set_size(server, width, height)?;
+        self.framebuffer = framebuffer;
+        self.decal_framebuffer = decal_framebuffer;
+        // Will be re-created on demand with the new size.
+        self.pre_pass_depth_framebuffer = None;
+        self.width = width as i32;
+        self.height = height as i32;
+
+        Ok(())
+    }
+
     pub fn framebuffer(&self) -> &GpuFrameBuffer {
         &self.framebuffer
     }
@@ -156,6 +290,51 @@ impl GBuffer {
         &self.framebuffer.depth_attachment().unwrap().texture
     }
 
+    /// Reads a single texel of the depth buffer and converts it to linear depth (distance from the
+    /// observer along its view direction), using the projection of the observer that filled the
+    /// G-Buffer last time. The origin of the coordinates is at the top left corner of the G-Buffer.
+    /// Returns [`None`] if the coordinates are out of bounds, the G-Buffer wasn't filled yet, or
+    /// the depth cannot be linearized (for example, if there's nothing at the pixel and the far
+    /// clipping plane is at infinity).
+    ///
+    /// # Performance
+    ///
+    /// Reading the depth waits until the GPU finishes rendering to the G-Buffer, which stalls the
+    /// pipeline. Call this method sparingly, for example once per frame for the pixel under a
+    /// crosshair.
+    pub fn read_depth_pixel(&self, server: &dyn GraphicsServer, x: i32, y: i32) -> Option<f32> {
+        let projection_matrix = self.depth_projection_matrix?;
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return None;
+        }
+
+        let _debug_scope = server.begin_scope("GBufferDepthReadback");
+
+        // Frame buffers have their origin at the bottom left corner.
+        let bytes = self
+            .framebuffer
+            .read_pixels_in_rect(ReadTarget::Depth, Rect::new(x, self.height - 1 - y, 1, 1))?;
+        let depth = match self.depth().pixel_kind() {
+            PixelKind::D24S8 => {
+                let value = u32::from_ne_bytes(bytes.get(0..4)?.try_into().ok()?);
+                (value >> 8) as f32 / 0xFF_FFFF as f32
+            }
+            PixelKind::D32F | PixelKind::D32FS8 => {
+                f32::from_ne_bytes(bytes.get(0..4)?.try_into().ok()?)
+            }
+            PixelKind::D16 => {
+                u16::from_ne_bytes(bytes.get(0..2)?.try_into().ok()?) as f32 / u16::MAX as f32
+            }
+            _ => return None,
+        };
+
+        let ndc = Vector2::new(
+            2.0 * (x as f32 + 0.5) / self.width as f32 - 1.0,
+            1.0 - 2.0 * (y as f32 + 0.5) / self.height as f32,
+        );
+        linearize_depth(&projection_matrix, ndc, depth)
+    }
+
     pub fn diffuse_texture(&self) -> &GpuTexture {
         &self.framebuffer.color_attachments()[0].texture
     }
@@ -176,6 +355,126 @@ impl GBuffer {
         &self.framebuffer.color_attachments()[4].texture
     }
 
+    /// Returns a texture with screen-space velocity (motion vectors) of each pixel, that could be
+    /// used for temporal anti-aliasing, motion blur, etc. The velocity is stored in texture
+    /// coordinates units (`current - previous`) and it is calculated using the view-projection
+    /// matrices of the current and the previous frames, so it contains only the motion caused by
+    /// the observer. Pixels of static geometry will have zero velocity if the observer does not
+    /// move. The velocity is written only by the `GBuffer` pass of the shaders, so the pixels that
+    /// are drawn by other passes only (forward-rendered sprites, particles, 2D, etc.) have zero
+    /// velocity.
+    pub fn velocity_texture(&self) -> &GpuTexture {
+        &self.framebuffer.color_attachments()[5].texture
+    }
+
+    /// Draws the given target of the G-Buffer into the given frame buffer, this is used to debug
+    /// deferred rendering. Normals are remapped from `[-1; 1]` to `[0; 1]` range and the depth is
+    /// linearized using the near and far clipping planes of the observer. Does nothing if the
+    /// target is [`GBufferDebugTarget::Disabled`].
+    pub fn debug_draw_target(
+        &self,
+        target: GBufferDebugTarget,
+        framebuffer: &GpuFrameBuffer,
+        viewport: Rect<i32>,
+        observer_position: &ObserverPosition,
+        uniform_buffer_cache: &mut UniformBufferCache,
+        renderer_resources: &RendererResources,
+    ) -> Result<DrawCallStatistics, FrameworkError> {
+        let (color_texture, mode) = match target {
+            GBufferDebugTarget::Disabled => return Ok(Default::default()),
+            GBufferDebugTarget::Diffuse => (self.diffuse_texture(), 0u32),
+            GBufferDebugTarget::Normal => (self.normal_texture(), 1),
+            GBufferDebugTarget::Ambient => (self.ambient_texture(), 0),
+            GBufferDebugTarget::Material => (self.material_texture(), 0),
+            GBufferDebugTarget::DecalMask => (self.diffuse_texture(), 2),
+            GBufferDebugTarget::Depth => (self.diffuse_texture(), 3),
+        };
+
+        let wvp = make_viewport_matrix(viewport);
+        let inv_projection = observer_position
+            .projection_matrix
+            .try_inverse()
+            .unwrap_or_default();
+        let properties = PropertyGroup::from([
+            property("worldViewProjection", &wvp),
+            property("invProjection", &inv_projection),
+            property("zNear", &observer_position.z_near),
+            property("zFar", &observer_position.z_far),
+            property("mode", &mode),
+        ]);
+        let material = RenderMaterial::from([
+            binding(
+                "colorTexture",
+                (color_texture, &renderer_resources.nearest_clamp_sampler),
+            ),
+            binding(
+                "depthTexture",
+                (self.depth(), &renderer_resources.nearest_clamp_sampler),
+            ),
+            binding(
+                "decalMask",
+                (
+                    self.decal_mask_texture(),
+                    &renderer_resources.nearest_clamp_sampler,
+                ),
+            ),
+            binding("properties", &properties),
+        ]);
+
+        renderer_resources.shaders.gbuffer_debug.run_pass(
+            1,
+            &ImmutableString::new("Primary"),
+            framebuffer,
+            &renderer_resources.quad,
+            viewport,
+            &material,
+            uniform_buffer_cache,
+            Default::default(),
+            None,
+        )
+    }
+
+    /// Returns descriptions of the custom attachments the G-Buffer was created with.
+    pub fn custom_attachments(&self) -> &[CustomAttachmentDesc] {
+        &self.custom_attachments
+    }
+
+    /// Returns a texture of the custom attachment with the given name.
+    pub fn custom_attachment(&self, name: &str) -> Option<&GpuTexture> {
+        self.custom_attachments
+            .iter()
+            .position(|desc| desc.name.as_str() == name)
+            .map(|index| {
+                &self.framebuffer.color_attachments()[FIRST_CUSTOM_ATTACHMENT_INDEX + index].texture
+            })
+    }
+
+    /// Changes the size (in meters) of the cells of the grid that caches occlusion culling results.
+    /// The cache is re-created if the value was changed.
+    pub fn set_occlusion_grid_cell_size(&mut self, occlusion_grid_cell_size: f32) {
+        self.occlusion_tester
+            .set_grid_cell_size(occlusion_grid_cell_size);
+    }
+
+    /// Returns the latest occlusion culling verdict for the given node: `Some(true)` if the node
+    /// was hidden behind other objects, `Some(false)` if it was visible and `None` if the node was
+    /// never tested or occlusion culling is disabled. It only reads the cached results of the
+    /// occlusion queries and never waits for the GPU, so the verdict could be a few frames old.
+    pub fn is_occluded(&self, node: Handle<Node>) -> Option<bool> {
+        let cell = self
+            .occlusion_tester
+            .grid_cache
+            .cell(self.occlusion_observer_position?)?;
+        cell.contains_key(&node)
+            .then(|| !cell.is_visible(node, self.occlusion_culling_delay))
+    }
+
+    /// Returns the amount of objects that were drawn and culled by occlusion culling during the
+    /// last [`Self::fill`] call.
+    pub fn occlusion_statistics(&self) -> OcclusionStatistics {
+        self.occlusion_statistics
+    }
+
     pub(crate) fn fill(
         &mut self,
         args: GBufferRenderContext,
@@ -197,6 +496,8 @@ impl GBuffer {
             uniform_buffer_cache,
             uniform_memory_allocator,
             resource_manager,
+            previous_view_projection_matrix,
+            ambient_light,
             ..
         } = args;
 
@@ -204,14 +505,36 @@ impl GBuffer {
             self.occlusion_tester.try_query_visibility_results(graph);
         };
 
+        self.occlusion_statistics = Default::default();
+        self.occlusion_observer_position = quality_settings
+            .use_occlusion_culling
+            .then_some(observer.position.translation);
+        self.occlusion_culling_delay = quality_settings.occlusion_culling_delay;
+        self.depth_projection_matrix = Some(observer.position.projection_matrix);
+
+        // Reversed depth maps the far clipping plane to zero, so the depth buffer must be cleared
+        // with it. Observers may keep the depth of previously rendered observers, so their content
+        // will be occluded by it.
+        let depth_clear_value = if !observer.clear_depth {
+            None
+        } else if quality_settings.use_reversed_z {
+            Some(0.0)
+        } else {
+            Some(1.0)
+        };
+        // G-Buffer always must be cleared, it contains surface properties and not the final color.
+        // Zero alpha marks the pixels without any surfaces, which is then used by the lighting to
+        // keep the previous content of the frame. This also clears the velocity, so the pixels that
+        // are not covered by the G-Buffer geometry have zero velocity.
         let viewport = Rect::new(0, 0, self.width, self.height);
         self.framebuffer.clear(
             viewport,
             Some(Color::from_rgba(0, 0, 0, 0)),
-            Some(1.0),
+            depth_clear_value,
             Some(0),
         );
 
+        let occlusion_culling_delay = self.occlusion_culling_delay;
         let grid_cell = self
             .occlusion_tester
             .grid_cache
@@ -219,7 +542,77 @@ impl GBuffer {
 
         let instance_filter = |instance: &SurfaceInstanceData| {
             !quality_settings.use_occlusion_culling
-                || grid_cell.is_none_or(|cell| cell.is_visible(instance.node_handle))
+                || grid_cell.is_none_or(|cell| {
+                    cell.is_visible(instance.node_handle, occlusion_culling_delay)
+                })
+        };
+
+        if quality_settings.use_z_prepass {
+            statistics += bundle_storage.render_to_frame_buffer(
+                server,
+                geom_cache,
+                shader_cache,
+                |bundle| bundle.render_path == RenderPath::Deferred,
+                instance_filter,
+                BundleRenderContext {
+                    texture_cache: &mut *texture_cache,
+                    render_pass_name: &self.render_pass_name,
+                    frame_buffer: &self.framebuffer,
+                    viewport,
+                    uniform_memory_allocator: &mut *uniform_memory_allocator,
+                    resource_manager,
+                    // Must match the shading pass, because texture coordinates affect alpha test.
+                    use_pom: quality_settings.use_parallax_mapping,
+                    light_position: &Default::default(),
+                    renderer_resources,
+                    ambient_light,
+                    scene_depth: None,
+                    gbuffer: None,
+                    reversed_z: quality_settings.use_reversed_z,
+                    previous_view_projection_matrix: Some(previous_view_projection_matrix),
+                    depth_pre_pass: DepthPrePass::DepthOnly,
+                },
+            )?;
+
+            // The depth buffer of the G-Buffer can't be sampled while it is attached to the frame
+            // buffer, so the shaders will get a copy of it.
+            if self.pre_pass_depth_framebuffer.is_none() {
+                self.pre_pass_depth_framebuffer = Some(server.create_frame_buffer(
+                    Some(Attachment::depth_stencil(server.create_2d_render_target(
+                        "GBufferPrePassDepthTexture",
+                        self.depth().pixel_kind(),
+                        self.width as usize,
+                        self.height as usize,
+                    )?)),
+                    vec![],
+                )?);
+            }
+        }
+
+        let scene_depth = if let Some(pre_pass_depth_framebuffer) = self
+            .pre_pass_depth_framebuffer
+            .as_ref()
+            .filter(|_| quality_settings.use_z_prepass)
+        {
+            self.framebuffer.blit_to(
+                pre_pass_depth_framebuffer,
+                0,
+                0,
+                self.width,
+                self.height,
+                0,
+                0,
+                self.width,
+                self.height,
+                false,
+                true,
+                false,
+            );
+            pre_pass_depth_framebuffer
+                .depth_attachment()
+                .map(|attachment| &attachment.texture)
+        } else {
+            None
         };
 
         statistics += bundle_storage.render_to_frame_buffer(
@@ -238,8 +631,16 @@ impl GBuffer {
                 use_pom: quality_settings.use_parallax_mapping,
                 light_position: &Default::default(),
                 renderer_resources,
-                ambient_light: Color::WHITE, // TODO
-                scene_depth: None,           // TODO. Add z-pre-pass.
+                ambient_light,
+                scene_depth,
+                gbuffer: None,
+                reversed_z: quality_settings.use_reversed_z,
+                previous_view_projection_matrix: Some(previous_view_projection_matrix),
+                depth_pre_pass: if quality_settings.use_z_prepass {
+                    DepthPrePass::Shading
+                } else {
+                    DepthPrePass::None
+                },
             },
         )?;
 
@@ -251,6 +652,22 @@ impl GBuffer {
                 }
             }
 
+            if let Some(grid_cell) = self
+                .occlusion_tester
+                .grid_cache
+                .cell(observer.position.translation)
+            {
+                for object in objects.iter() {
+                    if grid_cell.is_visible(*object, self.occlusion_culling_delay) {
+                        self.occlusion_statistics.drawn += 1;
+                    } else {
+                        self.occlusion_statistics.culled += 1;
+                    }
+                }
+            } else {
+                self.occlusion_statistics.drawn += objects.len();
+            }
+
             self.occlusion_tester.try_run_visibility_test(
                 server,
                 graph,
@@ -259,6 +676,7 @@ impl GBuffer {
                 &self.framebuffer,
                 observer.position.translation,
                 observer.position.view_projection_matrix,
+                quality_settings.use_reversed_z,
                 uniform_buffer_cache,
                 renderer_resources,
             )?;
@@ -269,6 +687,9 @@ impl GBuffer {
             .view_projection_matrix
             .try_inverse()
             .unwrap_or_default();
+        let raster_view_proj = server
+            .clip_depth_range()
+            .adjust_projection_matrix(&observer.position.view_projection_matrix);
         let depth = self.depth();
         let decal_mask = self.decal_mask_texture();
         let resolution = Vector2::new(self.width as f32, self.height as f32);
@@ -277,8 +698,7 @@ impl GBuffer {
         // for rendering. We'll render in the G-Buffer, but depth will be used from final frame, since
         // decals do not modify depth (only diffuse and normal maps).
         for decal in graph.linear_iter().filter_map(|n| n.cast::<Decal>()) {
-            let world_view_proj =
-                observer.position.view_projection_matrix * decal.global_transform();
+            let world_view_proj = raster_view_proj * decal.global_transform();
 
             let diffuse_texture = decal
                 .diffuse_texture()
@@ -307,8 +727,12 @@ impl GBuffer {
                 .clone();
 
             let inv_world_decal = decal.global_transform().try_inverse().unwrap_or_default();
-            let color = decal.color().srgb_to_linear_f32();
+            let color = decal.linear_color();
             let layer_index = decal.layer() as u32;
+            let layer_mask = decal.layer_mask();
+            let premultiplied_alpha = decal.premultiplied_alpha();
+            let two_sided = decal.two_sided();
+            let max_angle = decal.max_angle();
             let properties = PropertyGroup::from([
                 property("worldViewProjection", &world_view_proj),
                 property("invViewProj", &inv_view_proj),
@@ -316,6 +740,10 @@ impl GBuffer {
                 property("resolution", &resolution),
                 property("color", &color),
                 property("layerIndex", &layer_index),
+                property("layerMask", &layer_mask),
+                property("premultipliedAlpha", &premultiplied_alpha),
+                property("twoSided", &two_sided),
+                property("maxAngle", &max_angle),
             ]);
             let material = RenderMaterial::from([
                 binding(
@@ -331,19 +759,461 @@ impl GBuffer {
                 binding("properties", &properties),
             ]);
 
+            let pass_name = ImmutableString::new("Primary");
+
+            // Premultiplied alpha requires different blending function, the rest of the draw
+            // parameters is taken from the shader.
+            let premultiplied_draw_params = if premultiplied_alpha {
+                let mut draw_params = renderer_resources
+                    .shaders
+                    .decal
+                    .get(&pass_name)?
+                    .draw_params
+                    .clone();
+                draw_params.blend = Some(BlendParameters {
+                    func: BlendFunc::new(BlendFactor::One, BlendFactor::OneMinusSrcAlpha),
+                    ..Default::default()
+                });
+                Some(draw_params)
+            } else {
+                None
+            };
+
             statistics += renderer_resources.shaders.decal.run_pass(
                 1,
-                &ImmutableString::new("Primary"),
+                &pass_name,
                 &self.decal_framebuffer,
                 &renderer_resources.cube,
                 viewport,
                 &material,
                 uniform_buffer_cache,
                 Default::default(),
-                None,
+                premultiplied_draw_params.as_ref(),
             )?;
         }
 
         Ok(statistics)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Matrix4, Vector2, Vector4},
+            color::Color,
+            math::Rect,
+        },
+        graphics::{
+            buffer::{GpuBuffer, GpuBufferDescriptor},
+            error::FrameworkError,
+            framebuffer::{
+                Attachment, AttachmentKind, DrawCallStatistics, GpuFrameBuffer,
+                GpuFrameBufferTrait, ReadTarget, ResourceBindGroup,
+            },
+            geometry_buffer::{GpuGeometryBuffer, GpuGeometryBufferDescriptor},
+            gpu_program::{GpuProgram, GpuShader, ShaderKind, ShaderResourceDefinition},
+            gpu_texture::{
+                CubeMapFace, GpuTexture, GpuTextureDescriptor, GpuTextureKind, GpuTextureTrait,
+                PixelKind,
+            },
+            query::GpuQuery,
+            read_buffer::{GpuAsyncReadBuffer, GpuAsyncReadBufferTrait},
+            sampler::{GpuSampler, GpuSamplerDescriptor},
+            server::{GraphicsServer, ServerCapabilities, ServerMemoryUsage},
+            stats::PipelineStatistics,
+            ClipDepthRange, DrawParameters, ElementRange, PolygonFace, PolygonFillMode,
+        },
+        renderer::gbuffer::{linearize_depth, CustomAttachmentDesc, GBuffer},
+        scene::camera::reverse_depth,
+    };
+    use std::{
+        cell::{Cell, RefCell},
+        rc::{Rc, Weak},
+    };
+
+    /// A texture that only remembers its parameters.
+    struct StubTexture {
+        name: String,
+        kind: Cell<GpuTextureKind>,
+        pixel_kind: Cell<PixelKind>,
+        mip_count: usize,
+        base_level: usize,
+        max_level: usize,
+    }
+
+    impl GpuTextureTrait for StubTexture {
+        fn set_data(
+            &self,
+            kind: GpuTextureKind,
+            pixel_kind: PixelKind,
+            _mip_count: usize,
+            _data: Option<&[u8]>,
+        ) -> Result<usize, FrameworkError> {
+            self.kind.set(kind);
+            self.pixel_kind.set(pixel_kind);
+            Ok(0)
+        }
+
+        fn kind(&self) -> GpuTextureKind {
+            self.kind.get()
+        }
+
+        fn pixel_kind(&self) -> PixelKind {
+            self.pixel_kind.get()
+        }
+    }
+
+    struct StubFrameBuffer {
+        depth_attachment: Option<Attachment>,
+        color_attachments: Vec<Attachment>,
+    }
+
+    impl GpuFrameBufferTrait for StubFrameBuffer {
+        fn color_attachments(&self) -> &[Attachment] {
+            &self.color_attachments
+        }
+
+        fn depth_attachment(&self) -> Option<&Attachment> {
+            self.depth_attachment.as_ref()
+        }
+
+        fn set_cubemap_face(&self, _attachment_index: usize, _face: CubeMapFace, _level: usize) {}
+
+        fn blit_to(
+            &self,
+            _dest: &GpuFrameBuffer,
+            _src_x0: i32,
+            _src_y0: i32,
+            _src_x1: i32,
+            _src_y1: i32,
+            _dst_x0: i32,
+            _dst_y0: i32,
+            _dst_x1: i32,
+            _dst_y1: i32,
+            _copy_color: bool,
+            _copy_depth: bool,
+            _copy_stencil: bool,
+        ) {
+        }
+
+        fn clear(
+            &self,
+            _viewport: Rect<i32>,
+            _color: Option<Color>,
+            _depth: Option<f32>,
+            _stencil: Option<i32>,
+        ) {
+        }
+
+        fn read_pixels(&self, _read_target: ReadTarget) -> Option<Vec<u8>> {
+            None
+        }
+
+        fn read_pixels_in_rect(
+            &self,
+            _read_target: ReadTarget,
+            _rect: Rect<i32>,
+        ) -> Option<Vec<u8>> {
+            None
+        }
+
+        fn draw(
+            &self,
+            _geometry: &GpuGeometryBuffer,
+            _viewport: Rect<i32>,
+            _program: &GpuProgram,
+            _params: &DrawParameters,
+            _resources: &[ResourceBindGroup],
+            _element_range: ElementRange,
+        ) -> Result<DrawCallStatistics, FrameworkError> {
+            unimplemented!()
+        }
+
+        fn draw_instances(
+            &self,
+            _instance_count: usize,
+            _geometry: &GpuGeometryBuffer,
+            _viewport: Rect<i32>,
+            _program: &GpuProgram,
+            _params: &DrawParameters,
+            _resources: &[ResourceBindGroup],
+            _element_range: ElementRange,
+        ) -> Result<DrawCallStatistics, FrameworkError> {
+            unimplemented!()
+        }
+    }
+
+    struct StubAsyncReadBuffer;
+
+    impl GpuAsyncReadBufferTrait for StubAsyncReadBuffer {
+        fn schedule_pixels_transfer(
+            &self,
+            _framebuffer: &dyn GpuFrameBufferTrait,
+            _color_buffer_index: u32,
+            _rect: Option<Rect<i32>>,
+        ) -> Result<(), FrameworkError> {
+            Ok(())
+        }
+
+        fn is_request_running(&self) -> bool {
+            false
+        }
+
+        fn try_read(&self) -> Option<Vec<u8>> {
+            None
+        }
+    }
+
+    /// A graphics server that does not need a GPU. It supports only the resources that are needed
+    /// to create the G-Buffer.
+    #[derive(Default)]
+    struct StubServer {
+        this: RefCell<Weak<StubServer>>,
+    }
+
+    impl StubServer {
+        fn new() -> Rc<Self> {
+            let server = Rc::new(Self::default());
+            *server.this.borrow_mut() = Rc::downgrade(&server);
+            server
+        }
+    }
+
+    impl GraphicsServer for StubServer {
+        fn create_buffer(&self, _desc: GpuBufferDescriptor) -> Result<GpuBuffer, FrameworkError> {
+            unimplemented!()
+        }
+
+        fn create_texture(&self, desc: GpuTextureDescriptor) -> Result<GpuTexture, FrameworkError> {
+            Ok(GpuTexture(Rc::new(StubTexture {
+                name: desc.name.to_string(),
+                kind: Cell::new(desc.kind),
+                pixel_kind: Cell::new(desc.pixel_kind),
+                mip_count: desc.mip_count,
+                base_level: desc.base_level,
+                max_level: desc.max_level,
+            })))
+        }
+
+        fn create_sampler(
+            &self,
+            _desc: GpuSamplerDescriptor,
+        ) -> Result<GpuSampler, FrameworkError> {
+            // Wrap modes and filters of the attachments are defined by the samplers of the renderer
+            // resources, the G-Buffer must never create (and thus replace) them.
+            unimplemented!()
+        }
+
+        fn create_frame_buffer(
+            &self,
+            depth_attachment: Option<Attachment>,
+            color_attachments: Vec<Attachment>,
+        ) -> Result<GpuFrameBuffer, FrameworkError> {
+            Ok(GpuFrameBuffer(Rc::new(StubFrameBuffer {
+                depth_attachment,
+                color_attachments,
+            })))
+        }
+
+        fn back_buffer(&self) -> GpuFrameBuffer {
+            unimplemented!()
+        }
+
+        fn create_query(&self) -> Result<GpuQuery, FrameworkError> {
+            unimplemented!()
+        }
+
+        fn create_shader(
+            &self,
+            _name: String,
+            _kind: ShaderKind,
+            _source: String,
+            _resources: &[ShaderResourceDefinition],
+            _line_offset: isize,
+        ) -> Result<GpuShader, FrameworkError> {
+            unimplemented!()
+        }
+
+        fn create_program(
+            &self,
+            _name: &str,
+            _vertex_source: String,
+            _vertex_source_line_offset: isize,
+            _fragment_source: String,
+            _fragment_source_line_offset: isize,
+            _resources: &[ShaderResourceDefinition],
+        ) -> Result<GpuProgram, FrameworkError> {
+            unimplemented!()
+        }
+
+        fn create_program_from_shaders(
+            &self,
+            _name: &str,
+            _vertex_shader: &GpuShader,
+            _fragment_shader: &GpuShader,
+            _resources: &[ShaderResourceDefinition],
+        ) -> Result<GpuProgram, FrameworkError> {
+            unimplemented!()
+        }
+
+        fn create_async_read_buffer(
+            &self,
+            _name: &str,
+            _pixel_size: usize,
+            _pixel_count: usize,
+        ) -> Result<GpuAsyncReadBuffer, FrameworkError> {
+            Ok(GpuAsyncReadBuffer(Rc::new(StubAsyncReadBuffer)))
+        }
+
+        fn create_geometry_buffer(
+            &self,
+            _desc: GpuGeometryBufferDescriptor,
+        ) -> Result<GpuGeometryBuffer, FrameworkError> {
+            unimplemented!()
+        }
+
+        fn weak(&self) -> Weak<dyn GraphicsServer> {
+            self.this.borrow().clone()
+        }
+
+        fn flush(&self) {}
+
+        fn finish(&self) {}
+
+        fn invalidate_resource_bindings_cache(&self) {}
+
+        fn pipeline_statistics(&self) -> PipelineStatistics {
+            Default::default()
+        }
+
+        fn swap_buffers(&self) -> Result<(), FrameworkError> {
+            Ok(())
+        }
+
+        fn set_frame_size(&self, _new_size: (u32, u32)) {}
+
+        fn capabilities(&self) -> ServerCapabilities {
+            unimplemented!()
+        }
+
+        fn set_polygon_fill_mode(
+            &self,
+            _polygon_face: PolygonFace,
+            _polygon_fill_mode: PolygonFillMode,
+        ) {
+        }
+
+        fn set_clip_depth_range(&self, _clip_depth_range: ClipDepthRange) {}
+
+        fn clip_depth_range(&self) -> ClipDepthRange {
+            Default::default()
+        }
+
+        fn generate_mipmap(&self, _texture: &GpuTexture) {}
+
+        fn memory_usage(&self) -> ServerMemoryUsage {
+            unimplemented!()
+        }
+
+        fn push_debug_group(&self, _name: &str) {}
+
+        fn pop_debug_group(&self) {}
+    }
+
+    /// Everything that defines an attachment, except its size.
+    fn attachment_settings(
+        attachment: &Attachment,
+    ) -> (AttachmentKind, String, PixelKind, usize, usize, usize) {
+        let texture = attachment
+            .texture
+            .as_any()
+            .downcast_ref::<StubTexture>()
+            .unwrap();
+        (
+            attachment.kind,
+            texture.name.clone(),
+            texture.pixel_kind(),
+            texture.mip_count,
+            texture.base_level,
+            texture.max_level,
+        )
+    }
+
+    fn all_attachments(gbuffer: &GBuffer) -> Vec<&Attachment> {
+        gbuffer
+            .framebuffer()
+            .depth_attachment()
+            .into_iter()
+            .chain(gbuffer.framebuffer().color_attachments())
+            .chain(gbuffer.decal_framebuffer.color_attachments())
+            .collect()
+    }
+
+    #[test]
+    fn test_gbuffer_set_size_preserves_attachments() {
+        let server = StubServer::new();
+        let custom_attachments = [CustomAttachmentDesc {
+            pixel_kind: PixelKind::RGBA16F,
+            name: "CustomAttachment".into(),
+        }];
+        let mut gbuffer = GBuffer::new(
+            &*server,
+            100,
+            50,
+            &custom_attachments,
+            1.0,
+            PixelKind::D32FS8,
+        )
+        .unwrap();
+
+        let settings_before = all_attachments(&gbuffer)
+            .into_iter()
+            .map(attachment_settings)
+            .collect::<Vec<_>>();
+        assert_eq!(settings_before.len(), 1 + 6 + custom_attachments.len() + 2);
+
+        gbuffer.set_size(&*server, 320, 240).unwrap();
+
+        assert_eq!((gbuffer.width, gbuffer.height), (320, 240));
+        assert_eq!(gbuffer.custom_attachments(), custom_attachments);
+        let attachments = all_attachments(&gbuffer);
+        for attachment in attachments.iter() {
+            assert!(matches!(
+                attachment.texture.kind(),
+                GpuTextureKind::Rectangle {
+                    width: 320,
+                    height: 240
+                }
+            ));
+        }
+        assert_eq!(
+            attachments
+                .into_iter()
+                .map(attachment_settings)
+                .collect::<Vec<_>>(),
+            settings_before
+        );
+    }
+
+    #[test]
+    fn test_linearize_depth() {
+        let mut projection = Matrix4::new_perspective(1.5, 1.2, 0.1, 100.0);
+        let depth_of = |projection: &Matrix4<f32>, distance: f32| {
+            let clip = projection * Vector4::new(0.0, 0.0, -distance, 1.0);
+            (clip.z / clip.w + 1.0) / 2.0
+        };
+
+        for distance in [0.1, 1.0, 25.0, 100.0] {
+            let depth = depth_of(&projection, distance);
+            let linear = linearize_depth(&projection, Vector2::zeros(), depth).unwrap();
+            assert!((linear - distance).abs() < distance * 0.01);
+        }
+
+        reverse_depth(&mut projection);
+        let depth = depth_of(&projection, 10.0);
+        assert!(depth < 0.5);
+        let linear = linearize_depth(&projection, Vector2::zeros(), depth).unwrap();
+        assert!((linear - 10.0).abs() < 0.1);
+    }
+}
@@ -64,11 +64,15 @@ impl AdaptationChain {
         out
     }
 
-    pub fn avg_lum_texture(&self) -> &GpuTexture {
+    pub fn avg_lum_buffer(&self) -> &LumBuffer {
         if self.swap.get() {
-            &self.lum_framebuffers[0].framebuffer.color_attachments()[0].texture
+            &self.lum_framebuffers[0]
         } else {
-            &self.lum_framebuffers[1].framebuffer.color_attachments()[0].texture
+            &self.lum_framebuffers[1]
         }
     }
+
+    pub fn avg_lum_texture(&self) -> &GpuTexture {
+        self.avg_lum_buffer().texture()
+    }
 }
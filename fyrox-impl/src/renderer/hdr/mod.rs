@@ -44,7 +44,7 @@ use crate::{
         resources::RendererResources,
         LuminanceCalculationMethod, QualitySettings, RenderPassStatistics,
     },
-    scene::camera::{ColorGradingLut, Exposure},
+    scene::camera::{ColorGradingLut, Exposure, WhiteBalance},
 };
 
 mod adaptation;
@@ -102,6 +102,7 @@ pub struct HdrRendererArgs<'a> {
     pub exposure: Exposure,
     pub color_grading_lut: Option<&'a ColorGradingLut>,
     pub use_color_grading: bool,
+    pub white_balance: WhiteBalance,
     pub texture_cache: &'a mut TextureCache,
     pub uniform_buffer_cache: &'a mut UniformBufferCache,
     pub renderer_resources: &'a RendererResources,
@@ -141,6 +142,18 @@ impl HighDynamicRangeRenderer {
         })
     }
 
+    /// Re-creates the size-dependent render targets. Luminance adaptation state is preserved, so
+    /// the exposure does not jump after resizing.
+    pub fn set_size(
+        &mut self,
+        server: &dyn GraphicsServer,
+        width: usize,
+        height: usize,
+    ) -> Result<(), FrameworkError> {
+        self.bloom_renderer = BloomRenderer::new(server, width, height)?;
+        Ok(())
+    }
+
     fn calculate_frame_luminance(
         &self,
         server: &dyn GraphicsServer,
@@ -319,6 +332,7 @@ impl HighDynamicRangeRenderer {
             exposure,
             color_grading_lut,
             use_color_grading,
+            white_balance,
             texture_cache,
             uniform_buffer_cache,
             renderer_resources,
@@ -343,6 +357,7 @@ impl HighDynamicRangeRenderer {
             Exposure::Auto {
                 min_luminance,
                 max_luminance,
+                ..
             } => (true, min_luminance, max_luminance, 0.0),
             Exposure::Manual(fixed_exposure) => (false, 0.0, 0.0, fixed_exposure),
         };
@@ -354,6 +369,7 @@ impl HighDynamicRangeRenderer {
         };
 
         let color_grading_enabled = use_color_grading && color_grading_lut.is_some();
+        let white_balance = white_balance.multiplier();
         let properties = PropertyGroup::from([
             property("worldViewProjection", &frame_matrix),
             property("useColorGrading", &color_grading_enabled),
@@ -361,6 +377,7 @@ impl HighDynamicRangeRenderer {
             property("maxLuminance", &max_luminance),
             property("autoExposure", &is_auto),
             property("fixedExposure", &fixed_exposure),
+            property("whiteBalance", &white_balance),
         ]);
         let material = RenderMaterial::from([
             binding(
@@ -395,6 +412,16 @@ impl HighDynamicRangeRenderer {
         )
     }
 
+    /// Reads adapted average luminance of the last rendered frame back from GPU. This is a
+    /// synchronous read that stalls the pipeline, so it should be done only on demand.
+    pub fn average_luminance(&self) -> Option<f32> {
+        self.adaptation_chain
+            .avg_lum_buffer()
+            .framebuffer
+            .read_pixels_of_type::<f32>(ReadTarget::Color(0))
+            .and_then(|pixels| pixels.first().copied())
+    }
+
     pub fn render(&self, args: HdrRendererArgs) -> Result<RenderPassStatistics, FrameworkError> {
         let _debug_scope = args.server.begin_scope("HDR");
         let mut stats = RenderPassStatistics::default();
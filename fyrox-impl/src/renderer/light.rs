@@ -23,7 +23,7 @@ use crate::{
     core::{
         algebra::{Matrix4, Point3, UnitQuaternion, Vector2, Vector3},
         color::Color,
-        math::{frustum::Frustum, Matrix4Ext, Rect, TriangleDefinition},
+        math::{frustum::Frustum, Matrix4Ext, Rect},
         ImmutableString,
     },
     graphics::{
@@ -33,8 +33,7 @@ use crate::{
         geometry_buffer::GpuGeometryBuffer,
         gpu_texture::{GpuTexture, GpuTextureKind},
         server::GraphicsServer,
-        ColorMask, CompareFunc, CullFace, DrawParameters, ElementRange, StencilAction, StencilFunc,
-        StencilOp,
+        ColorMask, CompareFunc, CullFace, DrawParameters, StencilAction, StencilFunc, StencilOp,
     },
     include_bytes_align_as,
     renderer::{
@@ -57,24 +56,20 @@ use crate::{
             spot::SpotShadowMapRenderer,
         },
         ssao::ScreenSpaceAmbientOcclusionRenderer,
+        ssr::Ssr,
         utils::make_brdf_lut,
         visibility::ObserverVisibilityCache,
         GeometryCache, LightingStatistics, QualitySettings, RenderPassStatistics, TextureCache,
     },
     scene::{
-        mesh::{
-            buffer::{TriangleBuffer, VertexBuffer},
-            surface::SurfaceData,
-            vertex::SimpleVertex,
-        },
-        EnvironmentLightingSource, Scene,
+        camera::CameraClearColor, mesh::surface::SurfaceData, EnvironmentLightingSource, Scene,
     },
 };
+use std::rc::Rc;
 
 pub struct DeferredLightRenderer {
     sphere: GpuGeometryBuffer,
     cone: GpuGeometryBuffer,
-    skybox: GpuGeometryBuffer,
     spot_shadow_map_renderer: SpotShadowMapRenderer,
     point_shadow_map_renderer: PointShadowMapRenderer,
     csm_renderer: CsmRenderer,
@@ -102,10 +97,13 @@ pub(crate) struct DeferredRendererContext<'a> {
     pub uniform_memory_allocator: &'a mut UniformMemoryAllocator,
     pub dynamic_surface_cache: &'a mut DynamicSurfaceCache,
     pub ssao_renderer: &'a ScreenSpaceAmbientOcclusionRenderer,
+    pub ssr_renderer: &'a Ssr,
+    pub previous_view_projection_matrix: Matrix4<f32>,
     pub resource_manager: &'a ResourceManager,
     pub environment_map_specular_convolution: &'a mut Option<EnvironmentMapSpecularConvolution>,
     pub environment_map_irradiance_convolution: &'a EnvironmentMapIrradianceConvolution,
     pub need_recalculate_convolution: &'a mut bool,
+    pub convolved_environment_map: &'a mut Option<GpuTexture>,
 }
 
 impl DeferredLightRenderer {
@@ -113,64 +111,9 @@ impl DeferredLightRenderer {
         server: &dyn GraphicsServer,
         settings: &QualitySettings,
     ) -> Result<Self, FrameworkError> {
-        let vertices = vec![
-            // Front
-            SimpleVertex::new(-0.5, 0.5, -0.5),
-            SimpleVertex::new(0.5, 0.5, -0.5),
-            SimpleVertex::new(0.5, -0.5, -0.5),
-            SimpleVertex::new(-0.5, -0.5, -0.5),
-            // Back
-            SimpleVertex::new(0.5, 0.5, 0.5),
-            SimpleVertex::new(-0.5, 0.5, 0.5),
-            SimpleVertex::new(-0.5, -0.5, 0.5),
-            SimpleVertex::new(0.5, -0.5, 0.5),
-            // Left
-            SimpleVertex::new(0.5, 0.5, -0.5),
-            SimpleVertex::new(0.5, 0.5, 0.5),
-            SimpleVertex::new(0.5, -0.5, 0.5),
-            SimpleVertex::new(0.5, -0.5, -0.5),
-            // Right
-            SimpleVertex::new(-0.5, 0.5, 0.5),
-            SimpleVertex::new(-0.5, 0.5, -0.5),
-            SimpleVertex::new(-0.5, -0.5, -0.5),
-            SimpleVertex::new(-0.5, -0.5, 0.5),
-            // Up
-            SimpleVertex::new(-0.5, 0.5, 0.5),
-            SimpleVertex::new(0.5, 0.5, 0.5),
-            SimpleVertex::new(0.5, 0.5, -0.5),
-            SimpleVertex::new(-0.5, 0.5, -0.5),
-            // Down
-            SimpleVertex::new(-0.5, -0.5, 0.5),
-            SimpleVertex::new(0.5, -0.5, 0.5),
-            SimpleVertex::new(0.5, -0.5, -0.5),
-            SimpleVertex::new(-0.5, -0.5, -0.5),
-        ];
-
         let quality_defaults = QualitySettings::default();
 
         Ok(Self {
-            skybox: GpuGeometryBuffer::from_surface_data(
-                "SkyBox",
-                &SurfaceData::new(
-                    VertexBuffer::new(vertices.len(), vertices).unwrap(),
-                    TriangleBuffer::new(vec![
-                        TriangleDefinition([0, 1, 2]),
-                        TriangleDefinition([0, 2, 3]),
-                        TriangleDefinition([4, 5, 6]),
-                        TriangleDefinition([4, 6, 7]),
-                        TriangleDefinition([8, 9, 10]),
-                        TriangleDefinition([8, 10, 11]),
-                        TriangleDefinition([12, 13, 14]),
-                        TriangleDefinition([12, 14, 15]),
-                        TriangleDefinition([16, 17, 18]),
-                        TriangleDefinition([16, 18, 19]),
-                        TriangleDefinition([20, 21, 22]),
-                        TriangleDefinition([20, 22, 23]),
-                    ]),
-                ),
-                BufferUsage::StaticDraw,
-                server,
-            )?,
             sphere: GpuGeometryBuffer::from_surface_data(
                 "Sphere",
                 &SurfaceData::make_sphere(10, 10, 1.0, &Matrix4::identity()),
@@ -280,10 +223,13 @@ impl DeferredLightRenderer {
             uniform_memory_allocator,
             dynamic_surface_cache,
             ssao_renderer,
+            ssr_renderer,
+            previous_view_projection_matrix,
             resource_manager,
             environment_map_specular_convolution,
             environment_map_irradiance_convolution,
             need_recalculate_convolution,
+            convolved_environment_map,
         } = args;
 
         let viewport = Rect::new(0, 0, gbuffer.width, gbuffer.height);
@@ -303,6 +249,11 @@ impl DeferredLightRenderer {
             .view_projection_matrix
             .try_inverse()
             .unwrap_or_default();
+        // The matrices of the observer are used as is to reconstruct positions from the depth, but
+        // the geometry must be rasterized using the current clip-space depth range.
+        let raster_view_projection = server
+            .clip_depth_range()
+            .adjust_projection_matrix(&observer.position.view_projection_matrix);
 
         // Fill SSAO map.
         if settings.use_ssao {
@@ -316,13 +267,33 @@ impl DeferredLightRenderer {
             )?;
         }
 
-        // Render skybox (if any).
-        if let Some(skybox) = scene.skybox_ref().and_then(|s| s.cubemap_ref()) {
+        // Fill SSR map. Reflection probes render multiple faces using the same render data, so
+        // there's no valid previous frame to take the reflections from.
+        let use_ssr = settings.use_ssr && observer.reflection_probe_data.is_none();
+        if use_ssr {
+            pass_stats += ssr_renderer.render(
+                server,
+                gbuffer,
+                &observer.position,
+                &previous_view_projection_matrix,
+                settings,
+                uniform_buffer_cache,
+                renderer_resources,
+            )?;
+        }
+
+        // Render skybox (if any). Observers that keep the previous content of the frame must not
+        // overwrite it with the skybox.
+        if let Some(skybox) = scene
+            .skybox_ref()
+            .and_then(|s| s.cubemap_ref())
+            .filter(|_| observer.clear_color != CameraClearColor::Keep)
+        {
             if let Some(texture_sampler_pair) = textures.get(server, resource_manager, skybox) {
                 let size = observer.position.z_far / 2.0f32.sqrt();
                 let scale = Matrix4::new_scaling(size);
                 let wvp = Matrix4::new_translation(&observer.position.translation) * scale;
-                let wvp = observer.position.view_projection_matrix * wvp;
+                let wvp = raster_view_projection * wvp;
                 let properties = PropertyGroup::from([property("worldViewProjection", &wvp)]);
                 let material = RenderMaterial::from([
                     binding(
@@ -339,14 +310,11 @@ impl DeferredLightRenderer {
                     1,
                     &ImmutableString::new("Primary"),
                     frame_buffer,
-                    &self.skybox,
+                    &renderer_resources.cube,
                     viewport,
                     &material,
                     uniform_buffer_cache,
-                    ElementRange::Specific {
-                        offset: 0,
-                        count: 12,
-                    },
+                    Default::default(),
                     None,
                 )?;
             }
@@ -364,6 +332,15 @@ impl DeferredLightRenderer {
             })
             .unwrap_or(&renderer_resources.environment_dummy);
 
+        // Convolutions are calculated only when the environment map changes, this includes the
+        // moment when the environment map finishes loading and replaces the dummy one.
+        if convolved_environment_map
+            .as_ref()
+            .is_none_or(|convolved| !Rc::ptr_eq(&convolved.0, &environment_map.0))
+        {
+            *need_recalculate_convolution = true;
+        }
+
         if *need_recalculate_convolution {
             // Prepare the specular convolution.
             let environment_map_size = if let GpuTextureKind::Cube { size } = environment_map.kind()
@@ -400,6 +377,7 @@ impl DeferredLightRenderer {
             )?;
 
             *need_recalculate_convolution = false;
+            *convolved_environment_map = Some(environment_map.clone());
         }
 
         let specular_convolution = environment_map_specular_convolution
@@ -414,11 +392,15 @@ impl DeferredLightRenderer {
         let gbuffer_material_map = gbuffer.material_texture();
         let gbuffer_ambient_map = gbuffer.ambient_texture();
         let ao_map = ssao_renderer.ao_map();
+        let ssr_map = ssr_renderer.reflection_map();
 
-        let skybox_lighting = matches!(
-            environment_lighting_source,
-            EnvironmentLightingSource::SkyBox
-        );
+        // Environment map of the observer always lights the scene, its irradiance map replaces the
+        // ambient color.
+        let skybox_lighting = observer.environment_map.is_some()
+            || matches!(
+                environment_lighting_source,
+                EnvironmentLightingSource::SkyBox
+            );
         let ambient_color = ambient_color.srgb_to_linear_f32();
         let properties = PropertyGroup::from([
             property("worldViewProjection", &frame_matrix),
@@ -430,6 +412,7 @@ impl DeferredLightRenderer {
                 "environmentLightingBrightness",
                 &scene.rendering_options.environment_lighting_brightness,
             ),
+            property("useSsr", &use_ssr),
         ]);
         let material = RenderMaterial::from([
             binding(
@@ -490,6 +473,17 @@ impl DeferredLightRenderer {
                 "brdfLUT",
                 (&self.brdf_lut, &renderer_resources.linear_clamp_sampler),
             ),
+            binding(
+                "ssrTexture",
+                if use_ssr {
+                    (&ssr_map, &renderer_resources.linear_clamp_sampler)
+                } else {
+                    (
+                        &renderer_resources.black_dummy,
+                        &renderer_resources.nearest_clamp_sampler,
+                    )
+                },
+            ),
             binding("properties", &properties),
         ]);
 
@@ -614,7 +608,7 @@ impl DeferredLightRenderer {
             let mut light_view_projection = Matrix4::identity();
 
             // Mark lit areas in stencil buffer to do light calculations only on them.
-            let shape_wvp_matrix = observer.position.view_projection_matrix * bounding_shape_matrix;
+            let shape_wvp_matrix = raster_view_projection * bounding_shape_matrix;
             for (cull_face, stencil_action) in [
                 (CullFace::Front, StencilAction::Incr),
                 (CullFace::Back, StencilAction::Decr),
@@ -631,7 +625,11 @@ impl DeferredLightRenderer {
                         zfail: stencil_action,
                         ..Default::default()
                     },
-                    depth_test: Some(CompareFunc::Less),
+                    depth_test: Some(if settings.use_reversed_z {
+                        CompareFunc::Greater
+                    } else {
+                        CompareFunc::Less
+                    }),
                     blend: None,
                     scissor_box: None,
                 };
@@ -1064,6 +1062,7 @@ impl DeferredLightRenderer {
                     observer.position.view_matrix,
                     inv_projection,
                     observer.position.view_projection_matrix,
+                    settings.use_reversed_z,
                     viewport,
                     &scene.graph,
                     frame_buffer,
@@ -80,6 +80,7 @@ impl LightVolumeRenderer {
         view: Matrix4<f32>,
         inv_proj: Matrix4<f32>,
         view_proj: Matrix4<f32>,
+        reversed_z: bool,
         viewport: Rect<i32>,
         graph: &Graph,
         frame_buffer: &GpuFrameBuffer,
@@ -90,6 +91,15 @@ impl LightVolumeRenderer {
 
         let mut stats = RenderPassStatistics::default();
 
+        let view_proj = server
+            .clip_depth_range()
+            .adjust_projection_matrix(&view_proj);
+        let marker_pass_name = ImmutableString::new("Primary");
+        let marker_draw_params = renderer_resources
+            .shaders
+            .volume_marker_vol
+            .depth_draw_params(&marker_pass_name, reversed_z)?;
+
         let frame_matrix = make_viewport_matrix(viewport);
         let position = view.transform_point(&Point3::from(light.position)).coords;
         let color = light.color.srgb_to_linear_f32().xyz();
@@ -128,14 +138,14 @@ impl LightVolumeRenderer {
                 let material = RenderMaterial::from([binding("properties", &properties)]);
                 stats += renderer_resources.shaders.volume_marker_vol.run_pass(
                     1,
-                    &ImmutableString::new("Primary"),
+                    &marker_pass_name,
                     frame_buffer,
                     &self.cone,
                     viewport,
                     &material,
                     uniform_buffer_cache,
                     Default::default(),
-                    None,
+                    marker_draw_params.as_ref(),
                 )?;
 
                 // Finally draw fullscreen quad, GPU will calculate scattering only on pixels that were
@@ -188,14 +198,14 @@ impl LightVolumeRenderer {
                 let material = RenderMaterial::from([binding("properties", &properties)]);
                 stats += renderer_resources.shaders.volume_marker_vol.run_pass(
                     1,
-                    &ImmutableString::new("Primary"),
+                    &marker_pass_name,
                     frame_buffer,
                     &self.sphere,
                     viewport,
                     &material,
                     uniform_buffer_cache,
                     Default::default(),
-                    None,
+                    marker_draw_params.as_ref(),
                 )?;
 
                 // Finally draw fullscreen quad, GPU will calculate scattering only on pixels that were
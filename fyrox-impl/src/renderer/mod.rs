@@ -39,25 +39,28 @@ pub mod visibility;
 
 mod bloom;
 mod convolution;
+mod depth_of_field;
 mod fxaa;
 mod gbuffer;
 mod hdr;
 mod light;
 mod light_volume;
 mod occlusion;
+mod post_effects;
 mod settings;
 mod shadow;
 mod ssao;
+mod ssr;
 
 use crate::renderer::hdr::HdrRendererArgs;
 use crate::{
     asset::{event::ResourceEvent, manager::ResourceManager},
     core::{
         algebra::{Matrix4, Vector2, Vector3},
-        color::Color,
+        color::{Color, Hsv},
         info,
         log::{Log, MessageKind},
-        math::Rect,
+        math::{aabb::AxisAlignedBoundingBox, Rect},
         pool::Handle,
         sstorage::ImmutableString,
     },
@@ -67,7 +70,7 @@ use crate::{
         framebuffer::{Attachment, DrawCallStatistics, GpuFrameBuffer},
         gpu_texture::{GpuTexture, GpuTextureDescriptor, GpuTextureKind, PixelKind},
         server::{GraphicsServer, SharedGraphicsServer},
-        PolygonFace, PolygonFillMode,
+        ClipDepthRange, PolygonFace, PolygonFillMode,
     },
     material::shader::Shader,
     renderer::{
@@ -83,21 +86,33 @@ use crate::{
         },
         convolution::{EnvironmentMapIrradianceConvolution, EnvironmentMapSpecularConvolution},
         debug_renderer::DebugRenderer,
+        depth_of_field::{DepthOfFieldArgs, DepthOfFieldRenderer},
         fxaa::FxaaRenderer,
         gbuffer::{GBuffer, GBufferRenderContext},
         hdr::HighDynamicRangeRenderer,
         light::{DeferredLightRenderer, DeferredRendererContext},
+        post_effects::PostEffectsRenderer,
         ssao::ScreenSpaceAmbientOcclusionRenderer,
+        ssr::Ssr,
         ui_renderer::UiRenderInfo,
         ui_renderer::{UiRenderContext, UiRenderer},
         visibility::VisibilityCache,
     },
     resource::texture::{Texture, TextureKind, TextureResource},
-    scene::{mesh::RenderPath, node::Node, Scene, SceneContainer},
+    scene::{
+        camera::{Camera, CameraClearColor, Exposure},
+        debug::SceneDrawingContext,
+        decal::Decal,
+        graph::Graph,
+        mesh::RenderPath,
+        node::Node,
+        Scene, SceneContainer,
+    },
 };
 use cache::DynamicSurfaceCache;
 use fxhash::FxHashMap;
 use fyrox_graph::SceneGraph;
+pub use gbuffer::CustomAttachmentDesc;
 use observer::{Observer, ObserversCollection};
 use resources::RendererResources;
 pub use settings::*;
@@ -112,6 +127,10 @@ use std::{
 };
 use winit::window::Window;
 
+/// Size of the faces of the irradiance cube maps, that are calculated from environment maps. See
+/// [`crate::scene::camera::Camera::environment_irradiance`] for more info.
+pub const IRRADIANCE_MAP_SIZE: usize = 32;
+
 static GBUFFER_PASS_NAME: LazyLock<ImmutableString> =
     LazyLock::new(|| ImmutableString::new("GBuffer"));
 
@@ -124,6 +143,9 @@ static SPOT_SHADOW_PASS_NAME: LazyLock<ImmutableString> =
 static POINT_SHADOW_PASS_NAME: LazyLock<ImmutableString> =
     LazyLock::new(|| ImmutableString::new("PointShadow"));
 
+static DEPTH_PRE_PASS_NAME: LazyLock<ImmutableString> =
+    LazyLock::new(|| ImmutableString::new("DepthPrePass"));
+
 /// Checks whether the provided render pass name is one of the names of built-in shadow render passes.
 pub fn is_shadow_pass(render_pass_name: &str) -> bool {
     render_pass_name == &**DIRECTIONAL_SHADOW_PASS_NAME
@@ -137,6 +159,9 @@ pub struct SceneRenderData {
     pub camera_data: FxHashMap<Handle<Node>, RenderDataContainer>,
     /// Scene-specific render data.
     pub scene_data: RenderDataContainer,
+    /// View-projection matrices of the observers from the previous frame. They're used to
+    /// calculate screen-space velocity in the G-Buffer.
+    pub previous_view_projection_matrices: FxHashMap<Handle<Node>, Matrix4<f32>>,
 }
 
 impl SceneRenderData {
@@ -145,13 +170,38 @@ impl SceneRenderData {
         server: &dyn GraphicsServer,
         frame_size: Vector2<f32>,
         final_frame_texture: FrameTextureKind,
+        custom_attachments: &[CustomAttachmentDesc],
+        occlusion_grid_cell_size: f32,
+        depth_stencil_pixel_kind: PixelKind,
     ) -> Result<Self, FrameworkError> {
         Ok(Self {
             camera_data: Default::default(),
-            scene_data: RenderDataContainer::new(server, frame_size, final_frame_texture)?,
+            scene_data: RenderDataContainer::new(
+                server,
+                frame_size,
+                final_frame_texture,
+                custom_attachments,
+                occlusion_grid_cell_size,
+                depth_stencil_pixel_kind,
+            )?,
+            previous_view_projection_matrices: Default::default(),
         })
     }
 
+    /// Returns `true` if the given node was hidden by occlusion culling for every observer of the
+    /// scene that has tested it. See [`Renderer::is_node_occluded`] for more info.
+    pub fn is_node_occluded(&self, node: Handle<Node>) -> bool {
+        let mut occluded = false;
+        for container in std::iter::once(&self.scene_data).chain(self.camera_data.values()) {
+            match container.gbuffer.is_occluded(node) {
+                Some(true) => occluded = true,
+                Some(false) => return false,
+                None => (),
+            }
+        }
+        occluded
+    }
+
     /// Sets the new quality settings.
     pub fn set_quality_settings(&mut self, settings: &QualitySettings) {
         for camera_data in self.camera_data.values_mut() {
@@ -167,10 +217,47 @@ fn recreate_render_data_if_needed<T: Any>(
     data: &mut RenderDataContainer,
     frame_size: Vector2<f32>,
     final_frame_texture: FrameTextureKind,
+    custom_attachments: &[CustomAttachmentDesc],
+    occlusion_grid_cell_size: f32,
+    depth_stencil_pixel_kind: PixelKind,
 ) -> Result<(), FrameworkError> {
-    if data.gbuffer.width != frame_size.x as i32 || data.gbuffer.height != frame_size.y as i32 {
+    if data.gbuffer.custom_attachments() != custom_attachments {
+        Log::info(format!(
+            "Associated scene rendering data was re-created for {} ({}), because the set of \
+                 custom G-Buffer attachments was changed.",
+            parent,
+            std::any::type_name::<T>(),
+        ));
+
+        *data = RenderDataContainer::new(
+            server,
+            frame_size,
+            final_frame_texture,
+            custom_attachments,
+            occlusion_grid_cell_size,
+            depth_stencil_pixel_kind,
+        )?;
+    } else if data.gbuffer.depth().pixel_kind() != depth_stencil_pixel_kind {
+        Log::info(format!(
+            "Associated scene rendering data was re-created for {} ({}), because the format of \
+                 the depth buffer was changed.",
+            parent,
+            std::any::type_name::<T>(),
+        ));
+
+        *data = RenderDataContainer::new(
+            server,
+            frame_size,
+            final_frame_texture,
+            custom_attachments,
+            occlusion_grid_cell_size,
+            depth_stencil_pixel_kind,
+        )?;
+    } else if data.gbuffer.width != frame_size.x as i32
+        || data.gbuffer.height != frame_size.y as i32
+    {
         Log::info(format!(
-            "Associated scene rendering data was re-created for {} ({}), because render \
+            "Associated scene rendering data was resized for {} ({}), because render \
                  frame size was changed. Old is {}x{}, new {}x{}!",
             parent,
             std::any::type_name::<T>(),
@@ -180,7 +267,12 @@ fn recreate_render_data_if_needed<T: Any>(
             frame_size.y
         ));
 
-        *data = RenderDataContainer::new(server, frame_size, final_frame_texture)?;
+        data.set_size(
+            server,
+            frame_size,
+            final_frame_texture,
+            depth_stencil_pixel_kind,
+        )?;
     }
 
     Ok(())
@@ -199,9 +291,16 @@ pub struct RenderDataContainer {
     /// for environment maps.
     pub need_recalculate_convolution: bool,
 
+    /// Environment map, that was used to calculate the current specular/irradiance convolution.
+    /// The convolution is recalculated when the environment map changes.
+    pub convolved_environment_map: Option<GpuTexture>,
+
     /// Screen space ambient occlusion renderer.
     pub ssao_renderer: ScreenSpaceAmbientOcclusionRenderer,
 
+    /// Screen space reflections renderer.
+    pub ssr_renderer: Ssr,
+
     /// G-Buffer of the container.
     pub gbuffer: GBuffer,
 
@@ -222,6 +321,76 @@ pub struct RenderDataContainer {
     pub statistics: SceneStatistics,
 }
 
+/// Creates the frame buffers of a [`RenderDataContainer`], that share the same depth-stencil buffer:
+/// the intermediate HDR frame buffer, the final frame buffer and two temporary frame buffers.
+fn create_scene_framebuffers(
+    server: &dyn GraphicsServer,
+    width: usize,
+    height: usize,
+    final_frame_texture: FrameTextureKind,
+    depth_stencil_pixel_kind: PixelKind,
+) -> Result<(GpuFrameBuffer, GpuFrameBuffer, [GpuFrameBuffer; 2]), FrameworkError> {
+    let depth_stencil = server.create_2d_render_target(
+        "ObserverDepthStencil",
+        depth_stencil_pixel_kind,
+        width,
+        height,
+    )?;
+    // Intermediate scene frame will be rendered in HDR render target.
+    let hdr_frame_texture =
+        server.create_2d_render_target("ObserverHdrFrame", PixelKind::RGB10A2, width, height)?;
+
+    let hdr_scene_framebuffer = server.create_frame_buffer(
+        Some(Attachment::depth_stencil(depth_stencil.clone())),
+        vec![Attachment::color(hdr_frame_texture)],
+    )?;
+
+    let ldr_frame_texture = server.create_texture(GpuTextureDescriptor {
+        name: "LdrFrameTexture",
+        kind: match final_frame_texture {
+            FrameTextureKind::Rectangle => GpuTextureKind::Rectangle { width, height },
+            FrameTextureKind::Cube => GpuTextureKind::Cube { size: width },
+        },
+        // Final scene frame is in standard sRGB space.
+        pixel_kind: PixelKind::RGBA8,
+        ..Default::default()
+    })?;
+
+    let ldr_scene_framebuffer = server.create_frame_buffer(
+        Some(Attachment::depth_stencil(depth_stencil.clone())),
+        vec![Attachment::color(ldr_frame_texture)],
+    )?;
+
+    fn make_ldr_temp_frame_buffer(
+        server: &dyn GraphicsServer,
+        width: usize,
+        height: usize,
+        depth_stencil: GpuTexture,
+    ) -> Result<GpuFrameBuffer, FrameworkError> {
+        let ldr_temp_texture = server.create_texture(GpuTextureDescriptor {
+            name: "LdrTempTexture",
+            kind: GpuTextureKind::Rectangle { width, height },
+            // Final scene frame is in standard sRGB space.
+            pixel_kind: PixelKind::RGBA8,
+            ..Default::default()
+        })?;
+
+        server.create_frame_buffer(
+            Some(Attachment::depth_stencil(depth_stencil)),
+            vec![Attachment::color(ldr_temp_texture)],
+        )
+    }
+
+    Ok((
+        hdr_scene_framebuffer,
+        ldr_scene_framebuffer,
+        [
+            make_ldr_temp_frame_buffer(server, width, height, depth_stencil.clone())?,
+            make_ldr_temp_frame_buffer(server, width, height, depth_stencil)?,
+        ],
+    ))
+}
+
 /// Texture kind that will be used to store final frame image.
 #[derive(Default)]
 pub enum FrameTextureKind {
@@ -233,11 +402,16 @@ pub enum FrameTextureKind {
 }
 
 impl RenderDataContainer {
-    /// Creates a new container.
+    /// Creates a new container. Custom attachments will be appended to the standard attachments
+    /// of the G-Buffer, see [`CustomAttachmentDesc`] docs for more info. `depth_stencil_pixel_kind`
+    /// defines the format of all depth-stencil buffers of the container.
     pub fn new(
         server: &dyn GraphicsServer,
         frame_size: Vector2<f32>,
         final_frame_texture: FrameTextureKind,
+        custom_attachments: &[CustomAttachmentDesc],
+        occlusion_grid_cell_size: f32,
+        depth_stencil_pixel_kind: PixelKind,
     ) -> Result<Self, FrameworkError> {
         let width = frame_size.x as usize;
         let height = frame_size.y as usize;
@@ -246,80 +420,82 @@ impl RenderDataContainer {
             assert_eq!(width, height);
         }
 
-        let depth_stencil = server.create_2d_render_target(
-            "ObserverDepthStencil",
-            PixelKind::D24S8,
-            width,
-            height,
-        )?;
-        // Intermediate scene frame will be rendered in HDR render target.
-        let hdr_frame_texture = server.create_2d_render_target(
-            "ObserverHdrFrame",
-            PixelKind::RGB10A2,
-            width,
-            height,
-        )?;
-
-        let hdr_scene_framebuffer = server.create_frame_buffer(
-            Some(Attachment::depth_stencil(depth_stencil.clone())),
-            vec![Attachment::color(hdr_frame_texture)],
-        )?;
-
-        let ldr_frame_texture = server.create_texture(GpuTextureDescriptor {
-            name: "LdrFrameTexture",
-            kind: match final_frame_texture {
-                FrameTextureKind::Rectangle => GpuTextureKind::Rectangle { width, height },
-                FrameTextureKind::Cube => GpuTextureKind::Cube { size: width },
-            },
-            // Final scene frame is in standard sRGB space.
-            pixel_kind: PixelKind::RGBA8,
-            ..Default::default()
-        })?;
-
-        let ldr_scene_framebuffer = server.create_frame_buffer(
-            Some(Attachment::depth_stencil(depth_stencil.clone())),
-            vec![Attachment::color(ldr_frame_texture)],
-        )?;
-
-        fn make_ldr_temp_frame_buffer(
-            server: &dyn GraphicsServer,
-            width: usize,
-            height: usize,
-            depth_stencil: GpuTexture,
-        ) -> Result<GpuFrameBuffer, FrameworkError> {
-            let ldr_temp_texture = server.create_texture(GpuTextureDescriptor {
-                name: "LdrTempTexture",
-                kind: GpuTextureKind::Rectangle { width, height },
-                // Final scene frame is in standard sRGB space.
-                pixel_kind: PixelKind::RGBA8,
-                ..Default::default()
-            })?;
-
-            server.create_frame_buffer(
-                Some(Attachment::depth_stencil(depth_stencil)),
-                vec![Attachment::color(ldr_temp_texture)],
-            )
-        }
+        let (hdr_scene_framebuffer, ldr_scene_framebuffer, ldr_temp_framebuffer) =
+            create_scene_framebuffers(
+                server,
+                width,
+                height,
+                final_frame_texture,
+                depth_stencil_pixel_kind,
+            )?;
 
         Ok(Self {
             need_recalculate_convolution: true,
+            convolved_environment_map: None,
             environment_map_specular_convolution: Default::default(),
             environment_map_irradiance_convolution: EnvironmentMapIrradianceConvolution::new(
-                server, 32,
+                server,
+                IRRADIANCE_MAP_SIZE,
             )?,
             ssao_renderer: ScreenSpaceAmbientOcclusionRenderer::new(server, width, height)?,
-            gbuffer: GBuffer::new(server, width, height)?,
+            ssr_renderer: Ssr::new(server, width, height)?,
+            gbuffer: GBuffer::new(
+                server,
+                width,
+                height,
+                custom_attachments,
+                occlusion_grid_cell_size,
+                depth_stencil_pixel_kind,
+            )?,
             hdr_renderer: HighDynamicRangeRenderer::new(width, height, server)?,
             hdr_scene_framebuffer,
             ldr_scene_framebuffer,
-            ldr_temp_framebuffer: [
-                make_ldr_temp_frame_buffer(server, width, height, depth_stencil.clone())?,
-                make_ldr_temp_frame_buffer(server, width, height, depth_stencil.clone())?,
-            ],
+            ldr_temp_framebuffer,
             statistics: Default::default(),
         })
     }
 
+    /// Changes the frame size of the container. Only the size-dependent render targets are
+    /// re-created (see [`GBuffer::set_size`]), the environment map convolutions, the luminance
+    /// adaptation state and the quality settings are preserved. Does nothing if the size is the same.
+    pub fn set_size(
+        &mut self,
+        server: &dyn GraphicsServer,
+        frame_size: Vector2<f32>,
+        final_frame_texture: FrameTextureKind,
+        depth_stencil_pixel_kind: PixelKind,
+    ) -> Result<(), FrameworkError> {
+        let width = frame_size.x as usize;
+        let height = frame_size.y as usize;
+
+        if self.gbuffer.width == width as i32 && self.gbuffer.height == height as i32 {
+            return Ok(());
+        }
+
+        if matches!(final_frame_texture, FrameTextureKind::Cube) {
+            assert_eq!(width, height);
+        }
+
+        let (hdr_scene_framebuffer, ldr_scene_framebuffer, ldr_temp_framebuffer) =
+            create_scene_framebuffers(
+                server,
+                width,
+                height,
+                final_frame_texture,
+                depth_stencil_pixel_kind,
+            )?;
+
+        self.gbuffer.set_size(server, width, height)?;
+        self.ssao_renderer.set_size(server, width, height)?;
+        self.ssr_renderer = Ssr::new(server, width, height)?;
+        self.hdr_renderer.set_size(server, width, height)?;
+        self.hdr_scene_framebuffer = hdr_scene_framebuffer;
+        self.ldr_scene_framebuffer = ldr_scene_framebuffer;
+        self.ldr_temp_framebuffer = ldr_temp_framebuffer;
+
+        Ok(())
+    }
+
     fn copy_depth_stencil_to_scene_framebuffer(&mut self) {
         self.gbuffer.framebuffer().blit_to(
             &self.hdr_scene_framebuffer,
@@ -337,6 +513,14 @@ impl RenderDataContainer {
         );
     }
 
+    fn update_ssr_history(&self) {
+        self.ssr_renderer.update_history(
+            &self.hdr_scene_framebuffer,
+            self.gbuffer.width,
+            self.gbuffer.height,
+        );
+    }
+
     /// Returns high-dynamic range frame buffer texture.
     pub fn hdr_scene_frame_texture(&self) -> &GpuTexture {
         &self.hdr_scene_framebuffer.color_attachments()[0].texture
@@ -355,9 +539,45 @@ impl RenderDataContainer {
     /// Sets the new quality settings.
     pub fn set_quality_settings(&mut self, settings: &QualitySettings) {
         self.ssao_renderer.set_radius(settings.ssao_radius);
+        self.ssao_renderer.set_intensity(settings.ssao_intensity);
+        self.gbuffer
+            .set_occlusion_grid_cell_size(settings.validated_occlusion_grid_cell_size());
     }
 }
 
+/// Returns the clip-space depth range and the pixel kind of depth-stencil buffers, that are used
+/// to render scenes. Reversed depth improves precision only with floating-point depth buffer and
+/// `[0; 1]` clip-space depth range, so they're used together when the device supports it.
+fn scene_depth_format(
+    settings: &QualitySettings,
+    clip_control: bool,
+) -> (ClipDepthRange, PixelKind) {
+    if settings.use_reversed_z && clip_control {
+        (ClipDepthRange::ZeroToOne, PixelKind::D32FS8)
+    } else {
+        (ClipDepthRange::NegativeOneToOne, PixelKind::D24S8)
+    }
+}
+
+/// Draws projection volumes of every decal in the graph as wireframe boxes. Each box is colored by
+/// decal's layer index, so decals on different layers could be told apart.
+fn draw_decal_volumes(graph: &Graph, drawing_context: &mut SceneDrawingContext) {
+    let unit_cube = AxisAlignedBoundingBox::unit();
+    for decal in graph.linear_iter().filter_map(|n| n.cast::<Decal>()) {
+        // Golden angle gives a good spread of hues for sequential indices.
+        let hue = (decal.layer() as f32 * 137.508) % 360.0;
+        drawing_context.draw_oob(
+            &unit_cube,
+            decal.global_transform(),
+            Color::from(Hsv::new(hue, 100.0, 100.0)),
+        );
+    }
+}
+
+fn effective_anisotropy(settings: &QualitySettings, max_anisotropy: f32) -> f32 {
+    (settings.anisotropy.max(1) as f32).min(max_anisotropy.max(1.0))
+}
+
 /// Creates a view-projection matrix that projects unit quad a screen with the specified viewport.
 pub fn make_viewport_matrix(viewport: Rect<i32>) -> Matrix4<f32> {
     Matrix4::new_orthographic(
@@ -387,6 +607,10 @@ pub struct Renderer {
     statistics: Statistics,
     frame_size: (u32, u32),
     quality_settings: QualitySettings,
+    /// Maximum level of anisotropic filtering supported by the device.
+    max_anisotropy: f32,
+    /// Whether the device supports changing the clip-space depth range.
+    clip_control: bool,
     /// Debug renderer instance can be used for debugging purposes
     pub debug_renderer: DebugRenderer,
     /// Screen space debug renderer instance can be used for debugging purposes to draw lines directly
@@ -402,6 +626,8 @@ pub struct Renderer {
     shader_cache: ShaderCache,
     geometry_cache: GeometryCache,
     fxaa_renderer: FxaaRenderer,
+    depth_of_field_renderer: DepthOfFieldRenderer,
+    post_effects_renderer: PostEffectsRenderer,
     texture_event_receiver: Receiver<ResourceEvent>,
     shader_event_receiver: Receiver<ResourceEvent>,
     /// TextureId -> FrameBuffer mapping. This mapping is used for temporal frame buffers
@@ -551,6 +777,16 @@ pub trait SceneRenderPass {
         Ok(RenderPassStatistics::default())
     }
 
+    /// Should return a list of custom attachments, that will be added to the G-Buffer. Shaders can
+    /// write to these attachments in the G-Buffer pass and read them in any later pass by declaring
+    /// a texture resource with the same name as the attachment. Attachments of every registered
+    /// render pass are appended in the registration order, see [`CustomAttachmentDesc`] docs for
+    /// the limits of the attachment count. G-Buffers are re-created when the set of attachments is
+    /// changed.
+    fn gbuffer_custom_attachments(&self) -> Vec<CustomAttachmentDesc> {
+        Vec::new()
+    }
+
     /// Should return type id of a plugin, that holds this render pass. **WARNING:** Setting incorrect
     /// (anything else, than a real plugin's type id) value here will result in hard crash with happy
     /// debugging times.
@@ -648,6 +884,10 @@ impl Renderer {
             caps.uniform_buffer_offset_alignment,
         );
 
+        let mut texture_cache = TextureCache::default();
+        texture_cache
+            .set_max_anisotropy(Some(effective_anisotropy(&settings, caps.max_anisotropy)));
+
         Ok(Self {
             backbuffer: server.back_buffer(),
             frame_size,
@@ -655,14 +895,18 @@ impl Renderer {
             renderer_resources: RendererResources::new(&*server)?,
             ui_renderer: UiRenderer::new(&*server)?,
             quality_settings: settings,
+            max_anisotropy: caps.max_anisotropy,
+            clip_control: caps.clip_control,
             debug_renderer: DebugRenderer::new(&*server)?,
             screen_space_debug_renderer: DebugRenderer::new(&*server)?,
             scene_data_map: Default::default(),
             backbuffer_clear_color: Color::BLACK,
-            texture_cache: Default::default(),
+            texture_cache,
             geometry_cache: Default::default(),
             ui_frame_buffers: Default::default(),
             fxaa_renderer: FxaaRenderer::default(),
+            depth_of_field_renderer: DepthOfFieldRenderer::default(),
+            post_effects_renderer: PostEffectsRenderer::default(),
             statistics: Statistics::default(),
             shader_event_receiver,
             texture_event_receiver,
@@ -702,11 +946,31 @@ impl Renderer {
         self.scene_render_passes.clear()
     }
 
+    /// Collects custom G-Buffer attachments of every registered render pass.
+    fn gbuffer_custom_attachments(&self) -> Vec<CustomAttachmentDesc> {
+        self.scene_render_passes
+            .iter()
+            .flat_map(|pass| pass.borrow().gbuffer_custom_attachments())
+            .collect()
+    }
+
     /// Returns statistics for last frame.
     pub fn get_statistics(&self) -> Statistics {
         self.statistics
     }
 
+    /// Returns `true` if the given node of the given scene was hidden behind other objects by
+    /// occlusion culling (see [`QualitySettings::use_occlusion_culling`]). Nodes that were never
+    /// tested are considered visible. Gameplay code could use this to skip updating of occluded
+    /// entities. The check is cheap, it only reads cached results of occlusion queries and never
+    /// waits for the GPU, which means that the result could be a few frames old. Use
+    /// [`SceneStatistics::occlusion`] to get the amount of drawn and culled objects per frame.
+    pub fn is_node_occluded(&self, scene: Handle<Scene>, node: Handle<Node>) -> bool {
+        self.scene_data_map
+            .get(&scene)
+            .is_some_and(|data| data.is_node_occluded(node))
+    }
+
     /// Unloads texture from GPU memory.
     pub fn unload_texture(&mut self, texture: &TextureResource) {
         self.texture_cache.unload(texture)
@@ -757,6 +1021,8 @@ impl Renderer {
         settings: &QualitySettings,
     ) -> Result<(), FrameworkError> {
         self.quality_settings = *settings;
+        self.texture_cache
+            .set_max_anisotropy(Some(self.effective_anisotropy()));
         for data in self.scene_data_map.values_mut() {
             data.set_quality_settings(settings);
         }
@@ -769,6 +1035,13 @@ impl Renderer {
         self.quality_settings
     }
 
+    /// Returns the maximum level of anisotropic filtering of the textures of materials, that is
+    /// actually used. It is [`QualitySettings::anisotropy`] clamped to the maximum supported by
+    /// the device.
+    pub fn effective_anisotropy(&self) -> f32 {
+        effective_anisotropy(&self.quality_settings, self.max_anisotropy)
+    }
+
     /// Removes all cached GPU data, forces renderer to re-upload data to GPU.
     /// Do not call this method until you absolutely need! It may cause **significant**
     /// performance lag!
@@ -937,12 +1210,26 @@ impl Renderer {
         need_recalculate_convolution: bool,
     ) -> Result<&mut RenderDataContainer, FrameworkError> {
         let server = &*self.server;
+        let custom_attachments = self.gbuffer_custom_attachments();
+        let occlusion_grid_cell_size = self.quality_settings.validated_occlusion_grid_cell_size();
+        let (clip_depth_range, depth_stencil_pixel_kind) =
+            scene_depth_format(&self.quality_settings, self.clip_control);
 
         let scene_render_data = self.scene_data_map.get_mut(&scene_handle).ok_or_else(|| {
             FrameworkError::Custom(format!(
                 "No associated render data for {scene_handle} scene!"
             ))
         })?;
+        let previous_view_projection_matrix = if observer.reflection_probe_data.is_some() {
+            // Reflection probes render multiple faces per frame using the same handle and they
+            // don't need motion vectors anyway.
+            observer.position.view_projection_matrix
+        } else {
+            scene_render_data
+                .previous_view_projection_matrices
+                .insert(observer.handle, observer.position.view_projection_matrix)
+                .unwrap_or(observer.position.view_projection_matrix)
+        };
         let render_data = if let Some(render_target) = observer.render_target.as_ref() {
             let (rt_size, final_frame_texture) = render_target_size(render_target)?;
             let observer_render_data = match scene_render_data.camera_data.entry(observer.handle) {
@@ -954,6 +1241,9 @@ impl Renderer {
                         observer_render_data,
                         rt_size,
                         final_frame_texture,
+                        &custom_attachments,
+                        occlusion_grid_cell_size,
+                        depth_stencil_pixel_kind,
                     )?;
                     observer_render_data
                 }
@@ -962,6 +1252,9 @@ impl Renderer {
                         server,
                         rt_size,
                         final_frame_texture,
+                        &custom_attachments,
+                        occlusion_grid_cell_size,
+                        depth_stencil_pixel_kind,
                     )?);
                     info!(
                         "A new associated scene rendering data was created for observer {}!",
@@ -1016,6 +1309,17 @@ impl Renderer {
             scene.rendering_options.polygon_rasterization_mode,
         );
 
+        // Ambient lighting color of the scene or of the reflection probe, it must be the same for
+        // every pass.
+        let ambient_color = match observer.reflection_probe_data.as_ref() {
+            None => scene.rendering_options.ambient_lighting_color,
+            Some(probe_data) => probe_data.ambient_lighting_color,
+        };
+
+        // Scene geometry is rasterized using the clip-space depth range that matches the format of
+        // the depth buffer. User render passes below get the default range.
+        server.set_clip_depth_range(clip_depth_range);
+
         render_data.statistics += render_data.gbuffer.fill(GBufferRenderContext {
             server,
             observer,
@@ -1028,22 +1332,30 @@ impl Renderer {
             graph: &scene.graph,
             uniform_buffer_cache: &mut self.uniform_buffer_cache,
             uniform_memory_allocator: &mut self.uniform_memory_allocator,
-            screen_space_debug_renderer: &mut self.screen_space_debug_renderer,
             resource_manager,
+            previous_view_projection_matrix,
+            ambient_light: ambient_color,
         })?;
+        render_data.statistics += render_data.gbuffer.occlusion_statistics();
 
         server.set_polygon_fill_mode(PolygonFace::FrontAndBack, PolygonFillMode::Fill);
 
         render_data.copy_depth_stencil_to_scene_framebuffer();
 
-        render_data.hdr_scene_framebuffer.clear(
-            observer.viewport,
-            Some(
+        let clear_color = match observer.clear_color {
+            CameraClearColor::Scene => Some(
                 scene
                     .rendering_options
                     .clear_color
                     .unwrap_or(self.backbuffer_clear_color),
             ),
+            CameraClearColor::Color(color) => Some(color),
+            // Keep the content rendered by previous observers.
+            CameraClearColor::Keep => None,
+        };
+        render_data.hdr_scene_framebuffer.clear(
+            observer.viewport,
+            clear_color,
             None, // Keep depth, we've just copied valid data in it.
             Some(0),
         );
@@ -1056,10 +1368,7 @@ impl Renderer {
                     scene,
                     observer,
                     gbuffer: &mut render_data.gbuffer,
-                    ambient_color: match observer.reflection_probe_data.as_ref() {
-                        None => scene.rendering_options.ambient_lighting_color,
-                        Some(probe_data) => probe_data.ambient_lighting_color,
-                    },
+                    ambient_color,
                     environment_lighting_source: match observer.reflection_probe_data.as_ref() {
                         None => scene.rendering_options.environment_lighting_source,
                         Some(probe_data) => probe_data.environment_lighting_source,
@@ -1076,14 +1385,28 @@ impl Renderer {
                     uniform_memory_allocator: &mut self.uniform_memory_allocator,
                     dynamic_surface_cache: &mut self.dynamic_surface_cache,
                     ssao_renderer: &render_data.ssao_renderer,
+                    ssr_renderer: &render_data.ssr_renderer,
+                    previous_view_projection_matrix,
                     resource_manager,
                     environment_map_specular_convolution: &mut render_data
                         .environment_map_specular_convolution,
                     environment_map_irradiance_convolution: &render_data
                         .environment_map_irradiance_convolution,
                     need_recalculate_convolution: &mut render_data.need_recalculate_convolution,
+                    convolved_environment_map: &mut render_data.convolved_environment_map,
                 })?;
 
+        if let Some(environment_irradiance) = observer.environment_irradiance.as_ref() {
+            self.texture_cache.try_register(
+                server,
+                environment_irradiance,
+                render_data
+                    .environment_map_irradiance_convolution
+                    .cube_map()
+                    .clone(),
+            )?;
+        }
+
         render_data.statistics += light_stats;
         render_data.statistics += pass_stats;
 
@@ -1108,12 +1431,22 @@ impl Renderer {
                     use_pom: self.quality_settings.use_parallax_mapping,
                     light_position: &Default::default(),
                     renderer_resources: &self.renderer_resources,
-                    ambient_light: scene.rendering_options.ambient_lighting_color,
+                    ambient_light: ambient_color,
                     scene_depth: Some(depth),
+                    gbuffer: Some(&render_data.gbuffer),
+                    reversed_z: self.quality_settings.use_reversed_z,
+                    previous_view_projection_matrix: None,
+                    depth_pre_pass: Default::default(),
                 },
             )?;
         }
 
+        server.set_clip_depth_range(ClipDepthRange::NegativeOneToOne);
+
+        if self.quality_settings.use_ssr && observer.reflection_probe_data.is_none() {
+            render_data.update_ssr_history();
+        }
+
         for render_pass in self.scene_render_passes.iter() {
             let _debug_scope = server.begin_scope(&format!(
                 "UserRenderPass::on_hdr_render {:p}",
@@ -1155,10 +1488,11 @@ impl Renderer {
             hdr_scene_frame: render_data.hdr_scene_frame_texture(),
             ldr_framebuffer: &render_data.ldr_temp_framebuffer[dest_buf],
             viewport: observer.viewport,
-            speed: observer.hdr_adaptation_speed * dt,
+            speed: 1.0 - (-dt * observer.hdr_adaptation_speed).exp(),
             exposure: observer.exposure,
             color_grading_lut: observer.color_grading_lut.as_ref(),
             use_color_grading: observer.color_grading_enabled,
+            white_balance: observer.white_balance,
             texture_cache: &mut self.texture_cache,
             uniform_buffer_cache: &mut self.uniform_buffer_cache,
             renderer_resources: &self.renderer_resources,
@@ -1167,6 +1501,36 @@ impl Renderer {
         })?;
         std::mem::swap(&mut dest_buf, &mut src_buf);
 
+        // Write the adapted luminance back to the camera, so it could be used to lock its exposure.
+        // Reading from GPU is slow and stalls the pipeline, so do this only on request.
+        if let Exposure::Auto { .. } = observer.exposure {
+            if let Ok(camera) = scene.graph.try_get_of_type::<Camera>(observer.handle) {
+                if camera.is_average_luminance_requested() {
+                    if let Some(average_luminance) = render_data.hdr_renderer.average_luminance() {
+                        camera.set_average_luminance(average_luminance);
+                    }
+                }
+            }
+        }
+
+        // Apply depth of field if needed.
+        if self.quality_settings.use_depth_of_field && observer.depth_of_field.enabled {
+            render_data.statistics += self.depth_of_field_renderer.render(DepthOfFieldArgs {
+                server,
+                viewport: observer.viewport,
+                frame_texture: render_data.ldr_temp_frame_texture(src_buf),
+                depth_texture: render_data.gbuffer.depth(),
+                frame_buffer: &render_data.ldr_temp_framebuffer[dest_buf],
+                depth_of_field: &observer.depth_of_field,
+                projection_matrix: observer.position.projection_matrix,
+                z_far: observer.projection.finite_z_far(),
+                sky_depth: if observer.reversed_z { 0.0 } else { 1.0 },
+                uniform_buffer_cache: &mut self.uniform_buffer_cache,
+                renderer_resources: &self.renderer_resources,
+            })?;
+            std::mem::swap(&mut dest_buf, &mut src_buf);
+        }
+
         // Apply FXAA if needed.
         if self.quality_settings.fxaa {
             render_data.statistics += self.fxaa_renderer.render(
@@ -1180,6 +1544,21 @@ impl Renderer {
             std::mem::swap(&mut dest_buf, &mut src_buf);
         }
 
+        // Apply post effects of the camera, disabled effects are skipped completely.
+        if PostEffectsRenderer::is_needed(&observer.vignette, observer.chromatic_aberration) {
+            render_data.statistics += self.post_effects_renderer.render(
+                server,
+                observer.viewport,
+                render_data.ldr_temp_frame_texture(src_buf),
+                &render_data.ldr_temp_framebuffer[dest_buf],
+                &observer.vignette,
+                observer.chromatic_aberration,
+                &mut self.uniform_buffer_cache,
+                &self.renderer_resources,
+            )?;
+            std::mem::swap(&mut dest_buf, &mut src_buf);
+        }
+
         render_data.statistics += blit_pixels(
             &mut self.uniform_buffer_cache,
             &render_data.ldr_scene_framebuffer,
@@ -1189,6 +1568,15 @@ impl Renderer {
             &self.renderer_resources,
         )?;
 
+        render_data.statistics += render_data.gbuffer.debug_draw_target(
+            scene.rendering_options.debug_gbuffer_target,
+            &render_data.ldr_scene_framebuffer,
+            observer.viewport,
+            &observer.position,
+            &mut self.uniform_buffer_cache,
+            &self.renderer_resources,
+        )?;
+
         // Render debug geometry in the LDR frame buffer.
         self.debug_renderer.set_lines(&scene.drawing_context.lines);
         render_data.statistics += self.debug_renderer.render(
@@ -1197,9 +1585,28 @@ impl Renderer {
             observer.viewport,
             &render_data.ldr_scene_framebuffer,
             observer.position.view_projection_matrix,
+            observer.reversed_z,
             &self.renderer_resources,
         )?;
 
+        if scene.rendering_options.debug_draw_decal_volumes {
+            let mut decal_volumes = SceneDrawingContext::default();
+            draw_decal_volumes(&scene.graph, &mut decal_volumes);
+            // The lines are drawn in world space, the lines of the screen space debug renderer set
+            // by users are kept intact.
+            let debug_statistics = self.screen_space_debug_renderer.render_lines(
+                &decal_volumes.lines,
+                server,
+                &mut self.uniform_buffer_cache,
+                observer.viewport,
+                &render_data.ldr_scene_framebuffer,
+                observer.position.view_projection_matrix,
+                observer.reversed_z,
+                &self.renderer_resources,
+            )?;
+            render_data.statistics.geometry.debug_draw_calls += debug_statistics.draw_calls;
+        }
+
         for render_pass in self.scene_render_passes.iter() {
             let _debug_scope = server.begin_scope(&format!(
                 "UserRenderPass::on_ldr_render {:p}",
@@ -1274,6 +1681,9 @@ impl Renderer {
             // Clamp to [1.0; infinity] range.
             .sup(&Vector2::new(1.0, 1.0));
 
+        let custom_attachments = self.gbuffer_custom_attachments();
+        let (_, depth_stencil_pixel_kind) =
+            scene_depth_format(&self.quality_settings, self.clip_control);
         let scene_render_data = match self.scene_data_map.entry(scene_handle) {
             Entry::Occupied(entry) => {
                 let render_data = entry.into_mut();
@@ -1283,6 +1693,9 @@ impl Renderer {
                     &mut render_data.scene_data,
                     frame_size,
                     FrameTextureKind::Rectangle,
+                    &custom_attachments,
+                    self.quality_settings.validated_occlusion_grid_cell_size(),
+                    depth_stencil_pixel_kind,
                 )?;
                 render_data
             }
@@ -1291,6 +1704,9 @@ impl Renderer {
                     &*self.server,
                     frame_size,
                     FrameTextureKind::Rectangle,
+                    &custom_attachments,
+                    self.quality_settings.validated_occlusion_grid_cell_size(),
+                    depth_stencil_pixel_kind,
                 )?);
                 info!(
                     "A new associated scene rendering data was created for scene {scene_handle}!"
@@ -1319,8 +1735,14 @@ impl Renderer {
         scene_render_data
             .camera_data
             .retain(|h, _| graph.is_valid_handle(*h));
+        scene_render_data
+            .previous_view_projection_matrices
+            .retain(|h, _| graph.is_valid_handle(*h));
 
-        let observers = ObserversCollection::from_scene(scene, frame_size);
+        let mut observers = ObserversCollection::from_scene(scene, frame_size);
+        if self.quality_settings.use_reversed_z {
+            observers.reverse_depth();
+        }
 
         // At first, render the reflection probes to off-screen render target.
         let mut need_recalculate_convolution = false;
@@ -1439,6 +1861,7 @@ impl Renderer {
             window_viewport,
             &self.backbuffer,
             screen_matrix,
+            false,
             &self.renderer_resources,
         )?;
 
@@ -31,7 +31,10 @@ use crate::{
     graphics::gpu_texture::CubeMapFace,
     renderer::utils::CubeMapFaceDescriptor,
     scene::{
-        camera::{Camera, ColorGradingLut, Exposure, PerspectiveProjection, Projection},
+        camera::{
+            reverse_depth, Camera, CameraClearColor, ColorGradingLut, DepthOfField, Exposure,
+            PerspectiveProjection, Projection, Vignette, WhiteBalance,
+        },
         collider::BitMask,
         node::Node,
         probe::ReflectionProbe,
@@ -65,7 +68,7 @@ impl ObserverPosition {
         Self {
             translation: camera.global_position(),
             z_near: camera.projection().z_near(),
-            z_far: camera.projection().z_far(),
+            z_far: camera.projection().finite_z_far(),
             view_matrix: camera.view_matrix(),
             projection_matrix: camera.projection_matrix(),
             view_projection_matrix: camera.view_projection_matrix(),
@@ -105,6 +108,8 @@ impl ObserversCollection {
                         fov: 90.0f32.to_radians(),
                         z_near: *probe.z_near,
                         z_far: *probe.z_far,
+                        infinite_far: false,
+                        oblique_clip_plane: None,
                     });
                     let resolution = probe.resolution() as f32;
                     let cube_size = Vector2::repeat(probe.resolution() as f32);
@@ -135,15 +140,23 @@ impl ObserversCollection {
                                 view_projection_matrix,
                             },
                             environment_map: None,
+                            environment_irradiance: None,
                             render_mask: *probe.render_mask,
                             projection: projection.clone(),
                             color_grading_lut: None,
                             color_grading_enabled: false,
+                            white_balance: Default::default(),
+                            depth_of_field: Default::default(),
+                            vignette: Default::default(),
+                            chromatic_aberration: 0.0,
                             exposure: Default::default(),
                             viewport: Rect::new(0, 0, resolution as i32, resolution as i32),
                             frustum: Frustum::from_view_projection_matrix(view_projection_matrix)
                                 .unwrap_or_default(),
                             hdr_adaptation_speed: 1.0,
+                            clear_color: CameraClearColor::Scene,
+                            clear_depth: true,
+                            reversed_z: false,
                         })
                     }
                 }
@@ -151,6 +164,18 @@ impl ObserversCollection {
         }
         observers
     }
+
+    /// Reverses depth of every observer in the collection. See [`Observer::reverse_depth`] for
+    /// more info.
+    pub fn reverse_depth(&mut self) {
+        for observer in self
+            .cameras
+            .iter_mut()
+            .chain(self.reflection_probes.iter_mut())
+        {
+            observer.reverse_depth();
+        }
+    }
 }
 
 /// The data used by the renderer when it's rendering a reflection probe.
@@ -181,6 +206,9 @@ pub struct Observer {
     /// Environment map which will be used for IBL and reflections. If not set, then scene's skybox
     /// will be used as an environment map.
     pub environment_map: Option<TextureResource>,
+    /// Render target, that receives the diffuse irradiance cube map of the environment map. See
+    /// [`Camera::environment_irradiance`] for more info.
+    pub environment_irradiance: Option<TextureResource>,
     /// A set of switches that defines which "layers" of the scene will be rendered.
     pub render_mask: BitMask,
     /// Projection mode that will be used to project the scene on screen's 2D plane.
@@ -189,6 +217,17 @@ pub struct Observer {
     pub color_grading_lut: Option<ColorGradingLut>,
     /// A flag, that defines whether the color grading enabled or not.
     pub color_grading_enabled: bool,
+    /// White balance that will be applied to scene's HDR image. See [`WhiteBalance`] docs for
+    /// more info.
+    pub white_balance: WhiteBalance,
+    /// Depth of field that will be applied to the final image. Its focus distance already accounts
+    /// for the auto-focus target of the camera. See [`DepthOfField`] docs for more info.
+    pub depth_of_field: DepthOfField,
+    /// Vignette that will be applied to the final image. See [`Vignette`] docs for more info.
+    pub vignette: Vignette,
+    /// Strength of chromatic aberration that will be applied to the final image. See
+    /// [`Camera::set_chromatic_aberration`] for more info.
+    pub chromatic_aberration: f32,
     /// Exposure settings that will be applied to scene's HDR image to convert it to the final
     /// low dynamic range image that will be shown on a display.
     pub exposure: Exposure,
@@ -198,36 +237,67 @@ pub struct Observer {
     pub frustum: Frustum,
     /// Defines the speed of automatic adaptation for the current frame luminance. In other words,
     /// it defines how fast the reaction to the new frame brightness will be. The lower the value,
-    /// the longer it will take to adjust the exposure for the new brightness level.
+    /// the longer it will take to adjust the exposure for the new brightness level. The exposure
+    /// moves by `1 - exp(-dt * hdr_adaptation_speed)` of the remaining distance each frame.
     pub hdr_adaptation_speed: f32,
+    /// Defines how the color buffer should be cleared before rendering. See [`CameraClearColor`]
+    /// docs for more info.
+    pub clear_color: CameraClearColor,
+    /// A flag, that defines whether the depth buffer should be cleared before rendering or not.
+    pub clear_depth: bool,
+    /// A flag, that defines whether the projection matrix of the observer maps the near clipping
+    /// plane to the depth of 1.0 and the far clipping plane to 0.0. See [`Self::reverse_depth`].
+    pub reversed_z: bool,
 }
 
 impl Observer {
     /// Creates a new observer from a scene camera.
-    pub fn from_camera(camera: &Camera, mut frame_size: Vector2<f32>) -> Self {
-        if let Some(render_target) = camera.render_target() {
-            if let Some(size) = render_target
-                .data_ref()
-                .as_loaded_ref()
-                .and_then(|rt| rt.kind().rectangle_size().map(|size| size.cast::<f32>()))
-            {
-                frame_size = size;
-            }
-        }
+    pub fn from_camera(camera: &Camera, frame_size: Vector2<f32>) -> Self {
+        let frame_size = camera.render_target_size().unwrap_or(frame_size);
         Observer {
             handle: camera.handle(),
             environment_map: camera.environment_map(),
-            render_mask: *camera.render_mask,
+            environment_irradiance: camera.environment_irradiance(),
+            render_mask: camera.effective_render_mask(),
             projection: camera.projection().clone(),
             position: ObserverPosition::from_camera(camera),
             render_target: camera.render_target().cloned(),
             color_grading_lut: camera.color_grading_lut(),
             color_grading_enabled: camera.color_grading_enabled(),
+            white_balance: camera.white_balance(),
+            depth_of_field: DepthOfField {
+                focus_distance: camera.focus_distance(),
+                ..camera.depth_of_field()
+            },
+            vignette: camera.vignette(),
+            chromatic_aberration: camera.chromatic_aberration(),
             exposure: camera.exposure(),
             viewport: camera.viewport_pixels(frame_size),
             frustum: camera.frustum(),
             reflection_probe_data: None,
-            hdr_adaptation_speed: camera.hdr_adaptation_speed(),
+            hdr_adaptation_speed: match camera.exposure() {
+                Exposure::Auto {
+                    adaptation_speed, ..
+                } if adaptation_speed > 0.0 => adaptation_speed,
+                _ => camera.hdr_adaptation_speed(),
+            },
+            clear_color: camera.clear_color(),
+            clear_depth: camera.clear_depth(),
+            reversed_z: false,
         }
     }
+
+    /// Modifies projection matrix of the observer, so it maps the near clipping plane to the depth
+    /// of 1.0 and the far clipping plane to 0.0. Does nothing if the depth is already
+    /// reversed.
+    pub fn reverse_depth(&mut self) {
+        if self.reversed_z {
+            return;
+        }
+
+        reverse_depth(&mut self.position.projection_matrix);
+        self.position.view_projection_matrix =
+            self.position.projection_matrix * self.position.view_matrix;
+        self.reversed_z = true;
+    }
 }
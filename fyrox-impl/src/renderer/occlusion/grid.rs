@@ -23,7 +23,7 @@ use crate::{
     scene::node::Node,
 };
 use fxhash::FxHashMap;
-use std::ops::{Deref, DerefMut};
+use std::ops::Deref;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Visibility {
@@ -52,17 +52,33 @@ impl Visibility {
 #[derive(Debug, Default)]
 pub struct NodeVisibilityMap {
     map: FxHashMap<Handle<Node>, Visibility>,
+    /// Amount of consecutive occlusion tests that reported a node as invisible.
+    invisible_streaks: FxHashMap<Handle<Node>, u32>,
 }
 
 impl NodeVisibilityMap {
     pub fn mark(&mut self, node: Handle<Node>, visibility: Visibility) {
         *self.map.entry(node).or_insert(visibility) = visibility;
+        match visibility {
+            Visibility::Visible => {
+                self.invisible_streaks.remove(&node);
+            }
+            Visibility::Invisible => *self.invisible_streaks.entry(node).or_default() += 1,
+        }
     }
 
-    pub fn is_visible(&self, node: Handle<Node>) -> bool {
+    /// Checks whether the node should be rendered. Visible nodes are shown immediately, but a node
+    /// is culled only after at least `culling_delay` consecutive occlusion tests reported it as
+    /// invisible. This hides flickering of the objects near the visibility boundary.
+    pub fn is_visible(&self, node: Handle<Node>, culling_delay: u32) -> bool {
+        let invisible_streak = self
+            .invisible_streaks
+            .get(&node)
+            .copied()
+            .unwrap_or_default();
         self.map
             .get(&node)
-            .is_none_or(|vis| vis.should_be_rendered())
+            .is_none_or(|vis| vis.should_be_rendered() || invisible_streak < culling_delay)
     }
 
     pub fn needs_occlusion_query(&self, node: Handle<Node>) -> bool {
@@ -80,42 +96,40 @@ impl Deref for NodeVisibilityMap {
     }
 }
 
-impl DerefMut for NodeVisibilityMap {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.map
-    }
-}
-
 /// Volumetric visibility cache based on occlusion query.
 #[derive(Debug)]
 pub struct GridCache {
     cells: FxHashMap<Vector3<i32>, NodeVisibilityMap>,
-    granularity: Vector3<u32>,
+    cell_size: Vector3<f32>,
 }
 
-fn world_to_grid(world_position: Vector3<f32>, granularity: Vector3<u32>) -> Vector3<i32> {
+fn world_to_grid(world_position: Vector3<f32>, cell_size: Vector3<f32>) -> Vector3<i32> {
     Vector3::new(
-        (world_position.x * (granularity.x as f32)).round() as i32,
-        (world_position.y * (granularity.y as f32)).round() as i32,
-        (world_position.z * (granularity.z as f32)).round() as i32,
+        (world_position.x / cell_size.x).round() as i32,
+        (world_position.y / cell_size.y).round() as i32,
+        (world_position.z / cell_size.z).round() as i32,
     )
 }
 
 impl GridCache {
-    /// Creates new visibility cache with the given granularity and distance discard threshold.
-    /// Granularity in means how much the cache should subdivide the world. For example 2 means that
-    /// 1 meter cell will be split into 8 blocks by 0.5 meters. Distance discard threshold means how
-    /// far an observer can without discarding visibility info about distant objects.
-    pub fn new(granularity: Vector3<u32>) -> Self {
+    /// Creates new visibility cache with the given size of its cells (in meters). The size defines
+    /// how much the cache should subdivide the world. For example 0.5 means that 1 meter cube will
+    /// be split into 8 cells by 0.5 meters.
+    pub fn new(cell_size: Vector3<f32>) -> Self {
         Self {
             cells: Default::default(),
-            granularity,
+            cell_size,
         }
     }
 
+    /// Returns the size of the cells of the cache.
+    pub fn cell_size(&self) -> Vector3<f32> {
+        self.cell_size
+    }
+
     /// Transforms the given world-space position into internal grid-space position.
     pub fn world_to_grid(&self, world_position: Vector3<f32>) -> Vector3<i32> {
-        world_to_grid(world_position, self.granularity)
+        world_to_grid(world_position, self.cell_size)
     }
 
     pub fn cell(&self, observer_position: Vector3<f32>) -> Option<&NodeVisibilityMap> {
@@ -131,3 +145,31 @@ impl GridCache {
             .or_default()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::pool::Handle,
+        renderer::occlusion::grid::{NodeVisibilityMap, Visibility},
+    };
+
+    #[test]
+    fn test_culling_delay() {
+        let node = Handle::new(1, 1);
+        let mut map = NodeVisibilityMap::default();
+        assert!(map.is_visible(node, 2));
+
+        map.mark(node, Visibility::Invisible);
+        assert!(map.is_visible(node, 2));
+        assert!(!map.is_visible(node, 1));
+
+        map.mark(node, Visibility::Invisible);
+        assert!(!map.is_visible(node, 2));
+
+        // Becomes visible immediately and the delay starts over.
+        map.mark(node, Visibility::Visible);
+        assert!(map.is_visible(node, 2));
+        map.mark(node, Visibility::Invisible);
+        assert!(map.is_visible(node, 2));
+    }
+}
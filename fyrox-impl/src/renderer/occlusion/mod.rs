@@ -121,16 +121,27 @@ fn inflated_world_aabb(graph: &Graph, object: Handle<Node>) -> Option<AxisAligne
     Some(aabb)
 }
 
-impl OcclusionTester {
-    pub fn new(
+/// A set of resources of the occlusion tester that depend on the frame size.
+struct SizeDependentResources {
+    framebuffer: GpuFrameBuffer,
+    visibility_mask: GpuTexture,
+    tile_buffer: GpuTexture,
+    w_tiles: usize,
+    h_tiles: usize,
+    visibility_buffer_optimizer: VisibilityBufferOptimizer,
+}
+
+impl SizeDependentResources {
+    fn new(
         server: &dyn GraphicsServer,
         width: usize,
         height: usize,
         tile_size: usize,
+        depth_stencil_pixel_kind: PixelKind,
     ) -> Result<Self, FrameworkError> {
         let depth_stencil = server.create_2d_render_target(
             "OcclusionTesterDepthStencilTexture",
-            PixelKind::D24S8,
+            depth_stencil_pixel_kind,
             width,
             height,
         )?;
@@ -155,21 +166,113 @@ impl OcclusionTester {
                 vec![Attachment::color(visibility_mask.clone())],
             )?,
             visibility_mask,
+            tile_buffer,
+            w_tiles,
+            h_tiles,
+            visibility_buffer_optimizer: VisibilityBufferOptimizer::new(server, w_tiles, h_tiles)?,
+        })
+    }
+}
+
+impl OcclusionTester {
+    /// Creates a new occlusion tester. `grid_cell_size` defines the size (in meters) of the cells of
+    /// the visibility cache, it must be positive. `depth_stencil_pixel_kind` must match the
+    /// pixel kind of the depth buffer, that is used for visibility tests.
+    pub fn new(
+        server: &dyn GraphicsServer,
+        width: usize,
+        height: usize,
+        tile_size: usize,
+        grid_cell_size: f32,
+        depth_stencil_pixel_kind: PixelKind,
+    ) -> Result<Self, FrameworkError> {
+        let SizeDependentResources {
+            framebuffer,
+            visibility_mask,
+            tile_buffer,
+            w_tiles,
+            h_tiles,
+            visibility_buffer_optimizer,
+        } = SizeDependentResources::new(
+            server,
+            width,
+            height,
+            tile_size,
+            depth_stencil_pixel_kind,
+        )?;
+
+        Ok(Self {
+            framebuffer,
+            visibility_mask,
             frame_size: Vector2::new(width, height),
             tile_size,
             w_tiles,
             tile_buffer,
             h_tiles,
-            visibility_buffer_optimizer: VisibilityBufferOptimizer::new(server, w_tiles, h_tiles)?,
+            visibility_buffer_optimizer,
             matrix_storage: MatrixStorage::new(server)?,
             objects_to_test: Default::default(),
             view_projection: Default::default(),
             observer_position: Default::default(),
-            grid_cache: GridCache::new(Vector3::repeat(1)),
+            grid_cache: GridCache::new(Vector3::repeat(grid_cell_size)),
             tiles: TileBuffer::new(w_tiles, h_tiles),
         })
     }
 
+    /// Changes the frame size of the occlusion tester. Re-creates all size-dependent resources,
+    /// but keeps the cached visibility grid. Any pending visibility query is discarded.
+    pub fn set_size(
+        &mut self,
+        server: &dyn GraphicsServer,
+        width: usize,
+        height: usize,
+    ) -> Result<(), FrameworkError> {
+        let SizeDependentResources {
+            framebuffer,
+            visibility_mask,
+            tile_buffer,
+            w_tiles,
+            h_tiles,
+            visibility_buffer_optimizer,
+        } = SizeDependentResources::new(
+            server,
+            width,
+            height,
+            self.tile_size,
+            self.depth_stencil_pixel_kind(),
+        )?;
+
+        self.framebuffer = framebuffer;
+        self.visibility_mask = visibility_mask;
+        self.tile_buffer = tile_buffer;
+        self.w_tiles = w_tiles;
+        self.h_tiles = h_tiles;
+        self.visibility_buffer_optimizer = visibility_buffer_optimizer;
+        self.frame_size = Vector2::new(width, height);
+        self.tiles = TileBuffer::new(w_tiles, h_tiles);
+        self.objects_to_test.clear();
+
+        Ok(())
+    }
+
+    fn depth_stencil_pixel_kind(&self) -> PixelKind {
+        self.framebuffer
+            .depth_attachment()
+            .map_or(PixelKind::D24S8, |a| a.texture.pixel_kind())
+    }
+
+    /// Changes the size (in meters) of the cells of the visibility cache. The cache is re-created
+    /// if the value differs from the current one, which discards all cached visibility info and
+    /// any pending visibility query. The value must be positive.
+    pub fn set_grid_cell_size(&mut self, grid_cell_size: f32) {
+        let cell_size = Vector3::repeat(grid_cell_size);
+        if self.grid_cache.cell_size() != cell_size {
+            self.grid_cache = GridCache::new(cell_size);
+            self.tiles.clear();
+            self.objects_to_test.clear();
+        }
+    }
+
     pub fn try_query_visibility_results(&mut self, graph: &Graph) {
         let Some(visibility_buffer) = self.visibility_buffer_optimizer.read_visibility_mask()
         else {
@@ -202,13 +305,16 @@ impl OcclusionTester {
             cell.mark(*obj, (*vis).into());
         }
 
-        for (object, visibility) in cell.iter_mut() {
-            let Some(aabb) = inflated_world_aabb(graph, *object) else {
-                continue;
-            };
-            if aabb.is_contains_point(self.observer_position) {
-                *visibility = Visibility::Visible;
-            }
+        let objects_around_observer = cell
+            .keys()
+            .filter(|object| {
+                inflated_world_aabb(graph, **object)
+                    .is_some_and(|aabb| aabb.is_contains_point(self.observer_position))
+            })
+            .copied()
+            .collect::<Vec<_>>();
+        for object in objects_around_observer {
+            cell.mark(object, Visibility::Visible);
         }
     }
 
@@ -334,6 +440,7 @@ impl OcclusionTester {
         prev_framebuffer: &GpuFrameBuffer,
         observer_position: Vector3<f32>,
         view_projection: Matrix4<f32>,
+        reversed_z: bool,
         uniform_buffer_cache: &mut UniformBufferCache,
         renderer_resources: &RendererResources,
     ) -> Result<RenderPassStatistics, FrameworkError> {
@@ -370,9 +477,12 @@ impl OcclusionTester {
             }))?;
 
         let tile_size = self.tile_size as i32;
+        let view_projection = server
+            .clip_depth_range()
+            .adjust_projection_matrix(&self.view_projection);
         let frame_buffer_height = self.frame_size.y as f32;
         let properties = PropertyGroup::from([
-            property("viewProjection", &self.view_projection),
+            property("viewProjection", &view_projection),
             property("tileSize", &tile_size),
             property("frameBufferHeight", &frame_buffer_height),
         ]);
@@ -391,16 +501,23 @@ impl OcclusionTester {
             binding("properties", &properties),
         ]);
 
+        // The depth buffer is copied from the G-Buffer, so the depth test must match its convention.
+        let pass_name = ImmutableString::new("Primary");
+        let reversed_draw_params = renderer_resources
+            .shaders
+            .visibility
+            .depth_draw_params(&pass_name, reversed_z)?;
+
         stats += renderer_resources.shaders.visibility.run_pass(
             self.objects_to_test.len(),
-            &ImmutableString::new("Primary"),
+            &pass_name,
             &self.framebuffer,
             &renderer_resources.cube,
             viewport,
             &material,
             uniform_buffer_cache,
             Default::default(),
-            None,
+            reversed_draw_params.as_ref(),
         )?;
 
         self.visibility_buffer_optimizer.optimize(
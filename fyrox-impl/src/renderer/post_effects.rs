@@ -0,0 +1,103 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Cheap screen-space post effects of a camera (vignette and chromatic aberration), that are
+//! applied to the final LDR image after tone mapping and color grading.
+
+use crate::{
+    core::{algebra::Vector4, math::Rect, sstorage::ImmutableString},
+    graphics::{error::FrameworkError, framebuffer::GpuFrameBuffer, gpu_texture::GpuTexture},
+    renderer::{
+        cache::{
+            shader::{binding, property, PropertyGroup, RenderMaterial},
+            uniform::UniformBufferCache,
+        },
+        make_viewport_matrix,
+        resources::RendererResources,
+        RenderPassStatistics,
+    },
+    scene::camera::Vignette,
+};
+use fyrox_graphics::server::GraphicsServer;
+
+#[derive(Default)]
+pub struct PostEffectsRenderer {}
+
+impl PostEffectsRenderer {
+    /// Returns `true` if at least one of the effects changes the image. Disabled effects must not
+    /// be rendered at all, so the pass is skipped if this method returns `false`.
+    pub(crate) fn is_needed(vignette: &Vignette, chromatic_aberration: f32) -> bool {
+        vignette.is_enabled() || chromatic_aberration > 0.0
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn render(
+        &self,
+        server: &dyn GraphicsServer,
+        viewport: Rect<i32>,
+        frame_texture: &GpuTexture,
+        frame_buffer: &GpuFrameBuffer,
+        vignette: &Vignette,
+        chromatic_aberration: f32,
+        uniform_buffer_cache: &mut UniformBufferCache,
+        renderer_resources: &RendererResources,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let _debug_scope = server.begin_scope("PostEffects");
+
+        let mut statistics = RenderPassStatistics::default();
+
+        let frame_matrix = make_viewport_matrix(viewport);
+
+        // The image is already in sRGB space, so the color of the vignette is used as is.
+        let vignette_color: Vector4<f32> = vignette.color.as_frgba();
+        let use_vignette = vignette.is_enabled();
+        let use_chromatic_aberration = chromatic_aberration > 0.0;
+        let properties = PropertyGroup::from([
+            property("worldViewProjection", &frame_matrix),
+            property("vignetteColor", &vignette_color),
+            property("vignetteIntensity", &vignette.intensity),
+            property("vignetteSmoothness", &vignette.smoothness),
+            property("chromaticAberration", &chromatic_aberration),
+            property("useVignette", &use_vignette),
+            property("useChromaticAberration", &use_chromatic_aberration),
+        ]);
+        let material = RenderMaterial::from([
+            binding(
+                "screenTexture",
+                (frame_texture, &renderer_resources.linear_clamp_sampler),
+            ),
+            binding("properties", &properties),
+        ]);
+
+        statistics += renderer_resources.shaders.post_effects.run_pass(
+            1,
+            &ImmutableString::new("Primary"),
+            frame_buffer,
+            &renderer_resources.quad,
+            viewport,
+            &material,
+            uniform_buffer_cache,
+            Default::default(),
+            None,
+        )?;
+
+        Ok(statistics)
+    }
+}
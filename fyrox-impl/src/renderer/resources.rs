@@ -59,6 +59,10 @@ pub struct ShadersContainer {
     pub debug: RenderPassContainer,
     /// Fast approximate antialiasing shader.
     pub fxaa: RenderPassContainer,
+    /// A shader for depth of field effect.
+    pub depth_of_field: RenderPassContainer,
+    /// A shader for cheap screen-space post effects of a camera (vignette, chromatic aberration).
+    pub post_effects: RenderPassContainer,
     /// A shader for volumetric spotlight.
     pub spot_light_volume: RenderPassContainer,
     /// A shader for volumetric point light.
@@ -70,10 +74,14 @@ pub struct ShadersContainer {
     pub visibility_optimizer: RenderPassContainer,
     /// Screen-space ambient occlusion shader.
     pub ssao: RenderPassContainer,
+    /// Screen-space reflections shader.
+    pub ssr: RenderPassContainer,
     /// A shader that is used in visibility test for occlusion culling.
     pub visibility: RenderPassContainer,
     /// A shader for simple image blitting.
     pub blit: RenderPassContainer,
+    /// A shader that draws G-Buffer targets for debugging.
+    pub gbuffer_debug: RenderPassContainer,
     /// A shader for eye adaptation for high dynamic range rendering.
     pub hdr_adaptation: RenderPassContainer,
     /// A shader for frame luminance calculations for high dynamic range rendering.
@@ -129,6 +137,14 @@ impl ShadersContainer {
             )?,
             debug: RenderPassContainer::from_str(server, include_str!("shaders/debug.shader"))?,
             fxaa: RenderPassContainer::from_str(server, include_str!("shaders/fxaa.shader"))?,
+            depth_of_field: RenderPassContainer::from_str(
+                server,
+                include_str!("shaders/depth_of_field.shader"),
+            )?,
+            post_effects: RenderPassContainer::from_str(
+                server,
+                include_str!("shaders/post_effects.shader"),
+            )?,
             spot_light_volume: RenderPassContainer::from_str(
                 server,
                 include_str!("shaders/spot_volumetric.shader"),
@@ -146,11 +162,16 @@ impl ShadersContainer {
                 include_str!("shaders/visibility_optimizer.shader"),
             )?,
             ssao: RenderPassContainer::from_str(server, include_str!("shaders/ssao.shader"))?,
+            ssr: RenderPassContainer::from_str(server, include_str!("shaders/ssr.shader"))?,
             visibility: RenderPassContainer::from_str(
                 server,
                 include_str!("shaders/visibility.shader"),
             )?,
             blit: RenderPassContainer::from_str(server, include_str!("shaders/blit.shader"))?,
+            gbuffer_debug: RenderPassContainer::from_str(
+                server,
+                include_str!("shaders/gbuffer_debug.shader"),
+            )?,
             hdr_adaptation: RenderPassContainer::from_str(
                 server,
                 include_str!("shaders/hdr_adaptation.shader"),
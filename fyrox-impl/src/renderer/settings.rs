@@ -138,6 +138,29 @@ pub struct QualitySettings {
     /// Radius of sampling hemisphere used in SSAO, it defines much ambient
     /// occlusion will be in your scene.
     pub ssao_radius: f32,
+    /// Strength of screen space ambient occlusion. `1.0` means the computed occlusion is used as
+    /// is, lower values make occlusion lighter and higher values make it darker.
+    #[serde(default = "default_ssao_intensity")]
+    pub ssao_intensity: f32,
+
+    /// Whether to use screen space reflections or not. Screen space reflections replace the
+    /// environment reflections of smooth surfaces with the content of the screen, so they can't
+    /// reflect anything that is off-screen or hidden behind other objects. Disabled by default.
+    #[serde(default)]
+    pub use_ssr: bool,
+    /// Maximum amount of steps that a reflected ray makes when marching through the depth buffer.
+    /// Higher values make reflections more precise, but increase the cost of the effect. Values
+    /// below 1 are treated as 1. Default is 32.
+    #[serde(default = "default_ssr_max_steps")]
+    #[reflect(min_value = 1.0)]
+    pub ssr_max_steps: u32,
+    /// Assumed thickness (in meters) of the objects in the depth buffer. A reflected ray that goes
+    /// behind a surface further than this distance is considered passing behind the object, rather
+    /// than hitting it. Low values produce gaps in reflections, high values produce false hits.
+    /// Default is 0.5.
+    #[serde(default = "default_ssr_thickness")]
+    #[reflect(min_value = 0.0)]
+    pub ssr_thickness: f32,
 
     /// Global switch to enable or disable light scattering. Each light can have
     /// its own scatter switch, but this one is able to globally disable scatter.
@@ -146,6 +169,12 @@ pub struct QualitySettings {
     /// Whether to use Fast Approximate AntiAliasing or not.
     pub fxaa: bool,
 
+    /// Whether to render depth of field of the cameras or not. The effect must also be enabled in
+    /// the camera itself, see [`crate::scene::camera::Camera::set_depth_of_field`]. Enabled only
+    /// for high and ultra presets.
+    #[serde(default)]
+    pub use_depth_of_field: bool,
+
     /// Whether to use Parallax Mapping or not.
     pub use_parallax_mapping: bool,
 
@@ -159,9 +188,81 @@ pub struct QualitySettings {
     #[serde(default)]
     pub use_light_occlusion_culling: bool,
 
+    /// Size (in meters, along each axis) of the cells of the grid that is used to cache results of
+    /// occlusion culling. The results are stored per grid cell in which the observer is, so finer
+    /// grids give more accurate results when the observer moves, but they need more occlusion
+    /// queries to fill the cache and more memory to store it (the cache keeps a visibility map for
+    /// every visited cell). Coarser grids work better for large open worlds, while finer ones
+    /// suit tight interiors. Values less than 0.1 are clamped to 0.1. Default is 1 meter.
+    #[serde(default = "default_occlusion_grid_cell_size")]
+    #[reflect(min_value = 0.1)]
+    pub occlusion_grid_cell_size: f32,
+
+    /// Amount of consecutive occlusion tests that must report an object as invisible before it is
+    /// actually culled. Objects become visible immediately. Occlusion queries have latency, so
+    /// objects near the visibility boundary may flicker when the camera moves past occluders,
+    /// higher values reduce such popping at the cost of drawing hidden objects a bit longer.
+    /// Values below 1 are treated as 1. Default is 1.
+    #[serde(default = "default_occlusion_culling_delay")]
+    #[reflect(min_value = 1.0)]
+    pub occlusion_culling_delay: u32,
+
     /// HDR pipeline settings.
     #[serde(default)]
     pub hdr_settings: HdrSettings,
+
+    /// Whether to use reversed depth buffer or not. Reversed depth maps the near clipping plane to
+    /// 1.0 and the far clipping plane to 0.0. The renderer reverses projection matrices of the
+    /// cameras, clears the depth buffer with 0.0 and flips depth tests of scene geometry. Custom
+    /// shaders that linearize depth manually must account for this. Disabled by default.
+    ///
+    /// When the graphics server supports clip control (see `ServerCapabilities::clip_control`), the
+    /// renderer also switches to floating-point depth buffer and `[0; 1]` clip-space depth range,
+    /// which gives almost uniform depth precision across the whole view range. Otherwise the depth
+    /// buffer stays fixed-point (24 bits) and the option does not improve precision.
+    #[serde(default)]
+    pub use_reversed_z: bool,
+
+    /// Whether to render depth of the scene geometry before filling the G-Buffer (z-pre-pass) or
+    /// not. When enabled, the G-Buffer is filled with depth test set to `Equal` and depth writes
+    /// disabled, which reduces overdraw, so heavy fragment shaders (parallax mapping, etc.) are
+    /// executed only once per pixel. The pre-pass itself is not free, so it should be used for
+    /// scenes with high overdraw. Disabled by default.
+    #[serde(default)]
+    pub use_z_prepass: bool,
+
+    /// Maximum level of anisotropic filtering of the textures of materials. Anisotropic filtering
+    /// keeps textures sharp when they're viewed at grazing angles (floors, terrains, etc.). Each
+    /// texture uses the lowest of this value and its own anisotropy level. The value is clamped
+    /// to the maximum supported by the device, see [`crate::renderer::Renderer::effective_anisotropy`].
+    /// Values below 1 are treated as 1 (no anisotropic filtering). Default is 16.
+    #[serde(default = "default_anisotropy")]
+    #[reflect(min_value = 1.0, max_value = 16.0)]
+    pub anisotropy: u32,
+}
+
+fn default_ssao_intensity() -> f32 {
+    1.0
+}
+
+fn default_ssr_max_steps() -> u32 {
+    32
+}
+
+fn default_ssr_thickness() -> f32 {
+    0.5
+}
+
+fn default_occlusion_grid_cell_size() -> f32 {
+    1.0
+}
+
+fn default_occlusion_culling_delay() -> u32 {
+    1
+}
+
+fn default_anisotropy() -> u32 {
+    16
 }
 
 impl Default for QualitySettings {
@@ -171,6 +272,12 @@ impl Default for QualitySettings {
 }
 
 impl QualitySettings {
+    /// Returns the size of the cells of the occlusion culling grid (see
+    /// [`Self::occlusion_grid_cell_size`]), clamped to be at least 0.1 meters.
+    pub fn validated_occlusion_grid_cell_size(&self) -> f32 {
+        self.occlusion_grid_cell_size.max(0.1)
+    }
+
     /// Highest possible graphics quality. Requires very powerful GPU.
     pub fn ultra() -> Self {
         Self {
@@ -188,6 +295,11 @@ impl QualitySettings {
 
             use_ssao: true,
             ssao_radius: 0.5,
+            ssao_intensity: 1.0,
+
+            use_ssr: true,
+            ssr_max_steps: 64,
+            ssr_thickness: default_ssr_thickness(),
 
             light_scatter_enabled: true,
 
@@ -195,6 +307,7 @@ impl QualitySettings {
             spot_shadow_map_precision: ShadowMapPrecision::Full,
 
             fxaa: true,
+            use_depth_of_field: true,
 
             hdr_settings: Default::default(),
 
@@ -204,6 +317,14 @@ impl QualitySettings {
 
             use_occlusion_culling: false,
             use_light_occlusion_culling: false,
+            occlusion_grid_cell_size: default_occlusion_grid_cell_size(),
+            occlusion_culling_delay: default_occlusion_culling_delay(),
+
+            use_reversed_z: false,
+
+            use_z_prepass: false,
+
+            anisotropy: default_anisotropy(),
         }
     }
 
@@ -224,6 +345,11 @@ impl QualitySettings {
 
             use_ssao: true,
             ssao_radius: 0.5,
+            ssao_intensity: 1.0,
+
+            use_ssr: false,
+            ssr_max_steps: default_ssr_max_steps(),
+            ssr_thickness: default_ssr_thickness(),
 
             light_scatter_enabled: true,
 
@@ -231,6 +357,7 @@ impl QualitySettings {
             spot_shadow_map_precision: ShadowMapPrecision::Full,
 
             fxaa: true,
+            use_depth_of_field: true,
 
             hdr_settings: Default::default(),
 
@@ -245,6 +372,14 @@ impl QualitySettings {
 
             use_occlusion_culling: false,
             use_light_occlusion_culling: false,
+            occlusion_grid_cell_size: default_occlusion_grid_cell_size(),
+            occlusion_culling_delay: default_occlusion_culling_delay(),
+
+            use_reversed_z: false,
+
+            use_z_prepass: false,
+
+            anisotropy: default_anisotropy(),
         }
     }
 
@@ -265,6 +400,11 @@ impl QualitySettings {
 
             use_ssao: true,
             ssao_radius: 0.5,
+            ssao_intensity: 1.0,
+
+            use_ssr: false,
+            ssr_max_steps: default_ssr_max_steps(),
+            ssr_thickness: default_ssr_thickness(),
 
             light_scatter_enabled: false,
 
@@ -272,6 +412,7 @@ impl QualitySettings {
             spot_shadow_map_precision: ShadowMapPrecision::Half,
 
             fxaa: true,
+            use_depth_of_field: false,
 
             hdr_settings: Default::default(),
 
@@ -286,6 +427,14 @@ impl QualitySettings {
 
             use_occlusion_culling: false,
             use_light_occlusion_culling: false,
+            occlusion_grid_cell_size: default_occlusion_grid_cell_size(),
+            occlusion_culling_delay: default_occlusion_culling_delay(),
+
+            use_reversed_z: false,
+
+            use_z_prepass: false,
+
+            anisotropy: 4,
         }
     }
 
@@ -306,6 +455,11 @@ impl QualitySettings {
 
             use_ssao: false,
             ssao_radius: 0.5,
+            ssao_intensity: 1.0,
+
+            use_ssr: false,
+            ssr_max_steps: 16,
+            ssr_thickness: default_ssr_thickness(),
 
             light_scatter_enabled: false,
 
@@ -313,6 +467,7 @@ impl QualitySettings {
             spot_shadow_map_precision: ShadowMapPrecision::Half,
 
             fxaa: false,
+            use_depth_of_field: false,
 
             hdr_settings: HdrSettings {
                 bloom_settings: BloomSettings {
@@ -333,6 +488,14 @@ impl QualitySettings {
 
             use_occlusion_culling: false,
             use_light_occlusion_culling: false,
+            occlusion_grid_cell_size: default_occlusion_grid_cell_size(),
+            occlusion_culling_delay: default_occlusion_culling_delay(),
+
+            use_reversed_z: false,
+
+            use_z_prepass: false,
+
+            anisotropy: 1,
         }
     }
 }
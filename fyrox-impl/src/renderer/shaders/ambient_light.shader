@@ -46,6 +46,11 @@
             kind: Texture(kind: Sampler2D, fallback: White),
             binding: 8
         ),
+        (
+            name: "ssrTexture",
+            kind: Texture(kind: Sampler2D, fallback: Black),
+            binding: 9
+        ),
         (
             name: "properties",
             kind: PropertyGroup([
@@ -55,6 +60,7 @@
                 (name: "invViewProj", kind: Matrix4()),
                 (name: "skyboxLighting", kind: Bool()),
                 (name: "environmentLightingBrightness", kind: Float()),
+                (name: "useSsr", kind: Bool()),
             ]),
             binding: 0
         ),
@@ -137,6 +143,14 @@
                         float mip = roughness * (floor(log2(float(cubeMapSize.x))) + 1.0);
                         vec3 reflection = properties.skyboxLighting ? S_SRGBToLinear(textureLod(prefilteredSpecularMap, reflectionVector, mip)).rgb : properties.ambientColor.rgb;
 
+                        if (properties.useSsr) {
+                            // Screen-space reflection map contains the radiance of the hit point and
+                            // the confidence of the hit, which already accounts for the roughness of
+                            // the surface.
+                            vec4 ssr = texture(ssrTexture, texCoord);
+                            reflection = mix(reflection, ssr.rgb, ssr.a);
+                        }
+
                         vec3 F0 = mix(vec3(0.04), albedo.rgb, metallic);
                         vec3 F = S_FresnelSchlickRoughness(clampedCosViewAngle, F0, roughness);
                         vec3 kD = (vec3(1.0) - F) * (1.0 - metallic);
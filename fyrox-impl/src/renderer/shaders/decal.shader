@@ -30,6 +30,10 @@
                 (name: "resolution", kind: Vector2()),
                 (name: "color", kind: Vector4()),
                 (name: "layerIndex", kind: UInt()),
+                (name: "layerMask", kind: UInt()),
+                (name: "premultipliedAlpha", kind: Bool()),
+                (name: "twoSided", kind: Bool()),
+                (name: "maxAngle", kind: Float()),
             ]),
             binding: 0
         ),
@@ -101,8 +105,12 @@
 
                         uvec4 maskIndex = texture(decalMask, texCoord);
 
-                        // Masking.
-                        if (maskIndex.r != properties.layerIndex) {
+                        // Masking. Zero layer mask means that only the layer index is used.
+                        if (properties.layerMask == 0u) {
+                            if (maskIndex.r != properties.layerIndex) {
+                                discard;
+                            }
+                        } else if (maskIndex.r >= 32u || ((1u << maskIndex.r) & properties.layerMask) == 0u) {
                             discard;
                         }
 
@@ -118,14 +126,51 @@
                             discard;
                         }
 
-                        vec2 decalTexCoord = decalSpacePosition.xz + 0.5;
-
-                        outDiffuseMap = properties.color * texture(diffuseTexture, decalTexCoord);
-
                         vec3 fragmentTangent = dFdx(sceneWorldPosition);
                         vec3 fragmentBinormal = dFdy(sceneWorldPosition);
                         vec3 fragmentNormal = cross(fragmentTangent, fragmentBinormal);
 
+                        // The projection axis is the gradient of the decal-space Y coordinate,
+                        // which is the second row of the inverse world matrix.
+                        vec3 projectionAxis = vec3(
+                            properties.invWorldDecal[0][1],
+                            properties.invWorldDecal[1][1],
+                            properties.invWorldDecal[2][1]
+                        );
+                        float cosAngle = dot(normalize(fragmentNormal), normalize(projectionAxis));
+
+                        // One-sided decals are projected only on the surfaces that face the +Y
+                        // axis of the decal.
+                        if (!properties.twoSided && cosAngle <= 0.0) {
+                            discard;
+                        }
+
+                        // Fade the decal out on steep surfaces to hide stretching of its texture.
+                        // The fading starts at 80% of the max angle. The angle to the closest
+                        // direction of the axis never exceeds 90 degrees, so the max angle of 90
+                        // degrees (or more) disables the fading.
+                        float angle = acos(clamp(abs(cosAngle), 0.0, 1.0));
+                        float angleFade = 1.0;
+                        if (properties.maxAngle < 1.5707) {
+                            angleFade = 1.0 - smoothstep(0.8 * properties.maxAngle, properties.maxAngle, angle);
+                        }
+
+                        vec2 decalTexCoord = decalSpacePosition.xz + 0.5;
+
+                        vec4 diffuseColor = texture(diffuseTexture, decalTexCoord);
+                        if (properties.premultipliedAlpha) {
+                            // Color channels are already multiplied by alpha, so the tint's alpha
+                            // must be applied to them as well to keep them premultiplied.
+                            outDiffuseMap = vec4(properties.color.rgb * properties.color.a, properties.color.a) * diffuseColor;
+                        } else {
+                            outDiffuseMap = properties.color * diffuseColor;
+                        }
+                        if (properties.premultipliedAlpha) {
+                            outDiffuseMap *= angleFade;
+                        } else {
+                            outDiffuseMap.a *= angleFade;
+                        }
+
                         mat3 tangentToWorld;
                         tangentToWorld[0] = normalize(fragmentTangent); // Tangent
                         tangentToWorld[1] = normalize(fragmentBinormal); // Binormal
@@ -133,7 +178,13 @@
 
                         vec3 rawNormal = (texture(normalTexture, decalTexCoord) * 2.0 - 1.0).xyz;
                         vec3 worldSpaceNormal = tangentToWorld * rawNormal;
-                        outNormalMap = vec4(worldSpaceNormal * 0.5 + 0.5, outDiffuseMap.a);
+                        vec3 encodedNormal = worldSpaceNormal * 0.5 + 0.5;
+                        if (properties.premultipliedAlpha) {
+                            // Both targets share the same blending function, so the normal must be
+                            // premultiplied too.
+                            encodedNormal *= outDiffuseMap.a;
+                        }
+                        outNormalMap = vec4(encodedNormal, outDiffuseMap.a);
                     }
                 "#,
         )
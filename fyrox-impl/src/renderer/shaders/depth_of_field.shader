@@ -0,0 +1,121 @@
+(
+    name: "DepthOfField",
+    resources: [
+        (
+            name: "screenTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 0
+        ),
+        (
+            name: "depthTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 1
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "inverseProjectionMatrix", kind: Matrix4()),
+                (name: "inverseScreenSize", kind: Vector2()),
+                (name: "focusDistance", kind: Float()),
+                (name: "focalRange", kind: Float()),
+                (name: "maxBlur", kind: Float()),
+                (name: "zFar", kind: Float()),
+                (name: "skyDepth", kind: Float()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+                    layout (location = 1) in vec2 vertexTexCoord;
+
+                    out vec2 texCoord;
+
+                    void main()
+                    {
+                        texCoord = vertexTexCoord;
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    in vec2 texCoord;
+
+                    out vec4 FragColor;
+
+                    const int SAMPLE_COUNT = 24;
+                    const float GOLDEN_ANGLE = 2.39996323;
+
+                    // Returns normalized circle of confusion at the given point of the screen.
+                    float CircleOfConfusion(vec2 screenCoord) {
+                        float depth = texture(depthTexture, screenCoord).r;
+                        float distance;
+                        if (depth == properties.skyDepth) {
+                            // Nothing was rendered here (sky), it is infinitely far away, but
+                            // the far clipping plane is used to keep the blur predictable.
+                            distance = properties.zFar;
+                        } else {
+                            vec3 viewPosition = S_UnProject(vec3(screenCoord, depth), properties.inverseProjectionMatrix);
+                            distance = min(length(viewPosition), properties.zFar);
+                        }
+                        float range = max(properties.focalRange, 0.001);
+                        return clamp((abs(distance - properties.focusDistance) - 0.5 * range) / range, 0.0, 1.0);
+                    }
+
+                    void main()
+                    {
+                        float centerRadius = CircleOfConfusion(texCoord) * properties.maxBlur;
+
+                        vec3 color = texture(screenTexture, texCoord).rgb;
+                        float totalWeight = 1.0;
+
+                        // Gather samples on a spiral, a sample contributes only if both its own
+                        // circle of confusion and the one of the current pixel reach the sample,
+                        // so sharp objects neither get blurred nor bleed into blurry ones.
+                        for (int i = 1; i <= SAMPLE_COUNT; ++i) {
+                            float radius = properties.maxBlur * sqrt(float(i) / float(SAMPLE_COUNT));
+                            float angle = float(i) * GOLDEN_ANGLE;
+                            vec2 offset = vec2(cos(angle), sin(angle)) * radius;
+                            vec2 sampleCoord = texCoord + offset * properties.inverseScreenSize;
+
+                            float sampleRadius = CircleOfConfusion(sampleCoord) * properties.maxBlur;
+                            float weight = smoothstep(radius - 0.5, radius + 0.5, min(sampleRadius, centerRadius));
+
+                            color += texture(screenTexture, sampleCoord).rgb * weight;
+                            totalWeight += weight;
+                        }
+
+                        FragColor = vec4(color / totalWeight, texture(screenTexture, texCoord).a);
+                    }
+                "#,
+        )
+    ]
+)
@@ -0,0 +1,108 @@
+(
+    name: "GBufferDebug",
+    resources: [
+        (
+            name: "colorTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 0
+        ),
+        (
+            name: "depthTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 1
+        ),
+        (
+            name: "decalMask",
+            kind: Texture(kind: USampler2D, fallback: White),
+            binding: 2
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "invProjection", kind: Matrix4()),
+                (name: "zNear", kind: Float()),
+                (name: "zFar", kind: Float()),
+                // 0 - color as is, 1 - normal, 2 - decal mask, 3 - linear depth.
+                (name: "mode", kind: UInt()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+                    layout (location = 1) in vec2 vertexTexCoord;
+
+                    out vec2 texCoord;
+
+                    void main()
+                    {
+                        texCoord = vertexTexCoord;
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    out vec4 FragColor;
+
+                    in vec2 texCoord;
+
+                    vec3 LayerColor(uint layer)
+                    {
+                        // Golden angle gives a good spread of hues for sequential indices.
+                        float hue = mod(float(layer) * 137.508, 360.0) / 60.0;
+                        vec3 rgb = clamp(abs(mod(hue + vec3(0.0, 4.0, 2.0), 6.0) - 3.0) - 1.0, 0.0, 1.0);
+                        return rgb;
+                    }
+
+                    void main()
+                    {
+                        if (properties.mode == 1u) {
+                            // Normals are stored in [0; 1] range, decode them to [-1; 1] first to
+                            // normalize and then remap back for display.
+                            vec3 normal = normalize(texture(colorTexture, texCoord).xyz * 2.0 - 1.0);
+                            FragColor = vec4(normal * 0.5 + 0.5, 1.0);
+                        } else if (properties.mode == 2u) {
+                            uint layer = texture(decalMask, texCoord).r;
+                            FragColor = vec4(layer == 0u ? vec3(0.0) : LayerColor(layer), 1.0);
+                        } else if (properties.mode == 3u) {
+                            float depth = texture(depthTexture, texCoord).r;
+                            vec4 viewPosition = properties.invProjection * vec4(texCoord * 2.0 - 1.0, depth * 2.0 - 1.0, 1.0);
+                            float viewDepth = -viewPosition.z / viewPosition.w;
+                            float linearDepth = (viewDepth - properties.zNear) / (properties.zFar - properties.zNear);
+                            FragColor = vec4(vec3(clamp(linearDepth, 0.0, 1.0)), 1.0);
+                        } else {
+                            FragColor = vec4(texture(colorTexture, texCoord).rgb, 1.0);
+                        }
+                    }
+                "#,
+        )
+    ]
+)
@@ -30,6 +30,7 @@
                 (name: "maxLuminance", kind: Float()),
                 (name: "autoExposure", kind: Bool()),
                 (name: "fixedExposure", kind: Float()),
+                (name: "whiteBalance", kind: Vector3()),
             ]),
             binding: 0
         ),
@@ -101,6 +102,9 @@
                     void main() {
                         vec4 hdrColor = texture(hdrSampler, texCoord) + texture(bloomSampler, texCoord);
 
+                        // White balance is applied before tone mapping, so it works in linear space.
+                        hdrColor.rgb *= properties.whiteBalance;
+
                         vec3 Yxy = S_ConvertRgbToYxy(hdrColor.rgb);
 
                         float lp;
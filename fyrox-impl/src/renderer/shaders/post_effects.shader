@@ -0,0 +1,97 @@
+(
+    name: "PostEffects",
+    resources: [
+        (
+            name: "screenTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 0
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "vignetteColor", kind: Vector4()),
+                (name: "vignetteIntensity", kind: Float()),
+                (name: "vignetteSmoothness", kind: Float()),
+                (name: "chromaticAberration", kind: Float()),
+                (name: "useVignette", kind: Bool()),
+                (name: "useChromaticAberration", kind: Bool()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+                    layout (location = 1) in vec2 vertexTexCoord;
+
+                    out vec2 texCoord;
+
+                    void main()
+                    {
+                        texCoord = vertexTexCoord;
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    in vec2 texCoord;
+
+                    out vec4 FragColor;
+
+                    void main()
+                    {
+                        vec2 fromCenter = texCoord - vec2(0.5);
+
+                        vec4 color;
+                        if (properties.useChromaticAberration) {
+                            // Channels are shifted along the direction from the center of the
+                            // image, so the center stays sharp.
+                            vec2 shift = fromCenter * properties.chromaticAberration * 0.02;
+                            color = texture(screenTexture, texCoord);
+                            color.r = texture(screenTexture, texCoord + shift).r;
+                            color.b = texture(screenTexture, texCoord - shift).b;
+                        } else {
+                            color = texture(screenTexture, texCoord);
+                        }
+
+                        if (properties.useVignette) {
+                            // Distance to the center, that is 1.0 at the corners of the image.
+                            float distance = length(fromCenter) * 1.41421356;
+                            float start = 1.0 - properties.vignetteIntensity;
+                            float amount = smoothstep(start, start + properties.vignetteSmoothness, distance);
+                            color.rgb = mix(color.rgb, properties.vignetteColor.rgb, amount * properties.vignetteColor.a);
+                        }
+
+                        FragColor = color;
+                    }
+                "#,
+        )
+    ]
+)
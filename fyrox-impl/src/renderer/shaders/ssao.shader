@@ -26,6 +26,7 @@
                 (name: "noiseScale", kind: Vector2()),
                 (name: "viewMatrix", kind: Matrix3()),
                 (name: "radius", kind: Float()),
+                (name: "intensity", kind: Float()),
             ]),
             binding: 0
         ),
@@ -104,7 +105,7 @@
                             occlusion += rangeCheck * ((position.z > samplePoint.z + 0.04) ? 1.0 : 0.0);
                         }
 
-                        finalOcclusion = 1.0 - occlusion / float(kernelSize);
+                        finalOcclusion = clamp(1.0 - properties.intensity * occlusion / float(kernelSize), 0.0, 1.0);
                     }
                 "#,
         )
@@ -0,0 +1,201 @@
+(
+    name: "SSR",
+    resources: [
+        (
+            name: "depthSampler",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 0
+        ),
+        (
+            name: "normalSampler",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 1
+        ),
+        (
+            name: "materialSampler",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 2
+        ),
+        (
+            name: "historySampler",
+            kind: Texture(kind: Sampler2D, fallback: Black),
+            binding: 3
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "inverseProjectionMatrix", kind: Matrix4()),
+                (name: "projectionMatrix", kind: Matrix4()),
+                (name: "viewMatrix", kind: Matrix3()),
+                (name: "reprojectionMatrix", kind: Matrix4()),
+                (name: "maxSteps", kind: Int()),
+                (name: "thickness", kind: Float()),
+                (name: "clearDepth", kind: Float()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+                    layout (location = 1) in vec2 vertexTexCoord;
+
+                    out vec2 texCoord;
+
+                    void main()
+                    {
+                        texCoord = vertexTexCoord;
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    out vec4 reflection;
+
+                    in vec2 texCoord;
+
+                    // Maximum distance (in view space) that a ray can travel.
+                    const float maxDistance = 30.0;
+                    // Amount of binary search steps to refine the hit point.
+                    const int refinementSteps = 5;
+                    // Width of the screen border (in UV space) at which reflections fade out.
+                    const float edgeFade = 0.1;
+
+                    vec3 GetViewSpacePosition(vec2 screenCoord) {
+                        return S_UnProject(vec3(screenCoord, texture(depthSampler, screenCoord).r), properties.inverseProjectionMatrix);
+                    }
+
+                    vec2 Project(vec3 viewSpacePosition) {
+                        vec4 clipSpacePosition = properties.projectionMatrix * vec4(viewSpacePosition, 1.0);
+                        return (clipSpacePosition.xy / clipSpacePosition.w) * 0.5 + 0.5;
+                    }
+
+                    // Positive when the ray is behind the visible surface.
+                    float DepthDelta(vec3 rayPosition, vec2 screenCoord) {
+                        return GetViewSpacePosition(screenCoord).z - rayPosition.z;
+                    }
+
+                    bool IsSky(vec2 screenCoord) {
+                        return texture(depthSampler, screenCoord).r == properties.clearDepth;
+                    }
+
+                    float EdgeFactor(vec2 screenCoord) {
+                        vec2 edge = smoothstep(vec2(0.0), vec2(edgeFade), screenCoord) *
+                            (vec2(1.0) - smoothstep(vec2(1.0 - edgeFade), vec2(1.0), screenCoord));
+                        return edge.x * edge.y;
+                    }
+
+                    void main() {
+                        reflection = vec4(0.0);
+
+                        // There's no surface to reflect from.
+                        if (IsSky(texCoord)) {
+                            return;
+                        }
+
+                        float roughness = texture(materialSampler, texCoord).y;
+                        if (roughness >= 1.0) {
+                            return;
+                        }
+
+                        vec3 fragPos = GetViewSpacePosition(texCoord);
+                        vec3 worldSpaceNormal = texture(normalSampler, texCoord).xyz * 2.0 - 1.0;
+                        vec3 viewSpaceNormal = normalize(properties.viewMatrix * worldSpaceNormal);
+
+                        vec3 viewDir = normalize(fragPos);
+                        vec3 rayDir = normalize(reflect(viewDir, viewSpaceNormal));
+
+                        // Rays that go towards the camera will leave the screen almost immediately
+                        // and cannot be resolved reliably.
+                        float facingFade = 1.0 - smoothstep(0.25, 0.5, dot(-viewDir, rayDir));
+                        if (facingFade <= 0.0) {
+                            return;
+                        }
+
+                        int maxSteps = max(properties.maxSteps, 1);
+                        float stepLength = maxDistance / float(maxSteps);
+                        vec3 rayPos = fragPos;
+
+                        for (int i = 0; i < maxSteps; ++i) {
+                            vec3 prevRayPos = rayPos;
+                            rayPos += rayDir * stepLength;
+
+                            if (rayPos.z >= 0.0) {
+                                break;
+                            }
+
+                            vec2 uv = Project(rayPos);
+                            if (uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+                                break;
+                            }
+
+                            // The sky is infinitely far away, a ray can't hit it.
+                            if (IsSky(uv)) {
+                                continue;
+                            }
+
+                            float delta = DepthDelta(rayPos, uv);
+                            if (delta > 0.0 && delta < properties.thickness) {
+                                // Refine the hit point using binary search between the last two positions.
+                                vec3 a = prevRayPos;
+                                vec3 b = rayPos;
+                                for (int j = 0; j < refinementSteps; ++j) {
+                                    vec3 middle = (a + b) * 0.5;
+                                    if (DepthDelta(middle, Project(middle)) > 0.0) {
+                                        b = middle;
+                                    } else {
+                                        a = middle;
+                                    }
+                                }
+
+                                vec2 hitCoord = Project(b);
+
+                                // The lit color of the current frame is not available yet, so fetch
+                                // it from the previous frame at the position the hit point had there.
+                                vec4 previousClipPosition = properties.reprojectionMatrix * vec4(b, 1.0);
+                                if (previousClipPosition.w <= 0.0) {
+                                    return;
+                                }
+                                vec2 previousHitCoord = (previousClipPosition.xy / previousClipPosition.w) * 0.5 + 0.5;
+
+                                float edgeFactor = EdgeFactor(hitCoord) * EdgeFactor(previousHitCoord);
+                                float distanceFactor = 1.0 - float(i) / float(maxSteps);
+                                float roughnessFactor = 1.0 - roughness;
+
+                                vec3 radiance = texture(historySampler, previousHitCoord).rgb;
+                                reflection = vec4(radiance, clamp(edgeFactor * distanceFactor * roughnessFactor * facingFade, 0.0, 1.0));
+                                return;
+                            }
+                        }
+                    }
+                "#,
+        )
+    ]
+)
@@ -214,6 +214,10 @@ impl PointShadowMapRenderer {
                     renderer_resources,
                     ambient_light: Color::WHITE, // TODO
                     scene_depth: None,
+                    gbuffer: None,
+                    reversed_z: false,
+                    previous_view_projection_matrix: None,
+                    depth_pre_pass: Default::default(),
                 },
             )?;
         }
@@ -176,6 +176,10 @@ impl SpotShadowMapRenderer {
                 renderer_resources,
                 ambient_light: Color::WHITE, // TODO
                 scene_depth: None,
+                gbuffer: None,
+                reversed_z: false,
+                previous_view_projection_matrix: None,
+                depth_pre_pass: Default::default(),
             },
         )?;
 
@@ -61,6 +61,7 @@ pub struct ScreenSpaceAmbientOcclusionRenderer {
     noise: GpuTexture,
     kernel: [Vector3<f32>; KERNEL_SIZE],
     radius: f32,
+    intensity: f32,
 }
 
 impl ScreenSpaceAmbientOcclusionRenderer {
@@ -122,13 +123,40 @@ impl ScreenSpaceAmbientOcclusionRenderer {
                 })?
             },
             radius: 0.5,
+            intensity: 1.0,
         })
     }
 
+    /// Re-creates the size-dependent render targets, the kernel, the noise and the settings are
+    /// preserved.
+    pub fn set_size(
+        &mut self,
+        server: &dyn GraphicsServer,
+        frame_width: usize,
+        frame_height: usize,
+    ) -> Result<(), FrameworkError> {
+        let width = (frame_width / 2).max(1);
+        let height = (frame_height / 2).max(1);
+
+        let occlusion =
+            server.create_2d_render_target("SsaoTexture", PixelKind::R32F, width, height)?;
+
+        self.blur = Blur::new(server, width, height)?;
+        self.framebuffer = server.create_frame_buffer(None, vec![Attachment::color(occlusion)])?;
+        self.width = width as i32;
+        self.height = height as i32;
+
+        Ok(())
+    }
+
     pub fn set_radius(&mut self, radius: f32) {
         self.radius = radius.abs();
     }
 
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity.max(0.0);
+    }
+
     fn raw_ao_map(&self) -> GpuTexture {
         self.framebuffer.color_attachments()[0].texture.clone()
     }
@@ -176,6 +204,7 @@ impl ScreenSpaceAmbientOcclusionRenderer {
             property("noiseScale", &noise_scale),
             property("viewMatrix", &view_matrix),
             property("radius", &self.radius),
+            property("intensity", &self.intensity),
         ]);
 
         let material = RenderMaterial::from([
@@ -0,0 +1,287 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Screen-space reflections. See [`Ssr`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::Matrix4,
+        color::Color,
+        math::{Matrix4Ext, Rect},
+        sstorage::ImmutableString,
+    },
+    graphics::{
+        error::FrameworkError,
+        framebuffer::{Attachment, GpuFrameBuffer},
+        gpu_texture::{GpuTexture, PixelKind},
+        server::GraphicsServer,
+    },
+    renderer::{
+        cache::{
+            shader::{binding, property, PropertyGroup, RenderMaterial},
+            uniform::UniformBufferCache,
+        },
+        gbuffer::GBuffer,
+        make_viewport_matrix,
+        observer::ObserverPosition,
+        resources::RendererResources,
+        QualitySettings, RenderPassStatistics,
+    },
+};
+
+/// Screen-space reflections renderer. It ray-marches the depth buffer of the G-Buffer along the
+/// reflected view vector and fetches the radiance of the hit point from the lit frame of the
+/// previous frame (the lit color of the current frame is not yet available at this point). The hit
+/// point is reprojected using the view-projection matrix of the previous frame. The reflection map
+/// contains the radiance (RGB channels) together with the confidence of the hit (A channel). The
+/// confidence fades out near the screen edges, at the end of the ray and for rough surfaces. The
+/// reflection map is then used by the ambient lighting pass to replace the environment reflection
+/// with the content of the screen.
+pub struct Ssr {
+    framebuffer: GpuFrameBuffer,
+    history: GpuFrameBuffer,
+    width: i32,
+    height: i32,
+}
+
+impl Ssr {
+    pub fn new(
+        server: &dyn GraphicsServer,
+        frame_width: usize,
+        frame_height: usize,
+    ) -> Result<Self, FrameworkError> {
+        // The same resolution reduction as for SSAO, reflections are blurred by roughness anyway.
+        let width = (frame_width / 2).max(1);
+        let height = (frame_height / 2).max(1);
+
+        let reflection =
+            server.create_2d_render_target("SsrTexture", PixelKind::RGBA16F, width, height)?;
+        let history = server.create_2d_render_target(
+            "SsrHistoryTexture",
+            PixelKind::RGBA16F,
+            width,
+            height,
+        )?;
+
+        let ssr = Self {
+            framebuffer: server.create_frame_buffer(None, vec![Attachment::color(reflection)])?,
+            history: server.create_frame_buffer(None, vec![Attachment::color(history)])?,
+            width: width as i32,
+            height: height as i32,
+        };
+
+        // There's no previous frame yet.
+        ssr.history.clear(
+            Rect::new(0, 0, ssr.width, ssr.height),
+            Some(Color::from_rgba(0, 0, 0, 0)),
+            None,
+            None,
+        );
+
+        Ok(ssr)
+    }
+
+    pub fn reflection_map(&self) -> GpuTexture {
+        self.framebuffer.color_attachments()[0].texture.clone()
+    }
+
+    /// Copies the lit frame, so it could be used as the source of reflections in the next frame.
+    /// Must be called after all the lighting of the frame was done.
+    pub(crate) fn update_history(
+        &self,
+        hdr_framebuffer: &GpuFrameBuffer,
+        frame_width: i32,
+        frame_height: i32,
+    ) {
+        hdr_framebuffer.blit_to(
+            &self.history,
+            0,
+            0,
+            frame_width,
+            frame_height,
+            0,
+            0,
+            self.width,
+            self.height,
+            true,
+            false,
+            false,
+        );
+    }
+
+    pub(crate) fn render(
+        &self,
+        server: &dyn GraphicsServer,
+        gbuffer: &GBuffer,
+        observer_position: &ObserverPosition,
+        previous_view_projection_matrix: &Matrix4<f32>,
+        settings: &QualitySettings,
+        uniform_buffer_cache: &mut UniformBufferCache,
+        renderer_resources: &RendererResources,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let _debug_scope = server.begin_scope("SSR");
+
+        let mut stats = RenderPassStatistics::default();
+
+        let viewport = Rect::new(0, 0, self.width, self.height);
+
+        let frame_matrix = make_viewport_matrix(viewport);
+
+        self.framebuffer
+            .clear(viewport, Some(Color::from_rgba(0, 0, 0, 0)), None, None);
+
+        let projection_matrix = observer_position.projection_matrix;
+        let inv_projection = projection_matrix.try_inverse().unwrap_or_default();
+        let view_matrix = observer_position.view_matrix.basis();
+        let reprojection_matrix = reprojection_matrix(
+            &observer_position.view_matrix,
+            previous_view_projection_matrix,
+        );
+        let max_steps = validated_max_steps(settings.ssr_max_steps);
+        let thickness = settings.ssr_thickness.max(0.0);
+        let clear_depth = clear_depth(settings.use_reversed_z);
+
+        let properties = PropertyGroup::from([
+            property("worldViewProjection", &frame_matrix),
+            property("inverseProjectionMatrix", &inv_projection),
+            property("projectionMatrix", &projection_matrix),
+            property("viewMatrix", &view_matrix),
+            property("reprojectionMatrix", &reprojection_matrix),
+            property("maxSteps", &max_steps),
+            property("thickness", &thickness),
+            property("clearDepth", &clear_depth),
+        ]);
+
+        let material = RenderMaterial::from([
+            binding(
+                "depthSampler",
+                (gbuffer.depth(), &renderer_resources.nearest_clamp_sampler),
+            ),
+            binding(
+                "normalSampler",
+                (
+                    gbuffer.normal_texture(),
+                    &renderer_resources.nearest_clamp_sampler,
+                ),
+            ),
+            binding(
+                "materialSampler",
+                (
+                    gbuffer.material_texture(),
+                    &renderer_resources.nearest_clamp_sampler,
+                ),
+            ),
+            binding(
+                "historySampler",
+                (
+                    &self.history.color_attachments()[0].texture,
+                    &renderer_resources.linear_clamp_sampler,
+                ),
+            ),
+            binding("properties", &properties),
+        ]);
+
+        stats += renderer_resources.shaders.ssr.run_pass(
+            1,
+            &ImmutableString::new("Primary"),
+            &self.framebuffer,
+            &renderer_resources.quad,
+            viewport,
+            &material,
+            uniform_buffer_cache,
+            Default::default(),
+            None,
+        )?;
+
+        Ok(stats)
+    }
+}
+
+/// Returns a matrix that transforms a view-space point of the current frame into the clip space of
+/// the previous frame.
+fn reprojection_matrix(
+    view_matrix: &Matrix4<f32>,
+    previous_view_projection_matrix: &Matrix4<f32>,
+) -> Matrix4<f32> {
+    previous_view_projection_matrix * view_matrix.try_inverse().unwrap_or_default()
+}
+
+fn validated_max_steps(max_steps: u32) -> i32 {
+    max_steps.clamp(1, i32::MAX as u32) as i32
+}
+
+/// Returns the value of the depth buffer at the pixels that were not covered by any geometry (sky).
+fn clear_depth(reversed_z: bool) -> f32 {
+    if reversed_z {
+        0.0
+    } else {
+        1.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{clear_depth, reprojection_matrix, validated_max_steps};
+    use crate::core::algebra::{Matrix4, Point3, Vector3, Vector4};
+
+    fn view_projection(eye: Point3<f32>) -> (Matrix4<f32>, Matrix4<f32>) {
+        let view = Matrix4::look_at_rh(&eye, &(eye + Vector3::new(0.0, 0.0, -1.0)), &Vector3::y());
+        let projection = Matrix4::new_perspective(1.0, 1.0, 0.1, 100.0);
+        (view, projection * view)
+    }
+
+    #[test]
+    fn test_reprojection_of_static_camera() {
+        let (view, view_projection) = view_projection(Point3::new(1.0, 2.0, 3.0));
+        let world_point = Vector4::new(2.0, 1.0, -4.0, 1.0);
+        let view_point = view * world_point;
+
+        let expected = view_projection * world_point;
+        let reprojected = reprojection_matrix(&view, &view_projection) * view_point;
+        assert!((reprojected - expected).norm() < 1.0e-4);
+    }
+
+    #[test]
+    fn test_reprojection_of_moving_camera() {
+        let (_, previous_view_projection) = view_projection(Point3::new(0.0, 0.0, 0.0));
+        let (view, _) = view_projection(Point3::new(1.0, 0.0, 0.0));
+
+        // The point in front of the current position of the camera was to the right of the
+        // camera in the previous frame.
+        let view_point = Vector4::new(0.0, 0.0, -5.0, 1.0);
+        let previous_clip = reprojection_matrix(&view, &previous_view_projection) * view_point;
+        let expected = previous_view_projection * Vector4::new(1.0, 0.0, -5.0, 1.0);
+        assert!((previous_clip - expected).norm() < 1.0e-4);
+        assert!(previous_clip.x / previous_clip.w > 0.0);
+    }
+
+    #[test]
+    fn test_validated_max_steps() {
+        assert_eq!(validated_max_steps(0), 1);
+        assert_eq!(validated_max_steps(64), 64);
+        assert_eq!(validated_max_steps(u32::MAX), i32::MAX);
+    }
+
+    #[test]
+    fn test_clear_depth() {
+        assert_eq!(clear_depth(false), 1.0);
+        assert_eq!(clear_depth(true), 0.0);
+    }
+}
@@ -75,6 +75,34 @@ impl Display for LightingStatistics {
     }
 }
 
+/// Occlusion culling statistics.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct OcclusionStatistics {
+    /// How many objects passed the occlusion test and were drawn.
+    pub drawn: usize,
+    /// How many objects were skipped, because they were hidden behind other objects.
+    pub culled: usize,
+}
+
+impl AddAssign for OcclusionStatistics {
+    fn add_assign(&mut self, rhs: Self) {
+        self.drawn += rhs.drawn;
+        self.culled += rhs.culled;
+    }
+}
+
+impl Display for OcclusionStatistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Occlusion Statistics:\n\
+            \tDrawn: {}\n\
+            \tCulled: {}\n",
+            self.drawn, self.culled
+        )
+    }
+}
+
 /// Renderer statistics for a scene.
 #[derive(Debug, Copy, Clone, Default)]
 pub struct SceneStatistics {
@@ -84,6 +112,8 @@ pub struct SceneStatistics {
     pub lighting: LightingStatistics,
     /// Shows how many draw calls was made and how many triangles were rendered.
     pub geometry: RenderPassStatistics,
+    /// Shows how many objects were drawn and how many were culled by occlusion culling.
+    pub occlusion: OcclusionStatistics,
 }
 
 impl Display for SceneStatistics {
@@ -92,8 +122,9 @@ impl Display for SceneStatistics {
             f,
             "{}\n\
             {}\n\
+            {}\n\
             {}\n",
-            self.geometry, self.lighting, self.pipeline
+            self.geometry, self.lighting, self.occlusion, self.pipeline
         )
     }
 }
@@ -122,6 +153,12 @@ impl AddAssign<LightingStatistics> for SceneStatistics {
     }
 }
 
+impl AddAssign<OcclusionStatistics> for SceneStatistics {
+    fn add_assign(&mut self, rhs: OcclusionStatistics) {
+        self.occlusion += rhs;
+    }
+}
+
 /// Renderer statistics for one frame, also includes current frames per second
 /// number.
 #[derive(Debug, Copy, Clone)]
@@ -132,6 +169,8 @@ pub struct Statistics {
     pub lighting: LightingStatistics,
     /// Shows how many draw calls was made and how many triangles were rendered.
     pub geometry: RenderPassStatistics,
+    /// Shows how many objects were drawn and how many were culled by occlusion culling.
+    pub occlusion: OcclusionStatistics,
     /// Real time consumed to render a frame. Time given in **seconds**.
     pub pure_frame_time: f32,
     /// Total time renderer took to process single frame, usually includes time the renderer spent
@@ -158,6 +197,7 @@ impl std::ops::AddAssign<SceneStatistics> for Statistics {
         self.pipeline += rhs.pipeline;
         self.lighting += rhs.lighting;
         self.geometry += rhs.geometry;
+        self.occlusion += rhs.occlusion;
     }
 }
 
@@ -168,6 +208,7 @@ impl Display for Statistics {
         let capped_frame_time = self.capped_frame_time * 1000.0;
         let geometry_stats = &self.geometry;
         let lighting_stats = &self.lighting;
+        let occlusion_stats = &self.occlusion;
         let pipeline_stats = &self.pipeline;
         let texture_cache_size = self.texture_cache_size;
         let geometry_cache_size = self.geometry_cache_size;
@@ -180,6 +221,7 @@ impl Display for Statistics {
             Capped Frame Time: {capped_frame_time:.2} ms\n\
             {geometry_stats}\n\
             {lighting_stats}\n\
+            {occlusion_stats}\n\
             {pipeline_stats}\n\
             Texture Cache Size: {texture_cache_size}\n\
             Geometry Cache Size: {geometry_cache_size}\n\
@@ -201,6 +243,7 @@ impl Default for Statistics {
             pipeline: Default::default(),
             lighting: Default::default(),
             geometry: Default::default(),
+            occlusion: Default::default(),
             pure_frame_time: 0.0,
             capped_frame_time: 0.0,
             frames_per_second: 0,
@@ -221,6 +264,7 @@ impl Statistics {
         self.frame_start_time = instant::Instant::now();
         self.geometry = Default::default();
         self.lighting = Default::default();
+        self.occlusion = Default::default();
     }
 
     /// Must be called before SwapBuffers but after all rendering is done.
@@ -23,10 +23,15 @@
 use crate::{
     asset::{io::ResourceIo, Resource, ResourceData},
     core::{
-        io::FileError, math::curve::Curve, reflect::prelude::*, uuid::Uuid, visitor::prelude::*,
+        io::FileError,
+        math::curve::{Curve, CurveKey, CurveKeyKind},
+        reflect::prelude::*,
+        uuid::Uuid,
+        visitor::prelude::*,
         TypeUuidProvider,
     },
 };
+use serde::{Deserialize, Serialize};
 use std::error::Error;
 use std::{
     fmt::{Display, Formatter},
@@ -44,6 +49,9 @@ pub enum CurveResourceError {
 
     /// An error that may occur due to version incompatibilities.
     Visit(VisitError),
+
+    /// An error that may occur during reading or writing a curve in JSON format.
+    Json(serde_json::Error),
 }
 
 impl Display for CurveResourceError {
@@ -58,6 +66,9 @@ impl Display for CurveResourceError {
                     "An error that may occur due to version incompatibilities. {v:?}"
                 )
             }
+            CurveResourceError::Json(v) => {
+                write!(f, "A JSON error has occurred {v}")
+            }
         }
     }
 }
@@ -74,6 +85,129 @@ impl From<VisitError> for CurveResourceError {
     }
 }
 
+impl From<serde_json::Error> for CurveResourceError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+impl From<std::io::Error> for CurveResourceError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e.into())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum JsonCurveKeyKind {
+    Constant,
+    Linear,
+    Cubic {
+        left_tangent: f32,
+        right_tangent: f32,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonCurveKey {
+    id: Uuid,
+    location: f32,
+    value: f32,
+    kind: JsonCurveKeyKind,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonCurve {
+    id: Uuid,
+    name: String,
+    keys: Vec<JsonCurveKey>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonCurveFile {
+    curves: Vec<JsonCurve>,
+}
+
+impl From<&Curve> for JsonCurve {
+    fn from(curve: &Curve) -> Self {
+        Self {
+            id: curve.id(),
+            name: curve.name().to_string(),
+            keys: curve
+                .keys()
+                .iter()
+                .map(|key| JsonCurveKey {
+                    id: key.id,
+                    location: key.location(),
+                    value: key.value,
+                    kind: match key.kind {
+                        CurveKeyKind::Constant => JsonCurveKeyKind::Constant,
+                        CurveKeyKind::Linear => JsonCurveKeyKind::Linear,
+                        CurveKeyKind::Cubic {
+                            left_tangent,
+                            right_tangent,
+                        } => JsonCurveKeyKind::Cubic {
+                            left_tangent,
+                            right_tangent,
+                        },
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<JsonCurve> for Curve {
+    fn from(json: JsonCurve) -> Self {
+        let mut curve = Curve::from(
+            json.keys
+                .into_iter()
+                .map(|key| CurveKey {
+                    id: key.id,
+                    location: key.location,
+                    value: key.value,
+                    kind: match key.kind {
+                        JsonCurveKeyKind::Constant => CurveKeyKind::Constant,
+                        JsonCurveKeyKind::Linear => CurveKeyKind::Linear,
+                        JsonCurveKeyKind::Cubic {
+                            left_tangent,
+                            right_tangent,
+                        } => CurveKeyKind::Cubic {
+                            left_tangent,
+                            right_tangent,
+                        },
+                    },
+                })
+                .collect::<Vec<_>>(),
+        );
+        curve.set_id(json.id);
+        curve.set_name(json.name);
+        curve
+    }
+}
+
+/// Returns `true` if the given path points to a curve in JSON format (`.json` extension),
+/// `false` - otherwise.
+pub fn is_json_path(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| fyrox_core::cmp_strings_case_insensitive(ext.to_string_lossy(), "json"))
+}
+
+/// Serializes the given curves to a human-readable JSON text. Every curve is stored with its name and
+/// keys (location, value, interpolation kind and tangents). Floating point values are written with
+/// enough precision to be read back exactly.
+pub fn curves_to_json(curves: &[Curve]) -> Result<String, CurveResourceError> {
+    let file = JsonCurveFile {
+        curves: curves.iter().map(JsonCurve::from).collect(),
+    };
+    Ok(serde_json::to_string_pretty(&file)?)
+}
+
+/// Deserializes curves from a JSON text, created by [`curves_to_json`].
+pub fn curves_from_json(bytes: &[u8]) -> Result<Vec<Curve>, CurveResourceError> {
+    let file = serde_json::from_slice::<JsonCurveFile>(bytes)?;
+    Ok(file.curves.into_iter().map(Curve::from).collect())
+}
+
 /// State of the [`CurveResource`]
 #[derive(Debug, Clone, Visit, Default, Reflect)]
 pub struct CurveResourceState {
@@ -107,15 +241,99 @@ impl TypeUuidProvider for CurveResourceState {
 }
 
 impl CurveResourceState {
-    /// Load a curve resource from the specific file path.
+    /// Load a curve resource from the specific file path. Files with `.json` extension are read as
+    /// JSON text (see [`curves_from_json`]), the first curve of such file is used. Any other files
+    /// are read as binary curve files. Keep in mind, that the curve loader of the resource manager
+    /// is registered for `.curve` and `.crv` extensions only (`.json` is too generic to be claimed
+    /// by curves), so JSON curves can be loaded only by calling this method directly.
     pub async fn from_file(path: &Path, io: &dyn ResourceIo) -> Result<Self, CurveResourceError> {
         let bytes = io.load_file(path).await?;
+        if is_json_path(path) {
+            let curve = curves_from_json(&bytes)?
+                .into_iter()
+                .next()
+                .unwrap_or_default();
+            return Ok(Self { curve });
+        }
         let mut visitor = Visitor::load_from_memory(&bytes)?;
         let mut curve = Curve::default();
         curve.visit("Curve", &mut visitor)?;
         Ok(Self { curve })
     }
+
+    /// Writes the curve to the given file in a human-readable JSON format, see [`curves_to_json`]
+    /// for more info.
+    pub fn save_json(&self, path: &Path) -> Result<(), CurveResourceError> {
+        let text = curves_to_json(std::slice::from_ref(&self.curve))?;
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+
+    /// Evaluates the curve at `samples` evenly spaced points, see [`Curve::bake`] for more info.
+    pub fn bake(&self, samples: usize) -> Vec<f32> {
+        self.curve.bake(samples)
+    }
+
+    /// Evaluates the curve at the given location, respecting the interpolation kind and tangents
+    /// of each key. Locations outside the key range are clamped to the values of the first and
+    /// the last keys, see [`Curve::value_at`] for more info.
+    pub fn value_at(&self, location: f32) -> f32 {
+        self.curve.value_at(location)
+    }
 }
 
 /// Type alias for curve resources.
 pub type CurveResource = Resource<CurveResourceState>;
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::math::curve::{Curve, CurveKey, CurveKeyKind},
+        resource::curve::{curves_from_json, curves_to_json, CurveResourceState},
+    };
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut curve = Curve::from(vec![
+            CurveKey::new(0.0, 1.0, CurveKeyKind::Constant),
+            CurveKey::new(0.1, -0.3, CurveKeyKind::Linear),
+            CurveKey::new(1.7, 3.5, CurveKeyKind::new_cubic(0.3, -1.1)),
+        ]);
+        curve.set_name("Fade");
+        let curves = vec![curve, Curve::default()];
+
+        let json = curves_to_json(&curves).unwrap();
+        let loaded = curves_from_json(json.as_bytes()).unwrap();
+        assert_eq!(loaded, curves);
+    }
+
+    #[test]
+    fn test_value_at() {
+        let state = CurveResourceState {
+            curve: Curve::from(vec![
+                CurveKey::new(0.0, 0.0, CurveKeyKind::Constant),
+                CurveKey::new(1.0, 1.0, CurveKeyKind::Linear),
+                CurveKey::new(2.0, 3.0, CurveKeyKind::new_cubic(0.0, 0.0)),
+                CurveKey::new(3.0, 1.0, CurveKeyKind::new_cubic(0.0, 0.0)),
+            ]),
+        };
+
+        // Constant segment holds the value of the left key.
+        assert_eq!(state.value_at(0.0), 0.0);
+        assert_eq!(state.value_at(0.5), 0.0);
+        assert_eq!(state.value_at(0.99), 0.0);
+
+        // Linear segment.
+        assert_eq!(state.value_at(1.0), 1.0);
+        assert_eq!(state.value_at(1.5), 2.0);
+
+        // Cubic segment with flat tangents.
+        assert_eq!(state.value_at(2.0), 3.0);
+        assert_eq!(state.value_at(2.5), 2.0);
+        assert!((state.value_at(2.25) - 2.6875).abs() < 1.0e-5);
+
+        // Out-of-range locations are clamped to the endpoint values.
+        assert_eq!(state.value_at(-1.0), 0.0);
+        assert_eq!(state.value_at(10.0), 1.0);
+    }
+}
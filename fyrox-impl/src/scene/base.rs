@@ -395,7 +395,8 @@ pub struct Base {
     enabled: TrackedProperty<InheritableVariable<bool>>,
 
     /// Control whether this node should be rendered. A node should be rendered only if its render mask shares
-    /// some set bits in common with the render mask of the camera.
+    /// some set bits in common with the render mask of the camera (see
+    /// [`crate::scene::camera::Camera::render_mask`]).
     pub render_mask: InheritableVariable<BitMask>,
 
     /// Maximum amount of Some(time) that node will \"live\" or None if the node has unlimited lifetime.
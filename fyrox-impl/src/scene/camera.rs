@@ -23,10 +23,10 @@
 use crate::{
     asset::{state::LoadError, untyped::ResourceKind},
     core::{
-        algebra::{Matrix4, Point3, Vector2, Vector3, Vector4},
+        algebra::{Matrix4, Point3, RowVector4, UnitQuaternion, Vector2, Vector3, Vector4},
         color::Color,
-        math::{aabb::AxisAlignedBoundingBox, frustum::Frustum, ray::Ray, Rect},
-        pool::Handle,
+        math::{aabb::AxisAlignedBoundingBox, frustum::Frustum, plane::Plane, ray::Ray, Rect},
+        pool::{Handle, PoolError},
         reflect::prelude::*,
         type_traits::prelude::*,
         uuid::{uuid, Uuid},
@@ -35,20 +35,25 @@ use crate::{
         visitor::{Visit, VisitResult, Visitor},
     },
     graph::SceneGraph,
+    renderer::IRRADIANCE_MAP_SIZE,
     resource::texture::{
         TextureKind, TexturePixelKind, TextureResource, TextureResourceExtension, TextureWrapMode,
     },
     scene::{
         base::{Base, BaseBuilder},
+        collider::BitMask,
         debug::SceneDrawingContext,
         graph::Graph,
         node::constructor::NodeConstructor,
         node::{Node, NodeTrait, UpdateContext},
+        transform::TransformBuilder,
     },
 };
+use fxhash::FxHashSet;
 use fyrox_graph::constructor::ConstructorProvider;
 use serde::{Deserialize, Serialize};
 use std::{
+    cell::Cell,
     fmt::{Display, Formatter},
     ops::{Deref, DerefMut},
 };
@@ -72,6 +77,19 @@ pub struct PerspectiveProjection {
     /// treated like near clipping plane.
     #[reflect(min_value = 0.0, step = 0.1)]
     pub z_far: f32,
+    /// If set, the projection matrix will have its far clipping plane at infinity, which removes
+    /// clipping and precision artifacts at extreme distances (skyboxes, very large scenes, etc.).
+    /// [`Self::z_far`] is still used for frustum culling, shadows and other effects that require
+    /// finite depth range.
+    #[visit(optional)]
+    #[serde(default)]
+    pub infinite_far: bool,
+    /// Optional view-space plane that replaces the near clipping plane. See
+    /// [`Projection::with_oblique_clip_plane`] docs for more info.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    #[serde(skip)]
+    pub oblique_clip_plane: Option<Plane>,
 }
 
 impl Default for PerspectiveProjection {
@@ -80,10 +98,40 @@ impl Default for PerspectiveProjection {
             fov: 75.0f32.to_radians(),
             z_near: 0.025,
             z_far: 2048.0,
+            infinite_far: false,
+            oblique_clip_plane: None,
         }
     }
 }
 
+/// Modifies the given projection matrix so it maps the near clipping plane to the far end of the
+/// depth range and vice versa (reversed depth).
+pub fn reverse_depth(matrix: &mut Matrix4<f32>) {
+    let z_row = -matrix.row(2);
+    matrix.set_row(2, &z_row);
+}
+
+/// Modifies the given projection matrix so its near clipping plane coincides with the given
+/// view-space plane. See "Oblique View Frustum Depth Projection and Clipping" by Eric Lengyel for
+/// more info. The camera must be on the negative side of the plane, otherwise the matrix is left
+/// unchanged.
+fn apply_oblique_clip_plane(matrix: &mut Matrix4<f32>, plane: &Plane) {
+    let clip_plane = Vector4::new(plane.normal.x, plane.normal.y, plane.normal.z, plane.d);
+    if clip_plane.w >= 0.0 {
+        return;
+    }
+
+    let Some(inv_matrix) = matrix.try_inverse() else {
+        return;
+    };
+
+    // Point of the frustum opposite to the clip plane in clip space.
+    let q = inv_matrix * Vector4::new(clip_plane.x.signum(), clip_plane.y.signum(), 1.0, 1.0);
+    let c = clip_plane * (2.0 / clip_plane.dot(&q));
+    let z_row = c.transpose() - matrix.row(3);
+    matrix.set_row(2, &z_row);
+}
+
 impl PerspectiveProjection {
     /// Returns perspective projection matrix.
     #[inline]
@@ -98,15 +146,71 @@ impl PerspectiveProjection {
             z_far += limit;
         }
 
-        Matrix4::new_perspective(
-            (frame_size.x / frame_size.y).max(limit),
-            self.fov,
-            z_near,
-            z_far,
-        )
+        let aspect = (frame_size.x / frame_size.y).max(limit);
+
+        let mut matrix = if self.infinite_far {
+            // Limit of the regular perspective matrix when z_far approaches infinity.
+            let f = 1.0 / (self.fov * 0.5).tan();
+            Matrix4::new(
+                f / aspect,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                f,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                -1.0,
+                -2.0 * z_near,
+                0.0,
+                0.0,
+                -1.0,
+                0.0,
+            )
+        } else {
+            Matrix4::new_perspective(aspect, self.fov, z_near, z_far)
+        };
+
+        if let Some(plane) = self.oblique_clip_plane.as_ref() {
+            apply_oblique_clip_plane(&mut matrix, plane);
+        }
+
+        matrix
     }
 }
 
+/// Defines which extent of the orthographic "view box" stays constant when the aspect ratio of the
+/// viewport changes.
+#[derive(
+    Reflect,
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Visit,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+    Serialize,
+    Deserialize,
+)]
+pub enum SizeMode {
+    /// Vertical size of the view box is fixed, horizontal size is derived from the aspect ratio.
+    /// Wider viewports will show more of the world horizontally.
+    #[default]
+    Vertical,
+    /// Horizontal size of the view box is fixed, vertical size is derived from the aspect ratio.
+    /// This is useful for 2D games that must always show the full width of a level regardless of
+    /// the aspect ratio (for example, on ultrawide monitors).
+    Horizontal,
+}
+
+uuid_provider!(SizeMode = "45c69729-6a0d-4dfa-a500-7849dc9620d0");
+
 /// Parallel projection. Object's size won't be affected by distance from the viewer, it can be
 /// used for 2D games.
 #[derive(Reflect, Clone, Debug, PartialEq, Visit, Serialize, Deserialize)]
@@ -124,6 +228,18 @@ pub struct OrthographicProjection {
     /// some minimal value to prevent singularities from occuring.
     #[reflect(step = 0.1)]
     pub vertical_size: f32,
+    /// Horizontal size of the "view box". It is used only when [`Self::size_mode`] is set to
+    /// [`SizeMode::Horizontal`], the vertical size then depends on the aspect ratio of the
+    /// viewport. Values very close to zero are clamped the same way as [`Self::vertical_size`].
+    #[reflect(step = 0.1)]
+    #[visit(optional)]
+    #[serde(default)]
+    pub horizontal_size: f32,
+    /// Defines which size of the "view box" stays constant when the aspect ratio of the viewport
+    /// changes. See [`SizeMode`] docs for more info.
+    #[visit(optional)]
+    #[serde(default)]
+    pub size_mode: SizeMode,
 }
 
 impl Default for OrthographicProjection {
@@ -132,6 +248,8 @@ impl Default for OrthographicProjection {
             z_near: 0.0,
             z_far: 2048.0,
             vertical_size: 5.0,
+            horizontal_size: 5.0,
+            size_mode: SizeMode::Vertical,
         }
     }
 }
@@ -155,8 +273,18 @@ impl OrthographicProjection {
         let aspect = (frame_size.x / frame_size.y).max(limit);
 
         // Prevent collapsing projection "box" into a point, which could cause panic.
-        let vertical_size = clamp_to_limit_signed(self.vertical_size, limit);
-        let horizontal_size = clamp_to_limit_signed(aspect * vertical_size, limit);
+        let (horizontal_size, vertical_size) = match self.size_mode {
+            SizeMode::Vertical => {
+                let vertical_size = clamp_to_limit_signed(self.vertical_size, limit);
+                let horizontal_size = clamp_to_limit_signed(aspect * vertical_size, limit);
+                (horizontal_size, vertical_size)
+            }
+            SizeMode::Horizontal => {
+                let horizontal_size = clamp_to_limit_signed(self.horizontal_size, limit);
+                let vertical_size = clamp_to_limit_signed(horizontal_size / aspect, limit);
+                (horizontal_size, vertical_size)
+            }
+        };
 
         let z_near = self.z_far.min(self.z_near);
         let mut z_far = self.z_far.max(self.z_near);
@@ -174,12 +302,112 @@ impl OrthographicProjection {
     }
 }
 
+/// Off-center (asymmetric) perspective projection defined by the extents of the view frustum at
+/// the near clipping plane. Unlike [`PerspectiveProjection`], the frustum does not have to be
+/// symmetric around the view direction, which is useful for VR eye rendering, portals, tiled
+/// rendering, etc. The extents are not adjusted to the aspect ratio of the viewport.
+#[derive(Reflect, Clone, Debug, PartialEq, Visit, Serialize, Deserialize)]
+pub struct FrustumProjection {
+    /// Coordinate of the left side of the frustum at the near clipping plane.
+    #[reflect(step = 0.001)]
+    pub left: f32,
+    /// Coordinate of the right side of the frustum at the near clipping plane.
+    #[reflect(step = 0.001)]
+    pub right: f32,
+    /// Coordinate of the bottom side of the frustum at the near clipping plane.
+    #[reflect(step = 0.001)]
+    pub bottom: f32,
+    /// Coordinate of the top side of the frustum at the near clipping plane.
+    #[reflect(step = 0.001)]
+    pub top: f32,
+    /// Location of the near clipping plane. If it is larger than [`Self::z_far`] then it will be
+    /// treated like far clipping plane.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub z_near: f32,
+    /// Location of the far clipping plane. If it is less than [`Self::z_near`] then it will be
+    /// treated like near clipping plane.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub z_far: f32,
+}
+
+impl Default for FrustumProjection {
+    fn default() -> Self {
+        Self {
+            left: -0.02,
+            right: 0.02,
+            bottom: -0.02,
+            top: 0.02,
+            z_near: 0.025,
+            z_far: 2048.0,
+        }
+    }
+}
+
+impl FrustumProjection {
+    /// Returns vertical angle of the frustum, in radians.
+    #[inline]
+    pub fn fov(&self) -> f32 {
+        let z_near = self.z_far.min(self.z_near);
+        if z_near <= 0.0 {
+            return 0.0;
+        }
+        (self.top / z_near).atan() - (self.bottom / z_near).atan()
+    }
+
+    /// Returns off-center perspective projection matrix.
+    #[inline]
+    pub fn matrix(&self) -> Matrix4<f32> {
+        fn non_zero(value: f32, limit: f32) -> f32 {
+            if value.abs() < limit {
+                limit
+            } else {
+                value
+            }
+        }
+
+        let limit = 10.0 * f32::EPSILON;
+
+        let z_near = self.z_far.min(self.z_near).max(limit);
+        let mut z_far = self.z_far.max(self.z_near);
+
+        // Prevent planes from superimposing which could cause panic.
+        if z_far - z_near < limit {
+            z_far += limit;
+        }
+
+        // Prevent collapsing the frustum into a line.
+        let width = non_zero(self.right - self.left, limit);
+        let height = non_zero(self.top - self.bottom, limit);
+        let depth = z_far - z_near;
+
+        Matrix4::new(
+            2.0 * z_near / width,
+            0.0,
+            (self.right + self.left) / width,
+            0.0,
+            0.0,
+            2.0 * z_near / height,
+            (self.top + self.bottom) / height,
+            0.0,
+            0.0,
+            0.0,
+            -(z_far + z_near) / depth,
+            -2.0 * z_far * z_near / depth,
+            0.0,
+            0.0,
+            -1.0,
+            0.0,
+        )
+    }
+}
+
 /// A method of projection. Different projection types suitable for different purposes:
 ///
 /// 1) Perspective projection most useful for 3D games, it makes a scene to look most natural,
 /// objects will look smaller with increasing distance.
 /// 2) Orthographic projection most useful for 2D games, objects won't look smaller with increasing
 /// distance.
+/// 3) Frustum projection is an off-center perspective projection, it is useful for VR and portals.
 #[derive(
     Reflect,
     Clone,
@@ -197,6 +425,8 @@ pub enum Projection {
     Perspective(PerspectiveProjection),
     /// See [`OrthographicProjection`] docs.
     Orthographic(OrthographicProjection),
+    /// See [`FrustumProjection`] docs.
+    Frustum(FrustumProjection),
 }
 
 uuid_provider!(Projection = "0eb5bec0-fc4e-4945-99b6-e6c5392ad971");
@@ -209,6 +439,7 @@ impl Projection {
         match self {
             Projection::Perspective(ref mut v) => v.z_near = z_near,
             Projection::Orthographic(ref mut v) => v.z_near = z_near,
+            Projection::Frustum(ref mut v) => v.z_near = z_near,
         }
         self
     }
@@ -220,6 +451,7 @@ impl Projection {
         match self {
             Projection::Perspective(ref mut v) => v.z_far = z_far,
             Projection::Orthographic(ref mut v) => v.z_far = z_far,
+            Projection::Frustum(ref mut v) => v.z_far = z_far,
         }
         self
     }
@@ -230,6 +462,7 @@ impl Projection {
         match self {
             Projection::Perspective(v) => v.z_near = z_near,
             Projection::Orthographic(v) => v.z_near = z_near,
+            Projection::Frustum(v) => v.z_near = z_near,
         }
     }
 
@@ -239,6 +472,7 @@ impl Projection {
         match self {
             Projection::Perspective(v) => v.z_far = z_far,
             Projection::Orthographic(v) => v.z_far = z_far,
+            Projection::Frustum(v) => v.z_far = z_far,
         }
     }
 
@@ -248,15 +482,93 @@ impl Projection {
         match self {
             Projection::Perspective(v) => v.z_near,
             Projection::Orthographic(v) => v.z_near,
+            Projection::Frustum(v) => v.z_near,
         }
     }
 
-    /// Returns far clipping plane distance.
+    /// Returns far clipping plane distance. It is [`f32::INFINITY`] for perspective projection
+    /// with infinite far plane, use [`Self::finite_z_far`] if you need a finite value.
     #[inline]
     pub fn z_far(&self) -> f32 {
+        match self {
+            Projection::Perspective(v) if v.infinite_far => f32::INFINITY,
+            Projection::Perspective(v) => v.z_far,
+            Projection::Orthographic(v) => v.z_far,
+            Projection::Frustum(v) => v.z_far,
+        }
+    }
+
+    /// Returns far clipping plane distance ignoring infinite far plane flag. This value is used
+    /// for frustum culling, shadows, etc.
+    #[inline]
+    pub fn finite_z_far(&self) -> f32 {
         match self {
             Projection::Perspective(v) => v.z_far,
             Projection::Orthographic(v) => v.z_far,
+            Projection::Frustum(v) => v.z_far,
+        }
+    }
+
+    /// Returns `true` if the projection has its far clipping plane at infinity.
+    #[inline]
+    pub fn is_infinite_far(&self) -> bool {
+        matches!(self, Projection::Perspective(v) if v.infinite_far)
+    }
+
+    /// Sets a plane that replaces the near clipping plane of the projection (oblique near-plane
+    /// clipping). Everything on the negative side of the plane will be clipped, which is useful for
+    /// planar reflections (water, mirrors) to prevent the geometry behind the reflection plane from
+    /// leaking into the reflection. This works only for perspective projection, returns `false`
+    /// and does nothing for orthographic projection.
+    ///
+    /// The plane must be defined in view space and the camera must be on its negative side. A
+    /// world-space plane `(n, d)` could be transformed into view space by multiplying it by the
+    /// inverse-transpose of the view matrix of the camera. Keep in mind that the oblique plane
+    /// makes depth precision worse as the plane gets closer to the camera.
+    #[inline]
+    pub fn with_oblique_clip_plane(&mut self, plane: Plane) -> bool {
+        match self {
+            Projection::Perspective(v) => {
+                v.oblique_clip_plane = Some(plane);
+                true
+            }
+            Projection::Orthographic(_) | Projection::Frustum(_) => false,
+        }
+    }
+
+    /// Removes oblique clip plane and restores the regular near clipping plane.
+    #[inline]
+    pub fn clear_oblique_clip_plane(&mut self) {
+        if let Projection::Perspective(v) = self {
+            v.oblique_clip_plane = None;
+        }
+    }
+
+    /// Returns current oblique clip plane (if any).
+    #[inline]
+    pub fn oblique_clip_plane(&self) -> Option<Plane> {
+        match self {
+            Projection::Perspective(v) => v.oblique_clip_plane,
+            Projection::Orthographic(_) | Projection::Frustum(_) => None,
+        }
+    }
+
+    /// Returns vertical field of view (in radians) of perspective projection, `None` for other
+    /// projection kinds. Use [`FrustumProjection::fov`] to get the angle of off-center projection.
+    #[inline]
+    pub fn fov(&self) -> Option<f32> {
+        match self {
+            Projection::Perspective(v) => Some(v.fov),
+            Projection::Orthographic(_) | Projection::Frustum(_) => None,
+        }
+    }
+
+    /// Sets new vertical field of view (in radians) of perspective projection. Does nothing for
+    /// other projection kinds.
+    #[inline]
+    pub fn set_fov(&mut self, fov: f32) {
+        if let Projection::Perspective(v) = self {
+            v.fov = fov;
         }
     }
 
@@ -266,6 +578,7 @@ impl Projection {
         match self {
             Projection::Perspective(v) => v.matrix(frame_size),
             Projection::Orthographic(v) => v.matrix(frame_size),
+            Projection::Frustum(v) => v.matrix(),
         }
     }
 
@@ -280,6 +593,12 @@ impl Projection {
     pub fn is_orthographic(&self) -> bool {
         matches!(self, Projection::Orthographic(_))
     }
+
+    /// Returns `true` if the current projection is off-center perspective (frustum).
+    #[inline]
+    pub fn is_frustum(&self) -> bool {
+        matches!(self, Projection::Frustum(_))
+    }
 }
 
 impl Default for Projection {
@@ -315,6 +634,14 @@ pub enum Exposure {
         /// bright images. The default value is 10.0.
         #[reflect(min_value = 0.0, step = 0.1)]
         max_luminance: f32,
+        /// Rate at which the exposure eases toward the target exposure of the current frame. Each
+        /// frame the exposure moves by `1 - exp(-dt * adaptation_speed)` of the remaining distance,
+        /// so the higher the value, the faster the adaptation; very large values make it instant.
+        /// Zero value means that [`Camera::hdr_adaptation_speed`] is used instead.
+        #[reflect(min_value = 0.0, step = 0.1)]
+        #[visit(optional)]
+        #[serde(default)]
+        adaptation_speed: f32,
     },
 
     /// Specific exposure level. To "disable" any HDR effects use 1.0 as a value. This is the default
@@ -330,6 +657,203 @@ impl Default for Exposure {
     }
 }
 
+impl Exposure {
+    /// Calculates exposure level for the given average frame luminance. Manual exposure ignores
+    /// the luminance and returns its fixed level. This matches the calculation done by the
+    /// renderer when it converts HDR image to LDR.
+    pub fn evaluate(&self, average_luminance: f32) -> f32 {
+        match self {
+            Exposure::Auto {
+                min_luminance,
+                max_luminance,
+                ..
+            } => {
+                let clamped = average_luminance.clamp(*min_luminance, *max_luminance);
+                1.0 / (9.6 * clamped + 0.0001)
+            }
+            Exposure::Manual(fixed_exposure) => *fixed_exposure,
+        }
+    }
+}
+
+/// Manual white balance of a camera. It is a cheap per-pixel color transform that is applied to the
+/// HDR image of the camera before tone mapping, so it composes with [`ColorGradingLut`]. Neutral
+/// values (6500K temperature and zero tint) do not change the image.
+#[derive(Visit, Copy, Clone, PartialEq, Debug, Reflect)]
+pub struct WhiteBalance {
+    /// Color temperature (in Kelvins) of the light that tints the image. Lower values make the
+    /// image warmer (more orange), higher values make it colder (more blue). Default value is
+    /// 6500K, which is neutral.
+    #[reflect(min_value = 1000.0, max_value = 40000.0, step = 100.0)]
+    pub temperature: f32,
+    /// Shift of the image along green-magenta axis. Positive values make the image more magenta,
+    /// negative values make it more green. Default value is 0.0, which is neutral.
+    #[reflect(min_value = -1.0, max_value = 1.0, step = 0.01)]
+    pub tint: f32,
+}
+
+uuid_provider!(WhiteBalance = "91390946-b44d-48d9-ab47-6967e6272bc9");
+
+impl Default for WhiteBalance {
+    fn default() -> Self {
+        Self {
+            temperature: Self::NEUTRAL_TEMPERATURE,
+            tint: 0.0,
+        }
+    }
+}
+
+impl WhiteBalance {
+    /// Color temperature that does not change the image.
+    pub const NEUTRAL_TEMPERATURE: f32 = 6500.0;
+
+    /// Creates new white balance settings.
+    pub fn new(temperature: f32, tint: f32) -> Self {
+        Self {
+            temperature: temperature.clamp(1000.0, 40000.0),
+            tint: tint.clamp(-1.0, 1.0),
+        }
+    }
+
+    /// Calculates linear RGB multiplier, that is applied to each pixel of the image. The multiplier
+    /// is relative to the neutral temperature, so the neutral settings give exactly `(1, 1, 1)`.
+    pub fn multiplier(&self) -> Vector3<f32> {
+        let color = temperature_to_rgb(self.temperature);
+        let neutral = temperature_to_rgb(Self::NEUTRAL_TEMPERATURE);
+        let mut multiplier = color.component_div(&neutral);
+        multiplier.y *= 1.0 - 0.5 * self.tint;
+        multiplier
+    }
+}
+
+/// Approximates the color of black body radiation of the given temperature (in Kelvins). Based on
+/// the approximation by Tanner Helland. The result is not normalized.
+fn temperature_to_rgb(temperature: f32) -> Vector3<f32> {
+    let t = temperature.clamp(1000.0, 40000.0) / 100.0;
+    let r = if t <= 66.0 {
+        255.0
+    } else {
+        329.69873 * (t - 60.0).powf(-0.13320476)
+    };
+    let g = if t <= 66.0 {
+        99.4708 * t.ln() - 161.11957
+    } else {
+        288.12216 * (t - 60.0).powf(-0.075514846)
+    };
+    let b = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.51773 * (t - 10.0).ln() - 305.0448
+    };
+    // Keep a tiny amount of each channel, so the multiplier never fully kills a channel.
+    Vector3::new(r, g, b).map(|c| c.clamp(1.0, 255.0) / 255.0)
+}
+
+/// Defines how the color buffer of a camera is cleared before rendering the scene: with the scene
+/// clear color, with an explicit color or not at all.
+#[derive(
+    Visit, Copy, Clone, PartialEq, Debug, Default, Reflect, AsRefStr, EnumString, VariantNames,
+)]
+pub enum CameraClearColor {
+    /// Clears the color buffer with [`crate::scene::SceneRenderingOptions::clear_color`] (or with
+    /// the back buffer clear color of the renderer, if the scene does not specify one). The skybox
+    /// is drawn as usual. This is the default option.
+    #[default]
+    Scene,
+
+    /// Clears the color buffer with the specified color.
+    Color(Color),
+
+    /// Keeps the previous content of the color buffer, the skybox is not drawn either. Could be
+    /// used for overlay cameras that must draw their content on top of the frame of some other
+    /// camera.
+    Keep,
+}
+
+uuid_provider!(CameraClearColor = "83633bf8-2e3f-4303-9958-54830374366d");
+
+/// Vignette is a post effect that darkens (or tints) the edges of the final image of a camera. It is
+/// applied after tone mapping and color grading.
+#[derive(Visit, Copy, Clone, PartialEq, Debug, Reflect)]
+pub struct Vignette {
+    /// Strength of the vignette in `[0; 1]` range. It defines how far from the corners of the
+    /// image the vignette starts. Zero value disables the vignette. Default value is 0.0.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub intensity: f32,
+    /// Width of the transition between the image and the vignette in `[0.01; 1]` range. The
+    /// higher the value, the softer the vignette. Default value is 0.5.
+    #[reflect(min_value = 0.01, max_value = 1.0, step = 0.01)]
+    pub smoothness: f32,
+    /// Color of the vignette. Alpha channel defines the opacity of the vignette. Default value is
+    /// opaque black.
+    pub color: Color,
+}
+
+uuid_provider!(Vignette = "94618b53-7cdd-4427-ab4a-4138115f2786");
+
+impl Default for Vignette {
+    fn default() -> Self {
+        Self {
+            intensity: 0.0,
+            smoothness: 0.5,
+            color: Color::BLACK,
+        }
+    }
+}
+
+impl Vignette {
+    /// Returns `true` if the vignette changes the image, `false` - otherwise.
+    pub fn is_enabled(&self) -> bool {
+        self.intensity > 0.0 && self.color.a > 0
+    }
+}
+
+/// Depth of field is a post effect that blurs the parts of the image that are out of focus. The
+/// strength of the blur (circle of confusion) is calculated from the distance to each pixel, the
+/// sky is considered to be at the far clipping plane. The effect also must be enabled in the
+/// quality settings of the renderer (see `QualitySettings::use_depth_of_field`).
+#[derive(Visit, Copy, Clone, PartialEq, Debug, Reflect)]
+pub struct DepthOfField {
+    /// Whether the effect is enabled or not. Default value is `false`.
+    pub enabled: bool,
+    /// Distance (in meters) from the camera to the plane that is in perfect focus. It is ignored
+    /// if the camera has an auto-focus target, see [`Camera::set_auto_focus`]. Default value is
+    /// 10.0.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub focus_distance: f32,
+    /// Depth (in meters) of the area around the focus plane that stays sharp. Outside of it, the
+    /// blur reaches its maximum over the same distance. Default value is 5.0.
+    #[reflect(min_value = 0.001, step = 0.1)]
+    pub focal_range: f32,
+    /// Maximum radius of the blur in pixels. Default value is 8.0.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub max_blur: f32,
+}
+
+uuid_provider!(DepthOfField = "6933198a-4254-40d3-b395-0d3c1a056835");
+
+impl Default for DepthOfField {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            focus_distance: 10.0,
+            focal_range: 5.0,
+            max_blur: 8.0,
+        }
+    }
+}
+
+impl DepthOfField {
+    /// Calculates normalized circle of confusion (in `[0; 1]` range) for a point at the given
+    /// distance from the camera. This matches the calculation done by the renderer.
+    pub fn circle_of_confusion(&self, distance: f32) -> f32 {
+        let range = self.focal_range.max(0.001);
+        (((distance - self.focus_distance).abs() - 0.5 * range) / range).clamp(0.0, 1.0)
+    }
+}
+
 /// Camera allows you to see world from specific point in world. You must have at least one camera in
 /// your scene to see anything.
 ///
@@ -382,10 +906,63 @@ pub struct Camera {
     #[reflect(setter = "set_hdr_adaptation_speed")]
     hdr_adaptation_speed: InheritableVariable<f32>,
 
+    #[reflect(setter = "set_render_mask")]
+    #[visit(optional)]
+    render_mask: InheritableVariable<BitMask>,
+
+    #[reflect(setter = "set_white_balance_settings")]
+    #[visit(optional)]
+    white_balance: InheritableVariable<WhiteBalance>,
+
+    #[reflect(setter = "set_depth_of_field")]
+    #[visit(optional)]
+    depth_of_field: InheritableVariable<DepthOfField>,
+
+    #[reflect(setter = "set_auto_focus")]
+    #[visit(optional)]
+    auto_focus: InheritableVariable<Handle<Node>>,
+
+    #[reflect(setter = "set_vignette")]
+    #[visit(optional)]
+    vignette: InheritableVariable<Vignette>,
+
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    #[reflect(setter = "set_chromatic_aberration")]
+    #[visit(optional)]
+    chromatic_aberration: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_clear_color")]
+    #[visit(optional)]
+    clear_color: InheritableVariable<CameraClearColor>,
+
+    #[reflect(setter = "set_clear_depth")]
+    #[visit(optional)]
+    clear_depth: InheritableVariable<bool>,
+
     #[reflect(setter = "set_render_target")]
     #[visit(skip)]
     render_target: Option<TextureResource>,
 
+    #[reflect(setter = "set_visible_nodes_tracking")]
+    #[visit(optional)]
+    track_visible_nodes: InheritableVariable<bool>,
+
+    #[reflect(setter = "set_shake_intensity")]
+    #[visit(optional)]
+    shake_intensity: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_shake_seed")]
+    #[visit(optional)]
+    shake_seed: InheritableVariable<u64>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    shake: CameraShake,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    auto_focus_distance: Option<f32>,
+
     #[visit(skip)]
     #[reflect(hidden)]
     view_matrix: Matrix4<f32>,
@@ -393,6 +970,77 @@ pub struct Camera {
     #[visit(skip)]
     #[reflect(hidden)]
     projection_matrix: Matrix4<f32>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    visible_nodes: FxHashSet<Handle<Node>>,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    unlocked_exposure: Option<Exposure>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    average_luminance: Cell<Option<f32>>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    average_luminance_requested: Cell<bool>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    environment_irradiance: EnvironmentIrradiance,
+}
+
+/// Runtime state of the camera shake, see [`Camera::add_shake`].
+#[derive(Debug, Clone, Default)]
+struct CameraShake {
+    /// Current amount of trauma in `[0; 1]` range.
+    trauma: f32,
+    /// Time (in seconds) that is used to sample the noise. It advances only while the camera
+    /// is shaking, so the same sequence of impulses and time steps produces the same offsets.
+    time: f32,
+}
+
+/// Amount of trauma that is removed from the camera each second.
+const SHAKE_DECAY: f32 = 1.0;
+/// Frequency (in Hz) of the noise that drives the camera shake.
+const SHAKE_FREQUENCY: f32 = 15.0;
+/// Maximum rotation angle (in radians) of the camera shake at full trauma and unit intensity.
+const SHAKE_MAX_ANGLE: f32 = 0.1;
+/// Maximum positional offset of the camera shake at full trauma and unit intensity.
+const SHAKE_MAX_OFFSET: f32 = 0.1;
+
+/// Hashes the given lattice point to a pseudo-random value in `[-1; 1]` range.
+fn shake_hash(seed: u64, channel: u64, index: i64) -> f32 {
+    // SplitMix64 finalizer.
+    let mut x = seed
+        .wrapping_add(channel.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+        .wrapping_add((index as u64).wrapping_mul(0xBF58_476D_1CE4_E5B9));
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^= x >> 31;
+    (x >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+}
+
+/// Smooth one-dimensional value noise in `[-1; 1]` range. Every channel produces an independent
+/// signal for the same seed.
+fn shake_noise(seed: u64, channel: u64, t: f32) -> f32 {
+    let floor = t.floor();
+    let index = floor as i64;
+    let k = t - floor;
+    let k = k * k * (3.0 - 2.0 * k);
+    let a = shake_hash(seed, channel, index);
+    let b = shake_hash(seed, channel, index + 1);
+    a + (b - a) * k
+}
+
+/// Diffuse irradiance of the environment of a camera, see [`Camera::environment_irradiance`].
+#[derive(Debug, Clone, Default)]
+struct EnvironmentIrradiance {
+    /// Environment map the irradiance map was created for.
+    source: Option<TextureResource>,
+    cube_map: Option<TextureResource>,
 }
 
 impl Deref for Camera {
@@ -452,6 +1100,25 @@ impl FitParameters {
     }
 }
 
+fn frame_aspect_ratio(frame_size: Vector2<f32>) -> f32 {
+    if frame_size.y > 0.0 {
+        (frame_size.x / frame_size.y).max(f32::EPSILON)
+    } else {
+        1.0
+    }
+}
+
+/// Result of a classification of a volume against camera's frustum.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Intersection {
+    /// The volume is fully inside the frustum.
+    Inside,
+    /// The volume is partially inside the frustum (it crosses at least one plane of the frustum).
+    Intersecting,
+    /// The volume is fully outside the frustum.
+    Outside,
+}
+
 impl Camera {
     /// Explicitly calculates view and projection matrices. Normally, you should not call
     /// this method, it will be called automatically when new frame starts.
@@ -462,9 +1129,92 @@ impl Camera {
         let up = self.base.up_vector();
 
         self.view_matrix = Matrix4::look_at_rh(&Point3::from(pos), &Point3::from(pos + look), &up);
+        if let Some(shake) = self.shake_matrix() {
+            self.view_matrix = shake * self.view_matrix;
+        }
         self.projection_matrix = self.projection.matrix(frame_size);
     }
 
+    /// Adds the given amount of "trauma" to the camera, which makes it shake. Trauma accumulates
+    /// over multiple impulses, it is clamped to `[0; 1]` range and decays over time. The shake is
+    /// applied on top of the view matrix of the camera, so the transform of the camera is never
+    /// modified and can be freely controlled by the game code while the camera is shaking.
+    ///
+    /// The strength of the shake is proportional to the squared trauma, which makes small impulses
+    /// barely noticeable and large ones violent. See [`Self::set_shake_intensity`] to scale
+    /// the strength of the shake.
+    pub fn add_shake(&mut self, trauma: f32) {
+        self.shake.trauma = (self.shake.trauma + trauma).clamp(0.0, 1.0);
+    }
+
+    /// Returns current amount of trauma of the camera in `[0; 1]` range. See [`Self::add_shake`]
+    /// for more info.
+    pub fn shake_trauma(&self) -> f32 {
+        self.shake.trauma
+    }
+
+    /// Immediately stops the camera shake.
+    pub fn stop_shake(&mut self) {
+        self.shake = Default::default();
+    }
+
+    /// Sets a scale of the camera shake. It scales both the rotational and positional offsets of
+    /// the camera; zero value disables the shake completely. Default value is `1.0`.
+    pub fn set_shake_intensity(&mut self, intensity: f32) -> f32 {
+        self.shake_intensity
+            .set_value_and_mark_modified(intensity.max(0.0))
+    }
+
+    /// Returns current scale of the camera shake.
+    pub fn shake_intensity(&self) -> f32 {
+        *self.shake_intensity
+    }
+
+    /// Sets a seed of the noise that drives the camera shake. The camera shake is fully
+    /// deterministic: the same seed, the same impulses and the same time steps will always
+    /// produce the same offsets, which is useful for replays.
+    pub fn set_shake_seed(&mut self, seed: u64) -> u64 {
+        self.shake_seed.set_value_and_mark_modified(seed)
+    }
+
+    /// Returns current seed of the noise that drives the camera shake.
+    pub fn shake_seed(&self) -> u64 {
+        *self.shake_seed
+    }
+
+    /// Advances the camera shake by the given amount of time (in seconds) and decays the trauma.
+    /// Normally, you should not call this method, it will be called automatically on each update
+    /// of the scene graph.
+    pub fn update_shake(&mut self, dt: f32) {
+        if self.shake.trauma > 0.0 {
+            self.shake.time += dt;
+            self.shake.trauma = (self.shake.trauma - SHAKE_DECAY * dt).max(0.0);
+        } else {
+            self.shake.time = 0.0;
+        }
+    }
+
+    /// Calculates a matrix that applies the current shake offset to the view matrix, or `None`
+    /// if the camera is not shaking.
+    fn shake_matrix(&self) -> Option<Matrix4<f32>> {
+        let shake = self.shake.trauma * self.shake.trauma * *self.shake_intensity;
+        if shake <= 0.0 {
+            return None;
+        }
+        let seed = *self.shake_seed;
+        let t = self.shake.time * SHAKE_FREQUENCY;
+        let noise = |channel| shake_noise(seed, channel, t);
+        let rotation = UnitQuaternion::from_euler_angles(
+            SHAKE_MAX_ANGLE * shake * noise(0),
+            SHAKE_MAX_ANGLE * shake * noise(1),
+            SHAKE_MAX_ANGLE * shake * noise(2),
+        );
+        let offset = Vector3::new(noise(3), noise(4), noise(5)) * SHAKE_MAX_OFFSET * shake;
+        // The shake is applied in the local space of the camera, so the view matrix must be
+        // multiplied by the inverse of the shake transform.
+        Some(rotation.inverse().to_homogeneous() * Matrix4::new_translation(&-offset))
+    }
+
     /// Sets new viewport in resolution-independent format. In other words
     /// each parameter of viewport defines portion of your current resolution
     /// in percents. In example viewport (0.0, 0.0, 0.5, 1.0) will force camera
@@ -552,6 +1302,24 @@ impl Camera {
         self.projection.set_value_and_mark_modified(projection)
     }
 
+    /// Returns vertical field of view (in radians) of the camera, `None` if the camera does not
+    /// use perspective projection. See [`Projection::fov`].
+    #[inline]
+    pub fn fov(&self) -> Option<f32> {
+        self.projection.fov()
+    }
+
+    /// Sets new vertical field of view (in radians) of the camera and returns the previous one.
+    /// Does nothing and returns `None` if the camera does not use perspective projection. This
+    /// could be used to animate field of view (zoom, sprint effects, etc.) without matching the
+    /// projection.
+    #[inline]
+    pub fn set_fov(&mut self, fov: f32) -> Option<f32> {
+        let old = self.projection.fov()?;
+        self.projection_mut().set_fov(fov);
+        Some(old)
+    }
+
     /// Returns state of camera: enabled or not.
     #[inline]
     pub fn is_enabled(&self) -> bool {
@@ -589,9 +1357,31 @@ impl Camera {
         (*self.environment).clone()
     }
 
+    /// Returns a low-resolution cube map with the prefiltered diffuse irradiance of the current
+    /// environment map, that is used by the renderer as ambient lighting of the camera. The cube
+    /// map is a render target, its content is calculated on GPU once the environment map changes
+    /// (or finishes loading), not every frame. Returns `None` if the camera has no environment map.
+    pub fn environment_irradiance(&self) -> Option<TextureResource> {
+        self.environment_irradiance.cube_map.clone()
+    }
+
+    fn update_environment_irradiance(&mut self) {
+        let environment = self.environment.as_ref();
+        if self.environment_irradiance.source.as_ref() == environment {
+            return;
+        }
+
+        self.environment_irradiance = EnvironmentIrradiance {
+            source: environment.cloned(),
+            cube_map: environment
+                .map(|_| TextureResource::new_cube_render_target(IRRADIANCE_MAP_SIZE as u32)),
+        };
+    }
+
     /// Sets the speed of automatic adaptation for the current frame luminance. In other words,
     /// it defines how fast the reaction to the new frame brightness will be. The lower the value,
-    /// the longer it will take to adjust the exposure for the new brightness level.
+    /// the longer it will take to adjust the exposure for the new brightness level. It is used
+    /// only if [`Exposure::Auto`] does not specify its own adaptation speed.
     pub fn set_hdr_adaptation_speed(&mut self, speed: f32) -> f32 {
         self.hdr_adaptation_speed.set_value_and_mark_modified(speed)
     }
@@ -601,24 +1391,194 @@ impl Camera {
         *self.hdr_adaptation_speed
     }
 
-    /// Creates picking ray from given screen coordinates.
-    pub fn make_ray(&self, screen_coord: Vector2<f32>, screen_size: Vector2<f32>) -> Ray {
-        let viewport = self.viewport_pixels(screen_size);
-        let nx = screen_coord.x / (viewport.w() as f32) * 2.0 - 1.0;
+    /// Sets a set of "layers" that will be rendered by the camera. A node will be rendered only if
+    /// its own render mask (see [`Base::render_mask`]) shares at least one set bit with the render
+    /// mask of the camera. This could be used, for example, in split-screen games to render some
+    /// objects only by the camera of a particular player. By default, the camera renders everything.
+    ///
+    /// The render mask of the camera node itself still limits what the camera renders, see
+    /// [`Self::effective_render_mask`].
+    ///
+    /// Returns the old render mask.
+    pub fn set_render_mask(&mut self, render_mask: BitMask) -> BitMask {
+        self.render_mask.set_value_and_mark_modified(render_mask)
+    }
+
+    /// Returns current render mask of the camera. See [`Self::set_render_mask`] for more info.
+    pub fn render_mask(&self) -> BitMask {
+        *self.render_mask
+    }
+
+    /// Returns the mask of "layers" that are actually rendered by the camera. It is the intersection
+    /// of the render mask of the camera (see [`Self::set_render_mask`]) and the render mask of the
+    /// camera node (see [`Base::render_mask`]). Before cameras had their own render mask, the render
+    /// mask of the camera node was used for that, so the intersection keeps existing scenes working
+    /// as before: the camera render mask is set to [`BitMask::all`] by default.
+    pub fn effective_render_mask(&self) -> BitMask {
+        *self.render_mask & *self.base.render_mask
+    }
+
+    /// Sets how the color buffer of the camera should be cleared before rendering. Use
+    /// [`CameraClearColor::Keep`] to draw the camera on top of the content rendered by other cameras
+    /// (for example, for overlays or picture-in-picture insertions).
+    ///
+    /// Returns the old value.
+    pub fn set_clear_color(&mut self, clear_color: CameraClearColor) -> CameraClearColor {
+        self.clear_color.set_value_and_mark_modified(clear_color)
+    }
+
+    /// Returns current clear color mode of the camera. See [`Self::set_clear_color`] for more info.
+    pub fn clear_color(&self) -> CameraClearColor {
+        *self.clear_color
+    }
+
+    /// Sets whether the depth buffer should be cleared before rendering. Keeping the depth makes
+    /// the camera content to be occluded by the geometry drawn before, which is rarely needed,
+    /// so the default value is `true`.
+    ///
+    /// Returns the old value.
+    pub fn set_clear_depth(&mut self, clear_depth: bool) -> bool {
+        self.clear_depth.set_value_and_mark_modified(clear_depth)
+    }
+
+    /// Returns `true` if the depth buffer is cleared before rendering, `false` - otherwise.
+    pub fn clear_depth(&self) -> bool {
+        *self.clear_depth
+    }
+
+    /// Transforms the given screen coordinates (with the origin at the left *upper* corner of the
+    /// screen) to the coordinates relative to the left upper corner of the viewport of the camera
+    /// (see [`Self::set_viewport`]). Keep in mind, that the position of the viewport is defined
+    /// from the left *bottom* corner of the screen (as in OpenGL). This is useful for split screen,
+    /// where each camera occupies only a part of the screen.
+    pub fn screen_to_viewport(
+        &self,
+        screen_coord: Vector2<f32>,
+        screen_size: Vector2<f32>,
+    ) -> Vector2<f32> {
+        let viewport = self.viewport_pixels(screen_size);
+        let top = screen_size.y - (viewport.y() + viewport.h()) as f32;
+        Vector2::new(screen_coord.x - viewport.x() as f32, screen_coord.y - top)
+    }
+
+    fn screen_to_ndc(&self, screen_coord: Vector2<f32>, screen_size: Vector2<f32>) -> Vector2<f32> {
+        let viewport = self.viewport_pixels(screen_size);
+        let coord = self.screen_to_viewport(screen_coord, screen_size);
+        let nx = coord.x / (viewport.w() as f32) * 2.0 - 1.0;
         // Invert y here because OpenGL has origin at left bottom corner,
         // but window coordinates starts from left *upper* corner.
-        let ny = (viewport.h() as f32 - screen_coord.y) / (viewport.h() as f32) * 2.0 - 1.0;
-        let inv_view_proj = self
-            .view_projection_matrix()
+        let ny = (viewport.h() as f32 - coord.y) / (viewport.h() as f32) * 2.0 - 1.0;
+        Vector2::new(nx, ny)
+    }
+
+    fn inv_view_projection_matrix(&self) -> Matrix4<f32> {
+        self.view_projection_matrix()
             .try_inverse()
-            .unwrap_or_default();
-        let near = inv_view_proj * Vector4::new(nx, ny, -1.0, 1.0);
-        let far = inv_view_proj * Vector4::new(nx, ny, 1.0, 1.0);
+            .unwrap_or_default()
+    }
+
+    /// Creates picking ray from given screen coordinates. The coordinates are in the space of the
+    /// whole screen (not the viewport of the camera), so the same cursor position could be used to
+    /// pick with every camera in split screen.
+    pub fn make_ray(&self, screen_coord: Vector2<f32>, screen_size: Vector2<f32>) -> Ray {
+        let ndc = self.screen_to_ndc(screen_coord, screen_size);
+        let inv_view_proj = self.inv_view_projection_matrix();
+        let near = inv_view_proj * Vector4::new(ndc.x, ndc.y, -1.0, 1.0);
         let begin = near.xyz().scale(1.0 / near.w);
-        let end = far.xyz().scale(1.0 / far.w);
+        let end = if self.projection.is_infinite_far() {
+            // The far point lies at infinity (w = 0), so move the near point away from the eye
+            // up to the finite far plane instead.
+            self.finite_far_point(begin)
+        } else {
+            let far = inv_view_proj * Vector4::new(ndc.x, ndc.y, 1.0, 1.0);
+            far.xyz().scale(1.0 / far.w)
+        };
         Ray::from_two_points(begin, end)
     }
 
+    /// Returns a point on the finite far clipping plane (see [`Projection::finite_z_far`]), that
+    /// lies on the line from the eye through the given world-space point. Works only for projections
+    /// with a single eye point (perspective ones).
+    fn finite_far_point(&self, point: Vector3<f32>) -> Vector3<f32> {
+        let eye = self.global_position();
+        let to_point = point - eye;
+        let depth = to_point.dot(&self.look_vector().normalize());
+        if depth > f32::EPSILON {
+            eye + to_point.scale(self.projection.finite_z_far() / depth)
+        } else {
+            point
+        }
+    }
+
+    /// Returns a world-space point under the given screen coordinates at the given normalized
+    /// depth, where 0.0 is the near clipping plane and 1.0 is the far clipping plane. Keep in mind
+    /// that the depth is non-linear for perspective projection. This could be useful to place
+    /// objects under the mouse cursor at some distance. If the point lies at infinity (depth 1.0
+    /// with infinite far plane), the point on the finite far clipping plane is returned (see
+    /// [`Projection::finite_z_far`]).
+    pub fn unproject(
+        &self,
+        screen_coord: Vector2<f32>,
+        depth: f32,
+        screen_size: Vector2<f32>,
+    ) -> Vector3<f32> {
+        let ndc = self.screen_to_ndc(screen_coord, screen_size);
+        let inv_view_proj = self.inv_view_projection_matrix();
+        let point = inv_view_proj * Vector4::new(ndc.x, ndc.y, depth * 2.0 - 1.0, 1.0);
+        if point.w.abs() > f32::EPSILON {
+            point.xyz().scale(1.0 / point.w)
+        } else {
+            let near = inv_view_proj * Vector4::new(ndc.x, ndc.y, -1.0, 1.0);
+            self.finite_far_point(near.xyz().scale(1.0 / near.w))
+        }
+    }
+
+    /// Returns eight world-space corners of the current frustum of the camera. See
+    /// [`Self::frustum_corners_for_range`] for more info. Far plane of a projection with infinite far
+    /// plane is replaced with the finite one (see [`Projection::finite_z_far`]).
+    pub fn frustum_corners(&self) -> [Vector3<f32>; 8] {
+        self.frustum_corners_for_range(self.projection.z_near(), self.projection.finite_z_far())
+    }
+
+    /// Returns eight world-space corners of a slice of the current frustum of the camera, that lies
+    /// between the given view-space distances. This is useful for cascaded shadow maps, where each
+    /// cascade covers its own slice of the frustum. Corners of the near plane go first, then the
+    /// corners of the far plane; each group is in the following order: left-bottom, right-bottom,
+    /// right-top, left-top. Corners of orthographic projection form a box.
+    ///
+    /// Keep in mind, that the corners are calculated using the matrices of the camera, which are
+    /// updated once per frame.
+    pub fn frustum_corners_for_range(&self, near: f32, far: f32) -> [Vector3<f32>; 8] {
+        let inv_projection = self.projection_matrix.try_inverse().unwrap_or_default();
+        let inv_view = self.view_matrix.try_inverse().unwrap_or_default();
+        // Zero depth lies between the clipping planes and stays finite even for projections with
+        // infinite far plane.
+        let unproject = |x: f32, y: f32, z: f32| {
+            let point = inv_projection * Vector4::new(x, y, z, 1.0);
+            point.xyz().scale(1.0 / point.w)
+        };
+
+        let mut corners = [Vector3::default(); 8];
+        for (i, (x, y)) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+            .into_iter()
+            .enumerate()
+        {
+            // Pick two view-space points on the edge of the frustum and slide along it to the
+            // requested distances. The edge goes through the origin for perspective projection and
+            // is parallel to the view axis for orthographic one.
+            let a = unproject(x, y, -1.0);
+            let b = unproject(x, y, 0.0);
+            let point_at = |distance: f32| {
+                let t = (distance + a.z) / (a.z - b.z);
+                let point = a + (b - a).scale(t);
+                inv_view.transform_point(&Point3::from(point)).coords
+            };
+            corners[i] = point_at(near);
+            corners[i + 4] = point_at(far);
+        }
+        corners
+    }
+
     /// Calculates new fitting parameters for the given axis-aligned bounding box using current camera's
     /// global transform and provided aspect ratio. See [`FitParameters`] docs for more info.
     ///
@@ -643,21 +1603,24 @@ impl Camera {
             .try_normalize(f32::EPSILON)
             .unwrap_or_default();
 
-        match self.projection.deref() {
-            Projection::Perspective(perspective) => {
-                let radius = aabb.half_extents().max();
+        let fit_perspective = |fov: f32| {
+            let radius = aabb.half_extents().max();
 
-                let denominator = (perspective.fov * 0.5).sin();
-                if denominator == 0.0 {
-                    return FitParameters::fallback_perspective();
-                }
+            let denominator = (fov * 0.5).sin();
+            if denominator == 0.0 {
+                return FitParameters::fallback_perspective();
+            }
 
-                let distance = radius / denominator * scale;
-                FitParameters::Perspective {
-                    position: aabb.center() - look_vector.scale(distance),
-                    distance,
-                }
+            let distance = radius / denominator * scale;
+            FitParameters::Perspective {
+                position: aabb.center() - look_vector.scale(distance),
+                distance,
             }
+        };
+
+        match self.projection.deref() {
+            Projection::Perspective(perspective) => fit_perspective(perspective.fov),
+            Projection::Frustum(frustum) => fit_perspective(frustum.fov()),
             Projection::Orthographic(_) => {
                 let mut min_x = f32::MAX;
                 let mut min_y = f32::MAX;
@@ -689,13 +1652,217 @@ impl Camera {
         }
     }
 
+    /// Sets the size of orthographic projection and moves the camera along its look vector, so the
+    /// given world-space axis-aligned bounding box is fully visible with the current orientation of
+    /// the camera. `frame_size` is the size of the frame the camera renders to, it is needed to take
+    /// the aspect ratio into account. Depending on [`OrthographicProjection::size_mode`], either the
+    /// vertical or the horizontal size of the projection is modified.
+    ///
+    /// Unlike [`Self::fit`], this method modifies the camera. The new local position is calculated
+    /// using the current global transform of the camera, so the transform must be up-to-date.
+    ///
+    /// Returns `false` and leaves the camera unchanged if it does not use orthographic projection or
+    /// the bounding box is invalid or has zero size.
+    pub fn fit_orthographic_to_aabb(
+        &mut self,
+        aabb: AxisAlignedBoundingBox,
+        frame_size: Vector2<f32>,
+    ) -> bool {
+        if aabb.is_invalid_or_degenerate() || !self.projection.is_orthographic() {
+            return false;
+        }
+
+        let [side, up, look] = self.view_axes();
+        let center = aabb.center();
+        let mut half_width = 0.0f32;
+        let mut half_height = 0.0f32;
+        let mut half_depth = 0.0f32;
+        for corner in aabb.corners() {
+            let offset = corner - center;
+            half_width = half_width.max(offset.dot(&side).abs());
+            half_height = half_height.max(offset.dot(&up).abs());
+            half_depth = half_depth.max(offset.dot(&look).abs());
+        }
+
+        if half_width <= f32::EPSILON && half_height <= f32::EPSILON {
+            return false;
+        }
+
+        let aspect_ratio = frame_aspect_ratio(frame_size);
+        let Projection::Orthographic(orthographic) = self.projection_mut() else {
+            return false;
+        };
+        match orthographic.size_mode {
+            SizeMode::Vertical => {
+                orthographic.vertical_size = half_height.max(half_width / aspect_ratio);
+            }
+            SizeMode::Horizontal => {
+                orthographic.horizontal_size = half_width.max(half_height * aspect_ratio);
+            }
+        }
+        let distance = half_depth + orthographic.z_far.min(orthographic.z_near);
+
+        self.set_global_position(center - look.scale(distance));
+
+        true
+    }
+
+    /// Moves the camera along its look vector, so the given world-space axis-aligned bounding box is
+    /// fully visible with the current orientation and field of view of the camera. `frame_size` is the
+    /// size of the frame the camera renders to, it is needed to take the horizontal field of view
+    /// into account. The projection itself is not modified.
+    ///
+    /// Unlike [`Self::fit`], this method modifies the camera. The new local position is calculated
+    /// using the current global transform of the camera, so the transform must be up-to-date.
+    ///
+    /// Returns `false` and leaves the camera unchanged if it does not use perspective projection or
+    /// the bounding box is invalid or has zero size.
+    pub fn fit_perspective_to_aabb(
+        &mut self,
+        aabb: AxisAlignedBoundingBox,
+        frame_size: Vector2<f32>,
+    ) -> bool {
+        if aabb.is_invalid_or_degenerate() {
+            return false;
+        }
+
+        let Projection::Perspective(perspective) = self.projection.deref() else {
+            return false;
+        };
+
+        let half_vertical_fov = perspective.fov * 0.5;
+        let half_horizontal_fov = (half_vertical_fov.tan() * frame_aspect_ratio(frame_size)).atan();
+        let sin_half_fov = half_vertical_fov.min(half_horizontal_fov).sin();
+        if sin_half_fov <= f32::EPSILON {
+            return false;
+        }
+
+        // Fit the bounding sphere of the box, so it stays visible regardless of the orientation.
+        let radius = aabb.half_extents().norm();
+        let z_near = perspective.z_far.min(perspective.z_near);
+        let distance = (radius / sin_half_fov).max(radius + z_near);
+
+        let [_, _, look] = self.view_axes();
+        self.set_global_position(aabb.center() - look.scale(distance));
+
+        true
+    }
+
+    fn view_axes(&self) -> [Vector3<f32>; 3] {
+        [
+            self.side_vector()
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(Vector3::x),
+            self.up_vector()
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(Vector3::y),
+            self.look_vector()
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(Vector3::z),
+        ]
+    }
+
+    fn set_global_position(&mut self, position: Vector3<f32>) {
+        let parent_transform = self.global_transform()
+            * self
+                .local_transform()
+                .matrix()
+                .try_inverse()
+                .unwrap_or_default();
+        let local_position = parent_transform
+            .try_inverse()
+            .unwrap_or_default()
+            .transform_point(&Point3::from(position))
+            .coords;
+        self.local_transform_mut().set_position(local_position);
+    }
+
     /// Returns current frustum of the camera.
     #[inline]
     pub fn frustum(&self) -> Frustum {
-        Frustum::from_view_projection_matrix(self.view_projection_matrix()).unwrap_or_default()
+        self.try_frustum().unwrap_or_default()
+    }
+
+    /// Returns current frustum of the camera or `None` if the view-projection matrix of the camera
+    /// is degenerate and the frustum cannot be built.
+    fn try_frustum(&self) -> Option<Frustum> {
+        let view_projection_matrix = match self.projection.deref() {
+            // Far plane of infinite projection is degenerate, use the finite one for culling.
+            Projection::Perspective(perspective) if perspective.infinite_far => {
+                let z_near = perspective.z_far.min(perspective.z_near);
+                let z_far = perspective.z_far.max(perspective.z_near);
+                let mut projection_matrix = self.projection_matrix;
+                projection_matrix.set_row(
+                    2,
+                    &RowVector4::new(
+                        0.0,
+                        0.0,
+                        (z_far + z_near) / (z_near - z_far),
+                        2.0 * z_far * z_near / (z_near - z_far),
+                    ),
+                );
+                projection_matrix * self.view_matrix
+            }
+            _ => self.view_projection_matrix(),
+        };
+        Frustum::from_view_projection_matrix(view_projection_matrix)
+    }
+
+    /// Checks whether the given world-space bounding box intersects the current frustum of the
+    /// camera. Unlike [`Self::is_node_visible`], this method does not depend on the last update of
+    /// the camera and can be used for one-off queries. If the frustum of the camera is degenerate,
+    /// the method conservatively returns `true`.
+    #[inline]
+    pub fn is_aabb_visible(&self, aabb: &AxisAlignedBoundingBox) -> bool {
+        self.try_frustum()
+            .is_none_or(|frustum| frustum.is_intersects_aabb(aabb))
+    }
+
+    /// Returns world-space planes of the current frustum of the camera. The order of the planes is:
+    /// left, right, top, bottom, far, near (see [`Frustum`] constants). Normals of the planes point
+    /// inside the frustum.
+    #[inline]
+    pub fn frustum_planes(&self) -> [Plane; 6] {
+        self.frustum().planes
+    }
+
+    /// Classifies the given world-space sphere against the frustum of the camera. Unlike simple
+    /// visibility test, this method tells whether the sphere is fully inside the frustum, which
+    /// could be used to skip visibility tests of children nodes in hierarchical culling.
+    #[inline]
+    pub fn classify_sphere(&self, center: Vector3<f32>, radius: f32) -> Intersection {
+        let mut result = Intersection::Inside;
+        for plane in self.frustum_planes().iter() {
+            let distance = plane.dot(&center);
+            if distance < -radius {
+                return Intersection::Outside;
+            } else if distance < radius {
+                result = Intersection::Intersecting;
+            }
+        }
+        result
+    }
+
+    /// Returns the size (in world units) of a single pixel of the viewport of the camera at the given
+    /// distance from the camera. For perspective and frustum projections the size grows linearly
+    /// with the distance and depends on the field of view, for orthographic projection it is
+    /// constant. This could be used to select levels of detail of meshes or to size decals based
+    /// on their size on screen.
+    pub fn world_units_per_pixel(&self, distance: f32, frame_size: Vector2<f32>) -> f32 {
+        // Vertical scale of the projection: `1/tan(fov/2)` for perspective projections, and
+        // `2/height` for orthographic projection.
+        let scale = self.projection.matrix(frame_size)[(1, 1)];
+        let visible_height = if self.projection.is_orthographic() {
+            2.0 / scale
+        } else {
+            2.0 * distance.max(0.0) / scale
+        };
+        visible_height / self.viewport_pixels(frame_size).h() as f32
     }
 
-    /// Projects given world space point on screen plane.
+    /// Projects given world space point on screen plane. The result is in the space of the whole
+    /// screen with the origin at the left upper corner, the viewport of the camera is taken into
+    /// account.
     pub fn project(
         &self,
         world_pos: Vector3<f32>,
@@ -708,14 +1875,45 @@ impl Camera {
             let k = (1.0 / proj.w) * 0.5;
             Some(Vector2::new(
                 viewport.x() as f32 + viewport.w() as f32 * (proj.x * k + 0.5),
-                viewport.h() as f32
-                    - (viewport.y() as f32 + viewport.h() as f32 * (proj.y * k + 0.5)),
+                screen_size.y - (viewport.y() as f32 + viewport.h() as f32 * (proj.y * k + 0.5)),
             ))
         } else {
             None
         }
     }
 
+    /// Enables or disables tracking of the nodes that are inside the camera frustum (see
+    /// [`Self::visible_nodes`]). Tracking checks every node of the graph on each update of the
+    /// camera, so it is disabled by default and should be enabled only when needed.
+    ///
+    /// Returns the old value.
+    pub fn set_visible_nodes_tracking(&mut self, enabled: bool) -> bool {
+        self.track_visible_nodes
+            .set_value_and_mark_modified(enabled)
+    }
+
+    /// Returns `true` if the camera tracks the nodes inside its frustum, `false` - otherwise. See
+    /// [`Self::set_visible_nodes_tracking`] for more info.
+    pub fn is_visible_nodes_tracking_enabled(&self) -> bool {
+        *self.track_visible_nodes
+    }
+
+    /// Returns an iterator over the handles of the nodes, whose world bounding boxes were inside
+    /// the camera frustum at the last update of the camera. Invisible nodes are excluded. The set
+    /// is empty until the camera is updated for the first time and it is always empty if the
+    /// tracking is disabled (see [`Self::set_visible_nodes_tracking`]).
+    ///
+    /// This is a coarse frustum check only, it does not account for occlusion.
+    pub fn visible_nodes(&self) -> impl Iterator<Item = Handle<Node>> + '_ {
+        self.visible_nodes.iter().copied()
+    }
+
+    /// Checks whether the given node was inside the camera frustum at the last update of the
+    /// camera. See [`Self::visible_nodes`] for more info.
+    pub fn is_node_visible(&self, handle: Handle<Node>) -> bool {
+        self.visible_nodes.contains(&handle)
+    }
+
     /// Sets new color grading LUT.
     pub fn set_color_grading_lut(
         &mut self,
@@ -745,8 +1943,104 @@ impl Camera {
         *self.color_grading_enabled
     }
 
-    /// Sets new exposure. See `Exposure` struct docs for more info.
+    /// Sets new white balance of the camera: color temperature in Kelvins and green-magenta tint in
+    /// `[-1; 1]` range. The values are clamped to their valid ranges, see [`WhiteBalance`] docs for
+    /// more info. Neutral values (6500K and 0.0 tint) do not change the image. Returns previous
+    /// white balance.
+    pub fn set_white_balance(&mut self, temperature: f32, tint: f32) -> WhiteBalance {
+        self.white_balance
+            .set_value_and_mark_modified(WhiteBalance::new(temperature, tint))
+    }
+
+    /// Sets new white balance of the camera from the given settings. This is the same as
+    /// [`Self::set_white_balance`], but takes both values at once.
+    pub fn set_white_balance_settings(&mut self, white_balance: WhiteBalance) -> WhiteBalance {
+        self.set_white_balance(white_balance.temperature, white_balance.tint)
+    }
+
+    /// Returns current color temperature (in Kelvins) of the white balance of the camera.
+    pub fn white_balance_temperature(&self) -> f32 {
+        self.white_balance.temperature
+    }
+
+    /// Returns current green-magenta tint of the white balance of the camera.
+    pub fn white_balance_tint(&self) -> f32 {
+        self.white_balance.tint
+    }
+
+    /// Returns current white balance settings of the camera.
+    pub fn white_balance(&self) -> WhiteBalance {
+        *self.white_balance
+    }
+
+    /// Sets new depth of field settings of the camera. See [`DepthOfField`] docs for more info.
+    pub fn set_depth_of_field(&mut self, mut depth_of_field: DepthOfField) -> DepthOfField {
+        depth_of_field.focus_distance = depth_of_field.focus_distance.max(0.0);
+        depth_of_field.focal_range = depth_of_field.focal_range.max(0.001);
+        depth_of_field.max_blur = depth_of_field.max_blur.max(0.0);
+        self.depth_of_field
+            .set_value_and_mark_modified(depth_of_field)
+    }
+
+    /// Returns current depth of field settings of the camera.
+    pub fn depth_of_field(&self) -> DepthOfField {
+        *self.depth_of_field
+    }
+
+    /// Sets a node, that the camera will keep in focus. The focus distance of the depth of field
+    /// (see [`DepthOfField::focus_distance`]) is replaced with the distance to the node on each
+    /// update of the camera. Pass [`Handle::NONE`] to use the fixed focus distance.
+    pub fn set_auto_focus(&mut self, target: Handle<Node>) -> Handle<Node> {
+        if target.is_none() {
+            self.auto_focus_distance = None;
+        }
+        self.auto_focus.set_value_and_mark_modified(target)
+    }
+
+    /// Returns current auto-focus target of the camera.
+    pub fn auto_focus(&self) -> Handle<Node> {
+        *self.auto_focus
+    }
+
+    /// Returns the distance to the plane that is in perfect focus. It is either the distance to
+    /// the auto-focus target (as of the last update of the camera), or the fixed focus distance
+    /// of the depth of field.
+    pub fn focus_distance(&self) -> f32 {
+        self.auto_focus_distance
+            .unwrap_or(self.depth_of_field.focus_distance)
+    }
+
+    /// Sets new vignette of the camera. See [`Vignette`] docs for more info. Vignette with zero
+    /// intensity is not rendered at all.
+    pub fn set_vignette(&mut self, mut vignette: Vignette) -> Vignette {
+        vignette.intensity = vignette.intensity.clamp(0.0, 1.0);
+        vignette.smoothness = vignette.smoothness.clamp(0.01, 1.0);
+        self.vignette.set_value_and_mark_modified(vignette)
+    }
+
+    /// Returns current vignette of the camera.
+    pub fn vignette(&self) -> Vignette {
+        *self.vignette
+    }
+
+    /// Sets strength of chromatic aberration in `[0; 1]` range. Chromatic aberration is a post
+    /// effect that splits color channels of the final image towards its edges, imitating imperfect
+    /// lenses. Zero value disables the effect, and it is not rendered at all.
+    pub fn set_chromatic_aberration(&mut self, strength: f32) -> f32 {
+        self.chromatic_aberration
+            .set_value_and_mark_modified(strength.clamp(0.0, 1.0))
+    }
+
+    /// Returns current strength of chromatic aberration.
+    pub fn chromatic_aberration(&self) -> f32 {
+        *self.chromatic_aberration
+    }
+
+    /// Sets new exposure. See `Exposure` struct docs for more info. Removes exposure lock, if any
+    /// (see [`Self::lock_exposure`]).
     pub fn set_exposure(&mut self, exposure: Exposure) -> Exposure {
+        self.unlocked_exposure = None;
+        self.average_luminance_requested.set(false);
         self.exposure.set_value_and_mark_modified(exposure)
     }
 
@@ -755,6 +2049,70 @@ impl Camera {
         *self.exposure
     }
 
+    /// Captures current automatic exposure level and switches the camera to [`Exposure::Manual`]
+    /// with that level. This is useful for scripted camera moves (cutscenes, etc.) where automatic
+    /// adaptation to the scene luminance causes unwanted brightness pumping. Use
+    /// [`Self::unlock_exposure`] to restore previous automatic exposure parameters. Does nothing
+    /// if the exposure is already locked or if it is not automatic.
+    ///
+    /// The level is calculated using the average luminance of the next frame rendered by this
+    /// camera (see [`Self::average_luminance`]). Reading the luminance back from GPU is slow, so
+    /// it is done only on request and the exposure switches to [`Exposure::Manual`] on the first
+    /// update after the camera was rendered. The exposure counts as locked right away.
+    pub fn lock_exposure(&mut self) {
+        if self.is_exposure_locked() {
+            return;
+        }
+
+        let exposure = *self.exposure;
+        if let Exposure::Auto { .. } = exposure {
+            self.unlocked_exposure = Some(exposure);
+            self.average_luminance.set(None);
+            self.average_luminance_requested.set(true);
+        }
+    }
+
+    /// Restores automatic exposure parameters that were used before [`Self::lock_exposure`] call.
+    /// Does nothing if the exposure is not locked.
+    pub fn unlock_exposure(&mut self) {
+        if let Some(exposure) = self.unlocked_exposure.take() {
+            self.average_luminance_requested.set(false);
+            self.exposure.set_value_and_mark_modified(exposure);
+        }
+    }
+
+    // Finishes the lock started by `lock_exposure` once the renderer has read the luminance back.
+    fn apply_exposure_lock(&mut self) {
+        if let (Some(exposure), Exposure::Auto { .. }) = (self.unlocked_exposure, *self.exposure) {
+            if let Some(average_luminance) = self.average_luminance.get() {
+                self.exposure.set_value_and_mark_modified(Exposure::Manual(
+                    exposure.evaluate(average_luminance),
+                ));
+            }
+        }
+    }
+
+    /// Returns `true` if the exposure is currently locked by [`Self::lock_exposure`].
+    pub fn is_exposure_locked(&self) -> bool {
+        self.unlocked_exposure.is_some()
+    }
+
+    /// Returns average luminance of the frame rendered by this camera after the last
+    /// [`Self::lock_exposure`] call, `None` if there was no such frame yet. The value is written by
+    /// the renderer and takes eye adaptation into account.
+    pub fn average_luminance(&self) -> Option<f32> {
+        self.average_luminance.get()
+    }
+
+    pub(crate) fn is_average_luminance_requested(&self) -> bool {
+        self.average_luminance_requested.get()
+    }
+
+    pub(crate) fn set_average_luminance(&self, average_luminance: f32) {
+        self.average_luminance.set(Some(average_luminance));
+        self.average_luminance_requested.set(false);
+    }
+
     /// Sets a new render target of the camera. If set, the camera will render to the specified
     /// render target and will not appear in the final frame. Typical usage is something like this:
     ///
@@ -786,6 +2144,114 @@ impl Camera {
     pub fn render_target(&self) -> Option<&TextureResource> {
         self.render_target.as_ref()
     }
+
+    /// Returns the size of the current render target in pixels. The size follows the kind of the
+    /// texture: it is `width x height` for rectangular textures and `size x size` for cube maps.
+    /// Returns [`None`] if there's no render target, it is not loaded yet or its kind cannot be
+    /// used as a render target.
+    pub fn render_target_size(&self) -> Option<Vector2<f32>> {
+        let kind = self
+            .render_target
+            .as_ref()?
+            .data_ref()
+            .as_loaded_ref()?
+            .kind();
+        match kind {
+            TextureKind::Rectangle { width, height } => {
+                Some(Vector2::new(width as f32, height as f32))
+            }
+            TextureKind::Cube { size } => Some(Vector2::repeat(size as f32)),
+            _ => None,
+        }
+    }
+
+    /// Copies projection, viewport, environment, exposure, color grading, white balance, post effects
+    /// (depth of field with its auto-focus target, vignette, chromatic aberration), shake settings,
+    /// render mask, clear options and render target from the other camera. Everything that belongs
+    /// to the scene node itself (name, transform, hierarchy, etc.) stays untouched, as well as
+    /// enabled state of the camera and its current shake trauma.
+    pub fn copy_settings_from(&mut self, other: &Camera) {
+        self.set_projection(other.projection_value());
+        self.set_viewport(other.viewport());
+        self.set_environment(other.environment_map());
+        self.set_exposure(other.exposure());
+        self.set_color_grading_lut(other.color_grading_lut());
+        self.set_color_grading_enabled(other.color_grading_enabled());
+        self.set_hdr_adaptation_speed(other.hdr_adaptation_speed());
+        self.set_white_balance_settings(other.white_balance());
+        self.set_depth_of_field(other.depth_of_field());
+        self.set_auto_focus(other.auto_focus());
+        self.set_vignette(other.vignette());
+        self.set_chromatic_aberration(other.chromatic_aberration());
+        self.set_shake_intensity(other.shake_intensity());
+        self.set_shake_seed(other.shake_seed());
+        self.set_render_mask(other.render_mask());
+        self.set_clear_color(other.clear_color());
+        self.set_clear_depth(other.clear_depth());
+        self.set_visible_nodes_tracking(other.is_visible_nodes_tracking_enabled());
+        self.set_render_target(other.render_target().cloned());
+    }
+
+    /// Creates a "spectator" copy of the given camera, which is useful for debug fly-cams, replays,
+    /// etc. The spectator is added to the root of the graph at the current global position and
+    /// orientation of the original camera and has the same settings (see
+    /// [`Self::copy_settings_from`]). The original camera is disabled, so the spectator takes its
+    /// place in the final frame; enable it back when the spectator is no longer needed.
+    ///
+    /// Transforms of the cameras are independent: the spectator does not follow the original
+    /// camera (and its parents), and moving the spectator does not affect the original camera.
+    pub fn spawn_spectator(
+        graph: &mut Graph,
+        original: Handle<Camera>,
+    ) -> Result<Handle<Camera>, PoolError> {
+        let original_ref = graph.try_get(original)?;
+
+        let rotation =
+            UnitQuaternion::face_towards(&original_ref.look_vector(), &original_ref.up_vector());
+        let mut spectator = CameraBuilder::new(
+            BaseBuilder::new()
+                .with_name(format!("{} (Spectator)", original_ref.name()))
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(original_ref.global_position())
+                        .with_local_rotation(rotation)
+                        .build(),
+                ),
+        )
+        .build_camera();
+        spectator.copy_settings_from(original_ref);
+        spectator.set_enabled(true);
+
+        graph.try_get_mut(original)?.set_enabled(false);
+
+        Ok(graph.add_node(Node::new(spectator)).to_variant())
+    }
+}
+
+/// Returns the amount of cameras in the given graph that will render the given node. A camera is
+/// counted if it is enabled (both locally and globally), the node passes the camera's render mask
+/// and the world bounding box of the node intersects the camera's frustum. This is a cheap way to
+/// find out whether some expensive node-related work (animation, effects, etc.) can be skipped.
+///
+/// # Important notes
+///
+/// Camera matrices and bounding boxes of the nodes become valid **only** after the first `update`
+/// call of the graph. The method returns zero if the node handle is invalid.
+pub fn count_rendering_cameras(graph: &Graph, node: Handle<Node>) -> usize {
+    let Ok(node_ref) = graph.try_get(node) else {
+        return 0;
+    };
+
+    graph
+        .linear_iter()
+        .filter_map(|n| n.cast::<Camera>())
+        .filter(|camera| {
+            camera.is_enabled()
+                && camera.is_globally_enabled()
+                && node_ref
+                    .should_be_rendered(Some(&camera.frustum()), camera.effective_render_mask())
+        })
+        .count()
 }
 
 impl ConstructorProvider<Node, Graph> for Camera {
@@ -816,17 +2282,30 @@ impl NodeTrait for Camera {
     }
 
     fn update(&mut self, context: &mut UpdateContext) {
-        let frame_size = if let Some(TextureKind::Rectangle { width, height }) = self
-            .render_target
-            .as_ref()
-            .and_then(|rt| rt.data_ref().as_loaded_ref().map(|rt| rt.kind()))
-        {
-            Vector2::new(width as f32, height as f32)
-        } else {
-            context.frame_size
-        };
+        let frame_size = self.render_target_size().unwrap_or(context.frame_size);
 
+        self.update_shake(context.dt);
+        self.apply_exposure_lock();
         self.calculate_matrices(frame_size);
+
+        self.auto_focus_distance = context
+            .nodes
+            .try_borrow(*self.auto_focus)
+            .ok()
+            .map(|target| (target.global_position() - self.global_position()).norm());
+        self.update_environment_irradiance();
+
+        self.visible_nodes.clear();
+        if *self.track_visible_nodes {
+            let frustum = self.frustum();
+            for (handle, node) in context.nodes.pair_iter() {
+                if node.global_visibility()
+                    && frustum.is_intersects_aabb(&node.world_bounding_box())
+                {
+                    self.visible_nodes.insert(handle);
+                }
+            }
+        }
     }
 
     fn debug_draw(&self, ctx: &mut SceneDrawingContext) {
@@ -854,9 +2333,15 @@ pub enum ColorGradingLutCreationError {
         current: usize,
     },
 
-    /// Pixel format is not supported. It must be either RGB8 or RGBA8.
+    /// Pixel format is not supported. It must be one of RGB8, RGBA8, RGB16F, RGBA16F, RGB32F or
+    /// RGBA32F.
     InvalidPixelFormat(TexturePixelKind),
 
+    /// Texture size does not match any supported LUT dimension. It must be a rectangle texture
+    /// with the height of 16 (16^3 LUT) or 32 (32^3 LUT) pixels and the width, that is a multiple of
+    /// squared height (for example, 256x16, 1024x16, 1024x32 or 2048x32).
+    UnsupportedSize(TextureKind),
+
     /// Texture error.
     Texture(LoadError),
 }
@@ -874,8 +2359,16 @@ impl Display for ColorGradingLutCreationError {
             ColorGradingLutCreationError::InvalidPixelFormat(v) => {
                 write!(
                     f,
-                    "Pixel format is not supported. It must be either RGB8 \
-                or RGBA8, but texture has {v:?} pixel format"
+                    "Pixel format is not supported. It must be one of RGB8, RGBA8, \
+                RGB16F, RGBA16F, RGB32F or RGBA32F, but texture has {v:?} pixel format"
+                )
+            }
+            ColorGradingLutCreationError::UnsupportedSize(v) => {
+                write!(
+                    f,
+                    "Texture size does not match any supported LUT dimension. Its height must \
+                be 16 or 32 pixels and its width must be a multiple of squared height (for example, \
+                1024x16 for 16^3 LUT or 2048x32 for 32^3 LUT), but texture is {v:?}"
                 )
             }
             ColorGradingLutCreationError::Texture(v) => {
@@ -885,6 +2378,26 @@ impl Display for ColorGradingLutCreationError {
     }
 }
 
+/// Decodes a channel of a color grading strip, the size of the channel defines its format: 1 - `u8`,
+/// 2 - `f16`, 4 - `f32`.
+fn read_lut_channel(bytes: &[u8]) -> f32 {
+    match *bytes {
+        [v] => v as f32,
+        [a, b] => half::f16::from_le_bytes([a, b]).to_f32(),
+        [a, b, c, d] => f32::from_le_bytes([a, b, c, d]),
+        _ => 0.0,
+    }
+}
+
+/// Encodes a channel of a color grading look-up table, see [`read_lut_channel`] for more info.
+fn write_lut_channel(value: f32, channel_size: usize, bytes: &mut Vec<u8>) {
+    match channel_size {
+        1 => bytes.push(value.round().clamp(0.0, 255.0) as u8),
+        2 => bytes.extend(half::f16::from_f32(value).to_le_bytes()),
+        _ => bytes.extend(value.to_le_bytes()),
+    }
+}
+
 /// Color grading look up table (LUT). Color grading is used to modify color space of the
 /// rendered frame; it maps one color space to another. It is widely used effect in games,
 /// you've probably noticed either "warmness" or "coldness" in colors in various scenes in
@@ -903,13 +2416,19 @@ pub struct ColorGradingLut {
 uuid_provider!(ColorGradingLut = "bca9c90a-7cde-4960-8814-c132edfc9614");
 
 impl ColorGradingLut {
-    /// Creates 3D look-up texture from 2D strip.
+    /// Dimensions of look-up tables that could be created from a 2D strip.
+    pub const SUPPORTED_DIMENSIONS: [u32; 2] = [16, 32];
+
+    /// Creates 3D look-up texture from 2D strip. Dimension `N` of the look-up table is detected from
+    /// the height of the strip, the strip consists of `N` slices placed side by side. Each slice is
+    /// `width / N` pixels wide; slices wider than `N` pixels are treated as horizontally stretched
+    /// and every `width / N^2` adjacent columns are averaged.
     ///
     /// # Input Texture Requirements
     ///
-    /// Width: 1024px
-    /// Height: 16px
-    /// Pixel Format: RGB8/RGBA8
+    /// Size: 1024x16px or 256x16px (16^3 LUT), 2048x32px or 1024x32px (32^3 LUT)
+    /// Pixel Format: RGB8/RGBA8, RGB16F/RGBA16F or RGB32F/RGBA32F. Look-up tables made from
+    /// floating-point strips keep their precision and range, which is useful for HDR pipelines.
     ///
     /// # Usage
     ///
@@ -936,50 +2455,69 @@ impl ColorGradingLut {
             Ok(unwrapped_lut) => {
                 let data = unwrapped_lut.data_ref();
 
-                if data.pixel_kind() != TexturePixelKind::RGBA8
-                    && data.pixel_kind() != TexturePixelKind::RGB8
-                {
-                    return Err(ColorGradingLutCreationError::InvalidPixelFormat(
-                        data.pixel_kind(),
-                    ));
-                }
+                // Size of a single channel in bytes and the pixel kind of the resulting LUT. Data
+                // of high-precision strips is copied as is, so HDR look-up tables keep their range.
+                let (channel_size, lut_pixel_kind) = match data.pixel_kind() {
+                    TexturePixelKind::RGB8 | TexturePixelKind::RGBA8 => (1, TexturePixelKind::RGB8),
+                    TexturePixelKind::RGB16F | TexturePixelKind::RGBA16F => {
+                        (2, TexturePixelKind::RGB16F)
+                    }
+                    TexturePixelKind::RGB32F | TexturePixelKind::RGBA32F => {
+                        (4, TexturePixelKind::RGB32F)
+                    }
+                    pixel_kind => {
+                        return Err(ColorGradingLutCreationError::InvalidPixelFormat(pixel_kind))
+                    }
+                };
+
+                let (dimension, width) = match data.kind() {
+                    TextureKind::Rectangle { width, height }
+                        if Self::SUPPORTED_DIMENSIONS.contains(&height)
+                            && width > 0
+                            && width % (height * height) == 0 =>
+                    {
+                        (height, width)
+                    }
+                    kind => return Err(ColorGradingLutCreationError::UnsupportedSize(kind)),
+                };
 
                 let bytes = data.data();
 
-                const RGBA8_SIZE: usize = 16 * 16 * 16 * 4;
-                const RGB8_SIZE: usize = 16 * 16 * 16 * 3;
+                let pixel_size = data.pixel_kind().size_in_bytes().ok_or(
+                    ColorGradingLutCreationError::InvalidPixelFormat(data.pixel_kind()),
+                )?;
 
-                if data.pixel_kind() == TexturePixelKind::RGBA8 {
-                    if bytes.len() != RGBA8_SIZE {
-                        return Err(ColorGradingLutCreationError::NotEnoughData {
-                            required: RGBA8_SIZE,
-                            current: bytes.len(),
-                        });
-                    }
-                } else if bytes.len() != RGB8_SIZE {
+                let size = dimension as usize;
+                let width = width as usize;
+                // Amount of adjacent columns of the strip, that form a single texel of the LUT.
+                let stretch = width / (size * size);
+                let required = width * size * pixel_size;
+
+                if bytes.len() != required {
                     return Err(ColorGradingLutCreationError::NotEnoughData {
-                        required: RGB8_SIZE,
+                        required,
                         current: bytes.len(),
                     });
                 }
 
-                let pixel_size = if data.pixel_kind() == TexturePixelKind::RGBA8 {
-                    4
-                } else {
-                    3
-                };
-
-                let mut lut_bytes = Vec::with_capacity(16 * 16 * 16 * 3);
-
-                for z in 0..16 {
-                    for y in 0..16 {
-                        for x in 0..16 {
-                            let pixel_index = z * 16 + y * 16 * 16 + x;
-                            let pixel_byte_pos = pixel_index * pixel_size;
-
-                            lut_bytes.push(bytes[pixel_byte_pos]); // R
-                            lut_bytes.push(bytes[pixel_byte_pos + 1]); // G
-                            lut_bytes.push(bytes[pixel_byte_pos + 2]); // B
+                let mut lut_bytes = Vec::with_capacity(size * size * size * 3 * channel_size);
+
+                for z in 0..size {
+                    for y in 0..size {
+                        for x in 0..size {
+                            let first_column = (z * size + x) * stretch;
+                            // RGB, alpha channel is discarded.
+                            for channel in 0..3 {
+                                let value = (first_column..first_column + stretch)
+                                    .map(|column| {
+                                        let pos = (y * width + column) * pixel_size
+                                            + channel * channel_size;
+                                        read_lut_channel(&bytes[pos..pos + channel_size])
+                                    })
+                                    .sum::<f32>()
+                                    / stretch as f32;
+                                write_lut_channel(value, channel_size, &mut lut_bytes);
+                            }
                         }
                     }
                 }
@@ -987,11 +2525,11 @@ impl ColorGradingLut {
                 let lut = TextureResource::from_bytes(
                     Uuid::new_v4(),
                     TextureKind::Volume {
-                        width: 16,
-                        height: 16,
-                        depth: 16,
+                        width: dimension,
+                        height: dimension,
+                        depth: dimension,
                     },
-                    TexturePixelKind::RGB8,
+                    lut_pixel_kind,
                     lut_bytes,
                     ResourceKind::Embedded,
                 )
@@ -1047,6 +2585,17 @@ pub struct CameraBuilder {
     projection: Projection,
     render_target: Option<TextureResource>,
     hdr_adaptation_speed: f32,
+    render_mask: BitMask,
+    white_balance: WhiteBalance,
+    depth_of_field: DepthOfField,
+    auto_focus: Handle<Node>,
+    vignette: Vignette,
+    chromatic_aberration: f32,
+    clear_color: CameraClearColor,
+    clear_depth: bool,
+    track_visible_nodes: bool,
+    shake_intensity: f32,
+    shake_seed: u64,
 }
 
 impl CameraBuilder {
@@ -1066,6 +2615,17 @@ impl CameraBuilder {
             projection: Projection::default(),
             render_target: None,
             hdr_adaptation_speed: 0.5,
+            render_mask: BitMask::all(),
+            white_balance: Default::default(),
+            depth_of_field: Default::default(),
+            auto_focus: Handle::NONE,
+            vignette: Default::default(),
+            chromatic_aberration: 0.0,
+            clear_color: Default::default(),
+            clear_depth: true,
+            track_visible_nodes: false,
+            shake_intensity: 1.0,
+            shake_seed: 0,
         }
     }
 
@@ -1141,6 +2701,76 @@ impl CameraBuilder {
         self
     }
 
+    /// Sets desired render mask of the camera. See [`Camera::set_render_mask`] for more info.
+    pub fn with_render_mask(mut self, render_mask: BitMask) -> Self {
+        self.render_mask = render_mask;
+        self
+    }
+
+    /// Sets desired white balance of the camera. See [`Camera::set_white_balance`] for more info.
+    pub fn with_white_balance(mut self, temperature: f32, tint: f32) -> Self {
+        self.white_balance = WhiteBalance::new(temperature, tint);
+        self
+    }
+
+    /// Sets desired depth of field settings. See [`DepthOfField`] docs for more info.
+    pub fn with_depth_of_field(mut self, depth_of_field: DepthOfField) -> Self {
+        self.depth_of_field = depth_of_field;
+        self
+    }
+
+    /// Sets desired auto-focus target. See [`Camera::set_auto_focus`] for more info.
+    pub fn with_auto_focus(mut self, target: Handle<Node>) -> Self {
+        self.auto_focus = target;
+        self
+    }
+
+    /// Sets desired vignette of the camera. See [`Camera::set_vignette`] for more info.
+    pub fn with_vignette(mut self, vignette: Vignette) -> Self {
+        self.vignette = vignette;
+        self
+    }
+
+    /// Sets desired strength of chromatic aberration. See [`Camera::set_chromatic_aberration`] for
+    /// more info.
+    pub fn with_chromatic_aberration(mut self, strength: f32) -> Self {
+        self.chromatic_aberration = strength;
+        self
+    }
+
+    /// Sets how the color buffer of the camera should be cleared. See [`Camera::set_clear_color`]
+    /// for more info.
+    pub fn with_clear_color(mut self, clear_color: CameraClearColor) -> Self {
+        self.clear_color = clear_color;
+        self
+    }
+
+    /// Sets whether the depth buffer should be cleared before rendering. See
+    /// [`Camera::set_clear_depth`] for more info.
+    pub fn with_clear_depth(mut self, clear_depth: bool) -> Self {
+        self.clear_depth = clear_depth;
+        self
+    }
+
+    /// Enables or disables tracking of the nodes inside the camera frustum. See
+    /// [`Camera::set_visible_nodes_tracking`] for more info.
+    pub fn with_visible_nodes_tracking(mut self, enabled: bool) -> Self {
+        self.track_visible_nodes = enabled;
+        self
+    }
+
+    /// Sets desired scale of the camera shake. See [`Camera::set_shake_intensity`] for more info.
+    pub fn with_shake_intensity(mut self, intensity: f32) -> Self {
+        self.shake_intensity = intensity;
+        self
+    }
+
+    /// Sets desired seed of the camera shake. See [`Camera::set_shake_seed`] for more info.
+    pub fn with_shake_seed(mut self, seed: u64) -> Self {
+        self.shake_seed = seed;
+        self
+    }
+
     /// Creates new instance of camera.
     pub fn build_camera(self) -> Camera {
         Camera {
@@ -1152,12 +2782,35 @@ impl CameraBuilder {
             // recalculated before rendering.
             view_matrix: Matrix4::identity(),
             projection_matrix: Matrix4::identity(),
+            visible_nodes: Default::default(),
+            unlocked_exposure: None,
+            average_luminance: Default::default(),
+            average_luminance_requested: Default::default(),
+            environment_irradiance: Default::default(),
             environment: self.environment.into(),
             exposure: self.exposure.into(),
             color_grading_lut: self.color_grading_lut.into(),
             color_grading_enabled: self.color_grading_enabled.into(),
             hdr_adaptation_speed: self.hdr_adaptation_speed.into(),
+            render_mask: self.render_mask.into(),
+            white_balance: self.white_balance.into(),
+            vignette: Vignette {
+                intensity: self.vignette.intensity.clamp(0.0, 1.0),
+                smoothness: self.vignette.smoothness.clamp(0.01, 1.0),
+                ..self.vignette
+            }
+            .into(),
+            chromatic_aberration: self.chromatic_aberration.clamp(0.0, 1.0).into(),
+            depth_of_field: self.depth_of_field.into(),
+            auto_focus: self.auto_focus.into(),
+            auto_focus_distance: None,
+            clear_color: self.clear_color.into(),
+            clear_depth: self.clear_depth.into(),
             render_target: self.render_target,
+            track_visible_nodes: self.track_visible_nodes.into(),
+            shake_intensity: self.shake_intensity.max(0.0).into(),
+            shake_seed: self.shake_seed.into(),
+            shake: Default::default(),
         }
     }
 
@@ -1171,3 +2824,1052 @@ impl CameraBuilder {
         graph.add_node(self.build_node()).to_variant()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::untyped::ResourceKind,
+        core::{
+            algebra::{Matrix4, Vector2, Vector3, Vector4},
+            color::Color,
+            futures::executor::block_on,
+            math::{aabb::AxisAlignedBoundingBox, plane::Plane, Rect},
+            pool::Handle,
+            uuid::Uuid,
+        },
+        renderer::IRRADIANCE_MAP_SIZE,
+        resource::texture::{
+            TextureKind, TexturePixelKind, TextureResource, TextureResourceExtension,
+        },
+        scene::{
+            base::BaseBuilder,
+            camera::{
+                count_rendering_cameras, reverse_depth, Camera, CameraBuilder, CameraClearColor,
+                ColorGradingLut, ColorGradingLutCreationError, DepthOfField, Exposure,
+                FrustumProjection, Intersection, OrthographicProjection, PerspectiveProjection,
+                Projection, SizeMode, Vignette, WhiteBalance,
+            },
+            collider::BitMask,
+            graph::Graph,
+            node::Node,
+            pivot::PivotBuilder,
+            skybox::SkyBox,
+            transform::TransformBuilder,
+        },
+    };
+
+    fn make_lut_strip(width: u32, height: u32) -> TextureResource {
+        let bytes = (0..width * height)
+            .flat_map(|i| [(i % 256) as u8, (i / 256) as u8, 0])
+            .collect();
+        TextureResource::from_bytes(
+            Uuid::new_v4(),
+            TextureKind::Rectangle { width, height },
+            TexturePixelKind::RGB8,
+            bytes,
+            ResourceKind::Embedded,
+        )
+        .unwrap()
+    }
+
+    fn make_camera() -> Camera {
+        // Default camera is located at the origin and looks along +Z axis.
+        let mut camera = CameraBuilder::new(BaseBuilder::new()).build_camera();
+        camera.calculate_matrices(Vector2::new(100.0, 100.0));
+        camera
+    }
+
+    #[test]
+    fn test_classify_sphere_inside() {
+        let camera = make_camera();
+        assert_eq!(
+            camera.classify_sphere(Vector3::new(0.0, 0.0, 10.0), 1.0),
+            Intersection::Inside
+        );
+    }
+
+    #[test]
+    fn test_classify_sphere_outside() {
+        let camera = make_camera();
+        assert_eq!(
+            camera.classify_sphere(Vector3::new(0.0, 0.0, -10.0), 1.0),
+            Intersection::Outside
+        );
+        assert_eq!(
+            camera.classify_sphere(Vector3::new(100.0, 0.0, 10.0), 1.0),
+            Intersection::Outside
+        );
+    }
+
+    #[test]
+    fn test_classify_sphere_intersecting() {
+        let camera = make_camera();
+        // Straddles the near plane.
+        assert_eq!(
+            camera.classify_sphere(Vector3::new(0.0, 0.0, 0.0), 1.0),
+            Intersection::Intersecting
+        );
+        // Straddles the far plane.
+        assert_eq!(
+            camera.classify_sphere(Vector3::new(0.0, 0.0, 2048.0), 10.0),
+            Intersection::Intersecting
+        );
+    }
+
+    #[test]
+    fn test_visible_nodes() {
+        let mut graph = Graph::new();
+        let camera = CameraBuilder::new(BaseBuilder::new())
+            .with_visible_nodes_tracking(true)
+            .build(&mut graph);
+
+        let mut make_pivot = |position: Vector3<f32>, visibility: bool| {
+            PivotBuilder::new(
+                BaseBuilder::new()
+                    .with_visibility(visibility)
+                    .with_local_transform(
+                        TransformBuilder::new()
+                            .with_local_position(position)
+                            .build(),
+                    ),
+            )
+            .build(&mut graph)
+            .to_base()
+        };
+        let in_front = make_pivot(Vector3::new(0.0, 0.0, 10.0), true);
+        let behind = make_pivot(Vector3::new(0.0, 0.0, -10.0), true);
+        let hidden = make_pivot(Vector3::new(0.0, 0.0, 10.0), false);
+
+        // Nothing is visible until the first update.
+        assert_eq!(graph[camera].visible_nodes().count(), 0);
+        assert!(!graph[camera].is_node_visible(in_front));
+
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0, Default::default());
+
+        let camera_ref = &graph[camera];
+        assert!(camera_ref.is_node_visible(in_front));
+        assert!(!camera_ref.is_node_visible(behind));
+        assert!(!camera_ref.is_node_visible(hidden));
+        assert!(camera_ref.visible_nodes().any(|handle| handle == in_front));
+
+        // Disabled tracking leaves the set empty.
+        graph[camera].set_visible_nodes_tracking(false);
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0, Default::default());
+        assert_eq!(graph[camera].visible_nodes().count(), 0);
+    }
+
+    #[test]
+    fn test_oblique_clip_plane() {
+        let frame_size = Vector2::new(100.0, 100.0);
+        let ndc_depth = |matrix: &Matrix4<f32>, z: f32| {
+            let clip = matrix * Vector4::new(0.0, 0.0, z, 1.0);
+            clip.z / clip.w
+        };
+
+        let mut projection = Projection::default();
+        // Keep everything that is further than 5 units along the view direction (-Z).
+        let plane = Plane::from_abcd(0.0, 0.0, -1.0, -5.0).unwrap();
+        assert!(projection.with_oblique_clip_plane(plane));
+        let matrix = projection.matrix(frame_size);
+
+        assert!((ndc_depth(&matrix, -5.0) + 1.0).abs() < 1.0e-3);
+        assert!(ndc_depth(&matrix, -3.0) < -1.0);
+        let far_depth = ndc_depth(&matrix, -10.0);
+        assert!(far_depth > -1.0 && far_depth < 1.0);
+
+        projection.clear_oblique_clip_plane();
+        assert_eq!(projection.oblique_clip_plane(), None);
+
+        let mut orthographic = Projection::Orthographic(Default::default());
+        assert!(!orthographic.with_oblique_clip_plane(plane));
+        assert_eq!(orthographic.oblique_clip_plane(), None);
+    }
+
+    #[test]
+    fn test_fov() {
+        let mut camera = CameraBuilder::new(BaseBuilder::new())
+            .with_projection(Projection::Perspective(PerspectiveProjection {
+                fov: 1.0,
+                ..Default::default()
+            }))
+            .build_camera();
+        assert_eq!(camera.fov(), Some(1.0));
+        assert_eq!(camera.set_fov(1.5), Some(1.0));
+        assert_eq!(camera.fov(), Some(1.5));
+
+        camera.set_projection(Projection::Orthographic(Default::default()));
+        assert_eq!(camera.fov(), None);
+        assert_eq!(camera.set_fov(2.0), None);
+        assert_eq!(
+            camera.projection(),
+            &Projection::Orthographic(Default::default())
+        );
+
+        let mut frustum = Projection::Frustum(Default::default());
+        frustum.set_fov(2.0);
+        assert_eq!(frustum.fov(), None);
+        assert_eq!(frustum, Projection::Frustum(Default::default()));
+    }
+
+    #[test]
+    fn test_fit_to_aabb() {
+        let frame_size = Vector2::new(200.0, 100.0);
+        let aabb = AxisAlignedBoundingBox::from_min_max(
+            Vector3::new(8.0, -1.0, 9.0),
+            Vector3::new(12.0, 2.0, 11.0),
+        );
+
+        let is_aabb_visible = |graph: &mut Graph, camera: Handle<Camera>| {
+            graph.update_hierarchical_data();
+            let camera = &mut graph[camera];
+            camera.calculate_matrices(frame_size);
+            let frustum = camera.frustum();
+            // Orthographic fitting is exact, so pull the corners slightly inside the box to avoid
+            // testing points that lie on the planes of the frustum.
+            aabb.corners().iter().all(|corner| {
+                frustum.is_contains_point(aabb.center() + (corner - aabb.center()).scale(0.99))
+            })
+        };
+
+        for projection in [
+            Projection::Orthographic(Default::default()),
+            Projection::Perspective(Default::default()),
+        ] {
+            let mut graph = Graph::new();
+            let camera = CameraBuilder::new(BaseBuilder::new())
+                .with_projection(projection.clone())
+                .build(&mut graph);
+            // Make sure the parent transform is taken into account.
+            PivotBuilder::new(
+                BaseBuilder::new()
+                    .with_local_transform(
+                        TransformBuilder::new()
+                            .with_local_position(Vector3::new(-3.0, 4.0, 0.0))
+                            .build(),
+                    )
+                    .with_child(camera),
+            )
+            .build(&mut graph);
+
+            assert!(!is_aabb_visible(&mut graph, camera));
+
+            let fitted = if projection.is_orthographic() {
+                assert!(!graph[camera].fit_perspective_to_aabb(aabb, frame_size));
+                graph[camera].fit_orthographic_to_aabb(aabb, frame_size)
+            } else {
+                assert!(!graph[camera].fit_orthographic_to_aabb(aabb, frame_size));
+                graph[camera].fit_perspective_to_aabb(aabb, frame_size)
+            };
+            assert!(fitted);
+            assert!(is_aabb_visible(&mut graph, camera));
+
+            if let Projection::Orthographic(orthographic) = graph[camera].projection() {
+                assert_eq!(orthographic.vertical_size, 1.5);
+                assert_eq!(
+                    graph[camera].global_position(),
+                    Vector3::new(10.0, 0.5, 9.0)
+                );
+            }
+
+            // Zero-size boxes must not change the camera.
+            let projection = graph[camera].projection_value();
+            let position = graph[camera].global_position();
+            let point = AxisAlignedBoundingBox::from_point(Vector3::new(1.0, 2.0, 3.0));
+            assert!(!graph[camera].fit_orthographic_to_aabb(point, frame_size));
+            assert!(!graph[camera].fit_perspective_to_aabb(point, frame_size));
+            graph.update_hierarchical_data();
+            assert_eq!(graph[camera].projection_value(), projection);
+            assert_eq!(graph[camera].global_position(), position);
+        }
+    }
+
+    #[test]
+    fn test_spawn_spectator() {
+        let mut graph = Graph::new();
+        let original = CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(1.0, 2.0, 3.0))
+                    .build(),
+            ),
+        )
+        .with_fov(1.0)
+        .with_hdr_adaptation_speed(2.0)
+        .build(&mut graph);
+        let parent = PivotBuilder::new(BaseBuilder::new().with_child(original)).build(&mut graph);
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0, Default::default());
+
+        let spectator = Camera::spawn_spectator(&mut graph, original).unwrap();
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0, Default::default());
+
+        assert!(!graph[original].is_enabled());
+        let spectator_ref = &graph[spectator];
+        assert!(spectator_ref.is_enabled());
+        assert_eq!(spectator_ref.projection(), graph[original].projection());
+        assert_eq!(spectator_ref.hdr_adaptation_speed(), 2.0);
+        assert_ne!(spectator_ref.parent(), parent.to_base::<Node>());
+        assert_eq!(
+            spectator_ref.global_position(),
+            graph[original].global_position()
+        );
+    }
+
+    #[test]
+    fn test_count_rendering_cameras() {
+        let mut graph = Graph::new();
+        let node = PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 0.0, 10.0))
+                    .build(),
+            ),
+        )
+        .build(&mut graph);
+        // Looks at the node.
+        CameraBuilder::new(BaseBuilder::new()).build(&mut graph);
+        // Located behind the node, so it is outside of its frustum.
+        CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 0.0, 20.0))
+                    .build(),
+            ),
+        )
+        .build(&mut graph);
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0, Default::default());
+
+        assert_eq!(count_rendering_cameras(&graph, node.to_base()), 1);
+        assert_eq!(count_rendering_cameras(&graph, Default::default()), 0);
+    }
+
+    #[test]
+    fn test_infinite_far_projection() {
+        let mut projection = Projection::default();
+        if let Projection::Perspective(ref mut perspective) = projection {
+            perspective.infinite_far = true;
+        }
+        assert!(projection.is_infinite_far());
+        assert_eq!(projection.z_far(), f32::INFINITY);
+        assert_eq!(projection.finite_z_far(), 2048.0);
+
+        let matrix = projection.matrix(Vector2::new(100.0, 100.0));
+        let ndc_depth = |z: f32| {
+            let clip = matrix * Vector4::new(0.0, 0.0, z, 1.0);
+            clip.z / clip.w
+        };
+        assert!((ndc_depth(-projection.z_near()) + 1.0).abs() < 1.0e-3);
+        let far_depth = ndc_depth(-1.0e6);
+        assert!(far_depth > 0.99 && far_depth <= 1.0);
+
+        // Frustum culling must still work with the finite distance.
+        let mut camera = CameraBuilder::new(BaseBuilder::new())
+            .with_projection(projection)
+            .build_camera();
+        camera.calculate_matrices(Vector2::new(100.0, 100.0));
+        assert_eq!(
+            camera.classify_sphere(Vector3::new(0.0, 0.0, 10.0), 1.0),
+            Intersection::Inside
+        );
+        assert_eq!(
+            camera.classify_sphere(Vector3::new(0.0, 0.0, 4096.0), 1.0),
+            Intersection::Outside
+        );
+
+        // Picking must end at the finite far plane instead of the infinity.
+        let screen_size = Vector2::new(100.0, 100.0);
+        let center = Vector2::new(50.0, 50.0);
+        let finite_far = Vector3::new(0.0, 0.0, 2048.0);
+        let ray = camera.make_ray(center, screen_size);
+        assert!((ray.origin + ray.dir - finite_far).norm() < 1.0e-1);
+        let far = camera.unproject(center, 1.0, screen_size);
+        assert!((far - finite_far).norm() < 1.0e-1);
+    }
+
+    #[test]
+    fn test_reverse_depth() {
+        for projection in [
+            Projection::Perspective(Default::default()),
+            Projection::Orthographic(Default::default()),
+        ] {
+            let mut matrix = projection.matrix(Vector2::new(100.0, 100.0));
+            reverse_depth(&mut matrix);
+            let ndc_depth = |z: f32| {
+                let clip = matrix * Vector4::new(0.0, 0.0, z, 1.0);
+                clip.z / clip.w
+            };
+            assert!((ndc_depth(-projection.z_near()) - 1.0).abs() < 1.0e-3);
+            assert!((ndc_depth(-projection.z_far()) + 1.0).abs() < 1.0e-3);
+        }
+    }
+
+    #[test]
+    fn test_color_grading_lut_dimensions() {
+        for dimension in ColorGradingLut::SUPPORTED_DIMENSIONS {
+            let strip = make_lut_strip(dimension * dimension, dimension);
+            let lut = block_on(ColorGradingLut::new(strip)).unwrap().lut();
+            let lut = lut.data_ref();
+            assert!(matches!(
+                lut.kind(),
+                TextureKind::Volume { width, height, depth }
+                    if width == dimension && height == dimension && depth == dimension
+            ));
+            let size = dimension as usize;
+            assert_eq!(lut.data().len(), size * size * size * 3);
+            // The last texel of the second row of the first slice.
+            let strip_index = size * size + size - 1;
+            let lut_index = (size + size - 1) * 3;
+            assert_eq!(
+                &lut.data()[lut_index..lut_index + 2],
+                &[(strip_index % 256) as u8, (strip_index / 256) as u8]
+            );
+        }
+    }
+
+    #[test]
+    fn test_color_grading_lut_hdr() {
+        let size = 16usize;
+        let bytes = (0..size * size * size)
+            .flat_map(|i| {
+                [i as f32 / 256.0, 2.0, 0.5, 1.0]
+                    .map(|v| half::f16::from_f32(v).to_le_bytes())
+                    .concat()
+            })
+            .collect();
+        let strip = TextureResource::from_bytes(
+            Uuid::new_v4(),
+            TextureKind::Rectangle {
+                width: (size * size) as u32,
+                height: size as u32,
+            },
+            TexturePixelKind::RGBA16F,
+            bytes,
+            ResourceKind::Embedded,
+        )
+        .unwrap();
+        let lut = block_on(ColorGradingLut::new(strip)).unwrap().lut();
+        let lut = lut.data_ref();
+        assert_eq!(lut.pixel_kind(), TexturePixelKind::RGB16F);
+        assert_eq!(lut.data().len(), size * size * size * 6);
+        // The last texel of the second row of the first slice, alpha is dropped.
+        let strip_index = size * size + size - 1;
+        let lut_index = (size + size - 1) * 6;
+        let expected = [strip_index as f32 / 256.0, 2.0, 0.5]
+            .map(|v| half::f16::from_f32(v).to_le_bytes())
+            .concat();
+        assert_eq!(&lut.data()[lut_index..lut_index + 6], expected.as_slice());
+    }
+
+    #[test]
+    fn test_color_grading_lut_invalid_pixel_format() {
+        let strip = TextureResource::from_bytes(
+            Uuid::new_v4(),
+            TextureKind::Rectangle {
+                width: 256,
+                height: 16,
+            },
+            TexturePixelKind::R8,
+            vec![0; 256 * 16],
+            ResourceKind::Embedded,
+        )
+        .unwrap();
+        assert!(matches!(
+            block_on(ColorGradingLut::new(strip)),
+            Err(ColorGradingLutCreationError::InvalidPixelFormat(
+                TexturePixelKind::R8
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_color_grading_lut_stretched_strip() {
+        for (width, dimension) in [(1024u32, 16u32), (2048, 32)] {
+            let stretch = width / (dimension * dimension);
+            // Every group of stretched columns has the same color, except the blue channel, that
+            // alternates between the columns of a group and must be averaged.
+            let bytes = (0..dimension)
+                .flat_map(|y| {
+                    (0..width).flat_map(move |column| {
+                        let texel = column / stretch;
+                        let blue = if column % 2 == 0 { 10 } else { 20 };
+                        [(texel % 256) as u8, y as u8, blue]
+                    })
+                })
+                .collect();
+            let strip = TextureResource::from_bytes(
+                Uuid::new_v4(),
+                TextureKind::Rectangle {
+                    width,
+                    height: dimension,
+                },
+                TexturePixelKind::RGB8,
+                bytes,
+                ResourceKind::Embedded,
+            )
+            .unwrap();
+            let lut = block_on(ColorGradingLut::new(strip)).unwrap().lut();
+            let lut = lut.data_ref();
+            assert!(matches!(
+                lut.kind(),
+                TextureKind::Volume { width, height, depth }
+                    if width == dimension && height == dimension && depth == dimension
+            ));
+            let size = dimension as usize;
+            let data = lut.data();
+            assert_eq!(data.len(), size * size * size * 3);
+            for (z, y, x) in [(0, 0, 0), (1, 2, 3), (size - 1, size - 1, size - 1)] {
+                let index = ((z * size + y) * size + x) * 3;
+                assert_eq!(
+                    &data[index..index + 3],
+                    &[((z * size + x) % 256) as u8, y as u8, 15]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_color_grading_lut_unsupported_size() {
+        for (width, height) in [(1000, 16), (64, 8), (256, 32)] {
+            let strip = make_lut_strip(width, height);
+            assert!(matches!(
+                block_on(ColorGradingLut::new(strip)),
+                Err(ColorGradingLutCreationError::UnsupportedSize(
+                    TextureKind::Rectangle { .. }
+                ))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_exposure_lock() {
+        let auto = Exposure::Auto {
+            min_luminance: 0.1,
+            max_luminance: 2.0,
+            adaptation_speed: 1.0,
+        };
+        let mut camera = CameraBuilder::new(BaseBuilder::new())
+            .with_exposure(auto)
+            .build_camera();
+        assert!(!camera.is_exposure_locked());
+
+        // The luminance is read back only on request, the exposure stays automatic until then.
+        assert!(!camera.is_average_luminance_requested());
+        camera.lock_exposure();
+        assert!(camera.is_exposure_locked());
+        assert!(camera.is_average_luminance_requested());
+        camera.apply_exposure_lock();
+        assert_eq!(camera.exposure(), auto);
+
+        // This is what the renderer does for the requested camera.
+        camera.set_average_luminance(0.5);
+        assert!(!camera.is_average_luminance_requested());
+        camera.apply_exposure_lock();
+        assert_eq!(camera.exposure(), Exposure::Manual(auto.evaluate(0.5)));
+
+        // Locking twice must not overwrite the saved parameters.
+        camera.set_average_luminance(1.0);
+        camera.lock_exposure();
+        camera.apply_exposure_lock();
+        assert_eq!(camera.exposure(), Exposure::Manual(auto.evaluate(0.5)));
+
+        camera.unlock_exposure();
+        assert!(!camera.is_exposure_locked());
+        assert_eq!(camera.exposure(), auto);
+
+        // Manual exposure can't be locked.
+        camera.set_exposure(Exposure::Manual(2.0));
+        camera.lock_exposure();
+        assert!(!camera.is_exposure_locked());
+        assert_eq!(camera.exposure(), Exposure::Manual(2.0));
+    }
+
+    #[test]
+    fn test_orthographic_size_mode() {
+        let extents = |projection: &OrthographicProjection, frame_size: Vector2<f32>| {
+            let inv = projection.matrix(frame_size).try_inverse().unwrap();
+            let corner = inv * Vector4::new(1.0, 1.0, 0.0, 1.0);
+            Vector2::new(corner.x, corner.y)
+        };
+
+        let mut projection = OrthographicProjection {
+            vertical_size: 2.0,
+            horizontal_size: 4.0,
+            ..Default::default()
+        };
+        for frame_size in [Vector2::new(200.0, 100.0), Vector2::new(400.0, 100.0)] {
+            let aspect = frame_size.x / frame_size.y;
+            let size = extents(&projection, frame_size);
+            assert!((size.y - 2.0).abs() < 1.0e-4);
+            assert!((size.x - 2.0 * aspect).abs() < 1.0e-4);
+        }
+
+        projection.size_mode = SizeMode::Horizontal;
+        for frame_size in [Vector2::new(200.0, 100.0), Vector2::new(400.0, 100.0)] {
+            let aspect = frame_size.x / frame_size.y;
+            let size = extents(&projection, frame_size);
+            assert!((size.x - 4.0).abs() < 1.0e-4);
+            assert!((size.y - 4.0 / aspect).abs() < 1.0e-4);
+        }
+    }
+
+    #[test]
+    fn test_unproject() {
+        let camera = make_camera();
+        let screen_size = Vector2::new(100.0, 100.0);
+        let center = Vector2::new(50.0, 50.0);
+
+        let near = camera.unproject(center, 0.0, screen_size);
+        assert!((near - Vector3::new(0.0, 0.0, 0.025)).norm() < 1.0e-3);
+
+        // Depth of a point at the distance `d` is `(f + n - 2fn / d) / (2(f - n)) + 0.5`. Its
+        // derivative `fn / ((f - n) d²)` is ~0.025 at one unit away from the camera, so an error
+        // of a few ulps of the depth and the inverse matrix stays well below 1.0e-3 in distance.
+        // Points closer to the far plane are not checked, because the derivative falls to ~1.0e-8
+        // there and a single ulp of depth moves the point by hundreds of units.
+        let (n, f) = (camera.projection.z_near(), camera.projection.z_far());
+        let distance = 1.0;
+        let depth = (f + n - 2.0 * f * n / distance) / (2.0 * (f - n)) + 0.5;
+        let point = camera.unproject(center, depth, screen_size);
+        assert!((point - Vector3::new(0.0, 0.0, distance)).norm() < 1.0e-3);
+
+        // Unprojected point must be projected back to the same screen position.
+        let corner = Vector2::new(10.0, 20.0);
+        let point = camera.unproject(corner, 0.75, screen_size);
+        let projected = camera.project(point, screen_size).unwrap();
+        assert!((projected - corner).norm() < 1.0e-2);
+        let ray = camera.make_ray(corner, screen_size);
+        let direction = (point - ray.origin).normalize();
+        assert!((direction - ray.dir.normalize()).norm() < 1.0e-3);
+    }
+
+    #[test]
+    fn test_frustum_projection() {
+        let frame_size = Vector2::new(100.0, 100.0);
+
+        // Symmetric frustum must match regular perspective projection.
+        let perspective = PerspectiveProjection::default();
+        let half_height = perspective.z_near * (perspective.fov * 0.5).tan();
+        let symmetric = FrustumProjection {
+            left: -half_height,
+            right: half_height,
+            bottom: -half_height,
+            top: half_height,
+            z_near: perspective.z_near,
+            z_far: perspective.z_far,
+        };
+        assert!((symmetric.fov() - perspective.fov).abs() < 1.0e-5);
+        let difference = symmetric.matrix() - perspective.matrix(frame_size);
+        assert!(difference.iter().all(|v| v.abs() < 1.0e-3));
+
+        // Corners of an asymmetric frustum at the near plane must map to the corners of NDC cube.
+        let frustum = FrustumProjection {
+            left: -0.01,
+            right: 0.03,
+            bottom: -0.02,
+            top: 0.01,
+            z_near: 0.025,
+            z_far: 100.0,
+        };
+        let matrix = Projection::Frustum(frustum.clone()).matrix(frame_size);
+        let ndc = |x: f32, y: f32| {
+            let clip = matrix * Vector4::new(x, y, -frustum.z_near, 1.0);
+            clip.xyz().scale(1.0 / clip.w)
+        };
+        assert!(
+            (ndc(frustum.left, frustum.bottom) - Vector3::new(-1.0, -1.0, -1.0)).norm() < 1.0e-4
+        );
+        assert!((ndc(frustum.right, frustum.top) - Vector3::new(1.0, 1.0, -1.0)).norm() < 1.0e-4);
+
+        let mut camera = CameraBuilder::new(BaseBuilder::new())
+            .with_projection(Projection::Frustum(frustum))
+            .build_camera();
+        camera.calculate_matrices(frame_size);
+        let point = Vector2::new(30.0, 60.0);
+        let ray = camera.make_ray(point, frame_size);
+        let projected = camera.project(ray.origin + ray.dir.scale(0.5), frame_size);
+        assert!((projected.unwrap() - point).norm() < 1.0e-2);
+    }
+
+    #[test]
+    fn test_render_target_size() {
+        let mut camera = CameraBuilder::new(BaseBuilder::new()).build_camera();
+        assert_eq!(camera.render_target_size(), None);
+
+        camera.set_render_target(Some(TextureResource::new_render_target(320, 240)));
+        assert_eq!(
+            camera.render_target_size(),
+            Some(Vector2::new(320.0, 240.0))
+        );
+
+        camera.set_render_target(Some(TextureResource::new_cube_render_target(128)));
+        assert_eq!(
+            camera.render_target_size(),
+            Some(Vector2::new(128.0, 128.0))
+        );
+    }
+
+    #[test]
+    fn test_clear_options() {
+        let camera = make_camera();
+        assert_eq!(camera.clear_color(), CameraClearColor::Scene);
+        assert!(camera.clear_depth());
+
+        let mut overlay = CameraBuilder::new(BaseBuilder::new())
+            .with_clear_color(CameraClearColor::Keep)
+            .with_clear_depth(false)
+            .build_camera();
+        assert_eq!(overlay.clear_color(), CameraClearColor::Keep);
+        assert!(!overlay.clear_depth());
+
+        let color = CameraClearColor::Color(Color::RED);
+        assert_eq!(overlay.set_clear_color(color), CameraClearColor::Keep);
+        assert_eq!(overlay.clear_color(), color);
+
+        let mut copy = make_camera();
+        copy.copy_settings_from(&overlay);
+        assert_eq!(copy.clear_color(), color);
+        assert!(!copy.clear_depth());
+    }
+
+    #[test]
+    fn test_copy_post_effect_settings() {
+        let mut source = make_camera();
+        source.set_white_balance(3000.0, 0.25);
+        source.set_depth_of_field(DepthOfField {
+            enabled: true,
+            ..Default::default()
+        });
+        source.set_auto_focus(Handle::new(1, 1));
+        source.set_vignette(Vignette {
+            intensity: 0.5,
+            ..Default::default()
+        });
+        source.set_chromatic_aberration(0.5);
+        source.set_shake_intensity(2.0);
+        source.set_shake_seed(42);
+
+        let mut copy = make_camera();
+        copy.copy_settings_from(&source);
+        assert_eq!(copy.white_balance(), source.white_balance());
+        assert_eq!(copy.depth_of_field(), source.depth_of_field());
+        assert_eq!(copy.auto_focus(), source.auto_focus());
+        assert_eq!(copy.vignette(), source.vignette());
+        assert_eq!(copy.chromatic_aberration(), 0.5);
+        assert_eq!(copy.shake_intensity(), 2.0);
+        assert_eq!(copy.shake_seed(), 42);
+    }
+
+    #[test]
+    fn test_frustum_corners() {
+        let mut camera = make_camera();
+        let half_size = |distance: f32| distance * (75.0f32.to_radians() * 0.5).tan();
+
+        let corners = camera.frustum_corners_for_range(1.0, 10.0);
+        for (i, corner) in corners.iter().enumerate() {
+            let distance = if i < 4 { 1.0 } else { 10.0 };
+            assert!((corner.z - distance).abs() < 1.0e-3);
+            assert!((corner.x.abs() - half_size(distance)).abs() < 1.0e-3);
+            assert!((corner.y.abs() - half_size(distance)).abs() < 1.0e-3);
+        }
+
+        let corners = camera.frustum_corners();
+        assert!((corners[0].z - 0.025).abs() < 1.0e-3);
+        assert!((corners[4].z - 2048.0).abs() < 1.0);
+
+        // Orthographic frustum is a box.
+        camera.set_projection(Projection::Orthographic(OrthographicProjection::default()));
+        camera.calculate_matrices(Vector2::new(100.0, 100.0));
+        let corners = camera.frustum_corners_for_range(1.0, 10.0);
+        for (near, far) in corners[..4].iter().zip(&corners[4..]) {
+            assert!((near.xy() - far.xy()).norm() < 1.0e-3);
+            assert!((near.z - 1.0).abs() < 1.0e-3);
+            assert!((far.z - 10.0).abs() < 1.0e-3);
+        }
+    }
+
+    #[test]
+    fn test_environment_irradiance() {
+        let environment = SkyBox::from_single_color(Color::opaque(100, 150, 200))
+            .cubemap()
+            .unwrap();
+        let mut camera = CameraBuilder::new(BaseBuilder::new())
+            .with_environment(environment.clone())
+            .build_camera();
+        assert!(camera.environment_irradiance().is_none());
+
+        camera.update_environment_irradiance();
+        let irradiance = camera.environment_irradiance().unwrap();
+        {
+            let data = irradiance.data_ref();
+            assert!(data.is_render_target());
+            assert!(matches!(
+                data.kind(),
+                TextureKind::Cube { size } if size == IRRADIANCE_MAP_SIZE as u32
+            ));
+        }
+
+        // The environment did not change, so the same map is kept.
+        camera.set_environment(Some(environment));
+        camera.update_environment_irradiance();
+        assert_eq!(camera.environment_irradiance(), Some(irradiance));
+
+        camera.set_environment(None);
+        camera.update_environment_irradiance();
+        assert!(camera.environment_irradiance().is_none());
+    }
+
+    #[test]
+    fn test_shake_accumulates_and_decays() {
+        let mut camera = make_camera();
+        assert_eq!(camera.shake_trauma(), 0.0);
+        camera.add_shake(0.25);
+        camera.add_shake(0.5);
+        assert_eq!(camera.shake_trauma(), 0.75);
+        camera.add_shake(1.0);
+        assert_eq!(camera.shake_trauma(), 1.0);
+        camera.update_shake(0.5);
+        assert_eq!(camera.shake_trauma(), 0.5);
+        camera.update_shake(10.0);
+        assert_eq!(camera.shake_trauma(), 0.0);
+    }
+
+    #[test]
+    fn test_shake_does_not_modify_transform() {
+        let mut camera = make_camera();
+        let view_matrix = camera.view_matrix();
+        let local_transform = camera.local_transform().matrix();
+
+        camera.add_shake(1.0);
+        camera.update_shake(0.1);
+        camera.calculate_matrices(Vector2::new(100.0, 100.0));
+        assert_ne!(camera.view_matrix(), view_matrix);
+        assert_eq!(camera.local_transform().matrix(), local_transform);
+
+        camera.stop_shake();
+        camera.calculate_matrices(Vector2::new(100.0, 100.0));
+        assert_eq!(camera.view_matrix(), view_matrix);
+
+        // Zero intensity disables the shake.
+        camera.set_shake_intensity(0.0);
+        camera.add_shake(1.0);
+        camera.update_shake(0.1);
+        camera.calculate_matrices(Vector2::new(100.0, 100.0));
+        assert_eq!(camera.view_matrix(), view_matrix);
+    }
+
+    #[test]
+    fn test_shake_is_deterministic() {
+        let shake = |seed| {
+            let mut camera = CameraBuilder::new(BaseBuilder::new())
+                .with_shake_seed(seed)
+                .build_camera();
+            camera.add_shake(1.0);
+            (0..10)
+                .map(|_| {
+                    camera.update_shake(1.0 / 60.0);
+                    camera.calculate_matrices(Vector2::new(100.0, 100.0));
+                    camera.view_matrix()
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(shake(123), shake(123));
+        assert_ne!(shake(123), shake(321));
+    }
+
+    #[test]
+    fn test_split_screen_picking() {
+        let screen_size = Vector2::new(200.0, 100.0);
+
+        // Right half of the screen.
+        let mut camera = CameraBuilder::new(BaseBuilder::new())
+            .with_viewport(Rect::new(0.5, 0.0, 0.5, 1.0))
+            .build_camera();
+        camera.calculate_matrices(Vector2::new(100.0, 100.0));
+        let center = Vector2::new(150.0, 50.0);
+        assert_eq!(
+            camera.screen_to_viewport(center, screen_size),
+            Vector2::new(50.0, 50.0)
+        );
+        let ray = camera.make_ray(center, screen_size);
+        let dir = ray.dir.normalize();
+        assert!(dir.x.abs() < 1.0e-5 && dir.y.abs() < 1.0e-5 && dir.z > 0.0);
+        let projected = camera
+            .project(Vector3::new(0.0, 0.0, 10.0), screen_size)
+            .unwrap();
+        assert!((projected - center).norm() < 1.0e-3);
+
+        // Upper half of the screen, the position of the viewport is defined from the bottom.
+        camera.set_viewport(Rect::new(0.0, 0.5, 1.0, 0.5));
+        camera.calculate_matrices(Vector2::new(200.0, 50.0));
+        let center = Vector2::new(100.0, 25.0);
+        let dir = camera.make_ray(center, screen_size).dir.normalize();
+        assert!(dir.x.abs() < 1.0e-5 && dir.y.abs() < 1.0e-5 && dir.z > 0.0);
+        let projected = camera
+            .project(Vector3::new(0.0, 0.0, 10.0), screen_size)
+            .unwrap();
+        assert!((projected - center).norm() < 1.0e-3);
+    }
+
+    #[test]
+    fn test_is_aabb_visible() {
+        let mut camera = make_camera();
+        let in_front = AxisAlignedBoundingBox::from_min_max(
+            Vector3::new(-1.0, -1.0, 9.0),
+            Vector3::new(1.0, 1.0, 11.0),
+        );
+        let behind = AxisAlignedBoundingBox::from_min_max(
+            Vector3::new(-1.0, -1.0, -11.0),
+            Vector3::new(1.0, 1.0, -9.0),
+        );
+        assert!(camera.is_aabb_visible(&in_front));
+        assert!(!camera.is_aabb_visible(&behind));
+        // The query does not touch the visibility cache.
+        assert_eq!(camera.visible_nodes().count(), 0);
+
+        // Degenerate frustum is treated as visible.
+        camera.view_matrix = Matrix4::zeros();
+        assert!(camera.is_aabb_visible(&behind));
+    }
+
+    #[test]
+    fn test_white_balance() {
+        assert_eq!(
+            WhiteBalance::default().multiplier(),
+            Vector3::new(1.0, 1.0, 1.0)
+        );
+
+        let warm = WhiteBalance::new(3000.0, 0.0).multiplier();
+        assert!(warm.x >= 1.0 && warm.z < 1.0);
+        let cold = WhiteBalance::new(12000.0, 0.0).multiplier();
+        assert!(cold.x < 1.0 && cold.z >= 1.0);
+        let magenta = WhiteBalance::new(WhiteBalance::NEUTRAL_TEMPERATURE, 0.5).multiplier();
+        assert_eq!(magenta, Vector3::new(1.0, 0.75, 1.0));
+
+        let mut camera = make_camera();
+        assert_eq!(camera.white_balance(), WhiteBalance::default());
+        camera.set_white_balance(100.0, 5.0);
+        assert_eq!(camera.white_balance_temperature(), 1000.0);
+        assert_eq!(camera.white_balance_tint(), 1.0);
+
+        camera.set_white_balance_settings(WhiteBalance {
+            temperature: 50000.0,
+            tint: -5.0,
+        });
+        assert_eq!(camera.white_balance(), WhiteBalance::new(40000.0, -1.0));
+    }
+
+    #[test]
+    fn test_post_effects() {
+        let mut camera = make_camera();
+        assert!(!camera.vignette().is_enabled());
+        assert_eq!(camera.chromatic_aberration(), 0.0);
+
+        camera.set_vignette(Vignette {
+            intensity: 2.0,
+            smoothness: 0.0,
+            color: Color::BLACK,
+        });
+        let vignette = camera.vignette();
+        assert!(vignette.is_enabled());
+        assert_eq!(vignette.intensity, 1.0);
+        assert_eq!(vignette.smoothness, 0.01);
+
+        // Transparent vignette does not change the image.
+        camera.set_vignette(Vignette {
+            color: Color::TRANSPARENT,
+            ..vignette
+        });
+        assert!(!camera.vignette().is_enabled());
+
+        camera.set_chromatic_aberration(-1.0);
+        assert_eq!(camera.chromatic_aberration(), 0.0);
+        let camera = CameraBuilder::new(BaseBuilder::new())
+            .with_chromatic_aberration(0.5)
+            .build_camera();
+        assert_eq!(camera.chromatic_aberration(), 0.5);
+    }
+
+    #[test]
+    fn test_depth_of_field() {
+        let dof = DepthOfField {
+            enabled: true,
+            focus_distance: 10.0,
+            focal_range: 4.0,
+            max_blur: 8.0,
+        };
+        assert_eq!(dof.circle_of_confusion(10.0), 0.0);
+        assert_eq!(dof.circle_of_confusion(12.0), 0.0);
+        assert_eq!(dof.circle_of_confusion(16.0), 1.0);
+        assert_eq!(dof.circle_of_confusion(4.0), 1.0);
+        assert_eq!(dof.circle_of_confusion(1000.0), 1.0);
+        assert_eq!(dof.circle_of_confusion(6.0), 0.5);
+
+        let mut graph = Graph::new();
+        let target = PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 3.0, 4.0))
+                    .build(),
+            ),
+        )
+        .build(&mut graph)
+        .to_base();
+        let camera = CameraBuilder::new(BaseBuilder::new())
+            .with_depth_of_field(dof)
+            .with_auto_focus(target)
+            .build(&mut graph);
+
+        // The fixed distance is used until the first update.
+        assert_eq!(graph[camera].focus_distance(), 10.0);
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0, Default::default());
+        assert!((graph[camera].focus_distance() - 5.0).abs() < 1.0e-5);
+
+        graph[camera].set_auto_focus(Handle::NONE);
+        assert_eq!(graph[camera].focus_distance(), 10.0);
+    }
+
+    #[test]
+    fn test_world_units_per_pixel() {
+        let frame_size = Vector2::new(200.0, 100.0);
+
+        let camera = CameraBuilder::new(BaseBuilder::new())
+            .with_projection(Projection::Perspective(PerspectiveProjection {
+                fov: 90.0f32.to_radians(),
+                ..Default::default()
+            }))
+            .build_camera();
+        let near = camera.world_units_per_pixel(10.0, frame_size);
+        let far = camera.world_units_per_pixel(20.0, frame_size);
+        // 90 degrees vertical FOV shows 20 units at the distance of 10 units.
+        assert!((near - 20.0 / 100.0).abs() < 1.0e-5);
+        assert!((far - 2.0 * near).abs() < 1.0e-5);
+
+        let camera = CameraBuilder::new(BaseBuilder::new())
+            .with_projection(Projection::Orthographic(OrthographicProjection {
+                vertical_size: 5.0,
+                ..Default::default()
+            }))
+            .build_camera();
+        let near = camera.world_units_per_pixel(1.0, frame_size);
+        let far = camera.world_units_per_pixel(100.0, frame_size);
+        assert!((near - 10.0 / 100.0).abs() < 1.0e-5);
+        assert_eq!(near, far);
+    }
+
+    #[test]
+    fn test_render_mask_of_legacy_camera() {
+        // Cameras loaded from the scenes that were saved before cameras had their own render mask
+        // get the default one, so they still render only the layers of their node.
+        let mut camera = CameraBuilder::new(BaseBuilder::new()).build_camera();
+        camera
+            .base
+            .render_mask
+            .set_value_and_mark_modified(BitMask(0b01));
+        assert_eq!(camera.effective_render_mask(), BitMask(0b01));
+
+        camera.set_render_mask(BitMask(0b11));
+        assert_eq!(camera.effective_render_mask(), BitMask(0b01));
+
+        camera.set_render_mask(BitMask(0b10));
+        assert_eq!(camera.effective_render_mask(), BitMask::none());
+    }
+}
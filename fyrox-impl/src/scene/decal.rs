@@ -24,6 +24,7 @@
 
 use crate::{
     core::{
+        algebra::Vector4,
         color::Color,
         math::aabb::AxisAlignedBoundingBox,
         pool::Handle,
@@ -45,6 +46,34 @@ use crate::{
 use fyrox_graph::constructor::ConstructorProvider;
 use fyrox_graph::SceneGraph;
 use std::ops::{Deref, DerefMut};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Defines how the color of a decal should be interpreted.
+#[derive(
+    Default,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    Debug,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+    TypeUuidProvider,
+)]
+#[type_uuid(id = "5c1f8e0d-2a57-4d0c-9a8f-6f3b1c2e7d41")]
+pub enum DecalColorSpace {
+    /// The color is authored in sRGB color space and will be converted to linear color space before
+    /// rendering. This is the right choice for colors that tint albedo of surfaces.
+    #[default]
+    Srgb,
+    /// The color is used as is, without any conversion. This is the right choice for decals that
+    /// carry linear data.
+    Linear,
+}
 
 /// Decal is an image that gets projected to a geometry of a scene. Blood splatters, bullet holes, scratches
 /// etc. are done via decals.
@@ -63,6 +92,10 @@ use std::ops::{Deref, DerefMut};
 /// should not affect any surrounding objects, this can be achieved by using decal mask. Each decal has layer index,
 /// it will be drawn only if the index matches the index of the object that inside of decal bounds.
 ///
+/// If a decal must be applied to multiple layers at once, use [`Decal::set_layer_mask`]. The layer mask is a bit
+/// mask where every bit `N` allows the decal to be drawn on surfaces with layer index `N` (only the first 32 layers
+/// can be addressed this way). When the mask is zero (default), the decal falls back to the single layer index.
+///
 /// # Supported maps
 ///
 /// Currently, only diffuse and normal maps are supported. Diffuse and normal maps will be automatically projected
@@ -108,7 +141,7 @@ use std::ops::{Deref, DerefMut};
 ///         .build(graph)
 /// }
 /// ```
-#[derive(Debug, Visit, Default, Clone, Reflect, ComponentProvider)]
+#[derive(Debug, Visit, Clone, Reflect, ComponentProvider)]
 #[reflect(derived_type = "Node")]
 pub struct Decal {
     base: Base,
@@ -125,6 +158,33 @@ pub struct Decal {
     #[reflect(min_value = 0.0)]
     #[reflect(setter = "set_layer")]
     layer: InheritableVariable<u8>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_layer_mask")]
+    layer_mask: InheritableVariable<u32>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_premultiplied_alpha")]
+    premultiplied_alpha: InheritableVariable<bool>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_color_space")]
+    color_space: InheritableVariable<DecalColorSpace>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_two_sided")]
+    two_sided: InheritableVariable<bool>,
+
+    #[visit(optional)]
+    #[reflect(min_value = 0.0, max_value = 1.5708, step = 0.1)]
+    #[reflect(setter = "set_max_angle")]
+    max_angle: InheritableVariable<f32>,
+}
+
+impl Default for Decal {
+    fn default() -> Self {
+        DecalBuilder::new(BaseBuilder::new()).build_decal()
+    }
 }
 
 impl Deref for Decal {
@@ -213,6 +273,95 @@ impl Decal {
     pub fn layer(&self) -> u8 {
         *self.layer
     }
+
+    /// Sets layer mask of the decal. Every bit `N` of the mask allows the decal to be drawn on
+    /// surfaces with layer index `N`, for example `0b101` means that the decal will be drawn on
+    /// layers 0 and 2. Zero mask (default) means that the decal uses its layer index (see
+    /// [`Self::set_layer`]) instead.
+    pub fn set_layer_mask(&mut self, layer_mask: u32) -> u32 {
+        self.layer_mask.set_value_and_mark_modified(layer_mask)
+    }
+
+    /// Returns current layer mask.
+    pub fn layer_mask(&self) -> u32 {
+        *self.layer_mask
+    }
+
+    /// Checks whether the decal will be drawn on a surface with the given layer index. This is
+    /// the same check that is done by the decal shader.
+    pub fn affects_layer(&self, layer: u8) -> bool {
+        let layer_mask = *self.layer_mask;
+        if layer_mask == 0 {
+            layer == *self.layer
+        } else {
+            1u32.checked_shl(layer as u32)
+                .is_some_and(|bit| layer_mask & bit != 0)
+        }
+    }
+
+    /// Defines whether the diffuse texture of the decal has premultiplied alpha or not. Decals with
+    /// premultiplied alpha are blended using `One / OneMinusSrcAlpha` blending function, instead of
+    /// `SrcAlpha / OneMinusSrcAlpha`. Using straight alpha blending on premultiplied textures leads
+    /// to dark halos around the decal. Default is `false` (straight alpha).
+    pub fn set_premultiplied_alpha(&mut self, premultiplied_alpha: bool) -> bool {
+        self.premultiplied_alpha
+            .set_value_and_mark_modified(premultiplied_alpha)
+    }
+
+    /// Returns `true` if the decal uses premultiplied alpha, `false` - otherwise.
+    pub fn premultiplied_alpha(&self) -> bool {
+        *self.premultiplied_alpha
+    }
+
+    /// Sets color space of the decal color. See [`DecalColorSpace`] docs for more info. Default is
+    /// [`DecalColorSpace::Srgb`].
+    pub fn set_color_space(&mut self, color_space: DecalColorSpace) -> DecalColorSpace {
+        self.color_space.set_value_and_mark_modified(color_space)
+    }
+
+    /// Returns current color space of the decal color.
+    pub fn color_space(&self) -> DecalColorSpace {
+        *self.color_space
+    }
+
+    /// Defines whether the decal is projected on all surfaces inside its bounds (two-sided), or only
+    /// on the surfaces that face the local +Y axis of the decal (one-sided). Two-sided decals wrap
+    /// around thin geometry, such as fences, and cover both its sides. One-sided decals do not
+    /// leak through to the back side of such geometry. Default is `true`.
+    pub fn set_two_sided(&mut self, two_sided: bool) -> bool {
+        self.two_sided.set_value_and_mark_modified(two_sided)
+    }
+
+    /// Returns `true` if the decal is two-sided, `false` - otherwise.
+    pub fn two_sided(&self) -> bool {
+        *self.two_sided
+    }
+
+    /// Sets the max angle (in radians) between the normal of a surface and the projection axis
+    /// (local Y axis) of the decal. The decal smoothly fades out on the surfaces which angle is
+    /// in `[0.8 * max_angle; max_angle]` range and it is not visible on steeper surfaces. This
+    /// hides texture stretching on the surfaces that are nearly parallel to the projection axis.
+    /// The angle is measured to the closest direction of the axis, so it never exceeds 90 degrees
+    /// and the max angle is clamped to `[0; pi/2]` range. The max angle of 90 degrees disables the
+    /// fading, this is the default value.
+    pub fn set_max_angle(&mut self, max_angle: f32) -> f32 {
+        self.max_angle
+            .set_value_and_mark_modified(max_angle.clamp(0.0, std::f32::consts::FRAC_PI_2))
+    }
+
+    /// Returns the max angle (in radians) between the normal of a surface and the projection axis
+    /// of the decal. See [`Self::set_max_angle`] for more info.
+    pub fn max_angle(&self) -> f32 {
+        *self.max_angle
+    }
+
+    /// Returns the decal color in linear color space, ready to be passed to shaders.
+    pub fn linear_color(&self) -> Vector4<f32> {
+        match *self.color_space {
+            DecalColorSpace::Srgb => self.color.srgb_to_linear_f32(),
+            DecalColorSpace::Linear => self.color.as_frgba(),
+        }
+    }
 }
 
 impl ConstructorProvider<Node, Graph> for Decal {
@@ -250,6 +399,11 @@ pub struct DecalBuilder {
     normal_texture: Option<TextureResource>,
     color: Color,
     layer: u8,
+    layer_mask: u32,
+    premultiplied_alpha: bool,
+    color_space: DecalColorSpace,
+    two_sided: bool,
+    max_angle: f32,
 }
 
 impl DecalBuilder {
@@ -261,6 +415,11 @@ impl DecalBuilder {
             normal_texture: None,
             color: Color::opaque(255, 255, 255),
             layer: 0,
+            layer_mask: 0,
+            premultiplied_alpha: false,
+            color_space: Default::default(),
+            two_sided: true,
+            max_angle: std::f32::consts::FRAC_PI_2,
         }
     }
 
@@ -288,6 +447,37 @@ impl DecalBuilder {
         self
     }
 
+    /// Sets desired layer mask. See [`Decal::set_layer_mask`] for more info.
+    pub fn with_layer_mask(mut self, layer_mask: u32) -> Self {
+        self.layer_mask = layer_mask;
+        self
+    }
+
+    /// Sets whether the diffuse texture of the decal has premultiplied alpha or not.
+    pub fn with_premultiplied_alpha(mut self, premultiplied_alpha: bool) -> Self {
+        self.premultiplied_alpha = premultiplied_alpha;
+        self
+    }
+
+    /// Sets desired color space of the decal color.
+    pub fn with_color_space(mut self, color_space: DecalColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    /// Sets whether the decal is two-sided or not. See [`Decal::set_two_sided`] for more info.
+    pub fn with_two_sided(mut self, two_sided: bool) -> Self {
+        self.two_sided = two_sided;
+        self
+    }
+
+    /// Sets desired max angle (in radians) between the normal of a surface and the projection
+    /// axis of the decal. See [`Decal::set_max_angle`] for more info.
+    pub fn with_max_angle(mut self, max_angle: f32) -> Self {
+        self.max_angle = max_angle.clamp(0.0, std::f32::consts::FRAC_PI_2);
+        self
+    }
+
     /// Creates new Decal node.
     pub fn build_decal(self) -> Decal {
         Decal {
@@ -296,6 +486,11 @@ impl DecalBuilder {
             normal_texture: self.normal_texture.into(),
             color: self.color.into(),
             layer: self.layer.into(),
+            layer_mask: self.layer_mask.into(),
+            premultiplied_alpha: self.premultiplied_alpha.into(),
+            color_space: self.color_space.into(),
+            two_sided: self.two_sided.into(),
+            max_angle: self.max_angle.into(),
         }
     }
 
@@ -309,3 +504,60 @@ impl DecalBuilder {
         graph.add_node(self.build_node()).to_variant()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::scene::{
+        base::BaseBuilder,
+        decal::{Decal, DecalBuilder},
+    };
+
+    #[test]
+    fn test_decal_layer_mask() {
+        let decal = DecalBuilder::new(BaseBuilder::new())
+            .with_layer(3)
+            .build_decal();
+        assert!(decal.affects_layer(3));
+        assert!(!decal.affects_layer(0));
+
+        let mut decal = DecalBuilder::new(BaseBuilder::new())
+            .with_layer(3)
+            .with_layer_mask(0b101)
+            .build_decal();
+        assert!(decal.affects_layer(0));
+        assert!(decal.affects_layer(2));
+        assert!(!decal.affects_layer(3));
+        assert!(!decal.affects_layer(40));
+
+        assert_eq!(decal.set_layer_mask(0), 0b101);
+        assert!(decal.affects_layer(3));
+    }
+
+    #[test]
+    fn test_decal_two_sided() {
+        // Decals without the flag (including the ones from old scenes) must stay two-sided.
+        assert!(Decal::default().two_sided());
+
+        let mut decal = DecalBuilder::new(BaseBuilder::new())
+            .with_two_sided(false)
+            .build_decal();
+        assert!(!decal.two_sided());
+        assert!(!decal.set_two_sided(true));
+        assert!(decal.two_sided());
+    }
+
+    #[test]
+    fn test_decal_max_angle() {
+        // No fading by default.
+        assert_eq!(Decal::default().max_angle(), std::f32::consts::FRAC_PI_2);
+
+        let mut decal = DecalBuilder::new(BaseBuilder::new())
+            .with_max_angle(1.0)
+            .build_decal();
+        assert_eq!(decal.max_angle(), 1.0);
+        decal.set_max_angle(-1.0);
+        assert_eq!(decal.max_angle(), 0.0);
+        decal.set_max_angle(std::f32::consts::PI);
+        assert_eq!(decal.max_angle(), std::f32::consts::FRAC_PI_2);
+    }
+}
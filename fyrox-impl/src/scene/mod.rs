@@ -190,6 +190,43 @@ pub enum EnvironmentLightingSource {
     AmbientColor,
 }
 
+/// A G-Buffer target that will be drawn on screen instead of the final frame. It is a debug option
+/// that helps to see the contents of the G-Buffer, which is filled by the deferred renderer. By
+/// default, it is set to [`GBufferDebugTarget::Disabled`].
+#[derive(
+    Reflect,
+    Visit,
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+    TypeUuidProvider,
+)]
+#[type_uuid(id = "4e23b8d7-94ad-4695-81cd-50c703860bd2")]
+pub enum GBufferDebugTarget {
+    /// The final frame is drawn as usual.
+    #[default]
+    Disabled,
+    /// Diffuse color of the surfaces.
+    Diffuse,
+    /// World-space normals of the surfaces, remapped from `[-1; 1]` to `[0; 1]` range.
+    Normal,
+    /// Ambient lighting and emission of the surfaces.
+    Ambient,
+    /// Metallic (red), roughness (green) and ambient occlusion (blue) of the surfaces.
+    Material,
+    /// Decal layer indices of the surfaces. Each layer has its own color, zero layer is black.
+    DecalMask,
+    /// Depth, linearized using the near and far clipping planes of the camera. Black is the near
+    /// plane and white is the far plane.
+    Depth,
+}
+
 /// Rendering options of a scene. It allows you to specify a render target to render the scene to, change its clear color, etc.
 #[derive(Debug, Visit, Reflect, PartialEq)]
 pub struct SceneRenderingOptions {
@@ -220,6 +257,18 @@ pub struct SceneRenderingOptions {
     /// by this coefficient.
     #[visit(optional)]
     pub environment_lighting_brightness: f32,
+
+    /// Whether to draw projection volumes of every decal in the scene as wireframe boxes. Each box
+    /// is colored by decal's layer index. This is a debug option, that helps to see where decals
+    /// are projected. Default is `false`.
+    #[visit(optional)]
+    pub debug_draw_decal_volumes: bool,
+
+    /// A G-Buffer target that will be drawn on screen instead of the final frame. This is a debug
+    /// option, that helps to find issues in deferred rendering. Default is
+    /// [`GBufferDebugTarget::Disabled`].
+    #[visit(optional)]
+    pub debug_gbuffer_target: GBufferDebugTarget,
 }
 
 impl Default for SceneRenderingOptions {
@@ -231,6 +280,8 @@ impl Default for SceneRenderingOptions {
             ambient_lighting_color: Color::opaque(100, 100, 100),
             environment_lighting_source: Default::default(),
             environment_lighting_brightness: 1.0,
+            debug_draw_decal_volumes: false,
+            debug_gbuffer_target: Default::default(),
         }
     }
 }
@@ -244,6 +295,8 @@ impl Clone for SceneRenderingOptions {
             ambient_lighting_color: self.ambient_lighting_color,
             environment_lighting_source: self.environment_lighting_source,
             environment_lighting_brightness: self.environment_lighting_brightness,
+            debug_draw_decal_volumes: self.debug_draw_decal_volumes,
+            debug_gbuffer_target: self.debug_gbuffer_target,
         }
     }
 }
@@ -24,7 +24,7 @@ use std::{fmt::Display, sync::LazyLock};
 
 use crate::{
     asset::{builtin::BuiltInResource, embedded_data_source, untyped::ResourceKind},
-    core::{log::Log, reflect::prelude::*, uuid_provider, visitor::prelude::*},
+    core::{algebra::Vector3, log::Log, reflect::prelude::*, uuid_provider, visitor::prelude::*},
 };
 use fyrox_core::color::Color;
 use fyrox_texture::{
@@ -38,7 +38,7 @@ use uuid::{uuid, Uuid};
 /// skies and/or some other objects (mountains, buildings, etc.). Usually skyboxes used
 /// in outdoor scenes, however real use of it limited only by your imagination. Skybox
 /// will be drawn first, none of objects could be drawn before skybox.
-#[derive(Debug, Clone, Default, PartialEq, Reflect, Visit, Eq)]
+#[derive(Debug, Clone, PartialEq, Reflect, Visit, Eq)]
 pub struct SkyBox {
     /// Texture for front face.
     #[reflect(setter = "set_front")]
@@ -64,6 +64,23 @@ pub struct SkyBox {
     #[reflect(setter = "set_bottom")]
     pub(crate) bottom: Option<TextureResource>,
 
+    /// If set, faces of different sizes are resized to the largest common size when the cube map
+    /// is created, instead of failing with [`SkyBoxError::DifferentTexture`].
+    #[reflect(setter = "set_auto_resize")]
+    #[visit(optional)]
+    pub(crate) auto_resize: bool,
+
+    /// Color of the faces that have no texture. See [`Self::set_missing_face_color`] for more info.
+    #[reflect(setter = "set_missing_face_color")]
+    #[visit(optional)]
+    pub(crate) missing_face_color: Color,
+
+    /// If set, the cube map is created with a full chain of mip levels. See [`Self::set_mip_maps`]
+    /// for more info.
+    #[reflect(setter = "set_mip_maps")]
+    #[visit(optional)]
+    pub(crate) mip_maps: bool,
+
     /// Cubemap texture
     #[reflect(hidden)]
     #[visit(skip)]
@@ -72,6 +89,23 @@ pub struct SkyBox {
 
 uuid_provider!(SkyBox = "45f359f1-e26f-4ace-81df-097f63474c72");
 
+impl Default for SkyBox {
+    fn default() -> Self {
+        Self {
+            front: None,
+            back: None,
+            left: None,
+            right: None,
+            top: None,
+            bottom: None,
+            auto_resize: false,
+            missing_face_color: Color::TRANSPARENT,
+            mip_maps: false,
+            cubemap: None,
+        }
+    }
+}
+
 impl SkyBox {
     /// Creates a new sky box from a single color.
     pub fn from_single_color(color: Color) -> Self {
@@ -102,10 +136,58 @@ impl SkyBox {
         self.cubemap.as_ref()
     }
 
+    /// Calculates average luminance of the cube map by reading every pixel of its faces on CPU. The
+    /// value could be used as an initial value for automatic exposure when the sky box dominates
+    /// the scene, which reduces the flash on scene loading while the exposure adapts.
+    ///
+    /// # Important notes
+    ///
+    /// This method is slow for large cube maps, do not call it every frame. Returns `0.0` if there's
+    /// no cube map, or it is not loaded, or its pixel format is not supported (compressed formats).
+    /// Pixels in sRGB formats are converted to linear space first.
+    pub fn average_luminance(&self) -> f32 {
+        let Some(cubemap) = self.cubemap.as_ref() else {
+            return 0.0;
+        };
+        let state = cubemap.state();
+        let Some(data) = state.data_ref() else {
+            return 0.0;
+        };
+
+        let pixel_kind = data.pixel_kind();
+        let Some(pixel_size) = pixel_kind.size_in_bytes() else {
+            return 0.0;
+        };
+
+        // Cube map stores six faces of the first mip level one after another.
+        let bytes = data.mip_level_data(0);
+
+        let mut sum = 0.0f64;
+        let mut count = 0usize;
+        for pixel in bytes.chunks_exact(pixel_size) {
+            match pixel_luminance(pixel_kind, pixel) {
+                Some(luminance) => sum += luminance as f64,
+                None => return 0.0,
+            }
+            count += 1;
+        }
+
+        if count == 0 {
+            0.0
+        } else {
+            (sum / count as f64) as f32
+        }
+    }
+
     /// Validates input set of texture and checks if it possible to create a cube map from them.
     /// There are two main conditions for successful cube map creation:
     /// - All textures must have same width and height, and width must be equal to height.
+    ///   This condition is skipped if [`Self::auto_resize`] is enabled.
     /// - All textures must have same pixel kind.
+    ///
+    /// Faces with floating-point pixel kinds (such as [`TexturePixelKind::RGB16F`],
+    /// [`TexturePixelKind::RGBA16F`] or [`TexturePixelKind::RGBA32F`]) are supported as well and keep
+    /// their full range, which allows to use HDR environment maps.
     pub fn validate(&self) -> Result<(), SkyBoxError> {
         struct TextureInfo {
             pixel_kind: TexturePixelKind,
@@ -119,7 +201,7 @@ impl SkyBox {
             if let Some(texture) = texture {
                 if let Some(texture) = texture.state().data() {
                     if let TextureKind::Rectangle { width, height } = texture.kind() {
-                        if width != height {
+                        if !self.auto_resize && width != height {
                             return Err(SkyBoxError::NonSquareTexture {
                                 index,
                                 width,
@@ -128,8 +210,9 @@ impl SkyBox {
                         }
 
                         if let Some(first_info) = first_info.as_mut() {
-                            if first_info.width != width
-                                || first_info.height != height
+                            let same_size =
+                                first_info.width == width && first_info.height == height;
+                            if (!self.auto_resize && !same_size)
                                 || first_info.pixel_kind != texture.pixel_kind()
                             {
                                 return Err(SkyBoxError::DifferentTexture {
@@ -164,53 +247,104 @@ impl SkyBox {
     /// # Important notes.
     ///
     /// It will fail if provided face's kind is not TextureKind::Rectangle.
+    ///
+    /// If [`Self::auto_resize`] is enabled, faces that are smaller than the largest face (or not square)
+    /// are resized to the largest size using nearest neighbour sampling. Only the data of the cube map
+    /// is resized, the face textures are left untouched. Compressed faces cannot be resized.
+    ///
+    /// If [`Self::mip_maps`] is enabled, the mip levels of the cube map are generated by averaging
+    /// each 2x2 block of pixels of the previous level. Mip levels cannot be generated for compressed
+    /// faces, such cube maps have only one level.
     pub fn create_cubemap(&mut self) -> Result<(), SkyBoxError> {
         self.validate()?;
 
-        let (kind, pixel_kind, bytes_per_face) =
-            self.textures().iter().find(|face| face.is_some()).map_or(
-                (
-                    TextureKind::Rectangle {
-                        width: 1,
-                        height: 1,
-                    },
-                    TexturePixelKind::R8,
-                    1,
-                ),
-                |face| {
-                    let face = face.clone().unwrap();
-                    let data = face.data_ref();
-
-                    (data.kind(), data.pixel_kind(), data.mip_level_data(0).len())
-                },
-            );
-
-        let size = match kind {
-            TextureKind::Rectangle { width, height } => {
-                assert_eq!(width, height);
-                width
+        let textures = self.textures();
+
+        let mut size = None;
+        let mut pixel_kind = TexturePixelKind::R8;
+        for face in textures.iter().flatten() {
+            let data = face.data_ref();
+            match data.kind() {
+                TextureKind::Rectangle { width, height } => {
+                    let face_size = width.max(height);
+                    size = Some(size.map_or(face_size, |size: u32| size.max(face_size)));
+                    pixel_kind = data.pixel_kind();
+                }
+                kind => return Err(SkyBoxError::UnsupportedTextureKind(kind)),
             }
-            _ => return Err(SkyBoxError::UnsupportedTextureKind(kind)),
+        }
+        let size = size.unwrap_or(1);
+
+        // Compressed faces can only be used as is, so take the size of the face data from one of
+        // the faces that do not need resizing.
+        let bytes_per_face = textures
+            .iter()
+            .flatten()
+            .find_map(|face| {
+                let data = face.data_ref();
+                match data.kind() {
+                    TextureKind::Rectangle { width, height } if width == size && height == size => {
+                        Some(data.mip_level_data(0).len())
+                    }
+                    _ => None,
+                }
+            })
+            .or_else(|| {
+                pixel_kind
+                    .size_in_bytes()
+                    .map(|pixel_size| size as usize * size as usize * pixel_size)
+            })
+            .ok_or(SkyBoxError::UnsupportedPixelKind(pixel_kind))?;
+
+        // Compressed pixel kinds cannot be filled with a color, so such faces stay black.
+        let pixel_count = size as usize * size as usize;
+        let missing_face_data = match pack_pixel(pixel_kind, self.missing_face_color) {
+            Some(pixel) if pixel.len() * pixel_count == bytes_per_face => pixel.repeat(pixel_count),
+            _ => vec![0; bytes_per_face],
         };
 
         let mut data = Vec::<u8>::with_capacity(bytes_per_face * 6);
-        for face in self.textures().iter() {
+        for face in textures.iter() {
             if let Some(f) = face.clone() {
-                data.extend(f.data_ref().mip_level_data(0));
+                let face_data = f.data_ref();
+                match face_data.kind() {
+                    TextureKind::Rectangle { width, height } if width != size || height != size => {
+                        let pixel_size = pixel_kind
+                            .size_in_bytes()
+                            .ok_or(SkyBoxError::UnsupportedPixelKind(pixel_kind))?;
+                        data.extend(resize_nearest(
+                            face_data.mip_level_data(0),
+                            width,
+                            height,
+                            size,
+                            pixel_size,
+                        ));
+                    }
+                    _ => data.extend(face_data.mip_level_data(0)),
+                }
             } else {
-                let black_face_data = vec![0; bytes_per_face];
-                data.extend(black_face_data);
+                data.extend_from_slice(&missing_face_data);
             }
         }
 
-        let cubemap = TextureResource::from_bytes(
-            Uuid::new_v4(),
-            TextureKind::Cube { size },
-            pixel_kind,
-            data,
-            ResourceKind::Embedded,
-        )
-        .ok_or(SkyBoxError::UnableToBuildCubeMap)?;
+        let mut mip_count = 1;
+        if self.mip_maps {
+            match mip_channel_kind(pixel_kind) {
+                Some(channel_kind) => {
+                    mip_count = generate_cube_mips(&mut data, size, pixel_kind, channel_kind);
+                }
+                None => Log::warn(format!(
+                    "Unable to generate mip levels of the sky box, {pixel_kind:?} pixel kind is not supported."
+                )),
+            }
+        }
+
+        let cubemap =
+            Texture::from_bytes_with_mips(TextureKind::Cube { size }, pixel_kind, data, mip_count)
+                .map(|texture| {
+                    TextureResource::new_ok(Uuid::new_v4(), ResourceKind::Embedded, texture)
+                })
+                .ok_or(SkyBoxError::UnableToBuildCubeMap)?;
 
         let mut cubemap_ref = cubemap.data_ref();
         cubemap_ref.set_s_wrap_mode(TextureWrapMode::ClampToEdge);
@@ -222,6 +356,74 @@ impl SkyBox {
         Ok(())
     }
 
+    /// Enables or disables automatic resizing of the faces, and recreates the cube map.
+    /// See [`Self::create_cubemap`] for more info.
+    pub fn set_auto_resize(&mut self, auto_resize: bool) -> bool {
+        let prev = std::mem::replace(&mut self.auto_resize, auto_resize);
+        Log::verify(self.create_cubemap());
+        prev
+    }
+
+    /// Returns `true` if the faces of different sizes are resized when the cube map is created.
+    pub fn auto_resize(&self) -> bool {
+        self.auto_resize
+    }
+
+    /// Sets the color of the faces that have no texture, and recreates the cube map. The color is
+    /// packed into the pixel kind of the other faces, so for example only the red channel is used
+    /// for [`TexturePixelKind::R8`] faces. Faces with compressed pixel kinds are always black.
+    /// Default color is [`Color::TRANSPARENT`], which makes missing faces black.
+    pub fn set_missing_face_color(&mut self, color: Color) -> Color {
+        let prev = std::mem::replace(&mut self.missing_face_color, color);
+        Log::verify(self.create_cubemap());
+        prev
+    }
+
+    /// Returns the color of the faces that have no texture.
+    pub fn missing_face_color(&self) -> Color {
+        self.missing_face_color
+    }
+
+    /// Enables or disables generation of mip levels of the cube map, and recreates the cube map.
+    /// Mip levels are useful when the cube map is sampled with different levels of blurriness,
+    /// for example for blurred reflections. Keep in mind that mip levels increase the amount of
+    /// GPU memory used by the cube map by ~33%. Disabled by default.
+    pub fn set_mip_maps(&mut self, mip_maps: bool) -> bool {
+        let prev = std::mem::replace(&mut self.mip_maps, mip_maps);
+        Log::verify(self.create_cubemap());
+        prev
+    }
+
+    /// Returns `true` if the cube map is created with a full chain of mip levels.
+    pub fn mip_maps(&self) -> bool {
+        self.mip_maps
+    }
+
+    /// Replaces all six faces of the sky box with the faces of the given equirectangular
+    /// (spherical) panorama and creates a new cube map from them. The panorama must be a
+    /// [`TextureKind::Rectangle`] texture with 2:1 aspect ratio. Each face will be a square
+    /// with the size of the half of the panorama's height, the center of the panorama is
+    /// projected onto the front face.
+    ///
+    /// # Important notes.
+    ///
+    /// The faces are made by nearest neighbour sampling of the first mip level of the panorama,
+    /// so the cube map is then built exactly as if the faces were provided separately. Compressed
+    /// pixel kinds are not supported.
+    pub fn create_from_equirectangular(
+        &mut self,
+        texture: &TextureResource,
+    ) -> Result<(), SkyBoxError> {
+        let [left, right, top, bottom, front, back] = equirectangular_to_faces(texture)?;
+        self.left = Some(left);
+        self.right = Some(right);
+        self.top = Some(top);
+        self.bottom = Some(bottom);
+        self.front = Some(front);
+        self.back = Some(back);
+        self.create_cubemap()
+    }
+
     /// Returns slice with all textures, where: 0 - Left, 1 - Right, 2 - Top, 3 - Bottom
     /// 4 - Front, 5 - Back.
     ///
@@ -338,6 +540,348 @@ impl SkyBox {
     }
 }
 
+fn luminance(r: f32, g: f32, b: f32) -> f32 {
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+fn srgb_luminance(r: u8, g: u8, b: u8) -> f32 {
+    let linear = Color::opaque(r, g, b).srgb_to_linear_f32();
+    luminance(linear.x, linear.y, linear.z)
+}
+
+fn unorm8(v: u8) -> f32 {
+    v as f32 / 255.0
+}
+
+fn unorm16(bytes: &[u8]) -> f32 {
+    u16::from_le_bytes([bytes[0], bytes[1]]) as f32 / u16::MAX as f32
+}
+
+fn float16(bytes: &[u8]) -> f32 {
+    half::f16::from_le_bytes([bytes[0], bytes[1]]).to_f32()
+}
+
+fn float32(bytes: &[u8]) -> f32 {
+    f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn pack_unorm16(values: &[u8]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|&v| (v as u16 * 257).to_le_bytes())
+        .collect()
+}
+
+fn pack_float16(values: &[f32]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|&v| half::f16::from_f32(v).to_le_bytes())
+        .collect()
+}
+
+fn pack_float32(values: &[f32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+/// Packs the color into the bytes of a single pixel of the given kind. Returns `None` for
+/// compressed pixel kinds.
+fn pack_pixel(pixel_kind: TexturePixelKind, color: Color) -> Option<Vec<u8>> {
+    let Color { r, g, b, a } = color;
+    let l = (luminance(unorm8(r), unorm8(g), unorm8(b)) * 255.0).round() as u8;
+    let f = color.as_frgba();
+    let bytes = match pixel_kind {
+        TexturePixelKind::R8 => vec![r],
+        TexturePixelKind::Luminance8 => vec![l],
+        TexturePixelKind::RG8 => vec![r, g],
+        TexturePixelKind::LuminanceAlpha8 => vec![l, a],
+        TexturePixelKind::RGB8 | TexturePixelKind::SRGB8 => vec![r, g, b],
+        TexturePixelKind::RGBA8 | TexturePixelKind::SRGBA8 => vec![r, g, b, a],
+        TexturePixelKind::BGR8 => vec![b, g, r],
+        TexturePixelKind::BGRA8 => vec![b, g, r, a],
+        TexturePixelKind::R16 => pack_unorm16(&[r]),
+        TexturePixelKind::Luminance16 => pack_unorm16(&[l]),
+        TexturePixelKind::RG16 => pack_unorm16(&[r, g]),
+        TexturePixelKind::LuminanceAlpha16 => pack_unorm16(&[l, a]),
+        TexturePixelKind::RGB16 => pack_unorm16(&[r, g, b]),
+        TexturePixelKind::RGBA16 => pack_unorm16(&[r, g, b, a]),
+        TexturePixelKind::R16F => pack_float16(&[f.x]),
+        TexturePixelKind::RGB16F => pack_float16(&[f.x, f.y, f.z]),
+        TexturePixelKind::RGBA16F => pack_float16(&[f.x, f.y, f.z, f.w]),
+        TexturePixelKind::R32F => pack_float32(&[f.x]),
+        TexturePixelKind::RGB32F => pack_float32(&[f.x, f.y, f.z]),
+        TexturePixelKind::RGBA32F => pack_float32(&[f.x, f.y, f.z, f.w]),
+        TexturePixelKind::DXT1RGB
+        | TexturePixelKind::DXT1RGBA
+        | TexturePixelKind::DXT3RGBA
+        | TexturePixelKind::DXT5RGBA
+        | TexturePixelKind::R8RGTC
+        | TexturePixelKind::RG8RGTC => return None,
+    };
+    Some(bytes)
+}
+
+/// Resizes the given image into a square image of the given size using nearest neighbour sampling.
+fn resize_nearest(source: &[u8], width: u32, height: u32, size: u32, pixel_size: usize) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(size as usize * size as usize * pixel_size);
+    for y in 0..size {
+        let row = (y as u64 * height as u64 / size as u64) as usize;
+        for x in 0..size {
+            let column = (x as u64 * width as u64 / size as u64) as usize;
+            let offset = (row * width as usize + column) * pixel_size;
+            bytes.extend_from_slice(&source[offset..offset + pixel_size]);
+        }
+    }
+    bytes
+}
+
+/// Type of the channels of a pixel, used to average the pixels of mip levels.
+#[derive(Copy, Clone)]
+enum ChannelKind {
+    Unorm8,
+    Unorm16,
+    Float16,
+    Float32,
+}
+
+impl ChannelKind {
+    fn size(self) -> usize {
+        match self {
+            ChannelKind::Unorm8 => 1,
+            ChannelKind::Unorm16 | ChannelKind::Float16 => 2,
+            ChannelKind::Float32 => 4,
+        }
+    }
+
+    fn read(self, bytes: &[u8]) -> f32 {
+        match self {
+            ChannelKind::Unorm8 => bytes[0] as f32,
+            ChannelKind::Unorm16 => u16::from_le_bytes([bytes[0], bytes[1]]) as f32,
+            ChannelKind::Float16 => float16(bytes),
+            ChannelKind::Float32 => float32(bytes),
+        }
+    }
+
+    fn write(self, value: f32, bytes: &mut Vec<u8>) {
+        match self {
+            ChannelKind::Unorm8 => bytes.push(value.round().clamp(0.0, u8::MAX as f32) as u8),
+            ChannelKind::Unorm16 => {
+                bytes.extend((value.round().clamp(0.0, u16::MAX as f32) as u16).to_le_bytes())
+            }
+            ChannelKind::Float16 => bytes.extend(half::f16::from_f32(value).to_le_bytes()),
+            ChannelKind::Float32 => bytes.extend(value.to_le_bytes()),
+        }
+    }
+}
+
+/// Returns the type of the channels of the given pixel kind, or `None` for compressed pixel kinds.
+fn mip_channel_kind(pixel_kind: TexturePixelKind) -> Option<ChannelKind> {
+    match pixel_kind {
+        TexturePixelKind::R8
+        | TexturePixelKind::RG8
+        | TexturePixelKind::RGB8
+        | TexturePixelKind::RGBA8
+        | TexturePixelKind::BGR8
+        | TexturePixelKind::BGRA8
+        | TexturePixelKind::SRGB8
+        | TexturePixelKind::SRGBA8
+        | TexturePixelKind::Luminance8
+        | TexturePixelKind::LuminanceAlpha8 => Some(ChannelKind::Unorm8),
+        TexturePixelKind::R16
+        | TexturePixelKind::RG16
+        | TexturePixelKind::RGB16
+        | TexturePixelKind::RGBA16
+        | TexturePixelKind::Luminance16
+        | TexturePixelKind::LuminanceAlpha16 => Some(ChannelKind::Unorm16),
+        TexturePixelKind::R16F | TexturePixelKind::RGB16F | TexturePixelKind::RGBA16F => {
+            Some(ChannelKind::Float16)
+        }
+        TexturePixelKind::R32F | TexturePixelKind::RGB32F | TexturePixelKind::RGBA32F => {
+            Some(ChannelKind::Float32)
+        }
+        TexturePixelKind::DXT1RGB
+        | TexturePixelKind::DXT1RGBA
+        | TexturePixelKind::DXT3RGBA
+        | TexturePixelKind::DXT5RGBA
+        | TexturePixelKind::R8RGTC
+        | TexturePixelKind::RG8RGTC => None,
+    }
+}
+
+/// Halves the size of the given square image by averaging each 2x2 block of pixels.
+fn downsample_box(
+    source: &[u8],
+    size: u32,
+    pixel_size: usize,
+    channel_kind: ChannelKind,
+) -> Vec<u8> {
+    let half_size = (size / 2).max(1) as usize;
+    let size = size as usize;
+    let channel_size = channel_kind.size();
+    let mut bytes = Vec::with_capacity(half_size * half_size * pixel_size);
+    for y in 0..half_size {
+        for x in 0..half_size {
+            for channel in (0..pixel_size).step_by(channel_size) {
+                let mut sum = 0.0;
+                for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let row = (2 * y + dy).min(size - 1);
+                    let column = (2 * x + dx).min(size - 1);
+                    let offset = (row * size + column) * pixel_size + channel;
+                    sum += channel_kind.read(&source[offset..]);
+                }
+                channel_kind.write(sum / 4.0, &mut bytes);
+            }
+        }
+    }
+    bytes
+}
+
+/// Appends all mip levels down to 1x1 to the given cube map data, that contains the six faces of
+/// the first level. Returns the total amount of mip levels.
+fn generate_cube_mips(
+    data: &mut Vec<u8>,
+    size: u32,
+    pixel_kind: TexturePixelKind,
+    channel_kind: ChannelKind,
+) -> u32 {
+    let Some(pixel_size) = pixel_kind.size_in_bytes() else {
+        return 1;
+    };
+    let mut mip_count = 1;
+    let mut level_size = size;
+    let mut level_offset = 0;
+    while level_size > 1 {
+        let bytes_per_face = level_size as usize * level_size as usize * pixel_size;
+        for face in 0..6 {
+            let begin = level_offset + face * bytes_per_face;
+            let face_data = downsample_box(
+                &data[begin..begin + bytes_per_face],
+                level_size,
+                pixel_size,
+                channel_kind,
+            );
+            data.extend(face_data);
+        }
+        level_offset += 6 * bytes_per_face;
+        level_size /= 2;
+        mip_count += 1;
+    }
+    mip_count
+}
+
+/// Calculates the direction that corresponds to a texel of a cube map face. The faces use the
+/// same order as [`SkyBox::textures`], which matches the order of the faces in the cube map.
+fn cube_face_direction(face: usize, x: u32, y: u32, size: u32) -> Vector3<f32> {
+    let s = 2.0 * (x as f32 + 0.5) / size as f32 - 1.0;
+    let t = 2.0 * (y as f32 + 0.5) / size as f32 - 1.0;
+    match face {
+        0 => Vector3::new(1.0, -t, -s),
+        1 => Vector3::new(-1.0, -t, s),
+        2 => Vector3::new(s, 1.0, t),
+        3 => Vector3::new(s, -1.0, -t),
+        4 => Vector3::new(s, -t, 1.0),
+        _ => Vector3::new(-s, -t, -1.0),
+    }
+}
+
+/// Converts an equirectangular panorama into six cube map faces in the order of [`SkyBox::textures`].
+fn equirectangular_to_faces(
+    texture: &TextureResource,
+) -> Result<[TextureResource; 6], SkyBoxError> {
+    let state = texture.state();
+    let Some(data) = state.data_ref() else {
+        return Err(SkyBoxError::TextureIsNotReady { index: 0 });
+    };
+    let (width, height) = match data.kind() {
+        TextureKind::Rectangle { width, height } => (width, height),
+        kind => return Err(SkyBoxError::UnsupportedTextureKind(kind)),
+    };
+    if height == 0 || width != 2 * height {
+        return Err(SkyBoxError::InvalidEquirectangularAspect { width, height });
+    }
+    let pixel_kind = data.pixel_kind();
+    let pixel_size = pixel_kind
+        .size_in_bytes()
+        .ok_or(SkyBoxError::UnsupportedPixelKind(pixel_kind))?;
+    let source = data.mip_level_data(0);
+    let size = (height / 2).max(1);
+
+    let mut faces = Vec::with_capacity(6);
+    for face in 0..6 {
+        let mut bytes = Vec::with_capacity(size as usize * size as usize * pixel_size);
+        for y in 0..size {
+            for x in 0..size {
+                let direction = cube_face_direction(face, x, y, size).normalize();
+                let u = 0.5 + direction.x.atan2(direction.z) / std::f32::consts::TAU;
+                let v = 0.5 - direction.y.clamp(-1.0, 1.0).asin() / std::f32::consts::PI;
+                let column = ((u * width as f32) as u32).min(width - 1);
+                let row = ((v * height as f32) as u32).min(height - 1);
+                let offset = (row as usize * width as usize + column as usize) * pixel_size;
+                bytes.extend_from_slice(&source[offset..offset + pixel_size]);
+            }
+        }
+        let face = TextureResource::from_bytes(
+            Uuid::new_v4(),
+            TextureKind::Rectangle {
+                width: size,
+                height: size,
+            },
+            pixel_kind,
+            bytes,
+            ResourceKind::Embedded,
+        )
+        .ok_or(SkyBoxError::UnableToBuildCubeMap)?;
+        faces.push(face);
+    }
+    faces
+        .try_into()
+        .map_err(|_| SkyBoxError::UnableToBuildCubeMap)
+}
+
+/// Calculates luminance of a single pixel. Returns `None` for compressed pixel formats.
+fn pixel_luminance(pixel_kind: TexturePixelKind, pixel: &[u8]) -> Option<f32> {
+    let value = match pixel_kind {
+        TexturePixelKind::R8 | TexturePixelKind::Luminance8 => unorm8(pixel[0]),
+        TexturePixelKind::RG8 | TexturePixelKind::LuminanceAlpha8 => unorm8(pixel[0]),
+        TexturePixelKind::RGB8 | TexturePixelKind::RGBA8 => {
+            luminance(unorm8(pixel[0]), unorm8(pixel[1]), unorm8(pixel[2]))
+        }
+        TexturePixelKind::BGR8 | TexturePixelKind::BGRA8 => {
+            luminance(unorm8(pixel[2]), unorm8(pixel[1]), unorm8(pixel[0]))
+        }
+        TexturePixelKind::SRGB8 | TexturePixelKind::SRGBA8 => {
+            srgb_luminance(pixel[0], pixel[1], pixel[2])
+        }
+        TexturePixelKind::R16
+        | TexturePixelKind::RG16
+        | TexturePixelKind::Luminance16
+        | TexturePixelKind::LuminanceAlpha16 => unorm16(pixel),
+        TexturePixelKind::RGB16 | TexturePixelKind::RGBA16 => luminance(
+            unorm16(&pixel[0..2]),
+            unorm16(&pixel[2..4]),
+            unorm16(&pixel[4..6]),
+        ),
+        TexturePixelKind::R16F => float16(pixel),
+        TexturePixelKind::RGB16F | TexturePixelKind::RGBA16F => luminance(
+            float16(&pixel[0..2]),
+            float16(&pixel[2..4]),
+            float16(&pixel[4..6]),
+        ),
+        TexturePixelKind::R32F => float32(pixel),
+        TexturePixelKind::RGB32F | TexturePixelKind::RGBA32F => luminance(
+            float32(&pixel[0..4]),
+            float32(&pixel[4..8]),
+            float32(&pixel[8..12]),
+        ),
+        TexturePixelKind::DXT1RGB
+        | TexturePixelKind::DXT1RGBA
+        | TexturePixelKind::DXT3RGBA
+        | TexturePixelKind::DXT5RGBA
+        | TexturePixelKind::R8RGTC
+        | TexturePixelKind::RG8RGTC => return None,
+    };
+    Some(value)
+}
+
 /// An error that may occur during skybox creation.
 #[derive(Debug)]
 pub enum SkyBoxError {
@@ -376,6 +920,15 @@ pub enum SkyBoxError {
         /// Index of the faulty input texture.
         index: usize,
     },
+    /// Equirectangular texture must be exactly twice as wide as it is high.
+    InvalidEquirectangularAspect {
+        /// Width of the faulty texture.
+        width: u32,
+        /// Height of the faulty texture.
+        height: u32,
+    },
+    /// Pixel kind of the input texture cannot be converted (compressed pixel kinds).
+    UnsupportedPixelKind(TexturePixelKind),
 }
 
 impl std::error::Error for SkyBoxError {}
@@ -407,6 +960,13 @@ impl Display for SkyBoxError {
             Expected width: {expected_width}, height: {expected_height}, kind: {expected_pixel_kind:?}. \
             Actual width: {actual_width}, height: {actual_height}, kind: {actual_pixel_kind:?}."),
             SkyBoxError::TextureIsNotReady { index } => write!(f, "Input texture is not loaded. Index: {index}"),
+            SkyBoxError::InvalidEquirectangularAspect { width, height } => write!(
+                f,
+                "Equirectangular texture must have 2:1 aspect ratio. Width: {width}, height: {height}"
+            ),
+            SkyBoxError::UnsupportedPixelKind(pixel_kind) => {
+                write!(f, "Unsupported pixel kind: {pixel_kind:?}")
+            }
         }
     }
 }
@@ -425,6 +985,14 @@ pub struct SkyBoxBuilder {
     pub top: Option<TextureResource>,
     /// Texture for bottom face.
     pub bottom: Option<TextureResource>,
+    /// Equirectangular (spherical) panorama. If set, it replaces all six faces.
+    pub equirectangular: Option<TextureResource>,
+    /// Whether the faces of different sizes should be resized to the largest common size.
+    pub auto_resize: bool,
+    /// Color of the faces that have no texture.
+    pub missing_face_color: Color,
+    /// Whether the cube map should be created with a full chain of mip levels.
+    pub mip_maps: bool,
 }
 
 impl SkyBoxBuilder {
@@ -437,6 +1005,10 @@ impl SkyBoxBuilder {
             right: Some(texture.clone()),
             top: Some(texture.clone()),
             bottom: Some(texture.clone()),
+            equirectangular: None,
+            auto_resize: false,
+            missing_face_color: Color::TRANSPARENT,
+            mip_maps: false,
         }
     }
 
@@ -476,6 +1048,37 @@ impl SkyBoxBuilder {
         self
     }
 
+    /// Sets desired equirectangular (spherical) panorama, that will be converted into all six
+    /// faces of cubemap. See [`SkyBox::create_from_equirectangular`] for more info.
+    pub fn with_equirectangular(mut self, texture: TextureResource) -> Self {
+        self.equirectangular = Some(texture);
+        self
+    }
+
+    /// Sets whether the faces of different sizes should be resized (using nearest neighbour sampling)
+    /// to the size of the largest face, instead of failing with [`SkyBoxError::DifferentTexture`].
+    /// Pixel kinds of the faces still must match. The face textures are not modified, only the
+    /// cube map data is resized.
+    pub fn with_auto_resize(mut self, auto_resize: bool) -> Self {
+        self.auto_resize = auto_resize;
+        self
+    }
+
+    /// Sets the color of the faces that have no texture. See [`SkyBox::set_missing_face_color`]
+    /// for more info.
+    pub fn with_missing_face_color(mut self, color: Color) -> Self {
+        self.missing_face_color = color;
+        self
+    }
+
+    /// Sets whether the cube map should be created with a full chain of mip levels. Mip levels
+    /// increase the amount of GPU memory used by the cube map by ~33%. See [`SkyBox::set_mip_maps`]
+    /// for more info.
+    pub fn with_mip_maps(mut self, mip_maps: bool) -> Self {
+        self.mip_maps = mip_maps;
+        self
+    }
+
     /// Creates a new instance of skybox.
     pub fn build(self) -> Result<SkyBox, SkyBoxError> {
         let mut skybox = SkyBox {
@@ -485,10 +1088,17 @@ impl SkyBoxBuilder {
             bottom: self.bottom,
             front: self.front,
             back: self.back,
+            auto_resize: self.auto_resize,
+            missing_face_color: self.missing_face_color,
+            mip_maps: self.mip_maps,
             cubemap: None,
         };
 
-        skybox.create_cubemap()?;
+        if let Some(equirectangular) = self.equirectangular {
+            skybox.create_from_equirectangular(&equirectangular)?;
+        } else {
+            skybox.create_cubemap()?;
+        }
 
         Ok(skybox)
     }
@@ -573,6 +1183,10 @@ impl SkyBoxKind {
             right: Some(right),
             top: Some(top),
             bottom: Some(bottom),
+            equirectangular: None,
+            auto_resize: false,
+            missing_face_color: Color::TRANSPARENT,
+            mip_maps: false,
         }
         .build()
         .unwrap()
@@ -608,3 +1222,227 @@ pub enum SkyBoxKind {
     /// Specific skybox. One can be built using [`SkyBoxBuilder`].
     Specific(SkyBox),
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::untyped::ResourceKind,
+        scene::skybox::{SkyBox, SkyBoxBuilder, SkyBoxError},
+    };
+    use fyrox_core::color::Color;
+    use fyrox_texture::{TextureKind, TexturePixelKind, TextureResource, TextureResourceExtension};
+    use uuid::Uuid;
+
+    fn make_texture(width: u32, height: u32, pixel: impl Fn(u32) -> [u8; 4]) -> TextureResource {
+        let pixel = &pixel;
+        let bytes = (0..height)
+            .flat_map(|y| (0..width).flat_map(move |_| pixel(y)))
+            .collect();
+        TextureResource::from_bytes(
+            Uuid::new_v4(),
+            TextureKind::Rectangle { width, height },
+            TexturePixelKind::RGBA8,
+            bytes,
+            ResourceKind::Embedded,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_average_luminance_of_constant_cubemap() {
+        let skybox = SkyBox::from_single_color(Color::opaque(255, 255, 255));
+        assert!((skybox.average_luminance() - 1.0).abs() < 0.001);
+
+        let skybox = SkyBox::from_single_color(Color::opaque(0, 255, 0));
+        assert!((skybox.average_luminance() - 0.7152).abs() < 0.001);
+
+        assert_eq!(SkyBox::default().average_luminance(), 0.0);
+    }
+    #[test]
+    fn test_equirectangular_sky_box() {
+        // Upper half of the panorama is red, lower half is blue.
+        let red = [255, 0, 0, 255];
+        let blue = [0, 0, 255, 255];
+        let panorama = make_texture(16, 8, |y| if y < 4 { red } else { blue });
+        let skybox = SkyBoxBuilder::from_texture(&panorama)
+            .with_equirectangular(panorama)
+            .build()
+            .unwrap();
+
+        let face_data = |face: Option<TextureResource>| {
+            let face = face.unwrap();
+            let data = face.data_ref();
+            assert!(matches!(
+                data.kind(),
+                TextureKind::Rectangle {
+                    width: 4,
+                    height: 4
+                }
+            ));
+            data.mip_level_data(0).to_vec()
+        };
+
+        assert!(face_data(skybox.top()).chunks(4).all(|p| p == red));
+        assert!(face_data(skybox.bottom()).chunks(4).all(|p| p == blue));
+        let front = face_data(skybox.front());
+        let (upper, lower) = front.split_at(front.len() / 2);
+        assert!(upper.chunks(4).all(|p| p == red));
+        assert!(lower.chunks(4).all(|p| p == blue));
+
+        let cubemap = skybox.cubemap().unwrap();
+        assert!(matches!(
+            cubemap.data_ref().kind(),
+            TextureKind::Cube { size: 4 }
+        ));
+    }
+
+    #[test]
+    fn test_equirectangular_invalid_aspect() {
+        let texture = make_texture(4, 4, |_| [0, 0, 0, 255]);
+        let mut skybox = SkyBox::default();
+        assert!(matches!(
+            skybox.create_from_equirectangular(&texture),
+            Err(SkyBoxError::InvalidEquirectangularAspect {
+                width: 4,
+                height: 4
+            })
+        ));
+    }
+    #[test]
+    fn test_auto_resize() {
+        let small = make_texture(2, 2, |_| [255, 0, 0, 255]);
+        let large = make_texture(4, 4, |_| [0, 255, 0, 255]);
+
+        assert!(matches!(
+            SkyBoxBuilder::from_texture(&large)
+                .with_left(small.clone())
+                .build(),
+            Err(SkyBoxError::DifferentTexture { .. })
+        ));
+
+        let skybox = SkyBoxBuilder::from_texture(&large)
+            .with_left(small)
+            .with_auto_resize(true)
+            .build()
+            .unwrap();
+
+        // The face itself is untouched.
+        assert!(matches!(
+            skybox.left().unwrap().data_ref().kind(),
+            TextureKind::Rectangle {
+                width: 2,
+                height: 2
+            }
+        ));
+
+        let cubemap = skybox.cubemap().unwrap();
+        let cubemap = cubemap.data_ref();
+        assert!(matches!(cubemap.kind(), TextureKind::Cube { size: 4 }));
+        let data = cubemap.mip_level_data(0);
+        let (left, right) = data.split_at(4 * 4 * 4);
+        assert!(left.chunks(4).all(|p| p == [255, 0, 0, 255]));
+        assert!(right[..4 * 4 * 4].chunks(4).all(|p| p == [0, 255, 0, 255]));
+    }
+
+    #[test]
+    fn test_missing_face_color() {
+        let texture = make_texture(2, 2, |_| [0, 255, 0, 255]);
+        let mut skybox = SkyBoxBuilder::from_texture(&texture)
+            .with_missing_face_color(Color::opaque(255, 0, 0))
+            .build()
+            .unwrap();
+        skybox.set_right(None);
+
+        let cubemap = skybox.cubemap().unwrap();
+        let data = cubemap.data_ref().mip_level_data(0).to_vec();
+        let (left, rest) = data.split_at(2 * 2 * 4);
+        let (right, rest) = rest.split_at(2 * 2 * 4);
+        assert!(left.chunks(4).all(|p| p == [0, 255, 0, 255]));
+        assert!(right.chunks(4).all(|p| p == [255, 0, 0, 255]));
+        assert!(rest.chunks(4).all(|p| p == [0, 255, 0, 255]));
+
+        // The color is packed into the pixel kind of the other faces.
+        let texture = TextureResource::from_bytes(
+            Uuid::new_v4(),
+            TextureKind::Rectangle {
+                width: 2,
+                height: 2,
+            },
+            TexturePixelKind::R8,
+            vec![10; 4],
+            ResourceKind::Embedded,
+        )
+        .unwrap();
+        let mut skybox = SkyBox::default();
+        skybox.set_missing_face_color(Color::opaque(200, 0, 0));
+        skybox.set_top(Some(texture));
+
+        let cubemap = skybox.cubemap().unwrap();
+        let data = cubemap.data_ref().mip_level_data(0).to_vec();
+        assert_eq!(data.len(), 6 * 4);
+        assert_eq!(data[8..12], [10; 4]);
+        assert!(data[..8].iter().chain(&data[12..]).all(|&v| v == 200));
+    }
+
+    #[test]
+    fn test_hdr_faces() {
+        let pixel = [4.0f32, 2.0, 0.5, 1.0]
+            .iter()
+            .flat_map(|&v| half::f16::from_f32(v).to_le_bytes())
+            .collect::<Vec<_>>();
+        let texture = TextureResource::from_bytes(
+            Uuid::new_v4(),
+            TextureKind::Rectangle {
+                width: 2,
+                height: 2,
+            },
+            TexturePixelKind::RGBA16F,
+            pixel.repeat(4),
+            ResourceKind::Embedded,
+        )
+        .unwrap();
+        let mut skybox = SkyBox::default();
+        skybox.set_mip_maps(true);
+        skybox.set_missing_face_color(Color::opaque(255, 0, 0));
+        skybox.set_top(Some(texture));
+        assert!(skybox.validate().is_ok());
+
+        let cubemap = skybox.cubemap().unwrap();
+        let cubemap = cubemap.data_ref();
+        assert_eq!(cubemap.pixel_kind(), TexturePixelKind::RGBA16F);
+        assert_eq!(cubemap.mip_count(), 2);
+        // Top face is the third one, its values are above 1.0 and must survive mip generation.
+        let data = cubemap.data();
+        let second_level = &data[6 * 4 * 8..];
+        assert_eq!(&second_level[2 * 8..3 * 8], pixel.as_slice());
+        let red = [1.0f32, 0.0, 0.0, 1.0]
+            .iter()
+            .flat_map(|&v| half::f16::from_f32(v).to_le_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(&second_level[..8], red.as_slice());
+    }
+
+    #[test]
+    fn test_mip_maps() {
+        let texture = make_texture(4, 4, |y| if y < 2 { [200; 4] } else { [100; 4] });
+
+        let skybox = SkyBoxBuilder::from_texture(&texture).build().unwrap();
+        assert_eq!(skybox.cubemap().unwrap().data_ref().mip_count(), 1);
+
+        let skybox = SkyBoxBuilder::from_texture(&texture)
+            .with_mip_maps(true)
+            .build()
+            .unwrap();
+        let cubemap = skybox.cubemap().unwrap();
+        let cubemap = cubemap.data_ref();
+        assert_eq!(cubemap.mip_count(), 3);
+        let data = cubemap.data();
+        assert_eq!(data.len(), 6 * (16 + 4 + 1) * 4);
+
+        // Second level keeps the rows, third level averages everything.
+        let second = &data[6 * 16 * 4..6 * 16 * 4 + 4 * 4];
+        assert_eq!(second, [[200; 4], [200; 4], [100; 4], [100; 4]].concat());
+        let third = &data[6 * (16 + 4) * 4..];
+        assert!(third.chunks(4).all(|p| p == [150; 4]));
+    }
+}
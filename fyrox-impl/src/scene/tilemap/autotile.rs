@@ -21,11 +21,11 @@
 use std::fmt::Debug;
 
 use super::*;
-use fxhash::FxHashMap;
+use fxhash::{FxHashMap, FxHashSet};
 use fyrox_autotile::{
     AutoPatternConstraint, AutoPatternValueMap, AutoTerrainPatternMap, AutoTileContext, AutoTiler,
     HashConstraintMap, HashWfcConstraint, OffsetPosition, PatternSource, TileConstraint,
-    Vector2Offset, WfcConstrain, WfcFailure, WfcPropagator,
+    Vector2Offset, WfcConstrain, WfcControlFlow, WfcFailure, WfcPropagator,
 };
 use fyrox_core::log::Log;
 
@@ -121,12 +121,31 @@ impl TileSetWfcConstraint {
     ///   the terrain, unless the center value is 0, in which case the tile is ignored.
     /// - `frequency_property`: The UUID of a float property in `tile_set` that will be
     ///   used for the frequency of each tile. If None, then every tile has frequency 1.0.
+    /// - `cost_property`: The UUID of a float property in `tile_set` that will be used
+    ///   as the placement cost of each tile. The frequency of each tile is multiplied by
+    ///   `exp(-cost)`, so cheaper tiles are chosen preferentially, while a cost of 0.0
+    ///   leaves the frequency unchanged. If None, then only frequencies are used.
+    /// - `cohesion_property`: The UUID of a float property in `tile_set` that will be used
+    ///   as the cohesion of each tile. When a tile's pattern is chosen, its frequency is
+    ///   multiplied by `1.0 + cohesion` for each neighbor that already has the same terrain,
+    ///   so tiles with positive cohesion tend to form clusters. If None, or if the cohesion
+    ///   is 0.0 or less, then the plain frequency is used.
+    /// - `transform_property`: The UUID of an integer property in `tile_set` that marks which
+    ///   tiles may be rotated and reflected. For each tile with a non-zero value, every
+    ///   rotation and reflection of its pattern is also inserted into the map, using the
+    ///   transformed version of the tile from the tile set's transform sets as the tile for
+    ///   that pattern. Transformations without a transformed version are skipped, as are
+    ///   transformed versions that have a pattern of their own, since those tiles are
+    ///   already included. If None, then no tiles are transformed.
     /// - `terrain_freq`: A hash map of the terrains that will be used in wave function collapse.
     ///   Tiles whose center value are not keys in this hash map will be ignored.
     ///   Tiles whose center value are keys in this hash map will have their frequency
     ///   multiplied by the corresponding value in the hash map to calculate the final
     ///   frequency of the tile. This allows terrains to have their frequency weighted,
     ///   and allows unwanted terrains to be excluded.
+    /// - `forbidden_adjacencies`: Pairs of terrains that may never be placed in adjacent cells,
+    ///   even when the patterns of their tiles match. Each pair forbids both orders.
+    ///   The propagator treats these pairs as hard rules, like mismatched patterns.
     ///
     /// *Note:* Terrain 0 is treated specially. It has one pattern, the all-zeros default
     /// [`PatternBits`], and it corresponds to the empty tile. Tiles whose pattern value
@@ -134,12 +153,17 @@ impl TileSetWfcConstraint {
     /// function collapse, since 0 is the default value. The frequency of choosing empty
     /// tiles in wave function collapse can be controlled by setting `terrain_freq[0]` to
     /// some value.
+    #[allow(clippy::too_many_arguments)]
     pub fn fill_pattern_map(
         &mut self,
         tile_set: &TileSet,
         pattern_property: TileSetPropertyNine,
         frequency_property: Option<TileSetPropertyF32>,
+        cost_property: Option<TileSetPropertyF32>,
+        cohesion_property: Option<TileSetPropertyF32>,
+        transform_property: Option<TileSetPropertyI32>,
         terrain_freq: &FxHashMap<TileTerrainId, f32>,
+        forbidden_adjacencies: &FxHashSet<(TileTerrainId, TileTerrainId)>,
     ) -> Result<(), FillPatternMapError> {
         self.clear();
         if let Some(&frequency) = terrain_freq.get(&0) {
@@ -160,6 +184,21 @@ impl TileSetWfcConstraint {
                 return Err(FillPatternMapError::FrequencyInvalidId);
             }
         }
+        if let Some(id) = cost_property {
+            if tile_set.find_property(*id.property_uuid()).is_none() {
+                return Err(FillPatternMapError::CostInvalidId);
+            }
+        }
+        if let Some(id) = cohesion_property {
+            if tile_set.find_property(*id.property_uuid()).is_none() {
+                return Err(FillPatternMapError::CohesionInvalidId);
+            }
+        }
+        if let Some(id) = transform_property {
+            if tile_set.find_property(*id.property_uuid()).is_none() {
+                return Err(FillPatternMapError::TransformInvalidId);
+            }
+        }
         for handle in tile_set.all_tiles() {
             let frequency = if let Some(id) = frequency_property {
                 id.get_from_tile_set(tile_set, handle)
@@ -170,20 +209,140 @@ impl TileSetWfcConstraint {
             if frequency <= 0.0 {
                 continue;
             }
-            let pattern: NineI8 = pattern_property
+            let cost = if let Some(id) = cost_property {
+                id.get_from_tile_set(tile_set, handle)
+                    .map_err(|_| FillPatternMapError::CostWrongType)?
+            } else {
+                0.0
+            };
+            let frequency = frequency * (-cost).exp();
+            let cohesion = if let Some(id) = cohesion_property {
+                id.get_from_tile_set(tile_set, handle)
+                    .map_err(|_| FillPatternMapError::CohesionWrongType)?
+            } else {
+                0.0
+            };
+            let nine: NineI8 = pattern_property
                 .get_from_tile_set(tile_set, handle)
                 .map_err(|_| FillPatternMapError::PatternWrongType)?;
-            let pattern: PatternBits = pattern.into();
+            let pattern: PatternBits = nine.into();
             let center = pattern.center();
-            if center != 0 {
-                if let Some(terrain_frequency) = terrain_freq.get(&center) {
-                    self.add(pattern, frequency * terrain_frequency, handle);
+            if center == 0 {
+                continue;
+            }
+            let Some(terrain_frequency) = terrain_freq.get(&center) else {
+                continue;
+            };
+            let frequency = frequency * terrain_frequency;
+            self.add_with_cohesion(pattern, frequency, cohesion, handle);
+            let allow_transforms = if let Some(id) = transform_property {
+                id.get_from_tile_set(tile_set, handle)
+                    .map_err(|_| FillPatternMapError::TransformWrongType)?
+                    != 0
+            } else {
+                false
+            };
+            if allow_transforms {
+                for (pattern, handle) in
+                    transformed_variants(tile_set, pattern_property, nine, handle)
+                {
+                    self.add_with_cohesion(pattern, frequency, cohesion, handle);
+                }
+            }
+        }
+        if !forbidden_adjacencies.is_empty() {
+            let patterns = self.all_patterns().copied().collect::<Vec<_>>();
+            for a in patterns.iter() {
+                for b in patterns.iter() {
+                    if forbidden_adjacencies.contains(&(a.center(), b.center())) {
+                        self.forbid_neighbors(*a, *b);
+                    }
                 }
             }
         }
         self.finalize_with_terrain_normalization(PatternBits::center);
         Ok(())
     }
+
+    /// Build a terrain frequency map suitable for [`Self::fill_pattern_map`] from a float property
+    /// of the tile set, instead of entering the frequency of each terrain by hand.
+    /// The terrain of each tile is the center value of its `pattern_property`,
+    /// and the frequency of each terrain is the average value of `frequency_property`
+    /// among the tiles of that terrain. Tiles with a center value of 0 are ignored,
+    /// so the empty terrain is never included in the result.
+    pub fn terrain_freq_from_property(
+        tile_set: &TileSet,
+        pattern_property: TileSetPropertyNine,
+        frequency_property: TileSetPropertyF32,
+    ) -> Result<FxHashMap<TileTerrainId, f32>, FillPatternMapError> {
+        if tile_set
+            .find_property(*pattern_property.property_uuid())
+            .is_none()
+        {
+            return Err(FillPatternMapError::PatternInvalidId);
+        }
+        if tile_set
+            .find_property(*frequency_property.property_uuid())
+            .is_none()
+        {
+            return Err(FillPatternMapError::TerrainFrequencyInvalidId);
+        }
+        let mut sums = FxHashMap::<TileTerrainId, (f32, usize)>::default();
+        for handle in tile_set.all_tiles() {
+            let nine: NineI8 = pattern_property
+                .get_from_tile_set(tile_set, handle)
+                .map_err(|_| FillPatternMapError::PatternWrongType)?;
+            let center = PatternBits::from(nine).center();
+            if center == 0 {
+                continue;
+            }
+            let frequency = frequency_property
+                .get_from_tile_set(tile_set, handle)
+                .map_err(|_| FillPatternMapError::TerrainFrequencyWrongType)?;
+            let (sum, count) = sums.entry(center).or_default();
+            *sum += frequency;
+            *count += 1;
+        }
+        Ok(sums
+            .into_iter()
+            .map(|(terrain, (sum, count))| (terrain, sum / count as f32))
+            .collect())
+    }
+}
+
+/// The patterns and handles of the rotated and reflected versions of the given tile.
+/// A transformation is skipped if the tile set has no transformed version of the tile,
+/// since then there would be no way to render the transformed pattern, or if the
+/// transformed version has a pattern of its own, since then it is already a tile of its own.
+fn transformed_variants(
+    tile_set: &TileSet,
+    pattern_property: TileSetPropertyNine,
+    nine: NineI8,
+    handle: TileDefinitionHandle,
+) -> Vec<(PatternBits, TileDefinitionHandle)> {
+    let mut result: Vec<(PatternBits, TileDefinitionHandle)> = Vec::new();
+    for transform in OrthoTransformation::all() {
+        if transform.is_identity() {
+            continue;
+        }
+        let Some(variant) = tile_set.get_transformed_version(transform, handle) else {
+            continue;
+        };
+        if variant == handle || result.iter().any(|&(_, h)| h == variant) {
+            continue;
+        }
+        let own_pattern: Option<NineI8> =
+            pattern_property.get_from_tile_set(tile_set, variant).ok();
+        if own_pattern.is_some_and(|p| PatternBits::from(p).center() != 0) {
+            continue;
+        }
+        if let TileSetPropertyValue::NineSlice(nine) =
+            TileSetPropertyValue::NineSlice(nine).transformed(transform)
+        {
+            result.push((nine.into(), variant));
+        }
+    }
+    result
 }
 
 /// An error that might occur while filling a pattern map from a tile set
@@ -194,6 +353,22 @@ pub enum FillPatternMapError {
     FrequencyInvalidId,
     /// The frequency property was not f32.
     FrequencyWrongType,
+    /// The UUID for the cost property was not found in the tile set.
+    CostInvalidId,
+    /// The cost property was not f32.
+    CostWrongType,
+    /// The UUID for the cohesion property was not found in the tile set.
+    CohesionInvalidId,
+    /// The cohesion property was not f32.
+    CohesionWrongType,
+    /// The UUID for the transform property was not found in the tile set.
+    TransformInvalidId,
+    /// The transform property was not i32.
+    TransformWrongType,
+    /// The UUID for the terrain frequency property was not found in the tile set.
+    TerrainFrequencyInvalidId,
+    /// The terrain frequency property was not f32.
+    TerrainFrequencyWrongType,
     /// The UUID for the terrain property was not found in the tile set.
     PatternInvalidId,
     /// The terrain property was not a nine-slice.
@@ -212,6 +387,34 @@ impl Display for FillPatternMapError {
             FillPatternMapError::FrequencyWrongType => {
                 write!(f, "The frequency property should be an f32.")
             }
+            FillPatternMapError::CostInvalidId => write!(
+                f,
+                "The property UUID for the cost does not match any property in the tile set."
+            ),
+            FillPatternMapError::CostWrongType => {
+                write!(f, "The cost property should be an f32.")
+            }
+            FillPatternMapError::CohesionInvalidId => write!(
+                f,
+                "The property UUID for the cohesion does not match any property in the tile set."
+            ),
+            FillPatternMapError::CohesionWrongType => {
+                write!(f, "The cohesion property should be an f32.")
+            }
+            FillPatternMapError::TransformInvalidId => write!(
+                f,
+                "The property UUID for the transform does not match any property in the tile set."
+            ),
+            FillPatternMapError::TransformWrongType => {
+                write!(f, "The transform property should be an i32.")
+            }
+            FillPatternMapError::TerrainFrequencyInvalidId => write!(
+                f,
+                "The property UUID for the terrain frequency does not match any property in the tile set."
+            ),
+            FillPatternMapError::TerrainFrequencyWrongType => {
+                write!(f, "The terrain frequency property should be an f32.")
+            }
             FillPatternMapError::PatternInvalidId => write!(
                 f,
                 "The property UUID for the pattern does not match any property in the tile set."
@@ -401,7 +604,7 @@ impl PatternSource for TileSetPatternSource<'_, '_, '_> {
 #[derive(Debug, Default, Clone)]
 pub struct TileSetWfcPropagator {
     propagator: WfcPropagator<Vector2<i32>, PatternBits>,
-    edge_restrictions: FxHashMap<Vector2<i32>, PatternBits>,
+    edge_restrictions: Vec<(Vector2<i32>, PatternBits)>,
 }
 
 impl Deref for TileSetWfcPropagator {
@@ -428,6 +631,9 @@ impl TileSetWfcPropagator {
     /// tiles of the tile map, though such restrictions may make failure more likely.
     /// [`WfcFailure`] is returned if wave function collapse is made impossible by the
     /// restrictions.
+    ///
+    /// This is equivalent to calling [`find_edge_restrictions`](Self::find_edge_restrictions)
+    /// followed by repeatedly calling [`constrain_next_edge`](Self::constrain_next_edge).
     pub fn constrain_edges<Con>(
         &mut self,
         tile_set: &TileSet,
@@ -439,14 +645,51 @@ impl TileSetWfcPropagator {
     where
         Con: WfcConstrain<Pattern = PatternBits, Offset = Vector2Offset>,
     {
+        self.find_edge_restrictions(tile_set, pattern_property, tile_map, update);
+        while self.constrain_next_edge(constraint)? == WfcControlFlow::Continue {}
+        Ok(())
+    }
+    /// Use the given tile map to find the patterns of the tiles that surround the cells
+    /// of the wave, and remember them so that they can be applied one at a time using
+    /// [`constrain_next_edge`](Self::constrain_next_edge). This allows the edge restrictions
+    /// to be spread across multiple steps, independent of the tile map.
+    pub fn find_edge_restrictions(
+        &mut self,
+        tile_set: &TileSet,
+        pattern_property: TileSetPropertyNine,
+        tile_map: &TileMap,
+        update: &MacroTilesUpdate,
+    ) {
+        self.find_filtered_edge_restrictions(
+            tile_set,
+            pattern_property,
+            tile_map,
+            update,
+            true,
+            true,
+        );
+    }
+    /// Like [`find_edge_restrictions`](Self::find_edge_restrictions), but only the surrounding
+    /// cells of the chosen kinds are remembered. If `occupied` is true, then cells that contain
+    /// a tile are used as restrictions, so the wave fits with the tiles that are already placed.
+    /// If `empty` is true, then cells without a tile are used as restrictions, so the wave is
+    /// forced to match the empty border of the tile map.
+    pub fn find_filtered_edge_restrictions(
+        &mut self,
+        tile_set: &TileSet,
+        pattern_property: TileSetPropertyNine,
+        tile_map: &TileMap,
+        update: &MacroTilesUpdate,
+        occupied: bool,
+        empty: bool,
+    ) {
         let tiles = tile_map.tiles();
         let tiles = tiles.map(|r| r.data_ref());
-        let mut edge_restrictions = std::mem::take(&mut self.edge_restrictions);
-        edge_restrictions.clear();
+        let mut edge_restrictions = FxHashMap::default();
         for &p in self.positions() {
             for offset in Vector2::all_offsets() {
                 let p = p + offset;
-                if self.edge_restrictions.contains_key(&p) {
+                if edge_restrictions.contains_key(&p) {
                     continue;
                 }
                 if self.contains_cell(&p) {
@@ -464,18 +707,108 @@ impl TileSetWfcPropagator {
                 } else {
                     TileDefinitionHandle::EMPTY
                 };
+                let wanted = if handle.is_empty() { empty } else { occupied };
+                if !wanted {
+                    continue;
+                }
                 let pattern = pattern_property
                     .get_from_tile_set(tile_set, handle)
                     .unwrap_or_default();
                 edge_restrictions.insert(p, PatternBits(pattern.into()));
             }
         }
-        for (p, pattern) in edge_restrictions.drain() {
-            self.restrict_edge(&p, &pattern, constraint)?;
-        }
-        self.edge_restrictions = edge_restrictions;
+        self.edge_restrictions.clear();
+        self.edge_restrictions.extend(edge_restrictions);
+    }
+    /// The number of edge restrictions that were found by
+    /// [`find_edge_restrictions`](Self::find_edge_restrictions) and have not yet been applied.
+    pub fn remaining_edge_restrictions(&self) -> usize {
+        self.edge_restrictions.len()
+    }
+    /// Apply one of the edge restrictions that were found by
+    /// [`find_edge_restrictions`](Self::find_edge_restrictions).
+    /// [`WfcControlFlow::Finish`] is returned when there are no more edge restrictions,
+    /// and [`WfcFailure`] is returned if the restriction makes wave function collapse impossible.
+    pub fn constrain_next_edge<Con>(
+        &mut self,
+        constraint: &Con,
+    ) -> Result<WfcControlFlow, WfcFailure>
+    where
+        Con: WfcConstrain<Pattern = PatternBits, Offset = Vector2Offset>,
+    {
+        let Some((p, pattern)) = self.edge_restrictions.pop() else {
+            return Ok(WfcControlFlow::Finish);
+        };
+        self.propagator.restrict_edge(&p, &pattern, constraint)?;
+        Ok(WfcControlFlow::Continue)
+    }
+    /// Completely collapse the wave function like [`WfcPropagator::observe_all`], but with
+    /// backtracking. The state of the wave is saved before each of the most recent `depth`
+    /// observations, and when a contradiction is found the most recent observation is undone
+    /// and its pattern is forbidden so that an alternative may be tried. This continues until
+    /// the collapse succeeds or there are no more observations to undo, in which case
+    /// [`WfcFailure`] is returned.
+    ///
+    /// `backtracks` is incremented each time an observation is undone, so that the caller
+    /// may keep track of how much backtracking was required. If `depth` is 0, this behaves
+    /// exactly like `observe_all`.
+    pub fn observe_all_with_backtracking<R, Con>(
+        &mut self,
+        rng: &mut R,
+        constraint: &Con,
+        depth: u32,
+        backtracks: &mut u32,
+    ) -> Result<(), WfcFailure>
+    where
+        R: Rng + ?Sized,
+        Con: WfcConstrain<Pattern = PatternBits, Offset = Vector2Offset>,
+    {
+        self.propagator.clear_checkpoints();
+        while self.observe_one(rng, constraint, depth, backtracks)? == WfcControlFlow::Continue {}
         Ok(())
     }
+    /// Perform a single step of [`observe_all_with_backtracking`](Self::observe_all_with_backtracking),
+    /// so that the wave function collapse can be spread across many calls and aborted between
+    /// any two of them. Each step propagates the previous observation and then either observes
+    /// one more cell or, if a contradiction was found, backtracks.
+    ///
+    /// [`WfcControlFlow::Finish`] is returned when every cell has been given a pattern,
+    /// and [`WfcFailure`] is returned when a contradiction is found and there are no more
+    /// observations to undo.
+    pub fn observe_one<R, Con>(
+        &mut self,
+        rng: &mut R,
+        constraint: &Con,
+        depth: u32,
+        backtracks: &mut u32,
+    ) -> Result<WfcControlFlow, WfcFailure>
+    where
+        R: Rng + ?Sized,
+        Con: WfcConstrain<Pattern = PatternBits, Offset = Vector2Offset>,
+    {
+        let result = match self.propagator.propagate_until_finished(constraint) {
+            Ok(()) => {
+                let Some(position) = self.propagator.find_min_entropy(rng) else {
+                    return Ok(WfcControlFlow::Finish);
+                };
+                let Some(pattern) = self
+                    .propagator
+                    .choose_random_pattern(&position, rng, constraint)
+                else {
+                    return Err(WfcFailure);
+                };
+                self.propagator
+                    .push_checkpoint(position, pattern, depth as usize);
+                self.propagator
+                    .observe_cell(&position, &pattern, constraint)
+            }
+            Err(err) => Err(err),
+        };
+        if result.is_err() {
+            self.propagator.undo_observation(constraint, backtracks)?;
+        }
+        Ok(WfcControlFlow::Continue)
+    }
     /// Modify the given tile map update based on the result of the
     /// autotiler.
     pub fn apply_autotile_to_update<R: Rng + ?Sized>(
@@ -513,3 +846,99 @@ impl TileSetWfcPropagator {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::uuid::Uuid;
+
+    const PATTERN: TileSetPropertyNine = TileSetPropertyNine(Uuid::from_u128(1));
+    const COST: TileSetPropertyF32 = TileSetPropertyF32(Uuid::from_u128(2));
+
+    fn terrain(center: TileTerrainId) -> NineI8 {
+        let mut nine = NineI8::default();
+        nine.0[4] = center;
+        nine
+    }
+
+    /// A tile set with a single page that has one tile for each of the given terrains,
+    /// each tile with the given cost.
+    fn make_tile_set(tiles: &[(TileTerrainId, f32)]) -> TileSet {
+        let mut tile_set = TileSet::default();
+        tile_set.properties.push(TileSetPropertyLayer {
+            uuid: PATTERN.0,
+            name: "Pattern".into(),
+            prop_type: TileSetPropertyType::NineSlice,
+            named_values: Vec::new(),
+        });
+        tile_set.properties.push(TileSetPropertyLayer {
+            uuid: COST.0,
+            name: "Cost".into(),
+            prop_type: TileSetPropertyType::F32,
+            named_values: Vec::new(),
+        });
+        let mut tiles_map = TileGridMap::default();
+        for (x, &(center, cost)) in tiles.iter().enumerate() {
+            let mut definition = TileDefinition::default();
+            let properties = &mut definition.data.properties;
+            properties.insert(PATTERN.0, TileSetPropertyValue::NineSlice(terrain(center)));
+            properties.insert(COST.0, TileSetPropertyValue::F32(cost));
+            tiles_map.insert(Vector2::new(x as i32, 0), definition);
+        }
+        tile_set.insert_page(
+            Vector2::new(0, 0),
+            TileSetPage {
+                icon: TileDefinitionHandle::EMPTY,
+                source: TileSetPageSource::Freeform(tiles_map),
+            },
+        );
+        tile_set
+    }
+
+    fn fill(
+        tile_set: &TileSet,
+        cost_property: Option<TileSetPropertyF32>,
+    ) -> Result<TileSetWfcConstraint, FillPatternMapError> {
+        let terrain_freq = FxHashMap::from_iter([(1, 1.0), (2, 1.0)]);
+        let mut constraint = TileSetWfcConstraint::default();
+        constraint.fill_pattern_map(
+            tile_set,
+            PATTERN,
+            None,
+            cost_property,
+            None,
+            None,
+            &terrain_freq,
+            &FxHashSet::default(),
+        )?;
+        Ok(constraint)
+    }
+
+    #[test]
+    fn test_wfc_cost_prefers_cheaper_tiles() {
+        let tile_set = make_tile_set(&[(1, 0.0), (2, 3.0f32.ln())]);
+        let constraint = fill(&tile_set, Some(COST)).unwrap();
+        // The frequency of the second tile is divided by three.
+        let first = constraint.probability_of(&terrain(1).into());
+        let second = constraint.probability_of(&terrain(2).into());
+        assert!((first - 0.75).abs() < 1.0e-5, "{first}");
+        assert!((second - 0.25).abs() < 1.0e-5, "{second}");
+
+        // Without the cost property the costs are ignored.
+        let constraint = fill(&tile_set, None).unwrap();
+        let first = constraint.probability_of(&terrain(1).into());
+        let second = constraint.probability_of(&terrain(2).into());
+        assert!((first - 0.5).abs() < 1.0e-5, "{first}");
+        assert!((second - 0.5).abs() < 1.0e-5, "{second}");
+    }
+
+    #[test]
+    fn test_wfc_cost_property_must_exist() {
+        let tile_set = make_tile_set(&[(1, 0.0)]);
+        let missing = TileSetPropertyF32(Uuid::from_u128(3));
+        assert!(matches!(
+            fill(&tile_set, Some(missing)),
+            Err(FillPatternMapError::CostInvalidId)
+        ));
+    }
+}
@@ -136,6 +136,63 @@ impl TileMapEffect for TileOverlayEffect {
     }
 }
 
+/// Draws the given tiles with reduced opacity on top of the tile map,
+/// so that a proposed change can be seen without modifying the tile map.
+/// This effect is intended to be added to [`TileMap::after_effects`].
+#[derive(Debug, Default)]
+pub struct TilePreviewEffect {
+    /// True if the tiles are to be drawn. If false, then this effect does nothing.
+    pub active: bool,
+    /// The opacity of the tiles, from 0 for invisible to 255 for fully opaque.
+    pub alpha: u8,
+    /// The tiles to render
+    pub tiles: FxHashMap<Vector2<i32>, TileDefinitionHandle>,
+}
+
+impl TileMapEffect for TilePreviewEffect {
+    fn render_special_tiles(&self, context: &mut TileMapRenderContext) {
+        if !self.active {
+            return;
+        }
+        let bounds = context.visible_bounds();
+        for (&position, &handle) in self.tiles.iter() {
+            if handle.is_empty() || !bounds.contains(position) {
+                continue;
+            }
+            let Some(mut data) = context.tile_set.get_tile_render_data(handle.into()) else {
+                continue;
+            };
+            data.color.a = (data.color.a as u16 * self.alpha as u16 / 255) as u8;
+            context.push_tile(position, &data);
+        }
+    }
+}
+
+/// Fills the given cells with solid colors on top of the tile map, which is useful
+/// for visualizing data about each cell, such as the state of an auto-tiler.
+/// This effect is intended to be added to [`TileMap::after_effects`].
+#[derive(Debug, Default)]
+pub struct TileColorEffect {
+    /// True if the colors are to be drawn. If false, then this effect does nothing.
+    pub active: bool,
+    /// The color of each cell.
+    pub colors: FxHashMap<Vector2<i32>, Color>,
+}
+
+impl TileMapEffect for TileColorEffect {
+    fn render_special_tiles(&self, context: &mut TileMapRenderContext) {
+        if !self.active {
+            return;
+        }
+        let bounds = context.visible_bounds();
+        for (&position, &color) in self.colors.iter() {
+            if bounds.contains(position) {
+                context.push_tile(position, &TileRenderData::new(None, color));
+            }
+        }
+    }
+}
+
 /// Uses the given tile update to render the replacement tiles and make
 /// the erased tiles invisible.
 #[derive(Debug)]
@@ -795,7 +795,6 @@ mod test {
         utils::lightmap::{Lightmap, LightmapInputData},
     };
     use fyrox_resource::untyped::ResourceKind;
-    use std::path::Path;
     use uuid::Uuid;
 
     #[test]
@@ -841,11 +840,15 @@ mod test {
         let lightmap =
             Lightmap::new(data, 64, 0.005, Default::default(), Default::default()).unwrap();
 
+        // Keep the output out of the source tree.
+        let output_dir = std::env::temp_dir().join("fyrox_test_generate_lightmap");
+        std::fs::create_dir_all(&output_dir).unwrap();
+
         let mut counter = 0;
         for entry_set in lightmap.map.values() {
             for entry in entry_set {
                 let mut data = entry.texture.as_ref().unwrap().data_ref();
-                data.save(Path::new(&format!("{counter}.png"))).unwrap();
+                data.save(&output_dir.join(format!("{counter}.png"))).unwrap();
                 counter += 1;
             }
         }
@@ -180,7 +180,15 @@
 //!
 //! - `GBuffer` - A pass that fills a set of textures (render targets) with various data about each
 //!   rendered object (depth, normal, albedo, etc.). These textures then are used for physically-based
-//!   lighting. Use this pass when you want the standard lighting to work with your objects.
+//!   lighting. Use this pass when you want the standard lighting to work with your objects. The
+//!   fragment shader must write every output of the G-Buffer, including the screen-space velocity
+//!   at `location = 5` (see `S_ComputeVelocity`), otherwise its content will be undefined. Pixels
+//!   that are not covered by this pass have zero velocity.
+//!
+//! - `DepthPrePass` - An optional pass that is used by the depth pre-pass (if enabled) instead of
+//!   `GBuffer` pass. It should only compute the position and do the same alpha test as `GBuffer`
+//!   pass; the depth values must match exactly (use `invariant gl_Position;` in both passes). If a
+//!   shader has no such pass, `GBuffer` pass with disabled color writes is used instead.
 //!
 //! - `Forward` - A pass that draws an object directly in a render target. It could be used to render
 //!   translucent objects.
@@ -274,6 +282,10 @@
 //! | zNear                | `float`    | Near clipping plane location.                    |
 //! | zFar                 | `float`    | Far clipping plane location.                     |
 //! | zRange               | `float`    | `zFar - zNear`                                   |
+//! | prevViewProjection   | `mat4`     | `viewProjectionMatrix` of the previous frame.    |
+//!
+//! `prevViewProjection` is used to calculate screen-space velocity in G-Buffer pass, in other
+//! passes it is equal to `viewProjectionMatrix`.
 //!
 //! ### `fyrox_lightData`
 //!
@@ -718,6 +730,7 @@ impl ShaderDefinition {
                         ShaderProperty::new_float("zNear"),
                         ShaderProperty::new_float("zFar"),
                         ShaderProperty::new_float("zRange"),
+                        ShaderProperty::new_matrix4("prevViewProjection"),
                     ]);
                 }
                 "fyrox_lightData" => {
@@ -153,6 +153,11 @@
                 out vec3 tangent;
                 out vec3 binormal;
                 out vec2 secondTexCoord;
+                out vec4 clipPosition;
+                out vec4 prevClipPosition;
+
+                // Must produce exactly the same depth as the DepthPrePass pass.
+                invariant gl_Position;
 
                 void main()
                 {
@@ -215,6 +220,8 @@
                     secondTexCoord = vertexSecondTexCoord;
 
                     gl_Position = fyrox_instanceData.worldViewProjection * localPosition;
+                    clipPosition = gl_Position;
+                    prevClipPosition = fyrox_cameraData.prevViewProjection * vec4(position, 1.0);
                 }
                 "#,
             fragment_shader:
@@ -224,6 +231,7 @@
                 layout(location = 2) out vec4 outAmbient;
                 layout(location = 3) out vec4 outMaterial;
                 layout(location = 4) out uint outDecalMask;
+                layout(location = 5) out vec2 outVelocity;
 
                 in vec3 position;
                 in vec3 normal;
@@ -231,6 +239,8 @@
                 in vec3 tangent;
                 in vec3 binormal;
                 in vec2 secondTexCoord;
+                in vec4 clipPosition;
+                in vec4 prevClipPosition;
 
                 void main()
                 {
@@ -271,6 +281,143 @@
                     outAmbient.a = 1.0;
 
                     outDecalMask = properties.layerIndex;
+
+                    outVelocity = S_ComputeVelocity(clipPosition, prevClipPosition);
+                }
+                "#,
+        ),
+        (
+            name: "DepthPrePass",
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: false,
+                    green: false,
+                    blue: false,
+                    alpha: false,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: Some(Less),
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+            vertex_shader:
+                r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 1) in vec2 vertexTexCoord;
+                layout(location = 2) in vec3 vertexNormal;
+                layout(location = 3) in vec4 vertexTangent;
+                layout(location = 4) in vec4 boneWeights;
+                layout(location = 5) in vec4 boneIndices;
+
+                out vec3 position;
+                out vec3 normal;
+                out vec2 texCoord;
+                out vec3 tangent;
+                out vec3 binormal;
+
+                // Must produce exactly the same depth as the GBuffer pass.
+                invariant gl_Position;
+
+                void main()
+                {
+                    vec4 localPosition = vec4(0);
+                    vec3 localNormal = vec3(0);
+                    vec3 localTangent = vec3(0);
+
+                    vec4 inputPosition = vec4(vertexPosition, 1.0);
+                    vec3 inputNormal = vertexNormal;
+                    vec3 inputTangent = vertexTangent.xyz;
+
+                    for (int i = 0; i < fyrox_instanceData.blendShapesCount; ++i) {
+                        TBlendShapeOffsets offsets = S_FetchBlendShapeOffsets(blendShapesStorage, gl_VertexID, i);
+                        float weight = fyrox_instanceData.blendShapesWeights[i / 4][i % 4];
+                        inputPosition.xyz += offsets.position * weight;
+                        inputNormal += offsets.normal * weight;
+                        inputTangent += offsets.tangent * weight;
+                    }
+
+                    if (fyrox_instanceData.useSkeletalAnimation)
+                    {
+                        int i0 = int(boneIndices.x);
+                        int i1 = int(boneIndices.y);
+                        int i2 = int(boneIndices.z);
+                        int i3 = int(boneIndices.w);
+
+                        mat4 m0 = fyrox_boneMatrices.matrices[i0];
+                        mat4 m1 = fyrox_boneMatrices.matrices[i1];
+                        mat4 m2 = fyrox_boneMatrices.matrices[i2];
+                        mat4 m3 = fyrox_boneMatrices.matrices[i3];
+
+                        localPosition += m0 * inputPosition * boneWeights.x;
+                        localPosition += m1 * inputPosition * boneWeights.y;
+                        localPosition += m2 * inputPosition * boneWeights.z;
+                        localPosition += m3 * inputPosition * boneWeights.w;
+
+                        localNormal += mat3(m0) * inputNormal * boneWeights.x;
+                        localNormal += mat3(m1) * inputNormal * boneWeights.y;
+                        localNormal += mat3(m2) * inputNormal * boneWeights.z;
+                        localNormal += mat3(m3) * inputNormal * boneWeights.w;
+
+                        localTangent += mat3(m0) * inputTangent * boneWeights.x;
+                        localTangent += mat3(m1) * inputTangent * boneWeights.y;
+                        localTangent += mat3(m2) * inputTangent * boneWeights.z;
+                        localTangent += mat3(m3) * inputTangent * boneWeights.w;
+                    }
+                    else
+                    {
+                        localPosition = inputPosition;
+                        localNormal = inputNormal;
+                        localTangent = inputTangent;
+                    }
+
+                    mat3 nm = mat3(fyrox_instanceData.worldMatrix);
+                    normal = normalize(nm * localNormal);
+                    tangent = normalize(nm * localTangent);
+                    binormal = normalize(vertexTangent.w * cross(normal, tangent));
+                    texCoord = vertexTexCoord;
+                    position = vec3(fyrox_instanceData.worldMatrix * localPosition);
+                    gl_Position = fyrox_instanceData.worldViewProjection * localPosition;
+                }
+                "#,
+            fragment_shader:
+                r#"
+                in vec3 position;
+                in vec3 normal;
+                in vec2 texCoord;
+                in vec3 tangent;
+                in vec3 binormal;
+
+                void main()
+                {
+                    // Texture coordinates and the alpha test must match the GBuffer pass, otherwise
+                    // the shading pass will leave holes in the image.
+                    vec2 tc;
+                    if (fyrox_graphicsSettings.usePOM) {
+                        mat3 tangentSpace = mat3(tangent, binormal, normal);
+                        vec3 toFragment = normalize(position - fyrox_cameraData.position);
+                        vec3 toFragmentTangentSpace = normalize(transpose(tangentSpace) * toFragment);
+                        tc = S_ComputeParallaxTextureCoordinates(
+                            heightTexture,
+                            toFragmentTangentSpace,
+                            texCoord * properties.texCoordScale,
+                            properties.parallaxCenter,
+                            properties.parallaxScale
+                        );
+                    } else {
+                        tc = texCoord * properties.texCoordScale;
+                    }
+
+                    if ((properties.diffuseColor * texture(diffuseTexture, tc)).a < 0.5) {
+                        discard;
+                    }
                 }
                 "#,
         ),
@@ -169,6 +169,11 @@
                 out vec3 tangent;
                 out vec3 binormal;
                 out vec2 secondTexCoord;
+                out vec4 clipPosition;
+                out vec4 prevClipPosition;
+
+                // Must produce exactly the same depth as the DepthPrePass pass.
+                invariant gl_Position;
 
                 void main()
                 {
@@ -197,6 +202,8 @@
                     position = vec3(fyrox_instanceData.worldMatrix * finalVertexPosition);
                     secondTexCoord = vertexSecondTexCoord;
                     gl_Position = fyrox_instanceData.worldViewProjection * finalVertexPosition;
+                    clipPosition = gl_Position;
+                    prevClipPosition = fyrox_cameraData.prevViewProjection * vec4(position, 1.0);
                 }
                 "#,
             fragment_shader:
@@ -206,6 +213,7 @@
                 layout(location = 2) out vec4 outAmbient;
                 layout(location = 3) out vec4 outMaterial;
                 layout(location = 4) out uint outDecalMask;
+                layout(location = 5) out vec4 outVelocity;
 
                 in vec3 position;
                 in vec3 normal;
@@ -213,6 +221,8 @@
                 in vec3 tangent;
                 in vec3 binormal;
                 in vec2 secondTexCoord;
+                in vec4 clipPosition;
+                in vec4 prevClipPosition;
 
                 void main()
                 {
@@ -250,12 +260,71 @@
 
                     outDecalMask = properties.layerIndex;
 
+                    outVelocity = vec4(S_ComputeVelocity(clipPosition, prevClipPosition), 0.0, 1.0);
+
                     float mask = texture(maskTexture, texCoord).r;
 
                     outColor.a = mask;
                     outAmbient.a = mask;
                     outNormal.a = mask;
                     outMaterial.a = mask;
+                    outVelocity.a = mask;
+                }
+                "#,
+        ),
+        (
+            name: "DepthPrePass",
+            draw_parameters: DrawParameters(
+                cull_face: Some(Back),
+                color_write: ColorMask(
+                    red: false,
+                    green: false,
+                    blue: false,
+                    alpha: false,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: Some(LessOrEqual),
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+            vertex_shader:
+                r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 1) in vec2 vertexTexCoord;
+
+                out vec2 texCoord;
+
+                // Must produce exactly the same depth as the GBuffer pass.
+                invariant gl_Position;
+
+                void main()
+                {
+                    vec2 actualTexCoords = vec2(vertexTexCoord * properties.nodeUvOffsets.zw + properties.nodeUvOffsets.xy);
+                    vec2 heightSize = vec2(textureSize(heightMapTexture, 0));
+                    vec2 innerSize = heightSize - 3.0;
+                    vec2 pixelSize = 1.0 / heightSize;
+                    vec2 heightCoords = (actualTexCoords * innerSize + 1.5) * pixelSize;
+                    float height = texture(heightMapTexture, heightCoords).r;
+                    vec4 finalVertexPosition = vec4(vertexPosition.x, height, vertexPosition.z, 1.0);
+
+                    texCoord = actualTexCoords;
+                    gl_Position = fyrox_instanceData.worldViewProjection * finalVertexPosition;
+                }
+                "#,
+            fragment_shader:
+                r#"
+                in vec2 texCoord;
+
+                void main()
+                {
+                    if (texture(holeMaskTexture, texCoord).r < 0.5) discard;
                 }
                 "#,
         ),
@@ -278,11 +278,232 @@ impl Curve {
         self.fetch_at(location, |a, b, t| a.interpolate(b, t))
     }
 
+    /// Evaluates the curve at `samples` evenly spaced points between the locations of the first
+    /// and the last keys (inclusive) and returns the values. Returns an empty vector if the curve
+    /// has no keys. If the curve has a single key, every sample will have the value of the key.
+    pub fn bake(&self, samples: usize) -> Vec<f32> {
+        let (Some(first), Some(last)) = (self.keys.first(), self.keys.last()) else {
+            return Vec::new();
+        };
+
+        if samples == 1 {
+            return vec![first.value];
+        }
+
+        let span = last.location - first.location;
+        (0..samples)
+            .map(|i| self.value_at(first.location + span * i as f32 / (samples - 1) as f32))
+            .collect()
+    }
+
     #[inline]
     pub fn angle_at(&self, location: f32) -> f32 {
         self.fetch_at(location, |a, b, t| a.interpolate_angles(b, t))
     }
 
+    /// Mirrors the curve horizontally around the middle of its location range. The range itself
+    /// is preserved, the order of the keys is reversed and the tangents of cubic keys are mirrored.
+    /// Every key keeps its kind, use [`Self::reverse`] to reverse the curve in time.
+    pub fn flip_horizontal(&mut self) {
+        let (Some(first), Some(last)) = (self.keys.first(), self.keys.last()) else {
+            return;
+        };
+        let (min, max) = (first.location, last.location);
+
+        self.keys.reverse();
+        for key in self.keys.iter_mut() {
+            key.location = min + max - key.location;
+            if let CurveKeyKind::Cubic {
+                left_tangent,
+                right_tangent,
+            } = &mut key.kind
+            {
+                let left = *left_tangent;
+                *left_tangent = -*right_tangent;
+                *right_tangent = -left;
+            }
+        }
+
+        // Keep the range exact, regardless of rounding errors.
+        if let Some(first) = self.keys.first_mut() {
+            first.location = min;
+        }
+        if let Some(last) = self.keys.last_mut() {
+            last.location = max;
+        }
+    }
+
+    /// Reverses the curve in time: the keys are mirrored around the middle of the location range
+    /// (`x' = min + max - x`), so the reversed curve at `x'` has the same value as the original
+    /// curve at `x`. The range itself is preserved.
+    ///
+    /// Interpolation of a segment is defined by the kind of its left key, which is the right key
+    /// of the segment after the reversal. That's why the kinds are shifted to the new left keys of
+    /// their segments and the tangents of cubic segments are swapped and negated. A key that ends
+    /// a cubic segment and starts a linear one is turned into a cubic key with tangents of the
+    /// line, because only cubic keys could store the incoming tangent. Constant segments cannot
+    /// be reversed exactly: they hold the value of their left key, so the step moves to the other
+    /// end of the segment. For the same reason, a cubic segment that ends with a constant key in
+    /// the reversed curve has zero tangent at that key.
+    pub fn reverse(&mut self) {
+        enum Segment {
+            Constant,
+            Linear,
+            Cubic {
+                left_tangent: f32,
+                right_tangent: f32,
+            },
+        }
+
+        let (Some(first), Some(last)) = (self.keys.first(), self.keys.last()) else {
+            return;
+        };
+        let (min, max) = (first.location, last.location);
+
+        // Describe the original segments in their reversed form, from the new first key.
+        let segments = self
+            .keys
+            .windows(2)
+            .rev()
+            .map(|pair| match (&pair[0].kind, &pair[1].kind) {
+                (CurveKeyKind::Constant, _) => Segment::Constant,
+                (CurveKeyKind::Linear, _) => Segment::Linear,
+                (CurveKeyKind::Cubic { right_tangent, .. }, right_kind) => Segment::Cubic {
+                    left_tangent: match right_kind {
+                        CurveKeyKind::Cubic { left_tangent, .. } => -*left_tangent,
+                        _ => 0.0,
+                    },
+                    right_tangent: -*right_tangent,
+                },
+            })
+            .collect::<Vec<_>>();
+
+        self.keys.reverse();
+        for key in self.keys.iter_mut() {
+            key.location = min + max - key.location;
+        }
+
+        // Tangent that the current key must provide as the right key of the previous segment, if
+        // the segment is cubic.
+        let mut incoming = None;
+        for i in 0..self.keys.len() {
+            // Unused left tangent of a cubic key is mirrored the same way as in `flip_horizontal`.
+            let (mirrored_left, mirrored_right) = match self.keys[i].kind {
+                CurveKeyKind::Cubic {
+                    left_tangent,
+                    right_tangent,
+                } => (-right_tangent, -left_tangent),
+                _ => (0.0, 0.0),
+            };
+            let kind = match segments.get(i) {
+                Some(Segment::Constant) => {
+                    incoming = None;
+                    CurveKeyKind::Constant
+                }
+                Some(Segment::Linear) => match incoming {
+                    Some(left_tangent) if left_tangent != 0.0 => {
+                        // Cubic interpolation with the tangents equal to the sign of the slope
+                        // gives exactly the same line, see `cubicf`.
+                        let slope = self.keys[i + 1].value - self.keys[i].value;
+                        let right_tangent = if slope > 0.0 {
+                            1.0
+                        } else if slope < 0.0 {
+                            -1.0
+                        } else {
+                            0.0
+                        };
+                        incoming = Some(right_tangent);
+                        CurveKeyKind::Cubic {
+                            left_tangent,
+                            right_tangent,
+                        }
+                    }
+                    _ => {
+                        incoming = None;
+                        CurveKeyKind::Linear
+                    }
+                },
+                Some(Segment::Cubic {
+                    left_tangent,
+                    right_tangent,
+                }) => {
+                    let kind = CurveKeyKind::Cubic {
+                        left_tangent: incoming.unwrap_or(mirrored_left),
+                        right_tangent: *left_tangent,
+                    };
+                    incoming = Some(*right_tangent);
+                    kind
+                }
+                // The last key does not start a segment, so it keeps its own kind.
+                None => match (&self.keys[i].kind, incoming) {
+                    (CurveKeyKind::Cubic { .. }, _) => CurveKeyKind::Cubic {
+                        left_tangent: incoming.unwrap_or(mirrored_left),
+                        right_tangent: mirrored_right,
+                    },
+                    (_, Some(left_tangent)) if left_tangent != 0.0 => CurveKeyKind::Cubic {
+                        left_tangent,
+                        right_tangent: 0.0,
+                    },
+                    (kind, _) => kind.clone(),
+                },
+            };
+            self.keys[i].kind = kind;
+        }
+
+        // Keep the range exact, regardless of rounding errors.
+        if let Some(first) = self.keys.first_mut() {
+            first.location = min;
+        }
+        if let Some(last) = self.keys.last_mut() {
+            last.location = max;
+        }
+    }
+
+    /// Mirrors the curve vertically around the middle of the range of values of its keys. The range
+    /// itself is preserved and the tangents of cubic keys are mirrored.
+    pub fn flip_vertical(&mut self) {
+        let Some((min, max)) = self.keys.iter().fold(None, |range, key| match range {
+            None => Some((key.value, key.value)),
+            Some((min, max)) => Some((key.value.min(min), key.value.max(max))),
+        }) else {
+            return;
+        };
+
+        for key in self.keys.iter_mut() {
+            key.value = min + max - key.value;
+            if let CurveKeyKind::Cubic {
+                left_tangent,
+                right_tangent,
+            } = &mut key.kind
+            {
+                *left_tangent = -*left_tangent;
+                *right_tangent = -*right_tangent;
+            }
+        }
+    }
+
+    /// Remaps the values of the keys so the smallest one becomes 0 and the largest one becomes 1.
+    /// If all the keys have the same value, every value will be set to 0. Tangents of cubic keys
+    /// are relative to the difference between the values of neighbour keys, so they are kept as
+    /// is and the shape of the curve is preserved.
+    pub fn normalize_values(&mut self) {
+        let Some((min, max)) = self.keys.iter().fold(None, |range, key| match range {
+            None => Some((key.value, key.value)),
+            Some((min, max)) => Some((key.value.min(min), key.value.max(max))),
+        }) else {
+            return;
+        };
+
+        let range = max - min;
+        for key in self.keys.iter_mut() {
+            key.value = if range > 0.0 {
+                (key.value - min) / range
+            } else {
+                0.0
+            };
+        }
+    }
+
     pub fn bounds(&self) -> Rect<f32> {
         // Handle edge cases first.
         if self.keys.is_empty() {
@@ -532,6 +753,21 @@ mod test {
         assert_eq!(key3.interpolate(&key4, 0.0), 20.0);
     }
 
+    #[test]
+    fn test_curve_bake() {
+        let mut curve = Curve::default();
+        assert!(curve.bake(4).is_empty());
+
+        curve.add_key(CurveKey::new(1.0, 2.0, CurveKeyKind::Linear));
+        assert_eq!(curve.bake(0), Vec::<f32>::new());
+        assert_eq!(curve.bake(1), vec![2.0]);
+        assert_eq!(curve.bake(3), vec![2.0; 3]);
+
+        curve.add_key(CurveKey::new(3.0, 6.0, CurveKeyKind::Linear));
+        assert_eq!(curve.bake(1), vec![2.0]);
+        assert_eq!(curve.bake(5), vec![2.0, 3.0, 4.0, 5.0, 6.0]);
+    }
+
     #[test]
     fn test_curve_from_vec() {
         let key = CurveKey::new(-1.0, -1.0, CurveKeyKind::Constant);
@@ -542,4 +778,110 @@ mod test {
         assert_eq!(curve.name(), "");
         assert_eq!(curve.keys(), vec![key, key2, key4, key3,]);
     }
+
+    #[test]
+    fn test_curve_flip() {
+        let original = Curve::from(vec![
+            CurveKey::new(0.0, 1.0, CurveKeyKind::Linear),
+            CurveKey::new(0.25, 3.0, CurveKeyKind::new_cubic(0.5, -0.25)),
+            CurveKey::new(2.0, -1.0, CurveKeyKind::Constant),
+        ]);
+
+        let mut curve = original.clone();
+        curve.flip_horizontal();
+        let locations = curve.keys().iter().map(|k| k.location).collect::<Vec<_>>();
+        assert_eq!(locations, [0.0, 1.75, 2.0]);
+        assert_eq!(curve.keys()[0].value, -1.0);
+        assert_eq!(curve.value_at(1.75), 3.0);
+        curve.flip_horizontal();
+        assert_eq!(curve, original);
+
+        curve.flip_vertical();
+        let values = curve.keys().iter().map(|k| k.value).collect::<Vec<_>>();
+        assert_eq!(values, [1.0, -1.0, 3.0]);
+        curve.flip_vertical();
+        assert_eq!(curve, original);
+    }
+
+    #[test]
+    fn test_curve_reverse() {
+        fn assert_time_reversed(original: &Curve, reversed: &Curve, skip: std::ops::Range<f32>) {
+            let (min, max) = (original.keys[0].location, original.max_location());
+            assert_eq!(reversed.keys[0].location, min);
+            assert_eq!(reversed.max_location(), max);
+            for i in 0..=100 {
+                let x = min + (max - min) * i as f32 / 100.0;
+                if x > skip.start && x < skip.end {
+                    continue;
+                }
+                let mirrored = min + max - x;
+                let (a, b) = (reversed.value_at(mirrored), original.value_at(x));
+                assert!((a - b).abs() < 1.0e-4, "{x}: {a} != {b}");
+            }
+        }
+
+        for kind in [CurveKeyKind::Linear, CurveKeyKind::new_cubic(0.3, -0.6)] {
+            let original = Curve::from(vec![
+                CurveKey::new(-1.0, 1.0, kind.clone()),
+                CurveKey::new(0.5, 4.0, kind.clone()),
+                CurveKey::new(1.0, -2.0, kind.clone()),
+                CurveKey::new(3.0, 0.5, kind),
+            ]);
+            let mut reversed = original.clone();
+            reversed.reverse();
+            assert_time_reversed(&original, &reversed, 0.0..0.0);
+        }
+
+        let original = Curve::from(vec![
+            CurveKey::new(0.0, 1.0, CurveKeyKind::new_cubic(0.3, -0.6)),
+            CurveKey::new(1.0, 3.0, CurveKeyKind::Linear),
+            CurveKey::new(2.0, 2.0, CurveKeyKind::new_cubic(0.5, 0.8)),
+            CurveKey::new(3.0, 0.0, CurveKeyKind::new_cubic(-0.4, 0.2)),
+            CurveKey::new(4.0, 1.0, CurveKeyKind::Constant),
+            CurveKey::new(5.0, 2.0, CurveKeyKind::Linear),
+        ]);
+        let mut reversed = original.clone();
+        reversed.reverse();
+        // The constant segment is the only one that could not be reversed exactly.
+        assert_time_reversed(&original, &reversed, 4.0..5.0);
+        assert_eq!(reversed.value_at(0.5), 2.0);
+
+        // Kinds are shifted to the new left keys of their segments.
+        assert_eq!(reversed.keys[0].kind, CurveKeyKind::Constant);
+        assert_eq!(reversed.keys[0].id, original.keys[5].id);
+        assert!(matches!(reversed.keys[1].kind, CurveKeyKind::Cubic { .. }));
+        // The key between the cubic and the linear segments stores the tangent of the cubic one.
+        assert!(matches!(
+            reversed.keys[3].kind,
+            CurveKeyKind::Cubic { left_tangent, right_tangent: 1.0 } if left_tangent != 0.0
+        ));
+        assert!(matches!(reversed.keys[4].kind, CurveKeyKind::Cubic { .. }));
+    }
+
+    #[test]
+    fn test_curve_normalize_values() {
+        let mut curve = Curve::from(vec![
+            CurveKey::new(0.0, 2.0, CurveKeyKind::Linear),
+            CurveKey::new(1.0, 10.0, CurveKeyKind::new_cubic(0.5, -0.25)),
+            CurveKey::new(2.0, 4.0, CurveKeyKind::Constant),
+        ]);
+        let tangents = curve.keys()[1].kind.clone();
+        curve.normalize_values();
+        let values = curve.keys().iter().map(|k| k.value).collect::<Vec<_>>();
+        assert_eq!(values, [0.0, 1.0, 0.25]);
+        assert_eq!(curve.keys()[1].kind, tangents);
+
+        // Flat curves must not produce NaNs.
+        let mut flat = Curve::from(vec![
+            CurveKey::new(0.0, 3.0, CurveKeyKind::Linear),
+            CurveKey::new(1.0, 3.0, CurveKeyKind::Linear),
+        ]);
+        flat.normalize_values();
+        let values = flat.keys().iter().map(|k| k.value).collect::<Vec<_>>();
+        assert_eq!(values, [0.0, 0.0]);
+
+        let mut empty = Curve::default();
+        empty.normalize_values();
+        assert!(empty.is_empty());
+    }
 }
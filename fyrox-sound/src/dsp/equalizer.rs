@@ -0,0 +1,264 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Parametric equalizer. See [`Equalizer`] docs for more info.
+
+use crate::dsp::filters::{Biquad, BiquadKind};
+use fyrox_core::visitor::prelude::*;
+
+/// A single band of [`Equalizer`]. It is a biquad filter of the given kind, that is tuned using
+/// normalized frequency, quality and gain (see [`Biquad::new`] for more info about the parameters).
+/// Only the parameters of the band are serialized, the filter is re-tuned on load.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EqualizerBand {
+    kind: BiquadKind,
+    fc: f32,
+    quality: f32,
+    gain: f32,
+    filter: Biquad,
+}
+
+impl Default for EqualizerBand {
+    fn default() -> Self {
+        Self::new(
+            BiquadKind::Peaking,
+            0.1,
+            std::f32::consts::FRAC_1_SQRT_2,
+            1.0,
+        )
+    }
+}
+
+impl Visit for EqualizerBand {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        self.kind.visit("Kind", &mut region)?;
+        self.fc.visit("Fc", &mut region)?;
+        self.quality.visit("Quality", &mut region)?;
+        self.gain.visit("Gain", &mut region)?;
+
+        if region.is_reading() {
+            self.filter = self.make_filter();
+        }
+
+        Ok(())
+    }
+}
+
+impl EqualizerBand {
+    /// Creates new band of the given kind, normalized frequency, quality and gain.
+    pub fn new(kind: BiquadKind, fc: f32, quality: f32, gain: f32) -> Self {
+        Self {
+            kind,
+            fc,
+            quality,
+            gain,
+            filter: Biquad::new(kind, fc, gain, quality),
+        }
+    }
+
+    /// Creates new peaking band, that multiplies amplitude of frequencies around `fc` by `gain`.
+    pub fn peaking(fc: f32, quality: f32, gain: f32) -> Self {
+        Self::new(BiquadKind::Peaking, fc, quality, gain)
+    }
+
+    /// Creates new low-shelf band, that changes amplitude of frequencies below `fc`.
+    pub fn low_shelf(fc: f32, quality: f32, gain: f32) -> Self {
+        Self::new(BiquadKind::LowShelf, fc, quality, gain)
+    }
+
+    /// Creates new high-shelf band, that changes amplitude of frequencies above `fc`.
+    pub fn high_shelf(fc: f32, quality: f32, gain: f32) -> Self {
+        Self::new(BiquadKind::HighShelf, fc, quality, gain)
+    }
+
+    /// Returns kind of the band.
+    pub fn kind(&self) -> BiquadKind {
+        self.kind
+    }
+
+    /// Returns normalized frequency of the band.
+    pub fn fc(&self) -> f32 {
+        self.fc
+    }
+
+    /// Returns quality of the band.
+    pub fn quality(&self) -> f32 {
+        self.quality
+    }
+
+    /// Returns gain of the band.
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    fn make_filter(&self) -> Biquad {
+        Biquad::new(self.kind, self.fc, self.gain, self.quality)
+    }
+
+    /// Processes single sample.
+    pub fn feed(&mut self, sample: f32) -> f32 {
+        self.filter.feed(sample)
+    }
+}
+
+/// Multi-band parametric equalizer, that is used for tone shaping of sounds. It is a chain of
+/// biquad filters (bands), each band is applied to the output of the previous one. Bands with
+/// unit gain (peaking and shelving kinds) leave the signal unchanged.
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox_sound::dsp::{Equalizer, EqualizerBand};
+///
+/// let mut equalizer = Equalizer::default();
+/// // Boost low frequencies below 200 Hz at 44100 Hz sample rate.
+/// equalizer.add_band(EqualizerBand::low_shelf(200.0 / 44100.0, 0.7, 1.5));
+/// // Cut harsh frequencies around 3 kHz.
+/// equalizer.add_band(EqualizerBand::peaking(3000.0 / 44100.0, 2.0, 0.5));
+///
+/// let output = equalizer.feed(0.25);
+/// ```
+#[derive(Default, Debug, Clone, PartialEq, Visit)]
+pub struct Equalizer {
+    bands: Vec<EqualizerBand>,
+}
+
+impl Equalizer {
+    /// Creates new equalizer with the given bands.
+    pub fn new(bands: Vec<EqualizerBand>) -> Self {
+        Self { bands }
+    }
+
+    /// Adds new band to the end of the chain and returns its index.
+    pub fn add_band(&mut self, band: EqualizerBand) -> usize {
+        self.bands.push(band);
+        self.bands.len() - 1
+    }
+
+    /// Removes a band at the given index and returns it. Returns `None` if the index is out of
+    /// bounds.
+    pub fn remove_band(&mut self, index: usize) -> Option<EqualizerBand> {
+        if index < self.bands.len() {
+            Some(self.bands.remove(index))
+        } else {
+            None
+        }
+    }
+
+    /// Replaces parameters of a band at the given index and returns the previous ones. Returns
+    /// `None` and does nothing if the index is out of bounds. The state of the filter is kept, so
+    /// the band could be changed while the sound is playing without clicks.
+    pub fn set_band(&mut self, index: usize, band: EqualizerBand) -> Option<EqualizerBand> {
+        let current = self.bands.get_mut(index)?;
+        let previous = current.clone();
+        current.kind = band.kind;
+        current.fc = band.fc;
+        current.quality = band.quality;
+        current.gain = band.gain;
+        current
+            .filter
+            .tune(band.kind, band.fc, band.gain, band.quality);
+        Some(previous)
+    }
+
+    /// Returns a reference to a band at the given index.
+    pub fn band(&self, index: usize) -> Option<&EqualizerBand> {
+        self.bands.get(index)
+    }
+
+    /// Returns a slice with all the bands.
+    pub fn bands(&self) -> &[EqualizerBand] {
+        &self.bands
+    }
+
+    /// Resets the state of all the bands to silence.
+    pub fn clear(&mut self) {
+        for band in self.bands.iter_mut() {
+            band.filter = band.make_filter();
+        }
+    }
+
+    /// Processes single sample.
+    pub fn feed(&mut self, sample: f32) -> f32 {
+        self.bands
+            .iter_mut()
+            .fold(sample, |sample, band| band.feed(sample))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::dsp::{Equalizer, EqualizerBand};
+    use fyrox_core::visitor::{Visit, Visitor};
+
+    #[test]
+    fn test_neutral_equalizer() {
+        let mut equalizer = Equalizer::new(vec![
+            EqualizerBand::low_shelf(0.01, 0.7, 1.0),
+            EqualizerBand::peaking(0.1, 2.0, 1.0),
+        ]);
+        for i in 0..1024 {
+            let sample = (i as f32 * 0.37).sin() + 0.5 * (i as f32 * 0.05).sin();
+            assert!((equalizer.feed(sample) - sample).abs() < 1.0e-4);
+        }
+    }
+
+    #[test]
+    fn test_equalizer_bands() {
+        let mut equalizer = Equalizer::default();
+        assert_eq!(equalizer.add_band(EqualizerBand::peaking(0.1, 1.0, 2.0)), 0);
+        assert_eq!(
+            equalizer.add_band(EqualizerBand::high_shelf(0.2, 1.0, 0.5)),
+            1
+        );
+
+        let previous = equalizer
+            .set_band(0, EqualizerBand::peaking(0.05, 1.0, 0.5))
+            .unwrap();
+        assert_eq!(previous.fc(), 0.1);
+        assert_eq!(equalizer.band(0).unwrap().fc(), 0.05);
+        assert!(equalizer.set_band(2, Default::default()).is_none());
+
+        let removed = equalizer.remove_band(1).unwrap();
+        assert_eq!(removed.gain(), 0.5);
+        assert!(equalizer.remove_band(1).is_none());
+        assert_eq!(equalizer.bands().len(), 1);
+    }
+
+    #[test]
+    fn test_equalizer_visit() {
+        let mut equalizer = Equalizer::new(vec![
+            EqualizerBand::low_shelf(0.01, 0.7, 1.5),
+            EqualizerBand::peaking(0.1, 2.0, 0.5),
+        ]);
+
+        let mut visitor = Visitor::new();
+        equalizer.visit("Equalizer", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let mut loaded = Equalizer::default();
+        let mut visitor = Visitor::load_from_memory(&data).unwrap();
+        loaded.visit("Equalizer", &mut visitor).unwrap();
+
+        assert_eq!(loaded, equalizer);
+    }
+}
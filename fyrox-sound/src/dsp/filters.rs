@@ -131,6 +131,12 @@ impl LpfComb {
         self.low_pass.set_fc(fc)
     }
 
+    /// Sets pole of internal lowpass filter directly. Should be in 0..1 range, the higher the value
+    /// the more high frequencies are damped.
+    pub fn set_pole(&mut self, pole: f32) {
+        self.low_pass.set_pole(pole)
+    }
+
     /// Returns total length of internal delay line (in samples)
     pub fn len(&self) -> usize {
         self.delay_line.len()
@@ -193,13 +199,18 @@ impl AllPass {
 
 /// Exact kind of biquad filter - it defines coefficients of the filter.
 /// More info here: <https://shepazu.github.io/Audio-EQ-Cookbook/audio-eq-cookbook.html>
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Visit)]
 pub enum BiquadKind {
     /// Reduces amplitude of frequencies higher F_center.
+    #[default]
     LowPass,
 
     /// Reduces amplitude of frequencies lower F_center.
     HighPass,
 
+    /// Rejects frequencies in some band around F_center and passes all other frequencies giving ̅ \/̅  shape.
+    Notch,
+
     /// Reduces amplitude of all frequencies except in some band around F_center giving _/̅ \_ shape
     BandPass,
 
@@ -213,6 +224,11 @@ pub enum BiquadKind {
     /// Reduces amplitude of frequencies in a shape like this _/̅  where location of center of /
     /// defined by F_center.
     HighShelf,
+
+    /// Multiplies amplitude of frequencies in some band around F_center by the gain and passes all
+    /// other frequencies as is, giving _/̅ \_ shape for gains larger than one and ̅ \_/̅  shape for
+    /// gains less than one. This is the main building block of parametric equalizers.
+    Peaking,
 }
 
 /// Generic second order digital filter.
@@ -251,6 +267,30 @@ impl Biquad {
         filter
     }
 
+    /// Creates new low-pass filter with unit gain, where `fc` is normalized cutoff frequency and
+    /// `quality` is quality factor of the filter (`1/sqrt(2)` gives flat pass band).
+    pub fn low_pass(fc: f32, quality: f32) -> Self {
+        Self::new(BiquadKind::LowPass, fc, 1.0, quality)
+    }
+
+    /// Creates new high-pass filter with unit gain, where `fc` is normalized cutoff frequency and
+    /// `quality` is quality factor of the filter (`1/sqrt(2)` gives flat pass band).
+    pub fn high_pass(fc: f32, quality: f32) -> Self {
+        Self::new(BiquadKind::HighPass, fc, 1.0, quality)
+    }
+
+    /// Creates new band-pass filter with unit gain at `fc`, where `fc` is normalized center
+    /// frequency and `quality` defines width of the band.
+    pub fn band_pass(fc: f32, quality: f32) -> Self {
+        Self::new(BiquadKind::BandPass, fc, 1.0, quality)
+    }
+
+    /// Creates new notch (band-reject) filter, where `fc` is normalized center frequency of
+    /// rejected band and `quality` defines width of the band.
+    pub fn notch(fc: f32, quality: f32) -> Self {
+        Self::new(BiquadKind::Notch, fc, 1.0, quality)
+    }
+
     /// Creates new instance of filter with given coefficients.
     pub fn from_coefficients(b0: f32, b1: f32, b2: f32, a1: f32, a2: f32) -> Self {
         Self {
@@ -304,6 +344,15 @@ impl Biquad {
                 let a2 = 1.0 - alpha;
                 (b0, b1, b2, a0, a1, a2)
             }
+            BiquadKind::Notch => {
+                let b0 = 1.0;
+                let b1 = -2.0 * w0_cos;
+                let b2 = 1.0;
+                let a0 = 1.0 + alpha;
+                let a1 = -2.0 * w0_cos;
+                let a2 = 1.0 - alpha;
+                (b0, b1, b2, a0, a1, a2)
+            }
             BiquadKind::AllPass => {
                 let b0 = 1.0 - alpha;
                 let b1 = -2.0 * w0_cos;
@@ -333,6 +382,17 @@ impl Biquad {
                 let a2 = (gain + 1.0) - (gain - 1.0) * w0_cos - sq;
                 (b0, b1, b2, a0, a1, a2)
             }
+            BiquadKind::Peaking => {
+                // The cookbook's `A` is the square root of the gain at `fc`.
+                let a = gain.sqrt();
+                let b0 = 1.0 + alpha * a;
+                let b1 = -2.0 * w0_cos;
+                let b2 = 1.0 - alpha * a;
+                let a0 = 1.0 + alpha / a;
+                let a1 = -2.0 * w0_cos;
+                let a2 = 1.0 - alpha / a;
+                (b0, b1, b2, a0, a1, a2)
+            }
         };
 
         self.b0 = b0 / a0;
@@ -364,3 +424,74 @@ impl Default for Biquad {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::dsp::filters::{Biquad, BiquadKind};
+
+    // Feeds a periodic signal into the filter and returns the amplitude of the output after the
+    // filter has settled.
+    fn steady_state_amplitude(filter: &mut Biquad, signal: impl Fn(usize) -> f32) -> f32 {
+        for i in 0..4096 {
+            filter.feed(signal(i));
+        }
+        (4096..4160)
+            .map(|i| filter.feed(signal(i)).abs())
+            .fold(0.0, f32::max)
+    }
+
+    fn dc(_: usize) -> f32 {
+        1.0
+    }
+
+    fn nyquist(i: usize) -> f32 {
+        if i.is_multiple_of(2) {
+            1.0
+        } else {
+            -1.0
+        }
+    }
+
+    #[test]
+    fn test_low_pass_response() {
+        let q = std::f32::consts::FRAC_1_SQRT_2;
+
+        let dc_gain = steady_state_amplitude(&mut Biquad::low_pass(0.1, q), dc);
+        assert!((dc_gain - 1.0).abs() < 1.0e-3, "{dc_gain}");
+
+        let nyquist_gain = steady_state_amplitude(&mut Biquad::low_pass(0.1, q), nyquist);
+        assert!(nyquist_gain < 1.0e-3, "{nyquist_gain}");
+    }
+
+    #[test]
+    fn test_high_pass_band_pass_notch_response() {
+        let q = std::f32::consts::FRAC_1_SQRT_2;
+
+        assert!(steady_state_amplitude(&mut Biquad::high_pass(0.1, q), dc) < 1.0e-3);
+        assert!(
+            (steady_state_amplitude(&mut Biquad::high_pass(0.1, q), nyquist) - 1.0).abs() < 1.0e-3
+        );
+
+        assert!(steady_state_amplitude(&mut Biquad::band_pass(0.1, q), dc) < 1.0e-3);
+        assert!(steady_state_amplitude(&mut Biquad::band_pass(0.1, q), nyquist) < 1.0e-3);
+
+        assert!((steady_state_amplitude(&mut Biquad::notch(0.1, q), dc) - 1.0).abs() < 1.0e-3);
+        assert!((steady_state_amplitude(&mut Biquad::notch(0.1, q), nyquist) - 1.0).abs() < 1.0e-3);
+    }
+
+    #[test]
+    fn test_peaking_response() {
+        let q = std::f32::consts::FRAC_1_SQRT_2;
+        let center = |i: usize| (2.0 * std::f32::consts::PI * 0.1 * i as f32).cos();
+
+        for gain in [0.5, 1.0, 2.0] {
+            let mut filter = Biquad::new(BiquadKind::Peaking, 0.1, gain, q);
+            let center_gain = steady_state_amplitude(&mut filter, center);
+            assert!((center_gain - gain).abs() < 1.0e-2, "{center_gain}");
+
+            let mut filter = Biquad::new(BiquadKind::Peaking, 0.1, gain, q);
+            let dc_gain = steady_state_amplitude(&mut filter, dc);
+            assert!((dc_gain - 1.0).abs() < 1.0e-3, "{dc_gain}");
+        }
+    }
+}
@@ -28,10 +28,16 @@
 //!
 //! `fc` - normalized frequency, i.e. `fc = 0.2` with `sample rate = 44100 Hz` will be `f = 8820 Hz`
 
+use crate::engine::SoundEngine;
 use fyrox_core::visitor::pod::PodVecView;
 use fyrox_core::visitor::{Visit, VisitResult, Visitor};
 
+pub mod equalizer;
 pub mod filters;
+pub mod reverb;
+
+pub use equalizer::{Equalizer, EqualizerBand};
+pub use reverb::Reverb;
 
 #[derive(Debug, PartialEq, Clone)]
 struct SamplesContainer(pub Vec<f32>);
@@ -42,55 +48,918 @@ impl Visit for SamplesContainer {
     }
 }
 
-/// See more info here <https://ccrma.stanford.edu/~jos/pasp/Delay_Lines.html>
+/// Fixed-size circular buffer of samples. Pushing a new sample overwrites the oldest one, which
+/// makes it a basic building block for delay lines, multi-tap delays, FIR filter history, etc.
+/// Initially the buffer is filled with silence (zeros).
 #[derive(Debug, PartialEq, Clone, Visit)]
-pub struct DelayLine {
+pub struct RingBuffer {
     #[visit(optional)]
     samples: SamplesContainer,
-    last: f32,
     pos: u32,
 }
 
-impl DelayLine {
-    /// Creates new instance of delay line of given length in samples.
+impl RingBuffer {
+    /// Creates new ring buffer of given length in samples.
     pub fn new(len: usize) -> Self {
         Self {
             samples: SamplesContainer(vec![0.0; len]),
-            last: 0.0,
             pos: 0,
         }
     }
 
-    /// Returns length of delay line in samples.
+    /// Returns length of the ring buffer in samples.
     pub fn len(&self) -> usize {
         self.samples.0.len()
     }
 
-    /// Processes single sample.
-    pub fn feed(&mut self, sample: f32) -> f32 {
-        self.last = self.samples.0[self.pos as usize];
-        self.samples.0[self.pos as usize] = sample;
+    /// Pushes a new sample to the ring buffer and returns the oldest sample, that was overwritten
+    /// by the new one.
+    pub fn push(&mut self, sample: f32) -> f32 {
+        let oldest = std::mem::replace(&mut self.samples.0[self.pos as usize], sample);
         self.pos += 1;
         if self.pos >= self.samples.0.len() as u32 {
             self.pos -= self.samples.0.len() as u32
         }
+        oldest
+    }
+
+    /// Returns a sample that was pushed `offset_back` pushes ago, `get(0)` returns the most recent
+    /// sample and `get(len - 1)` returns the oldest one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset_back` is larger or equal to the length of the ring buffer.
+    pub fn get(&self, offset_back: usize) -> f32 {
+        let len = self.samples.0.len();
+        assert!(offset_back < len);
+        self.samples.0[(self.pos as usize + len - 1 - offset_back) % len]
+    }
+
+    /// Fills the ring buffer with silence (zeros).
+    pub fn clear(&mut self) {
+        self.samples.0.fill(0.0);
+        self.pos = 0;
+    }
+
+    /// Changes the length of the ring buffer in place, keeping as many of the most recent samples
+    /// as possible. When the buffer grows, the new (oldest) part of it is filled with silence.
+    pub fn resize(&mut self, len: usize) {
+        let samples = &mut self.samples.0;
+        // Make the oldest sample first, so the most recent samples are at the end.
+        samples.rotate_left(self.pos as usize);
+        if len < samples.len() {
+            samples.drain(..samples.len() - len);
+        } else {
+            samples.splice(0..0, std::iter::repeat_n(0.0, len - samples.len()));
+        }
+        self.pos = 0;
+    }
+}
+
+impl Default for RingBuffer {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+/// See more info here <https://ccrma.stanford.edu/~jos/pasp/Delay_Lines.html>
+#[derive(Debug, PartialEq, Clone)]
+pub struct DelayLine {
+    buffer: RingBuffer,
+    last: f32,
+    filled: u32,
+}
+
+impl Visit for DelayLine {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        // Fields of the ring buffer are stored in the same region for backward compatibility.
+        self.buffer.samples.visit("Samples", &mut region).ok();
+        self.last.visit("Last", &mut region)?;
+        self.buffer.pos.visit("Pos", &mut region)?;
+        self.filled.visit("Filled", &mut region).ok();
+
+        Ok(())
+    }
+}
+
+impl DelayLine {
+    /// Creates new instance of delay line of given length in samples.
+    pub fn new(len: usize) -> Self {
+        Self {
+            buffer: RingBuffer::new(len),
+            last: 0.0,
+            filled: 0,
+        }
+    }
+
+    /// Returns length of delay line in samples.
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Processes single sample.
+    pub fn feed(&mut self, sample: f32) -> f32 {
+        self.last = self.buffer.push(sample);
+        self.filled = (self.filled + 1).min(self.buffer.len() as u32);
+        self.last
+    }
+
+    /// Processes single sample with the given fractional delay (in samples), the output is linearly
+    /// interpolated between the two nearest stored samples. Zero delay returns the input sample,
+    /// the delay is clamped to `[0, len - 1]` range. Could be used to implement effects with
+    /// modulated delay time, such as chorus, flanger or pitch shifting.
+    ///
+    /// Integer delay `N` gives the same output as [`Self::feed`] of a delay line with length `N`.
+    pub fn feed_fractional(&mut self, sample: f32, delay: f32) -> f32 {
+        self.buffer.push(sample);
+        self.filled = (self.filled + 1).min(self.buffer.len() as u32);
+
+        let max_delay = self.buffer.len() - 1;
+        let delay = delay.clamp(0.0, max_delay as f32);
+        let index = delay.floor() as usize;
+        let fraction = delay - index as f32;
+        let a = self.buffer.get(index);
+        let b = self.buffer.get((index + 1).min(max_delay));
+        self.last = a + (b - a) * fraction;
         self.last
     }
 
+    /// Returns the amount of samples that were written to the delay line since its creation,
+    /// saturated at [`Self::len`]. The delay line is "primed" when this value is equal to its
+    /// length, until then its output contains silence instead of actual samples.
+    pub fn filled(&self) -> usize {
+        self.filled as usize
+    }
+
     /// Returns last processed sample.
     pub fn last(&self) -> f32 {
         self.last
     }
+
+    /// Resets the delay line to silence without reallocating its buffer. Could be used to reuse
+    /// the same delay line for another sound.
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.last = 0.0;
+        self.filled = 0;
+    }
+
+    /// Changes the length of the delay line in place, keeping as many of the most recent samples
+    /// as possible.
+    pub fn resize(&mut self, len: usize) {
+        self.buffer.resize(len);
+        self.filled = self.filled.min(len as u32);
+    }
 }
 
 impl Default for DelayLine {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+/// A set of `N` independent delay lines, that processes frames of `N` channels at once. Every
+/// channel has its own delay length, which could be used for example to widen a sound by delaying
+/// one of the channels a bit (Haas effect).
+#[derive(Debug, PartialEq, Clone)]
+pub struct MultiDelay<const N: usize> {
+    channels: [DelayLine; N],
+}
+
+impl<const N: usize> Visit for MultiDelay<N> {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        self.channels.visit("Channels", &mut region)?;
+
+        Ok(())
+    }
+}
+
+impl<const N: usize> Default for MultiDelay<N> {
     fn default() -> Self {
         Self {
-            samples: SamplesContainer(vec![0.0]),
-            last: 0.0,
-            pos: 0,
+            channels: std::array::from_fn(|_| DelayLine::default()),
+        }
+    }
+}
+
+impl<const N: usize> MultiDelay<N> {
+    /// Creates new multichannel delay with the given lengths (in samples) of each channel.
+    pub fn new(lengths: [usize; N]) -> Self {
+        Self {
+            channels: lengths.map(DelayLine::new),
+        }
+    }
+
+    /// Returns a reference to the delay line of the given channel.
+    pub fn channel(&self, index: usize) -> &DelayLine {
+        &self.channels[index]
+    }
+
+    /// Returns a mutable reference to the delay line of the given channel.
+    pub fn channel_mut(&mut self, index: usize) -> &mut DelayLine {
+        &mut self.channels[index]
+    }
+
+    /// Processes single frame, each sample of the frame is processed by its own delay line.
+    pub fn feed(&mut self, frame: [f32; N]) -> [f32; N] {
+        std::array::from_fn(|i| self.channels[i].feed(frame[i]))
+    }
+
+    /// Resets all the channels to silence.
+    pub fn clear(&mut self) {
+        for channel in self.channels.iter_mut() {
+            channel.clear();
+        }
+    }
+}
+
+/// Lightweight binaural panner, that turns a mono signal into a stereo pair with directional cues.
+/// It does not use HRTF, instead it approximates two main cues of human hearing: interaural time
+/// difference (ITD) - the sound reaches the far ear a bit later, and head shadow - the head
+/// muffles high frequencies of the sound for the far ear. ITD is calculated using Woodworth's
+/// formula for a spherical head and applied by a fractional delay line per ear, head shadow is
+/// approximated by a one-pole low-pass filter per ear.
+///
+/// Azimuth is the horizontal angle (in radians) of the sound source around the listener: zero
+/// means straight ahead, positive values - to the right, negative values - to the left. Sources
+/// behind the listener give the same cues as their mirror images in front of the listener.
+#[derive(Debug, PartialEq, Clone, Visit)]
+pub struct BinauralPanner {
+    left_delay: DelayLine,
+    right_delay: DelayLine,
+    left_shadow: filters::OnePole,
+    right_shadow: filters::OnePole,
+    sample_rate: u32,
+}
+
+impl Default for BinauralPanner {
+    fn default() -> Self {
+        Self::new(SoundEngine::DEFAULT_SAMPLE_RATE)
+    }
+}
+
+impl BinauralPanner {
+    /// Radius of an average human head in meters.
+    pub const HEAD_RADIUS: f32 = 0.0875;
+
+    /// Speed of sound in the air in meters per second.
+    pub const SPEED_OF_SOUND: f32 = 343.0;
+
+    /// Cutoff frequency (in Hz) of the head shadow filter for the ear that is fully turned away from
+    /// the sound source (azimuth is +/- 90 degrees).
+    pub const HEAD_SHADOW_CUTOFF: f32 = 1500.0;
+
+    /// Creates new panner for the given sample rate (in Hz).
+    pub fn new(sample_rate: u32) -> Self {
+        let sample_rate = sample_rate.max(1);
+        let max_delay =
+            Self::interaural_time_difference(std::f32::consts::FRAC_PI_2) * sample_rate as f32;
+        // Extra sample is needed for the interpolation of fractional delay.
+        let len = max_delay.ceil() as usize + 2;
+        Self {
+            left_delay: DelayLine::new(len),
+            right_delay: DelayLine::new(len),
+            left_shadow: Default::default(),
+            right_shadow: Default::default(),
+            sample_rate,
+        }
+    }
+
+    /// Returns sample rate (in Hz) of the panner.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Returns interaural time difference (in seconds) for the given azimuth (in radians). Positive
+    /// values mean that the sound reaches the right ear first, negative - the left ear first.
+    pub fn interaural_time_difference(azimuth: f32) -> f32 {
+        // Fold the rear hemisphere onto the front one.
+        let lateral = azimuth.sin().clamp(-1.0, 1.0).asin();
+        Self::HEAD_RADIUS / Self::SPEED_OF_SOUND * (lateral + lateral.sin())
+    }
+
+    /// Processes single mono sample coming from the given azimuth (in radians) and returns a pair
+    /// of samples for the left and the right ear. The azimuth could be changed every sample, the
+    /// delay is interpolated between the samples.
+    pub fn feed(&mut self, sample: f32, azimuth: f32) -> (f32, f32) {
+        let delay = Self::interaural_time_difference(azimuth) * self.sample_rate as f32;
+
+        let shadow_fc = (Self::HEAD_SHADOW_CUTOFF / self.sample_rate as f32).clamp(0.0, 1.0);
+        let shadow_pole = azimuth.sin().abs() * (-2.0 * std::f32::consts::PI * shadow_fc).exp();
+
+        // The ear that is closer to the source gets the sound as is, only the far ear is delayed
+        // and shadowed.
+        let (left_delay, left_pole, right_delay, right_pole) = if delay >= 0.0 {
+            (delay, shadow_pole, 0.0, 0.0)
+        } else {
+            (0.0, 0.0, -delay, shadow_pole)
+        };
+
+        self.left_shadow.set_pole(left_pole);
+        self.right_shadow.set_pole(right_pole);
+
+        let left = self
+            .left_shadow
+            .feed(self.left_delay.feed_fractional(sample, left_delay));
+        let right = self
+            .right_shadow
+            .feed(self.right_delay.feed_fractional(sample, right_delay));
+
+        (left, right)
+    }
+
+    /// Resets the panner to silence.
+    pub fn clear(&mut self) {
+        self.left_delay.clear();
+        self.right_delay.clear();
+        self.left_shadow = Default::default();
+        self.right_shadow = Default::default();
+    }
+}
+
+/// Generic comb filter, that combines feedforward and feedback comb filters. Its difference equation
+/// is `y[n] = b * x[n - D] - a * y[n - D]`, where `D` is the length of the internal delay line, `b` is
+/// the feedforward gain and `a` is the feedback gain. Comb filters are the building blocks of many
+/// effects, such as flangers, reverbs or physical modeling of strings and tubes.
+/// <https://ccrma.stanford.edu/~jos/pasp/Comb_Filters.html>
+#[derive(Debug, PartialEq, Clone, Visit)]
+pub struct CombFilter {
+    delay_line: DelayLine,
+    feedforward: f32,
+    feedback: f32,
+}
+
+impl Default for CombFilter {
+    fn default() -> Self {
+        Self {
+            delay_line: Default::default(),
+            feedforward: 1.0,
+            feedback: 0.0,
+        }
+    }
+}
+
+impl CombFilter {
+    /// Max absolute value of the feedback gain. Feedback gain must be strictly less than one by its
+    /// absolute value, otherwise the filter becomes unstable.
+    pub const MAX_FEEDBACK: f32 = 0.999;
+
+    /// Creates new comb filter with the given length of the delay line (in samples), feedforward
+    /// and feedback gains. The feedback gain is clamped to `[-MAX_FEEDBACK; MAX_FEEDBACK]` range.
+    pub fn new(len: usize, feedforward: f32, feedback: f32) -> Self {
+        Self {
+            delay_line: DelayLine::new(len),
+            feedforward,
+            feedback: feedback.clamp(-Self::MAX_FEEDBACK, Self::MAX_FEEDBACK),
+        }
+    }
+
+    /// Returns length of the internal delay line in samples.
+    pub fn len(&self) -> usize {
+        self.delay_line.len()
+    }
+
+    /// Sets feedforward gain (`b`) of the filter.
+    pub fn set_feedforward(&mut self, feedforward: f32) {
+        self.feedforward = feedforward;
+    }
+
+    /// Returns feedforward gain (`b`) of the filter.
+    pub fn feedforward(&self) -> f32 {
+        self.feedforward
+    }
+
+    /// Sets feedback gain (`a`) of the filter. The value is clamped to
+    /// `[-MAX_FEEDBACK; MAX_FEEDBACK]` range to keep the filter stable.
+    pub fn set_feedback(&mut self, feedback: f32) {
+        self.feedback = feedback.clamp(-Self::MAX_FEEDBACK, Self::MAX_FEEDBACK);
+    }
+
+    /// Returns feedback gain (`a`) of the filter.
+    pub fn feedback(&self) -> f32 {
+        self.feedback
+    }
+
+    /// Processes single sample.
+    pub fn feed(&mut self, sample: f32) -> f32 {
+        // Both delayed terms share the same delay, so it is enough to store their combination
+        // `b * x[n] - a * y[n]` in a single delay line. The oldest stored value is the output.
+        let output = self.delay_line.buffer.get(self.delay_line.len() - 1);
+        self.delay_line
+            .feed(self.feedforward * sample - self.feedback * output);
+        output
+    }
+
+    /// Resets the filter to silence.
+    pub fn clear(&mut self) {
+        self.delay_line.clear();
+    }
+}
+
+/// Schroeder allpass filter, its difference equation is `y[n] = -g * x[n] + x[n - D] + g * y[n - D]`,
+/// where `D` is the length of the internal delay line and `g` is the gain. The filter passes all
+/// frequencies with unit gain and only changes their phase, which makes it a good diffuser for
+/// reverbs. Unlike [`filters::AllPass`], the delay is exactly `D` samples.
+/// <https://ccrma.stanford.edu/~jos/pasp/Allpass_Two_Combs.html>
+#[derive(Debug, PartialEq, Clone, Visit)]
+pub struct AllPass {
+    delay_line: DelayLine,
+    gain: f32,
+}
+
+impl Default for AllPass {
+    fn default() -> Self {
+        Self::new(1, 0.5)
+    }
+}
+
+impl AllPass {
+    /// Max absolute value of the gain. The gain must be strictly less than one by its absolute
+    /// value, otherwise the filter becomes unstable.
+    pub const MAX_GAIN: f32 = 0.999;
+
+    /// Creates new allpass filter with the given length of the delay line (in samples) and gain.
+    /// The gain is clamped to `[-MAX_GAIN; MAX_GAIN]` range.
+    pub fn new(len: usize, gain: f32) -> Self {
+        Self {
+            delay_line: DelayLine::new(len),
+            gain: gain.clamp(-Self::MAX_GAIN, Self::MAX_GAIN),
+        }
+    }
+
+    /// Returns length of the internal delay line in samples.
+    pub fn len(&self) -> usize {
+        self.delay_line.len()
+    }
+
+    /// Sets gain (`g`) of the filter. The value is clamped to `[-MAX_GAIN; MAX_GAIN]` range to keep
+    /// the filter stable.
+    pub fn set_gain(&mut self, gain: f32) {
+        self.gain = gain.clamp(-Self::MAX_GAIN, Self::MAX_GAIN);
+    }
+
+    /// Returns gain (`g`) of the filter.
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Processes single sample.
+    pub fn feed(&mut self, sample: f32) -> f32 {
+        // Direct form II: the delay line stores `w[n] = x[n] + g * w[n - D]`, and the output is
+        // `y[n] = -g * w[n] + w[n - D]`, which needs only one delay line instead of two.
+        let delayed = self.delay_line.buffer.get(self.delay_line.len() - 1);
+        let w = sample + self.gain * delayed;
+        self.delay_line.feed(w);
+        delayed - self.gain * w
+    }
+
+    /// Resets the filter to silence.
+    pub fn clear(&mut self) {
+        self.delay_line.clear();
+    }
+}
+
+/// First-order DC-blocking high-pass filter, its difference equation is
+/// `y[n] = x[n] - x[n - 1] + R * y[n - 1]`. It removes constant offset from a signal, which could be
+/// accumulated by feedback loops of delay-based effects and cause clicks. The closer the pole `R` to
+/// one, the lower the cutoff frequency is.
+/// <https://ccrma.stanford.edu/~jos/filters/DC_Blocker.html>
+#[derive(Debug, PartialEq, Clone, Visit)]
+pub struct DcBlocker {
+    pole: f32,
+    last_input: f32,
+    last_output: f32,
+}
+
+impl Default for DcBlocker {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_POLE)
+    }
+}
+
+impl DcBlocker {
+    /// Default pole of the filter, it gives the cutoff frequency of about 35 Hz at 44100 Hz sample
+    /// rate.
+    pub const DEFAULT_POLE: f32 = 0.995;
+
+    /// Creates new DC blocker with the given pole. The pole is clamped to `[0; 1)` range.
+    pub fn new(pole: f32) -> Self {
+        Self {
+            pole: pole.clamp(0.0, 0.9999),
+            last_input: 0.0,
+            last_output: 0.0,
+        }
+    }
+
+    /// Sets pole of the filter. The pole is clamped to `[0; 1)` range.
+    pub fn set_pole(&mut self, pole: f32) {
+        self.pole = pole.clamp(0.0, 0.9999);
+    }
+
+    /// Returns pole of the filter.
+    pub fn pole(&self) -> f32 {
+        self.pole
+    }
+
+    /// Processes single sample.
+    pub fn feed(&mut self, sample: f32) -> f32 {
+        let output = sample - self.last_input + self.pole * self.last_output;
+        self.last_input = sample;
+        self.last_output = output;
+        output
+    }
+
+    /// Resets the state of the filter.
+    pub fn clear(&mut self) {
+        self.last_input = 0.0;
+        self.last_output = 0.0;
+    }
+}
+
+/// One-pole smoothing filter, that exponentially approaches a target value. It should be used to
+/// change parameters of other filters and effects (cutoff frequency, gain, etc.) per-sample without
+/// audible "zipper" noise caused by abrupt jumps of the values. Each call of [`Self::feed`] moves the
+/// current value by `1 - exp(-1 / tau)` of the remaining distance, where `tau` is the time constant
+/// in samples. After `tau` samples the remaining distance is about 37% of the initial one, after
+/// `5 * tau` samples - less than 1%.
+///
+/// The same type is also available as [`OnePole`]. This is a different thing than
+/// [`filters::OnePole`], which filters an input signal.
+#[derive(Debug, PartialEq, Clone, Visit)]
+pub struct ParameterSmoother {
+    value: f32,
+    target: f32,
+    time_constant: f32,
+    coefficient: f32,
+}
+
+/// One-pole smoother without an input signal, it is the same type as [`ParameterSmoother`]. The
+/// name follows the usual DSP terminology, while [`ParameterSmoother`] describes its purpose; both
+/// names could be used interchangeably. Keep in mind, that [`filters::OnePole`] is a different
+/// type - a low-pass filter of an input signal.
+pub type OnePole = ParameterSmoother;
+
+impl Default for ParameterSmoother {
+    fn default() -> Self {
+        Self::new(0.0, Self::DEFAULT_TIME_CONSTANT)
+    }
+}
+
+fn smoothing_coefficient(time_constant: f32) -> f32 {
+    if time_constant > 0.0 {
+        (-1.0 / time_constant).exp()
+    } else {
+        0.0
+    }
+}
+
+impl ParameterSmoother {
+    /// Default time constant in samples, which is 10 ms at 44100 Hz sample rate.
+    pub const DEFAULT_TIME_CONSTANT: f32 = 441.0;
+
+    /// Creates new smoother with the given initial value and time constant (in samples).
+    pub fn new(value: f32, time_constant: f32) -> Self {
+        let time_constant = time_constant.max(0.0);
+        Self {
+            value,
+            target: value,
+            time_constant,
+            coefficient: smoothing_coefficient(time_constant),
+        }
+    }
+
+    /// Sets new target value. The current value will approach it in a few time constants.
+    pub fn set_target(&mut self, target: f32) {
+        self.target = target;
+    }
+
+    /// Returns current target value.
+    pub fn target(&self) -> f32 {
+        self.target
+    }
+
+    /// Sets the time constant of the smoother in samples. Zero time constant makes the smoother to
+    /// jump to the target immediately.
+    pub fn set_time_constant(&mut self, time_constant: f32) {
+        self.time_constant = time_constant.max(0.0);
+        self.coefficient = smoothing_coefficient(self.time_constant);
+    }
+
+    /// Returns the time constant of the smoother in samples.
+    pub fn time_constant(&self) -> f32 {
+        self.time_constant
+    }
+
+    /// Sets both current and target values, skipping the smoothing.
+    pub fn set_value(&mut self, value: f32) {
+        self.value = value;
+        self.target = value;
+    }
+
+    /// Returns current value of the smoother.
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    /// Advances the smoother by one sample and returns new current value.
+    pub fn feed(&mut self) -> f32 {
+        self.value = self.target + (self.value - self.target) * self.coefficient;
+        self.value
+    }
+}
+
+/// Defines how [`EnvelopeFollower`] measures the level of a signal.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Visit)]
+pub enum EnvelopeMode {
+    /// Smoothed absolute value of the signal. Reacts quickly to transients, suitable for
+    /// compressors, gates and peak meters.
+    #[default]
+    Peak,
+    /// Smoothed root mean square of the signal. Closer to perceived loudness, suitable for VU meters
+    /// and ducking.
+    Rms,
+}
+
+/// Tracks the level (envelope) of a signal. The envelope rises with the attack time constant when
+/// the signal gets louder and falls with the release time constant when it gets quieter. Time
+/// constants are in samples and have the same meaning as in [`ParameterSmoother`]. Could be used
+/// for level meters or as a side-chain of dynamic effects, for example to duck music while dialog
+/// is playing.
+#[derive(Debug, PartialEq, Clone, Visit)]
+pub struct EnvelopeFollower {
+    mode: EnvelopeMode,
+    attack_time: f32,
+    release_time: f32,
+    attack_coefficient: f32,
+    release_coefficient: f32,
+    // Squared for RMS mode.
+    state: f32,
+}
+
+impl Default for EnvelopeFollower {
+    fn default() -> Self {
+        Self::new(
+            EnvelopeMode::Peak,
+            Self::DEFAULT_ATTACK_TIME,
+            Self::DEFAULT_RELEASE_TIME,
+        )
+    }
+}
+
+impl EnvelopeFollower {
+    /// Default attack time constant in samples, which is 10 ms at 44100 Hz sample rate.
+    pub const DEFAULT_ATTACK_TIME: f32 = 441.0;
+
+    /// Default release time constant in samples, which is 100 ms at 44100 Hz sample rate.
+    pub const DEFAULT_RELEASE_TIME: f32 = 4410.0;
+
+    /// Creates new envelope follower with the given mode, attack and release time constants (in
+    /// samples).
+    pub fn new(mode: EnvelopeMode, attack_time: f32, release_time: f32) -> Self {
+        let attack_time = attack_time.max(0.0);
+        let release_time = release_time.max(0.0);
+        Self {
+            mode,
+            attack_time,
+            release_time,
+            attack_coefficient: smoothing_coefficient(attack_time),
+            release_coefficient: smoothing_coefficient(release_time),
+            state: 0.0,
+        }
+    }
+
+    /// Sets new mode of the follower. The current envelope is kept.
+    pub fn set_mode(&mut self, mode: EnvelopeMode) {
+        if self.mode != mode {
+            let envelope = self.envelope();
+            self.mode = mode;
+            self.state = match mode {
+                EnvelopeMode::Peak => envelope,
+                EnvelopeMode::Rms => envelope * envelope,
+            };
+        }
+    }
+
+    /// Returns current mode of the follower.
+    pub fn mode(&self) -> EnvelopeMode {
+        self.mode
+    }
+
+    /// Sets attack time constant in samples. Zero makes the envelope to follow rising signal
+    /// immediately.
+    pub fn set_attack_time(&mut self, attack_time: f32) {
+        self.attack_time = attack_time.max(0.0);
+        self.attack_coefficient = smoothing_coefficient(self.attack_time);
+    }
+
+    /// Returns attack time constant in samples.
+    pub fn attack_time(&self) -> f32 {
+        self.attack_time
+    }
+
+    /// Sets release time constant in samples. Zero makes the envelope to follow falling signal
+    /// immediately.
+    pub fn set_release_time(&mut self, release_time: f32) {
+        self.release_time = release_time.max(0.0);
+        self.release_coefficient = smoothing_coefficient(self.release_time);
+    }
+
+    /// Returns release time constant in samples.
+    pub fn release_time(&self) -> f32 {
+        self.release_time
+    }
+
+    /// Returns current envelope.
+    pub fn envelope(&self) -> f32 {
+        match self.mode {
+            EnvelopeMode::Peak => self.state,
+            EnvelopeMode::Rms => self.state.sqrt(),
+        }
+    }
+
+    /// Processes single sample and returns new envelope.
+    pub fn feed(&mut self, sample: f32) -> f32 {
+        let input = match self.mode {
+            EnvelopeMode::Peak => sample.abs(),
+            EnvelopeMode::Rms => sample * sample,
+        };
+        let coefficient = if input > self.state {
+            self.attack_coefficient
+        } else {
+            self.release_coefficient
+        };
+        self.state = input + (self.state - input) * coefficient;
+        self.envelope()
+    }
+
+    /// Resets the envelope to zero.
+    pub fn clear(&mut self) {
+        self.state = 0.0;
+    }
+}
+
+fn linear_to_db(value: f32) -> f32 {
+    20.0 * value.max(1.0e-10).log10()
+}
+
+fn db_to_linear(db: f32) -> f32 {
+    10.0f32.powf(db / 20.0)
+}
+
+/// Dynamic range compressor, that reduces the level of a signal when it goes above the threshold.
+/// The level is measured by a peak [`EnvelopeFollower`], the part of the level above the threshold
+/// is divided by the ratio. The knee smooths the transition between uncompressed and compressed
+/// regions, zero knee gives "hard knee" compression. Makeup gain is applied after the compression
+/// to compensate the lost loudness.
+///
+/// Very large ratio (see [`Self::LIMITER_RATIO`]) with zero attack time turns the compressor into
+/// a brickwall limiter, that prevents the signal from going above the threshold. It could be used
+/// on the master bus to prevent clipping.
+///
+/// Threshold, knee and makeup gain are in decibels (relative to full scale), attack and release
+/// times are in samples. See
+/// <https://www.eecs.qmul.ac.uk/~josh/documents/2012/GiannoulisMassbergReiss-dynamicrangecompression-JAES2012.pdf>
+/// for more info.
+#[derive(Debug, PartialEq, Clone, Visit)]
+pub struct Compressor {
+    threshold: f32,
+    ratio: f32,
+    knee: f32,
+    makeup_gain: f32,
+    envelope: EnvelopeFollower,
+}
+
+impl Default for Compressor {
+    fn default() -> Self {
+        Self::new(
+            -12.0,
+            4.0,
+            6.0,
+            EnvelopeFollower::DEFAULT_ATTACK_TIME,
+            EnvelopeFollower::DEFAULT_RELEASE_TIME,
+            0.0,
+        )
+    }
+}
+
+impl Compressor {
+    /// Ratio that effectively turns the compressor into a limiter.
+    pub const LIMITER_RATIO: f32 = 1000.0;
+
+    /// Creates new compressor with the given threshold (in dB), ratio, knee width (in dB), attack
+    /// and release time constants (in samples) and makeup gain (in dB).
+    pub fn new(
+        threshold: f32,
+        ratio: f32,
+        knee: f32,
+        attack_time: f32,
+        release_time: f32,
+        makeup_gain: f32,
+    ) -> Self {
+        Self {
+            threshold,
+            ratio: ratio.max(1.0),
+            knee: knee.max(0.0),
+            makeup_gain,
+            envelope: EnvelopeFollower::new(EnvelopeMode::Peak, attack_time, release_time),
+        }
+    }
+
+    /// Creates new brickwall limiter with the given threshold (in dB) and release time constant (in
+    /// samples).
+    pub fn limiter(threshold: f32, release_time: f32) -> Self {
+        Self::new(threshold, Self::LIMITER_RATIO, 0.0, 0.0, release_time, 0.0)
+    }
+
+    /// Sets threshold in dB, the signal louder than the threshold is compressed.
+    pub fn set_threshold(&mut self, threshold: f32) {
+        self.threshold = threshold;
+    }
+
+    /// Returns threshold in dB.
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    /// Sets compression ratio, for example ratio of 4 means that every 4 dB of the level above the
+    /// threshold become 1 dB. The value is clamped to be at least 1 (no compression).
+    pub fn set_ratio(&mut self, ratio: f32) {
+        self.ratio = ratio.max(1.0);
+    }
+
+    /// Returns compression ratio.
+    pub fn ratio(&self) -> f32 {
+        self.ratio
+    }
+
+    /// Sets width of the knee in dB. Negative values are clamped to zero (hard knee).
+    pub fn set_knee(&mut self, knee: f32) {
+        self.knee = knee.max(0.0);
+    }
+
+    /// Returns width of the knee in dB.
+    pub fn knee(&self) -> f32 {
+        self.knee
+    }
+
+    /// Sets attack time constant in samples.
+    pub fn set_attack_time(&mut self, attack_time: f32) {
+        self.envelope.set_attack_time(attack_time);
+    }
+
+    /// Returns attack time constant in samples.
+    pub fn attack_time(&self) -> f32 {
+        self.envelope.attack_time()
+    }
+
+    /// Sets release time constant in samples.
+    pub fn set_release_time(&mut self, release_time: f32) {
+        self.envelope.set_release_time(release_time);
+    }
+
+    /// Returns release time constant in samples.
+    pub fn release_time(&self) -> f32 {
+        self.envelope.release_time()
+    }
+
+    /// Sets makeup gain in dB.
+    pub fn set_makeup_gain(&mut self, makeup_gain: f32) {
+        self.makeup_gain = makeup_gain;
+    }
+
+    /// Returns makeup gain in dB.
+    pub fn makeup_gain(&self) -> f32 {
+        self.makeup_gain
+    }
+
+    /// Calculates gain reduction (in dB, zero or negative) for the given level (in dB).
+    pub fn gain_reduction(&self, level: f32) -> f32 {
+        let overshoot = level - self.threshold;
+        let slope = 1.0 / self.ratio - 1.0;
+        if 2.0 * overshoot <= -self.knee {
+            0.0
+        } else if 2.0 * overshoot.abs() < self.knee {
+            let x = overshoot + 0.5 * self.knee;
+            slope * x * x / (2.0 * self.knee)
+        } else {
+            slope * overshoot
         }
     }
+
+    /// Processes single sample.
+    pub fn feed(&mut self, sample: f32) -> f32 {
+        let level = linear_to_db(self.envelope.feed(sample));
+        sample * db_to_linear(self.gain_reduction(level) + self.makeup_gain)
+    }
+
+    /// Resets the state of the compressor.
+    pub fn clear(&mut self) {
+        self.envelope.clear();
+    }
 }
 
 /// Calculates single coefficient of Hamming window.
@@ -105,8 +974,661 @@ pub fn hann_window(i: usize, sample_count: usize) -> f32 {
     0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / (sample_count - 1) as f32).cos()
 }
 
+/// Calculates single coefficient of 4-term Blackman-Harris window. It has much lower side lobes
+/// than Hann or Hamming windows, at the cost of wider main lobe.
+/// <https://en.wikipedia.org/wiki/Window_function#Blackman%E2%80%93Harris_window>
+pub fn blackman_harris_window(i: usize, sample_count: usize) -> f32 {
+    let x = 2.0 * std::f32::consts::PI * i as f32 / (sample_count - 1) as f32;
+    0.35875 - 0.48829 * x.cos() + 0.14128 * (2.0 * x).cos() - 0.01168 * (3.0 * x).cos()
+}
+
+/// Zeroth-order modified Bessel function of the first kind, calculated using its power series.
+fn i0(x: f64) -> f64 {
+    let half_x = 0.5 * x;
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let mut k = 1.0;
+    loop {
+        term *= half_x / k;
+        let squared = term * term;
+        sum += squared;
+        if squared < sum * 1.0e-12 {
+            return sum;
+        }
+        k += 1.0;
+    }
+}
+
+/// Calculates single coefficient of Kaiser window. `beta` defines the trade-off between main lobe
+/// width and side lobe level, the larger it is the lower side lobes are (`beta = 0` gives
+/// rectangular window). Use a closure to plug it into [`make_window`], for example
+/// `make_window(1024, |i, n| kaiser_window(i, n, 8.6))`.
+/// <https://en.wikipedia.org/wiki/Kaiser_window>
+pub fn kaiser_window(i: usize, sample_count: usize, beta: f32) -> f32 {
+    let beta = beta as f64;
+    let x = 2.0 * i as f64 / (sample_count - 1) as f64 - 1.0;
+    (i0(beta * (1.0 - x * x).max(0.0).sqrt()) / i0(beta)) as f32
+}
+
 /// Creates new window using specified window function.
 /// <https://en.wikipedia.org/wiki/Window_function>
 pub fn make_window<W: Fn(usize, usize) -> f32>(sample_count: usize, func: W) -> Vec<f32> {
     (0..sample_count).map(|i| func(i, sample_count)).collect()
 }
+
+/// In-place iterative radix-2 Cooley-Tukey FFT. Length of both slices must be the same power of two.
+/// <https://en.wikipedia.org/wiki/Cooley%E2%80%93Tukey_FFT_algorithm>
+fn fft(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+    debug_assert!(n.is_power_of_two() && im.len() == n);
+
+    // Bit-reversal permutation.
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let half_len = len / 2;
+        let angle = -2.0 * std::f64::consts::PI / len as f64;
+        for k in 0..half_len {
+            let (sin, cos) = (angle * k as f64).sin_cos();
+            let (w_re, w_im) = (cos as f32, sin as f32);
+            for start in (0..n).step_by(len) {
+                let a = start + k;
+                let b = a + half_len;
+                let t_re = re[b] * w_re - im[b] * w_im;
+                let t_im = re[b] * w_im + im[b] * w_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// Calculates single-sided amplitude spectrum of the given samples. The samples are multiplied by
+/// the window (see [`make_window`]), zero-padded to the next power of two `N` and transformed using
+/// FFT. The result contains `N / 2 + 1` bins, where `k`-th bin corresponds to the frequency of
+/// `k * sample_rate / N` Hz. The magnitudes are normalized by the sum of the window, so a sine wave
+/// with amplitude `A` gives a peak of about `A` in its bin. Could be used to drive audio visualizers.
+///
+/// # Panics
+///
+/// Panics if the length of the window is not equal to the amount of samples.
+pub fn analyze_spectrum(samples: &[f32], window: &[f32]) -> Vec<f32> {
+    assert_eq!(samples.len(), window.len());
+
+    if samples.is_empty() {
+        return Vec::new();
+    }
+
+    let n = samples.len().next_power_of_two();
+    let mut re = vec![0.0; n];
+    let mut im = vec![0.0; n];
+    for (value, (sample, weight)) in re.iter_mut().zip(samples.iter().zip(window)) {
+        *value = sample * weight;
+    }
+
+    fft(&mut re, &mut im);
+
+    let window_sum = window.iter().sum::<f32>();
+    let scale = if window_sum != 0.0 {
+        1.0 / window_sum
+    } else {
+        0.0
+    };
+
+    (0..=n / 2)
+        .map(|k| {
+            let magnitude = re[k].hypot(im[k]) * scale;
+            // Energy of negative frequencies is folded into positive ones, except for DC and
+            // Nyquist bins that have no counterparts.
+            if k == 0 || k == n / 2 {
+                magnitude
+            } else {
+                2.0 * magnitude
+            }
+        })
+        .collect()
+}
+
+/// Block-based processor that splits a continuous stream of samples into overlapping windowed
+/// frames, passes every frame to a user-defined callback and overlap-adds processed frames back
+/// into a continuous output stream. This is the basis for spectral (STFT-based) effects.
+///
+/// A new frame is formed every `hop_size` samples from the last `window.len()` input samples. The
+/// output is normalized by the overlap factor of the window, so windows satisfying the "constant
+/// overlap-add" (COLA) condition for the given hop size (i.e. rectangular window with any integer
+/// overlap or periodic Hann window with 50% overlap) reproduce the input when the callback does
+/// nothing. The output is delayed by `window.len()` samples, see [`Self::latency`].
+/// <https://en.wikipedia.org/wiki/Overlap%E2%80%93add_method>
+#[derive(Debug, Clone)]
+pub struct OverlapAdd {
+    window: Vec<f32>,
+    hop_size: usize,
+    gain: f32,
+    input: Vec<f32>,
+    output: Vec<f32>,
+    frame: Vec<f32>,
+    pos: usize,
+    counter: usize,
+}
+
+impl OverlapAdd {
+    /// Creates new overlap-add processor using specified window (see [`make_window`]) and hop size
+    /// in samples.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window is empty or if the hop size is zero or larger than the window.
+    pub fn new(window: Vec<f32>, hop_size: usize) -> Self {
+        assert!(!window.is_empty());
+        assert!(hop_size > 0 && hop_size <= window.len());
+
+        let window_sum = window.iter().sum::<f32>();
+        let gain = if window_sum != 0.0 {
+            hop_size as f32 / window_sum
+        } else {
+            0.0
+        };
+
+        let len = window.len();
+        Self {
+            window,
+            hop_size,
+            gain,
+            input: vec![0.0; len],
+            output: vec![0.0; len],
+            frame: vec![0.0; len],
+            pos: 0,
+            counter: 0,
+        }
+    }
+
+    /// Returns length of a frame in samples.
+    pub fn frame_len(&self) -> usize {
+        self.window.len()
+    }
+
+    /// Returns distance between starts of two consecutive frames in samples.
+    pub fn hop_size(&self) -> usize {
+        self.hop_size
+    }
+
+    /// Returns a delay between input and output in samples.
+    pub fn latency(&self) -> usize {
+        self.window.len()
+    }
+
+    /// Processes single sample. `process` is called with a windowed frame every
+    /// [`Self::hop_size`] samples, the frame must be modified in-place.
+    pub fn feed<F>(&mut self, sample: f32, mut process: F) -> f32
+    where
+        F: FnMut(&mut [f32]),
+    {
+        let len = self.window.len();
+
+        let out = self.output[self.pos];
+        self.output[self.pos] = 0.0;
+        self.input[self.pos] = sample;
+        self.pos = (self.pos + 1) % len;
+
+        self.counter += 1;
+        if self.counter == self.hop_size {
+            self.counter = 0;
+
+            // Oldest sample is at the current write position.
+            for (i, (frame_sample, weight)) in self.frame.iter_mut().zip(&self.window).enumerate() {
+                *frame_sample = self.input[(self.pos + i) % len] * weight;
+            }
+
+            process(&mut self.frame);
+
+            for (i, frame_sample) in self.frame.iter().enumerate() {
+                self.output[(self.pos + i) % len] += frame_sample * self.gain;
+            }
+        }
+
+        out
+    }
+
+    /// Processes a block of samples. Input and output must have the same length.
+    pub fn process<F>(&mut self, input: &[f32], output: &mut [f32], mut process: F)
+    where
+        F: FnMut(&mut [f32]),
+    {
+        assert_eq!(input.len(), output.len());
+        for (in_sample, out_sample) in input.iter().zip(output.iter_mut()) {
+            *out_sample = self.feed(*in_sample, &mut process);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::dsp::{
+        analyze_spectrum, blackman_harris_window, hann_window, kaiser_window, make_window, AllPass,
+        BinauralPanner, CombFilter, Compressor, DcBlocker, DelayLine, EnvelopeFollower,
+        EnvelopeMode, MultiDelay, OnePole, OverlapAdd, RingBuffer,
+    };
+
+    fn assert_symmetric_with_center_peak(window: &[f32]) {
+        let center = window.len() / 2;
+        for (a, b) in window.iter().zip(window.iter().rev()) {
+            assert!((a - b).abs() < 1.0e-5);
+        }
+        assert!(window.iter().all(|w| *w <= window[center]));
+        assert!((window[center] - 1.0).abs() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_blackman_harris_window() {
+        let window = make_window(33, blackman_harris_window);
+        assert_symmetric_with_center_peak(&window);
+        assert!(window[0] < 1.0e-4);
+    }
+
+    #[test]
+    fn test_kaiser_window() {
+        let window = make_window(33, |i, n| kaiser_window(i, n, 8.6));
+        assert_symmetric_with_center_peak(&window);
+        assert!(window[0] < 1.0e-2);
+
+        // Zero beta gives rectangular window.
+        assert!(make_window(16, |i, n| kaiser_window(i, n, 0.0))
+            .iter()
+            .all(|w| (w - 1.0).abs() < 1.0e-6));
+    }
+
+    #[test]
+    fn test_analyze_spectrum() {
+        let sample_rate = 8000.0;
+        let frequency = 1000.0;
+        let amplitude = 0.5;
+        let sine = |count: usize| {
+            (0..count)
+                .map(|i| {
+                    amplitude
+                        * (2.0 * std::f32::consts::PI * frequency * i as f32 / sample_rate).sin()
+                })
+                .collect::<Vec<_>>()
+        };
+        let peak_bin = |spectrum: &[f32]| {
+            spectrum
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(b.1))
+                .unwrap()
+                .0
+        };
+
+        let samples = sine(1024);
+        let spectrum = analyze_spectrum(&samples, &make_window(1024, hann_window));
+        assert_eq!(spectrum.len(), 513);
+        assert_eq!(peak_bin(&spectrum), 128);
+        assert!(
+            (spectrum[128] - amplitude).abs() < 1.0e-2,
+            "{}",
+            spectrum[128]
+        );
+
+        // Non-power-of-two input is zero-padded.
+        let samples = sine(1000);
+        let spectrum = analyze_spectrum(&samples, &make_window(1000, blackman_harris_window));
+        assert_eq!(spectrum.len(), 513);
+        assert_eq!(peak_bin(&spectrum), 128);
+
+        assert!(analyze_spectrum(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn test_delay_line_filled() {
+        let mut delay_line = DelayLine::new(4);
+        assert_eq!(delay_line.filled(), 0);
+
+        delay_line.feed(1.0);
+        delay_line.feed(1.0);
+        assert_eq!(delay_line.filled(), 2);
+
+        for _ in 0..10 {
+            delay_line.feed(1.0);
+        }
+        assert_eq!(delay_line.filled(), delay_line.len());
+    }
+
+    #[test]
+    fn test_delay_line_clear() {
+        let mut delay_line = DelayLine::new(4);
+        delay_line.feed(1.0);
+        delay_line.feed(2.0);
+
+        delay_line.clear();
+        assert_eq!(delay_line.filled(), 0);
+        assert_eq!(delay_line.last(), 0.0);
+        for i in 0..delay_line.len() {
+            assert_eq!(delay_line.feed(i as f32 + 10.0), 0.0);
+        }
+        assert_eq!(delay_line.feed(0.0), 10.0);
+    }
+
+    #[test]
+    fn test_delay_line_resize() {
+        let mut delay_line = DelayLine::new(4);
+        for sample in [1.0, 2.0, 3.0, 4.0, 5.0] {
+            delay_line.feed(sample);
+        }
+
+        // Shrinking keeps the most recent samples.
+        delay_line.resize(2);
+        assert_eq!(delay_line.len(), 2);
+
+        // Growing adds silence before the stored samples.
+        delay_line.resize(3);
+        assert_eq!(delay_line.len(), 3);
+        assert_eq!(delay_line.feed(0.0), 0.0);
+        assert_eq!(delay_line.feed(0.0), 4.0);
+        assert_eq!(delay_line.feed(0.0), 5.0);
+    }
+
+    #[test]
+    fn test_multi_delay() {
+        let mut delay = MultiDelay::new([1, 3]);
+        assert_eq!(delay.feed([1.0, 10.0]), [0.0, 0.0]);
+        assert_eq!(delay.feed([2.0, 20.0]), [1.0, 0.0]);
+        assert_eq!(delay.feed([3.0, 30.0]), [2.0, 0.0]);
+        assert_eq!(delay.feed([4.0, 40.0]), [3.0, 10.0]);
+        assert_eq!(delay.feed([5.0, 50.0]), [4.0, 20.0]);
+    }
+
+    #[test]
+    fn test_binaural_panner_center() {
+        let mut panner = BinauralPanner::new(44100);
+        for i in 0..64 {
+            let sample = (i as f32 * 0.3).sin();
+            assert_eq!(panner.feed(sample, 0.0), (sample, sample));
+        }
+    }
+
+    #[test]
+    fn test_binaural_panner_side() {
+        let sample_rate = 44100;
+        let azimuth = std::f32::consts::FRAC_PI_2;
+        let expected_delay =
+            BinauralPanner::interaural_time_difference(azimuth) * sample_rate as f32;
+
+        let mut panner = BinauralPanner::new(sample_rate);
+        let (left, right): (Vec<f32>, Vec<f32>) = (0..64)
+            .map(|i| panner.feed(if i == 0 { 1.0 } else { 0.0 }, azimuth))
+            .unzip();
+
+        // The near (right) ear gets the impulse immediately and untouched.
+        assert_eq!(right[0], 1.0);
+        assert!(right[1..].iter().all(|s| *s == 0.0));
+
+        // The far (left) ear gets delayed and low-passed impulse.
+        let first_non_zero = left.iter().position(|s| *s != 0.0).unwrap();
+        assert_eq!(first_non_zero, expected_delay.floor() as usize);
+        assert!(left.iter().all(|s| *s < 1.0));
+
+        // Mirrored source gives mirrored output.
+        let mut panner = BinauralPanner::new(sample_rate);
+        let (mirrored_left, mirrored_right): (Vec<f32>, Vec<f32>) = (0..64)
+            .map(|i| panner.feed(if i == 0 { 1.0 } else { 0.0 }, -azimuth))
+            .unzip();
+        assert_eq!(mirrored_left, right);
+        assert_eq!(mirrored_right, left);
+    }
+
+    #[test]
+    fn test_delay_line_feed_fractional() {
+        let input = (0..32).map(|i| (i as f32 * 0.7).sin()).collect::<Vec<_>>();
+
+        let mut delay_line = DelayLine::new(5);
+        let mut fractional = DelayLine::new(8);
+        for &sample in input.iter() {
+            assert_eq!(
+                delay_line.feed(sample),
+                fractional.feed_fractional(sample, 5.0)
+            );
+        }
+
+        let mut fractional = DelayLine::new(8);
+        let mut prev = 0.0;
+        for &sample in input.iter() {
+            let expected = (sample + prev) * 0.5;
+            assert!((fractional.feed_fractional(sample, 0.5) - expected).abs() < 1.0e-6);
+            prev = sample;
+        }
+
+        // Delay is clamped to the length of the delay line.
+        let mut fractional = DelayLine::new(2);
+        fractional.feed_fractional(1.0, 100.0);
+        assert_eq!(fractional.feed_fractional(2.0, 100.0), 1.0);
+    }
+
+    #[test]
+    fn test_overlap_add_identity() {
+        let frame_len = 8;
+        let mut overlap_add = OverlapAdd::new(make_window(frame_len, |_, _| 1.0), frame_len / 2);
+
+        let input = (0..64).map(|i| (i as f32 * 0.3).sin()).collect::<Vec<_>>();
+        let mut output = vec![0.0; input.len()];
+        overlap_add.process(&input, &mut output, |_| ());
+
+        let latency = overlap_add.latency();
+        // Skip the first frames, they're not fully overlapped yet.
+        let skip = 2 * frame_len;
+        for (out, expected) in output[skip..].iter().zip(&input[skip - latency..]) {
+            assert!((out - expected).abs() < 1.0e-5);
+        }
+    }
+
+    #[test]
+    fn test_ring_buffer_wrap_around() {
+        let contents = |b: &RingBuffer| (0..b.len()).map(|i| b.get(i)).collect::<Vec<_>>();
+
+        let mut buffer = RingBuffer::new(3);
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer.get(0), 0.0);
+
+        assert_eq!(buffer.push(1.0), 0.0);
+        assert_eq!(buffer.push(2.0), 0.0);
+        assert_eq!(buffer.push(3.0), 0.0);
+        assert_eq!(contents(&buffer), [3.0, 2.0, 1.0]);
+
+        // Wraps around and overwrites the oldest samples.
+        assert_eq!(buffer.push(4.0), 1.0);
+        assert_eq!(buffer.push(5.0), 2.0);
+        assert_eq!(contents(&buffer), [5.0, 4.0, 3.0]);
+
+        buffer.clear();
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(contents(&buffer), [0.0, 0.0, 0.0]);
+        assert_eq!(buffer.push(6.0), 0.0);
+        assert_eq!(buffer.get(0), 6.0);
+    }
+
+    #[test]
+    fn test_comb_filter() {
+        let input = (0..32).map(|i| (i as f32 * 0.7).sin()).collect::<Vec<_>>();
+
+        // Zero feedback gives pure feedforward comb.
+        let mut delay_line = DelayLine::new(5);
+        let mut comb = CombFilter::new(5, 0.5, 0.0);
+        for &sample in input.iter() {
+            assert_eq!(comb.feed(sample), 0.5 * delay_line.feed(sample));
+        }
+
+        // Impulse response of feedback comb is a decaying series of impulses.
+        let mut comb = CombFilter::new(2, 1.0, 0.5);
+        let response = (0..7)
+            .map(|i| comb.feed(if i == 0 { 1.0 } else { 0.0 }))
+            .collect::<Vec<_>>();
+        assert_eq!(response, [0.0, 0.0, 1.0, 0.0, -0.5, 0.0, 0.25]);
+
+        comb.set_feedback(2.0);
+        assert_eq!(comb.feedback(), CombFilter::MAX_FEEDBACK);
+    }
+
+    #[test]
+    fn test_dc_blocker() {
+        let mut dc_blocker = DcBlocker::default();
+        assert_eq!(dc_blocker.feed(1.0), 1.0);
+        let output = (0..4096).map(|_| dc_blocker.feed(1.0)).last().unwrap();
+        assert!(output.abs() < 1.0e-6);
+
+        // Oscillating signal passes through.
+        let mut dc_blocker = DcBlocker::default();
+        let output = (0..4096)
+            .map(|i| dc_blocker.feed(if i % 2 == 0 { 1.5 } else { 0.5 }))
+            .last()
+            .unwrap();
+        assert!((output.abs() - 0.5).abs() < 1.0e-2);
+    }
+
+    #[test]
+    fn test_parameter_smoother() {
+        let tau = 100.0;
+        let mut smoother = OnePole::new(0.0, tau);
+        smoother.set_target(1.0);
+        let mut prev = smoother.value();
+        for _ in 0..(5.0 * tau) as usize {
+            let value = smoother.feed();
+            assert!(value > prev && value <= 1.0);
+            prev = value;
+        }
+        assert!((smoother.value() - 1.0).abs() < 0.01);
+
+        smoother.set_time_constant(0.0);
+        smoother.set_target(-2.0);
+        assert_eq!(smoother.feed(), -2.0);
+    }
+
+    #[test]
+    fn test_envelope_follower() {
+        let attack = 100.0;
+        let release = 1000.0;
+        let mut follower = EnvelopeFollower::new(EnvelopeMode::Peak, attack, release);
+
+        // Rises to `1 - 1/e` of the step after one attack time constant.
+        let mut envelope = 0.0;
+        for _ in 0..attack as usize {
+            let value = follower.feed(-1.0);
+            assert!(value > envelope);
+            envelope = value;
+        }
+        assert!(
+            (envelope - (1.0 - (-1.0f32).exp())).abs() < 1.0e-3,
+            "{envelope}"
+        );
+
+        // Falls to `1/e` of the level after one release time constant.
+        let level = envelope;
+        for _ in 0..release as usize {
+            let value = follower.feed(0.0);
+            assert!(value < envelope);
+            envelope = value;
+        }
+        assert!(
+            (envelope - level * (-1.0f32).exp()).abs() < 1.0e-3,
+            "{envelope}"
+        );
+
+        // RMS of a sine wave is its amplitude divided by square root of two.
+        let mut follower = EnvelopeFollower::new(EnvelopeMode::Rms, 4410.0, 4410.0);
+        let amplitude = 0.8;
+        let envelope = (0..44100)
+            .map(|i| follower.feed(amplitude * (i as f32 * 0.1).sin()))
+            .last()
+            .unwrap();
+        assert!(
+            (envelope - amplitude * std::f32::consts::FRAC_1_SQRT_2).abs() < 1.0e-2,
+            "{envelope}"
+        );
+
+        follower.set_mode(EnvelopeMode::Peak);
+        assert!((follower.envelope() - envelope).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn test_compressor() {
+        // -6 dB above the threshold of -12 dB with 4:1 ratio gives 4.5 dB of gain reduction.
+        let mut compressor = Compressor::new(-12.0, 4.0, 0.0, 10.0, 1000.0, 0.0);
+        let amplitude = 0.5; // About -6 dB.
+        let expected_reduction = (-12.0 - db(amplitude)) * (1.0 - 1.0 / 4.0);
+        let output = (0..4096)
+            .map(|_| compressor.feed(amplitude))
+            .last()
+            .unwrap();
+        assert!(
+            (db(output) - db(amplitude) - expected_reduction).abs() < 1.0e-2,
+            "{output}"
+        );
+
+        // Signal below the threshold passes as is.
+        let mut compressor = Compressor::default();
+        let output = (0..4096).map(|_| compressor.feed(0.01)).last().unwrap();
+        assert!((output - 0.01).abs() < 1.0e-6);
+
+        // Soft knee is continuous at its edges.
+        let compressor = Compressor::new(-12.0, 4.0, 6.0, 0.0, 0.0, 0.0);
+        assert!(compressor.gain_reduction(-15.0).abs() < 1.0e-6);
+        assert!((compressor.gain_reduction(-9.0) - (-3.0 * 0.75)).abs() < 1.0e-6);
+
+        // Limiter does not let the signal above the threshold.
+        let mut limiter = Compressor::limiter(-6.0, 1000.0);
+        let threshold = 10.0f32.powf(-6.0 / 20.0);
+        for i in 0..4096 {
+            let output = limiter.feed((i as f32 * 0.05).sin());
+            assert!(output.abs() <= threshold * 1.001, "{output}");
+        }
+    }
+
+    fn db(value: f32) -> f32 {
+        20.0 * value.abs().log10()
+    }
+
+    #[test]
+    fn test_all_pass() {
+        // Impulse response matches the difference equation.
+        let mut all_pass = AllPass::new(2, 0.5);
+        let response = (0..5)
+            .map(|i| all_pass.feed(if i == 0 { 1.0 } else { 0.0 }))
+            .collect::<Vec<_>>();
+        assert_eq!(response, [-0.5, 0.0, 0.75, 0.0, 0.375]);
+
+        // Magnitude response is flat - sines of any frequency pass with unit gain. Every tested
+        // frequency has an integer number of periods in the measured window.
+        for k in 1..32 {
+            let fc = k as f32 / 64.0;
+            let signal = |i: usize| (2.0 * std::f32::consts::PI * fc * i as f32).sin();
+            let mut all_pass = AllPass::new(7, 0.7);
+            for i in 0..4096 {
+                all_pass.feed(signal(i));
+            }
+            let (input, output) = (4096..5120).fold((0.0, 0.0), |(input, output), i| {
+                let x = signal(i);
+                (input + x * x, output + all_pass.feed(x).powi(2))
+            });
+            let gain = (output / input).sqrt();
+            assert!((gain - 1.0).abs() < 1.0e-3, "{fc}: {gain}");
+        }
+
+        let mut all_pass = AllPass::default();
+        all_pass.set_gain(-2.0);
+        assert_eq!(all_pass.gain(), -AllPass::MAX_GAIN);
+    }
+}
@@ -0,0 +1,238 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Stereo reverberator built on top of comb and all-pass filters. See [`Reverb`] docs for more info.
+
+use crate::dsp::filters::{AllPass, LpfComb};
+use fyrox_core::visitor::prelude::*;
+
+/// Filter lengths given in samples, designed for 44100 Hz sample rate.
+const COMB_LENGTHS: [usize; 8] = [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+const ALLPASS_LENGTHS: [usize; 4] = [556, 441, 341, 225];
+
+/// Difference in lengths of the filters of left and right channels (in samples).
+const STEREO_SPREAD: usize = 23;
+
+/// Input gain that prevents the sum of the comb filters from overflowing.
+const INPUT_GAIN: f32 = 0.015;
+const WET_GAIN: f32 = 3.0;
+
+const ROOM_SIZE_SCALE: f32 = 0.28;
+const ROOM_SIZE_OFFSET: f32 = 0.7;
+const DAMPING_SCALE: f32 = 0.4;
+
+#[derive(Default, Debug, Clone, PartialEq)]
+struct Channel {
+    combs: Vec<LpfComb>,
+    all_passes: Vec<AllPass>,
+}
+
+impl Channel {
+    fn new(spread: usize, feedback: f32, pole: f32) -> Self {
+        Self {
+            combs: COMB_LENGTHS
+                .iter()
+                .map(|len| {
+                    let mut comb = LpfComb::new(len + spread, 0.0, feedback);
+                    comb.set_pole(pole);
+                    comb
+                })
+                .collect(),
+            all_passes: ALLPASS_LENGTHS
+                .iter()
+                .map(|len| AllPass::new(len + spread, 0.5))
+                .collect(),
+        }
+    }
+
+    fn set_feedback(&mut self, feedback: f32) {
+        for comb in self.combs.iter_mut() {
+            comb.set_feedback(feedback);
+        }
+    }
+
+    fn set_pole(&mut self, pole: f32) {
+        for comb in self.combs.iter_mut() {
+            comb.set_pole(pole);
+        }
+    }
+
+    fn feed(&mut self, sample: f32) -> f32 {
+        let mut result = 0.0;
+        for comb in self.combs.iter_mut() {
+            result += comb.feed(sample);
+        }
+        for all_pass in self.all_passes.iter_mut() {
+            result = all_pass.feed(result);
+        }
+        result
+    }
+}
+
+/// Stereo reverberator based on [Freeverb](https://ccrma.stanford.edu/~jos/pasp/Freeverb.html)
+/// design - each channel is processed by eight parallel lowpass-feedback comb filters followed by
+/// four serial all-pass filters. Filters of the right channel are slightly longer than the
+/// filters of the left channel, which makes the output wider.
+///
+/// Unlike [`crate::effects::reverb::Reverb`], this is a plain signal processor that is not bound
+/// to the sound engine and could be used to process arbitrary samples. It is designed for
+/// 44100 Hz sample rate.
+///
+/// Only the parameters of the reverb are serialized, its internal state is reset on loading.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reverb {
+    room_size: f32,
+    damping: f32,
+    mix: f32,
+    left: Channel,
+    right: Channel,
+}
+
+impl Visit for Reverb {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        self.room_size.visit("RoomSize", &mut region)?;
+        self.damping.visit("Damping", &mut region)?;
+        self.mix.visit("Mix", &mut region)?;
+
+        if region.is_reading() {
+            *self = Self::new(self.room_size, self.damping, self.mix);
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Reverb {
+    fn default() -> Self {
+        Self::new(0.5, 0.5, 0.33)
+    }
+}
+
+impl Reverb {
+    /// Creates new reverb with the given room size, damping and wet/dry mix. See respective
+    /// setters for more info about the parameters.
+    pub fn new(room_size: f32, damping: f32, mix: f32) -> Self {
+        let room_size = room_size.clamp(0.0, 1.0);
+        let damping = damping.clamp(0.0, 1.0);
+        Self {
+            room_size,
+            damping,
+            mix: mix.clamp(0.0, 1.0),
+            left: Channel::new(0, Self::feedback(room_size), Self::pole(damping)),
+            right: Channel::new(
+                STEREO_SPREAD,
+                Self::feedback(room_size),
+                Self::pole(damping),
+            ),
+        }
+    }
+
+    fn feedback(room_size: f32) -> f32 {
+        room_size * ROOM_SIZE_SCALE + ROOM_SIZE_OFFSET
+    }
+
+    fn pole(damping: f32) -> f32 {
+        damping * DAMPING_SCALE
+    }
+
+    /// Sets size of the simulated room in 0..1 range. Larger rooms have longer reverberation tail.
+    pub fn set_room_size(&mut self, room_size: f32) {
+        self.room_size = room_size.clamp(0.0, 1.0);
+        let feedback = Self::feedback(self.room_size);
+        self.left.set_feedback(feedback);
+        self.right.set_feedback(feedback);
+    }
+
+    /// Returns current room size.
+    pub fn room_size(&self) -> f32 {
+        self.room_size
+    }
+
+    /// Sets how much high frequencies are absorbed by the walls of the simulated room, in 0..1
+    /// range. Higher values make the reverberation tail more muffled.
+    pub fn set_damping(&mut self, damping: f32) {
+        self.damping = damping.clamp(0.0, 1.0);
+        let pole = Self::pole(self.damping);
+        self.left.set_pole(pole);
+        self.right.set_pole(pole);
+    }
+
+    /// Returns current damping.
+    pub fn damping(&self) -> f32 {
+        self.damping
+    }
+
+    /// Sets wet/dry mix in 0..1 range, where 0.0 - only input signal is passed to the output,
+    /// 1.0 - only reverberated signal is passed to the output.
+    pub fn set_mix(&mut self, mix: f32) {
+        self.mix = mix.clamp(0.0, 1.0);
+    }
+
+    /// Returns current wet/dry mix.
+    pub fn mix(&self) -> f32 {
+        self.mix
+    }
+
+    /// Processes single stereo sample.
+    pub fn feed(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let input = (left + right) * INPUT_GAIN;
+
+        let wet = self.mix * WET_GAIN;
+        let dry = 1.0 - self.mix;
+
+        (
+            self.left.feed(input) * wet + left * dry,
+            self.right.feed(input) * wet + right * dry,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::dsp::Reverb;
+
+    #[test]
+    fn test_reverb_impulse_response_decays() {
+        let mut reverb = Reverb::new(0.5, 0.5, 1.0);
+
+        // Energy of the output in 50 ms blocks.
+        let block_len = 2205;
+        let energies = (0..60)
+            .map(|block| {
+                (0..block_len)
+                    .map(|i| {
+                        let impulse = if block == 0 && i == 0 { 1.0 } else { 0.0 };
+                        let (left, right) = reverb.feed(impulse, impulse);
+                        left * left + right * right
+                    })
+                    .sum::<f32>()
+            })
+            .collect::<Vec<_>>();
+
+        assert!(energies[0] > 0.0);
+
+        // Skip the initial buildup, when the echoes are still accumulating.
+        for pair in energies[2..].windows(2) {
+            assert!(pair[1] <= pair[0], "{energies:?}");
+        }
+    }
+}
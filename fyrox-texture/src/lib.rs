@@ -316,6 +316,7 @@ impl ResourceData for Texture {
             | TexturePixelKind::BGR8
             | TexturePixelKind::BGRA8
             | TexturePixelKind::RGB16F
+            | TexturePixelKind::RGBA16F
             | TexturePixelKind::R32F
             | TexturePixelKind::R16F => return Err(Box::new(TextureError::UnsupportedFormat)),
         };
@@ -1120,6 +1121,9 @@ pub enum TexturePixelKind {
 
     SRGBA8 = 25,
     SRGB8 = 26,
+
+    /// Red, green, blue, alpha components, each by 2 byte half-precision float.
+    RGBA16F = 27,
 }
 
 impl TexturePixelKind {
@@ -1152,6 +1156,7 @@ impl TexturePixelKind {
             24 => Ok(Self::R16F),
             25 => Ok(Self::SRGBA8),
             26 => Ok(Self::SRGB8),
+            27 => Ok(Self::RGBA16F),
             _ => Err(format!("Invalid texture kind {id}!")),
         }
     }
@@ -1176,7 +1181,7 @@ impl TexturePixelKind {
                 Some(2)
             }
             Self::RGB16 | Self::RGB16F => Some(6),
-            Self::RGBA16 => Some(8),
+            Self::RGBA16 | Self::RGBA16F => Some(8),
             Self::RGB32F => Some(12),
             Self::RGBA32F => Some(16),
             // Pixels of compressed textures consumes less than a byte, so there's no way to express
@@ -1410,7 +1415,7 @@ fn bytes_in_mip_level(kind: TextureKind, pixel_kind: TexturePixelKind, mip: usiz
         | TexturePixelKind::LuminanceAlpha16
         | TexturePixelKind::R32F => 4 * pixel_count,
         TexturePixelKind::RGB16 | TexturePixelKind::RGB16F => 6 * pixel_count,
-        TexturePixelKind::RGBA16 => 8 * pixel_count,
+        TexturePixelKind::RGBA16 | TexturePixelKind::RGBA16F => 8 * pixel_count,
         TexturePixelKind::RGB32F => 12 * pixel_count,
         TexturePixelKind::RGBA32F => 16 * pixel_count,
 
@@ -1783,6 +1788,32 @@ impl Texture {
         }
     }
 
+    /// Creates new texture instance with the given amount of mip levels. The data must contain all
+    /// mip levels one after another, starting from the largest one. Returns `None` if the size of
+    /// the data does not match the amount of mip levels.
+    pub fn from_bytes_with_mips(
+        kind: TextureKind,
+        pixel_kind: TexturePixelKind,
+        bytes: Vec<u8>,
+        mip_count: u32,
+    ) -> Option<Self> {
+        let byte_count = (0..mip_count as usize)
+            .map(|mip| bytes_in_mip_level(kind, pixel_kind, mip) as usize)
+            .sum::<usize>();
+        if mip_count == 0 || byte_count != bytes.len() {
+            None
+        } else {
+            Some(Self {
+                kind,
+                modifications_counter: 0,
+                bytes: bytes.into(),
+                pixel_kind,
+                mip_count,
+                ..Default::default()
+            })
+        }
+    }
+
     /// Sets new minification filter. It is used when texture becomes smaller.
     #[inline]
     pub fn set_minification_filter(&mut self, filter: TextureMinificationFilter) {
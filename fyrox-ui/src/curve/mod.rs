@@ -79,6 +79,18 @@ pub enum CurveEditorMessage {
         after_layout: bool,
     },
     HighlightZones(Vec<HighlightZone>),
+    /// Ids of the selected keys. The curve editor sends this message (with
+    /// [`MessageDirection::FromWidget`] direction) every time the selection changes. When sent to
+    /// the curve editor, selects the keys with the given ids; unknown ids are ignored.
+    SelectedKeys(Vec<Uuid>),
+    /// Sets grid snapping options. When enabled, moved and added keys are snapped to the nearest
+    /// grid node. The steps are defined in the curve space, so they do not depend on the zoom. Zero
+    /// step disables snapping along the respective axis.
+    SetSnap {
+        x_step: f32,
+        y_step: f32,
+        enabled: bool,
+    },
 
     // Internal messages. Use only when you know what you're doing.
     // These are internal because you must use Sync message to request changes
@@ -100,6 +112,44 @@ impl MessageData for CurveEditorMessage {
     }
 }
 
+/// Relative margin that is added on each side of the curves bounds when zooming to fit.
+const ZOOM_TO_FIT_MARGIN: f32 = 0.1;
+
+/// Calculates zoom and view position that makes the given bounds (in curve space) fully visible
+/// in a view of the given size, with a small margin around. If the bounds are degenerate along
+/// some axis (for example, there's only one key), a unit range is shown along this axis.
+fn fit_view(bounds: Option<Rect<f32>>, view_size: Vector2<f32>) -> (Vector2<f32>, Vector2<f32>) {
+    let Some(bounds) = bounds else {
+        return (Vector2::repeat(1.0), Vector2::default());
+    };
+
+    let fit = |view_size: f32, size: f32| {
+        let size = if size > f32::EPSILON { size } else { 1.0 };
+        view_size / (size * (1.0 + 2.0 * ZOOM_TO_FIT_MARGIN))
+    };
+
+    (
+        Vector2::new(
+            fit(view_size.x, bounds.size.x),
+            fit(view_size.y, bounds.size.y),
+        ),
+        bounds.center(),
+    )
+}
+
+/// Rounds the given position (in curve space) to the nearest node of a grid with the given steps.
+/// Non-positive step leaves the respective coordinate as is.
+fn snap_position(position: Vector2<f32>, step: Vector2<f32>) -> Vector2<f32> {
+    let snap = |value: f32, step: f32| {
+        if step > 0.0 {
+            (value / step).round() * step
+        } else {
+            value
+        }
+    };
+    Vector2::new(snap(position.x, step.x), snap(position.y, step.y))
+}
+
 /// Highlight zone in values space.
 #[derive(Clone, Debug, PartialEq, Visit, Reflect, Default)]
 pub struct HighlightZone {
@@ -450,6 +500,10 @@ pub struct CurveEditor {
     #[reflect(hidden)]
     clipboard: Vec<(Vector2<f32>, CurveKeyKind)>,
     show_background_curves: bool,
+    #[visit(optional)]
+    snap_enabled: bool,
+    #[visit(optional)]
+    snap_step: Vector2<f32>,
 }
 
 impl ConstructorProvider<UiNode, UserInterface> for CurveEditor {
@@ -601,9 +655,13 @@ impl Control for CurveEditor {
                                     initial_mouse_pos,
                                 } => {
                                     let local_delta = curve_mouse_pos - initial_mouse_pos;
+                                    let snap_step = self.active_snap_step();
                                     for entry in entries {
                                         if let Some(key) = self.curves.key_mut(entry.key_id) {
-                                            key.position = entry.initial_position + local_delta;
+                                            key.position = snap_position(
+                                                entry.initial_position + local_delta,
+                                                snap_step,
+                                            );
                                         }
                                     }
                                     self.sort_keys();
@@ -902,7 +960,10 @@ impl Control for CurveEditor {
                         self.invalidate_visual();
                     }
                     CurveEditorMessage::AddKey(screen_pos) => {
-                        let local_pos = self.screen_to_curve_space(*screen_pos);
+                        let local_pos = snap_position(
+                            self.screen_to_curve_space(*screen_pos),
+                            self.active_snap_step(),
+                        );
 
                         let mut curves = Vec::new();
                         if let Some(selection) = self.selection.as_ref() {
@@ -987,6 +1048,7 @@ impl Control for CurveEditor {
                             let menu_pos =
                                 ui.node(self.context_menu.widget.handle()).screen_position();
                             let local_menu_pos = self.screen_to_curve_space(menu_pos);
+                            let snap_step = self.active_snap_step();
 
                             let mut selection = FxHashSet::default();
                             for (offset, kind) in self.clipboard.iter().cloned() {
@@ -996,7 +1058,7 @@ impl Control for CurveEditor {
                                     selection.insert(id);
 
                                     curve.add(CurveKeyView {
-                                        position: local_menu_pos + offset,
+                                        position: snap_position(local_menu_pos + offset, snap_step),
                                         kind: kind.clone(),
                                         id,
                                     });
@@ -1010,6 +1072,25 @@ impl Control for CurveEditor {
                             self.invalidate_visual();
                         }
                     }
+                    CurveEditorMessage::SelectedKeys(keys) => {
+                        let keys = keys
+                            .iter()
+                            .copied()
+                            .filter(|id| self.curves.key_ref(*id).is_some())
+                            .collect::<FxHashSet<_>>();
+                        let selection = (!keys.is_empty()).then_some(Selection::Keys { keys });
+                        self.set_selection(selection, ui);
+
+                        self.invalidate_visual();
+                    }
+                    CurveEditorMessage::SetSnap {
+                        x_step,
+                        y_step,
+                        enabled,
+                    } => {
+                        self.snap_enabled = *enabled;
+                        self.snap_step = Vector2::new(*x_step, *y_step);
+                    }
                     CurveEditorMessage::ShowBackgroundCurves(show) => {
                         if self.show_background_curves != *show {
                             self.show_background_curves = *show;
@@ -1137,48 +1218,27 @@ impl CurveEditor {
     }
 
     fn zoom_to_fit(&mut self, sender: &Sender<UiMessage>) {
-        let mut min = Vector2::repeat(f32::MAX);
-        let mut max = Vector2::repeat(-f32::MAX);
-
-        for curve in self.curves.iter() {
-            let bounds = curve.curve().bounds();
-            if bounds.position.x < min.x {
-                min.x = bounds.position.x;
-            }
-            if bounds.position.y < min.y {
-                min.y = bounds.position.y;
-            }
-            let local_max = bounds.position + bounds.size;
-            if local_max.x > max.x {
-                max.x = local_max.x;
-            }
-            if local_max.y > max.y {
-                max.y = local_max.y;
-            }
+        let mut curves = self.curves.iter().collect::<Vec<_>>();
+        if self.show_background_curves {
+            curves.extend(self.background_curves.iter());
         }
 
-        let mut bounds = Rect {
-            position: min,
-            size: max - min,
-        };
+        let bounds = curves
+            .into_iter()
+            .filter(|curve| !curve.curve().keys().is_empty())
+            .map(|curve| curve.curve().bounds())
+            .reduce(|a, b| {
+                let mut bounds = a;
+                bounds.extend_to_contain(b);
+                bounds
+            });
 
-        // Prevent division by zero.
-        if bounds.size.x < 0.001 {
-            bounds.size.x = 0.001;
-        }
-        if bounds.size.y < 0.001 {
-            bounds.size.y = 0.001;
-        }
-
-        let center = bounds.center();
+        let (zoom, center) = fit_view(bounds, self.actual_local_size());
 
         sender
             .send(UiMessage::for_widget(
                 self.handle,
-                CurveEditorMessage::Zoom(Vector2::new(
-                    self.actual_local_size().x / bounds.w(),
-                    self.actual_local_size().y / bounds.h(),
-                )),
+                CurveEditorMessage::Zoom(zoom),
             ))
             .unwrap();
 
@@ -1233,9 +1293,24 @@ impl CurveEditor {
         }
     }
 
+    /// Returns current snapping steps, or zero steps if the snapping is disabled.
+    fn active_snap_step(&self) -> Vector2<f32> {
+        if self.snap_enabled {
+            self.snap_step
+        } else {
+            Vector2::default()
+        }
+    }
+
     fn set_selection(&mut self, selection: Option<Selection>, ui: &UserInterface) {
         self.selection = selection;
 
+        let selected_keys = match self.selection.as_ref() {
+            Some(Selection::Keys { keys }) => keys.iter().copied().collect(),
+            _ => Vec::new(),
+        };
+        ui.post(self.handle, CurveEditorMessage::SelectedKeys(selected_keys));
+
         ui.send(
             self.context_menu.remove,
             WidgetMessage::Enabled(self.selection.is_some()),
@@ -1731,6 +1806,8 @@ pub struct CurveEditorBuilder {
     max_zoom: Vector2<f32>,
     highlight_zones: Vec<HighlightZone>,
     show_background_curves: bool,
+    snap_enabled: bool,
+    snap_step: Vector2<f32>,
 }
 
 impl CurveEditorBuilder {
@@ -1749,6 +1826,8 @@ impl CurveEditorBuilder {
             max_zoom: Vector2::new(1000.0, 1000.0),
             highlight_zones: Default::default(),
             show_background_curves: true,
+            snap_enabled: false,
+            snap_step: Vector2::new(0.1, 0.1),
         }
     }
 
@@ -1813,6 +1892,13 @@ impl CurveEditorBuilder {
         self
     }
 
+    /// Sets grid snapping options, see [`CurveEditorMessage::SetSnap`] for more info.
+    pub fn with_snap(mut self, step: Vector2<f32>, enabled: bool) -> Self {
+        self.snap_step = step;
+        self.snap_enabled = enabled;
+        self
+    }
+
     pub fn build(mut self, ctx: &mut BuildContext) -> Handle<CurveEditor> {
         let background_curve_brush = ctx.style.get_or_default::<Brush>(Style::BRUSH_LIGHT);
         let key_brush = Brush::Solid(Color::opaque(140, 140, 140));
@@ -2022,6 +2108,8 @@ impl CurveEditorBuilder {
             clipboard: Default::default(),
             background_curve_brush,
             show_background_curves: self.show_background_curves,
+            snap_enabled: self.snap_enabled,
+            snap_step: self.snap_step,
         };
 
         ctx.add(editor)
@@ -2030,9 +2118,46 @@ impl CurveEditorBuilder {
 
 #[cfg(test)]
 mod test {
-    use crate::{curve::CurveEditorBuilder, test::test_widget_deletion, widget::WidgetBuilder};
+    use crate::{
+        core::{algebra::Vector2, math::Rect},
+        curve::{fit_view, snap_position, CurveEditorBuilder},
+        test::test_widget_deletion,
+        widget::WidgetBuilder,
+    };
     #[test]
     fn test_curve_editor_deletion() {
         test_widget_deletion(|ctx| CurveEditorBuilder::new(WidgetBuilder::new()).build(ctx));
     }
+
+    #[test]
+    fn test_fit_view() {
+        let view_size = Vector2::new(120.0, 60.0);
+
+        let (zoom, center) = fit_view(Some(Rect::new(0.0, 0.0, 10.0, 5.0)), view_size);
+        assert_eq!(center, Vector2::new(5.0, 2.5));
+        assert!((zoom - Vector2::new(10.0, 10.0)).norm() < 1.0e-4);
+
+        // Single key.
+        let (zoom, center) = fit_view(Some(Rect::new(2.0, 3.0, 0.0, 0.0)), view_size);
+        assert_eq!(center, Vector2::new(2.0, 3.0));
+        assert!((zoom - Vector2::new(100.0, 50.0)).norm() < 1.0e-4);
+
+        // No keys.
+        let (zoom, center) = fit_view(None, view_size);
+        assert_eq!(center, Vector2::default());
+        assert_eq!(zoom, Vector2::repeat(1.0));
+    }
+
+    #[test]
+    fn test_snap_position() {
+        let step = Vector2::new(0.5, 0.25);
+        assert_eq!(
+            snap_position(Vector2::new(1.3, -0.3), step),
+            Vector2::new(1.5, -0.25)
+        );
+        assert_eq!(
+            snap_position(Vector2::new(1.3, 0.6), Vector2::new(0.0, 0.5)),
+            Vector2::new(1.3, 0.5)
+        );
+    }
 }
@@ -95,6 +95,7 @@ where
                     max_value: property_info.max_value,
                     step: property_info.step,
                     precision: property_info.precision,
+                    max_length: property_info.max_length,
                     tag: property_info.tag,
                     doc: property_info.doc,
                 },
@@ -143,6 +144,7 @@ where
                     max_value: property_info.max_value,
                     step: property_info.step,
                     precision: property_info.precision,
+                    max_length: property_info.max_length,
                     tag: property_info.tag,
                     doc: property_info.doc,
                 },
@@ -183,6 +185,7 @@ where
 
                     message: ctx.message,
                     definition_container: ctx.definition_container.clone(),
+                    max_length: ctx.max_length,
                 },
             );
         }
@@ -248,6 +248,7 @@ where
                     max_value: property_info.max_value,
                     step: property_info.step,
                     precision: property_info.precision,
+                    max_length: property_info.max_length,
                     tag: property_info.tag,
                     doc: property_info.doc,
                 },
@@ -588,6 +589,7 @@ where
                             max_value: property_info.max_value,
                             step: property_info.step,
                             precision: property_info.precision,
+                            max_length: property_info.max_length,
                             tag: property_info.tag,
                             doc: property_info.doc,
                         },
@@ -650,6 +652,7 @@ where
                                         name: "",
                                         message,
                                         definition_container: ctx.definition_container.clone(),
+                                        max_length: None,
                                     })?
                                     .action,
                             },
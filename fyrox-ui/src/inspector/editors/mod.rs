@@ -73,6 +73,7 @@ use crate::{
             string::StringPropertyEditorDefinition,
             style::StyledPropertyEditorDefinition,
             texture_slice::TextureSlicePropertyEditorDefinition,
+            utf16::Utf16StringPropertyEditorDefinition,
             utf32::Utf32StringPropertyEditorDefinition,
             uuid::UuidPropertyEditorDefinition,
             vec::{
@@ -150,6 +151,7 @@ pub mod refcell;
 pub mod string;
 pub mod style;
 pub mod texture_slice;
+pub mod utf16;
 pub mod utf32;
 pub mod uuid;
 pub mod vec;
@@ -258,6 +260,9 @@ pub struct PropertyEditorTranslationContext<'b, 'c> {
     /// The list of the Inspectors property editors.
     /// This allows one property editor to make use of other property editors.
     pub definition_container: Arc<PropertyEditorDefinitionContainer>,
+    /// A copy of [FieldMetadata::max_length](crate::core::reflect::FieldMetadata::max_length) of
+    /// the property being edited.
+    pub max_length: Option<usize>,
 }
 
 /// A widget handle that is to act as an editor in an [Inspector](crate::inspector::Inspector), with or without
@@ -587,6 +592,9 @@ impl PropertyEditorDefinitionContainer {
         container.insert(Utf32StringPropertyEditorDefinition);
         container.insert(InheritablePropertyEditorDefinition::<Vec<char>>::new());
 
+        container.insert(Utf16StringPropertyEditorDefinition);
+        container.insert(InheritablePropertyEditorDefinition::<Vec<u16>>::new());
+
         container.insert(InheritablePropertyEditorDefinition::<Thickness>::new());
 
         container.register_inheritable_enum::<EventKind, _>();
@@ -59,6 +59,7 @@ impl PropertyEditorDefinition for StringPropertyEditorDefinition {
             .with_text_commit_mode(TextCommitMode::Changed)
             .with_text(value)
             .with_vertical_text_alignment(VerticalAlignment::Center)
+            .with_editable(!ctx.property_info.read_only)
             .build(ctx.build_context),
         ))
     }
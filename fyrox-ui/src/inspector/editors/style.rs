@@ -530,6 +530,7 @@ where
                     max_value: property_info.max_value,
                     step: property_info.step,
                     precision: property_info.precision,
+                    max_length: property_info.max_length,
                     tag: property_info.tag,
                     doc: property_info.doc,
                 },
@@ -615,6 +616,7 @@ where
                     max_value: property_info.max_value,
                     step: property_info.step,
                     precision: property_info.precision,
+                    max_length: property_info.max_length,
                     tag: property_info.tag,
                     doc: property_info.doc,
                 },
@@ -663,6 +665,7 @@ where
                         name: ctx.name,
                         message: ctx.message,
                         definition_container: ctx.definition_container.clone(),
+                        max_length: ctx.max_length,
                     })?;
 
             property_change.name += ".";
@@ -29,13 +29,53 @@ use crate::{
         FieldAction, InspectorError, PropertyChanged,
     },
     message::{MessageDirection, UiMessage},
+    style::{resource::StyleResourceExt, Style},
     text::TextMessage,
     text_box::TextBoxBuilder,
-    widget::WidgetBuilder,
+    widget::{WidgetBuilder, WidgetMessage},
     Thickness, VerticalAlignment,
 };
 use std::any::TypeId;
 
+const LINE_HEIGHT: f32 = 17.0;
+
+/// Text of the editor is highlighted when the length of the string reaches its limit.
+fn foreground_name(length: usize, max_length: Option<usize>) -> &'static str {
+    match max_length {
+        Some(max_length) if length >= max_length => Style::BRUSH_WARNING,
+        _ => Style::BRUSH_TEXT,
+    }
+}
+
+fn create_message(ctx: PropertyEditorMessageContext) -> Result<Option<UiMessage>, InspectorError> {
+    let value = ctx.property_info.cast_value::<Vec<char>>()?;
+    if let Some(max_length) = ctx.property_info.max_length {
+        let foreground = ctx
+            .ui
+            .style
+            .property(foreground_name(value.len(), Some(max_length)));
+        ctx.ui
+            .send_sync(ctx.instance, WidgetMessage::Foreground(foreground));
+    }
+    Ok(Some(UiMessage::for_widget(
+        ctx.instance,
+        TextMessage::Text(value.iter().collect::<String>()),
+    )))
+}
+
+fn translate_message(ctx: PropertyEditorTranslationContext) -> Option<PropertyChanged> {
+    if ctx.message.direction() == MessageDirection::FromWidget {
+        if let Some(TextMessage::Text(value)) = ctx.message.data::<TextMessage>() {
+            let max_length = ctx.max_length.unwrap_or(usize::MAX);
+            return Some(PropertyChanged {
+                name: ctx.name.to_string(),
+                action: FieldAction::object(value.chars().take(max_length).collect::<Vec<_>>()),
+            });
+        }
+    }
+    None
+}
+
 #[derive(Debug)]
 pub struct Utf32StringPropertyEditorDefinition;
 
@@ -52,12 +92,18 @@ impl PropertyEditorDefinition for Utf32StringPropertyEditorDefinition {
         Ok(PropertyEditorInstance::simple(
             TextBoxBuilder::new(
                 WidgetBuilder::new()
-                    .with_min_size(Vector2::new(0.0, 17.0))
-                    .with_margin(Thickness::uniform(1.0)),
+                    .with_min_size(Vector2::new(0.0, LINE_HEIGHT))
+                    .with_margin(Thickness::uniform(1.0))
+                    .with_foreground(
+                        ctx.build_context
+                            .style
+                            .property(foreground_name(value.len(), ctx.property_info.max_length)),
+                    ),
             )
             .with_wrap(WrapMode::Word)
             .with_text(value.iter().collect::<String>())
             .with_vertical_text_alignment(VerticalAlignment::Center)
+            .with_editable(!ctx.property_info.read_only)
             .build(ctx.build_context),
         ))
     }
@@ -66,22 +112,118 @@ impl PropertyEditorDefinition for Utf32StringPropertyEditorDefinition {
         &self,
         ctx: PropertyEditorMessageContext,
     ) -> Result<Option<UiMessage>, InspectorError> {
+        create_message(ctx)
+    }
+
+    fn translate_message(&self, ctx: PropertyEditorTranslationContext) -> Option<PropertyChanged> {
+        translate_message(ctx)
+    }
+}
+
+/// Same as [`Utf32StringPropertyEditorDefinition`], but creates a multiline text box, that is tall
+/// enough to show the given amount of rows. It is not registered by default, insert it in a
+/// property editors container to replace the single line editor.
+#[derive(Debug)]
+pub struct MultilineUtf32StringPropertyEditorDefinition {
+    /// Amount of rows of text that should fit in the text box without scrolling.
+    pub rows: usize,
+}
+
+impl Default for MultilineUtf32StringPropertyEditorDefinition {
+    fn default() -> Self {
+        Self { rows: 4 }
+    }
+}
+
+impl PropertyEditorDefinition for MultilineUtf32StringPropertyEditorDefinition {
+    fn value_type_id(&self) -> TypeId {
+        TypeId::of::<Vec<char>>()
+    }
+
+    fn create_instance(
+        &self,
+        ctx: PropertyEditorBuildContext,
+    ) -> Result<PropertyEditorInstance, InspectorError> {
         let value = ctx.property_info.cast_value::<Vec<char>>()?;
-        Ok(Some(UiMessage::for_widget(
-            ctx.instance,
-            TextMessage::Text(value.iter().collect::<String>()),
-        )))
+        Ok(PropertyEditorInstance::simple(
+            TextBoxBuilder::new(
+                WidgetBuilder::new()
+                    .with_min_size(Vector2::new(0.0, self.rows.max(1) as f32 * LINE_HEIGHT))
+                    .with_margin(Thickness::uniform(1.0))
+                    .with_foreground(
+                        ctx.build_context
+                            .style
+                            .property(foreground_name(value.len(), ctx.property_info.max_length)),
+                    ),
+            )
+            .with_wrap(WrapMode::Word)
+            .with_multiline(true)
+            .with_text(value.iter().collect::<String>())
+            .with_vertical_text_alignment(VerticalAlignment::Top)
+            .with_editable(!ctx.property_info.read_only)
+            .build(ctx.build_context),
+        ))
+    }
+
+    fn create_message(
+        &self,
+        ctx: PropertyEditorMessageContext,
+    ) -> Result<Option<UiMessage>, InspectorError> {
+        create_message(ctx)
     }
 
     fn translate_message(&self, ctx: PropertyEditorTranslationContext) -> Option<PropertyChanged> {
-        if ctx.message.direction() == MessageDirection::FromWidget {
-            if let Some(TextMessage::Text(value)) = ctx.message.data::<TextMessage>() {
-                return Some(PropertyChanged {
-                    name: ctx.name.to_string(),
-                    action: FieldAction::object(value.chars().collect::<Vec<_>>()),
-                });
-            }
-        }
-        None
+        translate_message(ctx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::pool::Handle,
+        inspector::{
+            editors::{
+                utf32::Utf32StringPropertyEditorDefinition, PropertyEditorDefinition,
+                PropertyEditorDefinitionContainer, PropertyEditorTranslationContext,
+            },
+            FieldAction,
+        },
+        message::UiMessage,
+        text::TextMessage,
+        UiNode,
+    };
+    use std::sync::Arc;
+
+    fn translate(text: &str, max_length: Option<usize>) -> Vec<char> {
+        let message =
+            UiMessage::from_widget(Handle::<UiNode>::NONE, TextMessage::Text(text.to_string()));
+        let property_changed = Utf32StringPropertyEditorDefinition
+            .translate_message(PropertyEditorTranslationContext {
+                environment: None,
+                name: "name",
+                message: &message,
+                definition_container: Arc::new(PropertyEditorDefinitionContainer::empty()),
+                max_length,
+            })
+            .unwrap();
+        assert_eq!(property_changed.name, "name");
+        let FieldAction::ObjectAction(value) = property_changed.action else {
+            unreachable!()
+        };
+        let mut chars = None;
+        value.cast_clone::<Vec<char>>(&mut |v| chars = v);
+        chars.unwrap()
+    }
+
+    #[test]
+    fn test_utf32_max_length() {
+        assert_eq!(
+            translate("abcdef", None),
+            vec!['a', 'b', 'c', 'd', 'e', 'f']
+        );
+        assert_eq!(translate("abcdef", Some(3)), vec!['a', 'b', 'c']);
+        assert_eq!(translate("ab", Some(3)), vec!['a', 'b']);
+        // Truncation is done by code points, not by bytes.
+        assert_eq!(translate("äöüß", Some(2)), vec!['ä', 'ö']);
     }
 }
@@ -776,6 +776,12 @@ pub struct ContextEntry {
     /// surrounding the editor instead of the editor itself.
     pub property_container: Handle<UiNode>,
     pub property_path: String,
+    /// A copy of [FieldMetadata::read_only]. Messages from editors of read-only properties are
+    /// never translated to [`InspectorMessage::PropertyChanged`].
+    pub property_read_only: bool,
+    /// A copy of [FieldMetadata::max_length]. It is passed to the property editor when it
+    /// translates its messages.
+    pub property_max_length: Option<usize>,
 }
 
 impl PartialEq for ContextEntry {
@@ -1157,6 +1163,8 @@ impl InspectorContext {
                                 property_debug_output: field_text.clone(),
                                 property_container: container,
                                 property_path,
+                                property_read_only: info.read_only,
+                                property_max_length: info.max_length,
                             });
 
                             if info.read_only {
@@ -1451,7 +1459,7 @@ impl Control for Inspector {
         if message.delivery_mode != DeliveryMode::SyncOnly {
             let env = self.context.environment.clone();
             for entry in self.context.entries.iter() {
-                if message.destination() == entry.property_editor {
+                if message.destination() == entry.property_editor && !entry.property_read_only {
                     if let Some(args) = entry
                         .property_editor_definition_container
                         .definitions()
@@ -1463,6 +1471,7 @@ impl Control for Inspector {
                                     name: &entry.property_name,
                                     message,
                                     definition_container: self.context.property_definitions.clone(),
+                                    max_length: entry.property_max_length,
                                 })
                         })
                     {
@@ -1540,10 +1549,63 @@ impl InspectorBuilder {
 #[cfg(test)]
 mod test {
     use crate::inspector::InspectorBuilder;
-    use crate::{test::test_widget_deletion, widget::WidgetBuilder};
+    use crate::{
+        core::{algebra::Vector2, reflect::prelude::*},
+        inspector::{
+            editors::PropertyEditorDefinitionContainer, InspectorContext, InspectorContextArgs,
+        },
+        test::test_widget_deletion,
+        text_box::TextBox,
+        widget::WidgetBuilder,
+        UserInterface,
+    };
+    use fyrox_graph::SceneGraph;
+    use std::sync::Arc;
 
     #[test]
     fn test_deletion() {
         test_widget_deletion(|ctx| InspectorBuilder::new(WidgetBuilder::new()).build(ctx));
     }
+
+    #[derive(Reflect, Clone, Debug)]
+    struct ReadOnlyText {
+        #[reflect(read_only)]
+        read_only_text: Vec<char>,
+        text: Vec<char>,
+    }
+
+    #[test]
+    fn test_read_only_string_editor() {
+        let object = ReadOnlyText {
+            read_only_text: "foo".chars().collect(),
+            text: "bar".chars().collect(),
+        };
+        let mut ui = UserInterface::new(Vector2::new(100.0, 100.0));
+        let context = InspectorContext::from_object(InspectorContextArgs {
+            object: &object,
+            ctx: &mut ui.build_ctx(),
+            definition_container: Arc::new(
+                PropertyEditorDefinitionContainer::with_default_editors(),
+            ),
+            environment: None,
+            layer_index: 0,
+            generate_property_string_values: false,
+            filter: Default::default(),
+            name_column_width: 150.0,
+            base_path: Default::default(),
+            has_parent_object: false,
+        });
+
+        for (name, text, read_only) in [("read_only_text", "foo", true), ("text", "bar", false)] {
+            let entry = context
+                .entries
+                .iter()
+                .find(|entry| entry.property_name == name)
+                .unwrap();
+            assert_eq!(entry.property_read_only, read_only);
+            let text_box = ui.node(entry.property_editor).cast::<TextBox>().unwrap();
+            assert_eq!(text_box.text(), text);
+            assert_eq!(*text_box.editable, !read_only);
+        }
+    }
 }